@@ -6,11 +6,16 @@ use soroban_sdk::{
     auth::{Context, CustomAccountInterface},
     contract, contractevent, contractimpl,
     crypto::Hash,
-    Address, BytesN, Env, Vec,
+    Address, Bytes, BytesN, Env, Vec,
 };
 use stellar_access::ownable;
 use stellar_contract_utils::upgradeable;
 
+use crate::{
+    migration::{self, SCHEMA_VERSION},
+    registry::{self, ChannelRecord},
+};
+
 #[contractevent(data_format = "single-value")]
 pub struct ContractInitialized {
     #[topic]
@@ -30,10 +35,13 @@ pub struct ProviderRemoved {
 }
 
 // UC6: the council's quorum-authorized record that an asset channel was enabled or disabled.
-// The contract holds NO channel/asset state — this event is the only on-chain artifact. The
-// council-platform DB (sole authoritative writer) and every provider converge on it: `enabled`
-// distinguishes enable/re-enable (true) from disable (false). `channel` is the privacy-channel
-// contract id; `asset` is its token contract id (a channel is single-asset, so this is self-describing).
+// The same state is kept in the channel registry (`registry.rs`) for on-chain discovery; the
+// council-platform DB and every provider converge on this event: `enabled` distinguishes
+// enable/re-enable (true) from disable (false). `channel` is the privacy-channel contract id;
+// `asset` is its token contract id (a channel is single-asset, so this is self-describing).
+// Either call registers a channel it has not seen, so disabling one that was never enabled
+// registers it disabled. A channel keeps the asset it was registered under: a call naming another
+// asset panics with `ChannelAssetMismatch` and emits nothing.
 #[contractevent(data_format = "single-value")]
 pub struct ChannelStateChanged {
     #[topic]
@@ -51,6 +59,13 @@ pub struct Upgraded {
     pub wasm_hash: BytesN<32>,
 }
 
+/// Emitted by `migrate` once storage is rewritten from schema version `from` to `to`.
+#[contractevent]
+pub struct Migrated {
+    pub from: u32,
+    pub to: u32,
+}
+
 #[contract]
 pub struct ChannelAuthContract;
 
@@ -72,6 +87,7 @@ fn bump_instance_ttl(e: &Env) {
 impl ChannelAuthContract {
     pub fn __constructor(env: &Env, admin: &Address) {
         ownable::set_owner(env, admin);
+        migration::write_schema_version(env, SCHEMA_VERSION);
         bump_instance_ttl(env);
         ContractInitialized {
             admin: admin.clone(),
@@ -99,6 +115,26 @@ impl ChannelAuthContract {
         .publish(e);
         upgradeable::upgrade(e, &wasm_hash);
     }
+
+    /// The storage schema version, `0` for contracts deployed before it was stored.
+    pub fn version(e: &Env) -> u32 {
+        migration::read_schema_version(e)
+    }
+
+    /// Migrates storage left by an older wasm to this code's schema version. Run once after each
+    /// `upgrade` that bumps the version; fails with `AlreadyMigrated` otherwise. `params` is
+    /// passed to the migration steps; the step from version `0` takes the channels enabled before
+    /// the registry existed (see `migration.rs`). Admin only. Emits `Migrated`.
+    pub fn migrate(e: &Env, params: Bytes) {
+        ownable::enforce_owner_auth(e);
+        let from = migration::migrate(e, &params);
+        bump_instance_ttl(e);
+        Migrated {
+            from,
+            to: SCHEMA_VERSION,
+        }
+        .publish(e);
+    }
 }
 
 impl ProviderAuthorizable for ChannelAuthContract {}
@@ -124,16 +160,19 @@ impl ChannelAuthContract {
     }
 }
 
-// UC6: asset-lifecycle. Quorum-gated, event-only — the contract stores no channel/asset state;
-// it only emits the quorum-authorized record that the council DB and providers converge on. The
-// owner is the council quorum account, so `enforce_owner_auth` is the quorum gate (mirrors
+// UC6: asset-lifecycle. Quorum-gated; each call updates the channel registry and emits the
+// quorum-authorized record that the council DB and providers converge on. The owner is the
+// council quorum account, so `enforce_owner_auth` is the quorum gate (mirrors
 // add_provider/remove_provider).
 #[contractimpl]
 impl ChannelAuthContract {
     /// Enable an asset `channel` for service. Also used to RE-ENABLE a previously disabled
     /// channel — both resume full service, so both emit `ChannelStateChanged { enabled: true }`.
+    /// Registers the channel under `asset` on first use; panics with `ChannelAssetMismatch` if it
+    /// is registered under another asset.
     pub fn enable_channel(e: &Env, channel: Address, asset: Address) {
         ownable::enforce_owner_auth(e);
+        registry::write_channel_state(e, &channel, &asset, true);
         ChannelStateChanged {
             channel,
             asset,
@@ -144,8 +183,11 @@ impl ChannelAuthContract {
 
     /// Disable an asset `channel`. The channel becomes withdraw-only (new deposits/sends rejected);
     /// that enforcement lives provider-side. Emits `ChannelStateChanged { enabled: false }`.
+    /// Registers the channel disabled if it was never enabled, and panics with
+    /// `ChannelAssetMismatch` like `enable_channel`.
     pub fn disable_channel(e: &Env, channel: Address, asset: Address) {
         ownable::enforce_owner_auth(e);
+        registry::write_channel_state(e, &channel, &asset, false);
         ChannelStateChanged {
             channel,
             asset,
@@ -153,6 +195,30 @@ impl ChannelAuthContract {
         }
         .publish(e);
     }
+
+    /// The registry entry for `channel`, if it was ever enabled or disabled here.
+    pub fn channel(e: &Env, channel: Address) -> Option<ChannelRecord> {
+        registry::read_channel(e, &channel)
+    }
+
+    pub fn channel_count(e: &Env) -> u32 {
+        registry::channel_count(e)
+    }
+
+    /// Registered channels in registration order, from index `start`, at most
+    /// `min(limit, MAX_PAGE_SIZE)` of them.
+    pub fn channels(e: &Env, start: u32, limit: u32) -> Vec<ChannelRecord> {
+        registry::channels(e, start, limit)
+    }
+
+    pub fn asset_channel_count(e: &Env, asset: Address) -> u32 {
+        registry::asset_channel_count(e, &asset)
+    }
+
+    /// Like `channels`, restricted to the channels registered under `asset`.
+    pub fn asset_channels(e: &Env, asset: Address, start: u32, limit: u32) -> Vec<ChannelRecord> {
+        registry::asset_channels(e, &asset, start, limit)
+    }
 }
 
 #[contractimpl]
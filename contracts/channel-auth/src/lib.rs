@@ -3,5 +3,7 @@
 extern crate alloc;
 
 pub mod contract;
+pub mod migration;
+pub mod registry;
 #[cfg(test)]
 pub mod tests;
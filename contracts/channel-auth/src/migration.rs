@@ -0,0 +1,67 @@
+//! Storage schema versions. A wasm upgrade replaces the code but not the storage, so every change
+//! to `ProviderDataKey`, the registry keys or the values stored under them bumps `SCHEMA_VERSION`
+//! and adds the step that rewrites the previous layout. The admin runs the steps with `migrate`
+//! right after `upgrade`.
+
+use moonlight_errors::Error;
+use soroban_sdk::{contracttype, panic_with_error, xdr::FromXdr, Address, Bytes, Env, Vec};
+
+use crate::registry;
+
+/// The layout this code reads and writes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Clone)]
+#[contracttype]
+pub(crate) enum MigrationKey {
+    SchemaVersion, //u32
+}
+
+pub fn write_schema_version(e: &Env, version: u32) {
+    e.storage()
+        .instance()
+        .set(&MigrationKey::SchemaVersion, &version);
+}
+
+/// `0` for contracts deployed before the schema version was stored.
+pub fn read_schema_version(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get(&MigrationKey::SchemaVersion)
+        .unwrap_or(0)
+}
+
+/// Runs every step from the stored version up to `SCHEMA_VERSION` and returns the version it
+/// started from. Fails with `AlreadyMigrated` if storage is already current.
+pub fn migrate(e: &Env, params: &Bytes) -> u32 {
+    let from = read_schema_version(e);
+    if from >= SCHEMA_VERSION {
+        panic_with_error!(e, Error::AlreadyMigrated);
+    }
+    for version in from..SCHEMA_VERSION {
+        migrate_from(e, version, params);
+    }
+    write_schema_version(e, SCHEMA_VERSION);
+    from
+}
+
+/// Rewrites storage from layout `version` to `version + 1`. `params` carries whatever that step
+/// needs from the admin; it is empty unless the step says otherwise.
+fn migrate_from(e: &Env, version: u32, params: &Bytes) {
+    match version {
+        // Unversioned deployments predate the channel registry: the channels they enabled have no
+        // record. `params` is the XDR of the `Vec<(Address, Address)>` of those `(channel, asset)`
+        // pairs, which are registered enabled; empty `params` register none.
+        0 => {
+            if params.is_empty() {
+                return;
+            }
+            let channels = Vec::<(Address, Address)>::from_xdr(e, params)
+                .unwrap_or_else(|_| panic_with_error!(e, Error::InvalidMigrationParams));
+            for (channel, asset) in channels.iter() {
+                registry::write_channel_state(e, &channel, &asset, true);
+            }
+        }
+        _ => panic_with_error!(e, Error::MissingMigrationStep),
+    }
+}
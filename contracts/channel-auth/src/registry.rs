@@ -0,0 +1,155 @@
+//! Asset-to-channel registry, written by `enable_channel` / `disable_channel`.
+//!
+//! Channels are kept in registration order, once overall and once per asset, so both lists can
+//! be paged by index. A channel is registered on its first enable or disable and keeps its slot
+//! afterwards; later calls only update its state.
+
+use moonlight_errors::Error;
+use soroban_sdk::{
+    contracttype, panic_with_error, Address, BytesN, Env, Executable, IntoVal, TryFromVal, Val, Vec,
+};
+
+/// Largest page `channels` and `asset_channels` return.
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+const DAY_IN_LEDGERS: u32 = 17_280;
+const BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+const LIFETIME_THRESHOLD: u32 = BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ChannelRecord {
+    pub channel: Address,
+    pub asset: Address,
+    pub enabled: bool,
+    /// The channel's current wasm hash, read from its executable whenever the record is read so
+    /// it follows channel upgrades; `None` if `channel` is not a deployed wasm contract.
+    pub wasm_hash: Option<BytesN<32>>,
+}
+
+#[derive(Clone)]
+#[contracttype]
+enum RegistryKey {
+    Channel(Address),             //ChannelRecord
+    ChannelCount,                 //u32
+    ChannelAt(u32),               //Address
+    AssetChannelCount(Address),   //u32
+    AssetChannelAt(Address, u32), //Address
+}
+
+fn read<V: TryFromVal<Env, Val>>(e: &Env, key: &RegistryKey) -> Option<V> {
+    let value = e.storage().persistent().get(key);
+    if value.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(key, LIFETIME_THRESHOLD, BUMP_AMOUNT);
+    }
+    value
+}
+
+fn write<V: IntoVal<Env, Val>>(e: &Env, key: &RegistryKey, value: &V) {
+    e.storage().persistent().set(key, value);
+    e.storage()
+        .persistent()
+        .extend_ttl(key, LIFETIME_THRESHOLD, BUMP_AMOUNT);
+}
+
+/// Appends `channel` to the list counted by `count_key`.
+fn push(e: &Env, count_key: RegistryKey, at: impl Fn(u32) -> RegistryKey, channel: &Address) {
+    let count: u32 = read(e, &count_key).unwrap_or(0);
+    write(e, &at(count), channel);
+    write(e, &count_key, &(count + 1));
+}
+
+fn page(
+    e: &Env,
+    count_key: RegistryKey,
+    at: impl Fn(u32) -> RegistryKey,
+    start: u32,
+    limit: u32,
+) -> Vec<ChannelRecord> {
+    let count: u32 = read(e, &count_key).unwrap_or(0);
+    let end = count.min(start.saturating_add(limit.min(MAX_PAGE_SIZE)));
+    let mut records = Vec::new(e);
+    for i in start..end {
+        let channel: Address = read(e, &at(i)).unwrap();
+        records.push_back(read_channel(e, &channel).unwrap());
+    }
+    records
+}
+
+fn wasm_hash(channel: &Address) -> Option<BytesN<32>> {
+    match channel.executable() {
+        Some(Executable::Wasm(hash)) => Some(hash),
+        _ => None,
+    }
+}
+
+pub fn read_channel(e: &Env, channel: &Address) -> Option<ChannelRecord> {
+    let record: ChannelRecord = read(e, &RegistryKey::Channel(channel.clone()))?;
+    Some(ChannelRecord {
+        wasm_hash: wasm_hash(channel),
+        ..record
+    })
+}
+
+/// Registers `channel` under `asset` on first sight, enabled or not, then records its state.
+/// Fails with `ChannelAssetMismatch` if `channel` is already registered under another asset.
+pub fn write_channel_state(e: &Env, channel: &Address, asset: &Address, enabled: bool) {
+    match read_channel(e, channel) {
+        Some(record) if &record.asset != asset => {
+            panic_with_error!(e, Error::ChannelAssetMismatch)
+        }
+        Some(_) => {}
+        None => {
+            push(
+                e,
+                RegistryKey::ChannelCount,
+                RegistryKey::ChannelAt,
+                channel,
+            );
+            push(
+                e,
+                RegistryKey::AssetChannelCount(asset.clone()),
+                |i| RegistryKey::AssetChannelAt(asset.clone(), i),
+                channel,
+            );
+        }
+    }
+
+    let record = ChannelRecord {
+        channel: channel.clone(),
+        asset: asset.clone(),
+        enabled,
+        wasm_hash: wasm_hash(channel),
+    };
+    write(e, &RegistryKey::Channel(channel.clone()), &record);
+}
+
+pub fn channel_count(e: &Env) -> u32 {
+    read(e, &RegistryKey::ChannelCount).unwrap_or(0)
+}
+
+pub fn asset_channel_count(e: &Env, asset: &Address) -> u32 {
+    read(e, &RegistryKey::AssetChannelCount(asset.clone())).unwrap_or(0)
+}
+
+pub fn channels(e: &Env, start: u32, limit: u32) -> Vec<ChannelRecord> {
+    page(
+        e,
+        RegistryKey::ChannelCount,
+        RegistryKey::ChannelAt,
+        start,
+        limit,
+    )
+}
+
+pub fn asset_channels(e: &Env, asset: &Address, start: u32, limit: u32) -> Vec<ChannelRecord> {
+    page(
+        e,
+        RegistryKey::AssetChannelCount(asset.clone()),
+        |i| RegistryKey::AssetChannelAt(asset.clone(), i),
+        start,
+        limit,
+    )
+}
@@ -0,0 +1,76 @@
+use moonlight_errors::Error as ContractError;
+use soroban_sdk::{testutils::Address as _, vec, xdr::ToXdr, Address, Bytes, Env, Error};
+
+use super::tests::create_contract;
+use crate::{
+    migration::{MigrationKey, SCHEMA_VERSION},
+    registry::ChannelRecord,
+};
+
+#[test]
+fn test_migrate_keeps_providers_and_registry() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, _) = create_contract(&e);
+    let (provider, channel, asset) = (
+        Address::generate(&e),
+        Address::generate(&e),
+        Address::generate(&e),
+    );
+    assert_eq!(client.version(), SCHEMA_VERSION);
+
+    client.add_provider(&provider);
+    client.enable_channel(&channel, &asset);
+    let record = client.channel(&channel);
+
+    // `upgrade` leaves storage as it is, so an auth upgraded from an unversioned wasm has the
+    // current layout without a schema version.
+    e.as_contract(&client.address, || {
+        e.storage().instance().remove(&MigrationKey::SchemaVersion)
+    });
+    assert_eq!(client.version(), 0);
+
+    client.migrate(&Bytes::new(&e));
+    assert_eq!(client.version(), SCHEMA_VERSION);
+    assert!(client.is_provider(&provider));
+    assert_eq!(client.channel(&channel), record);
+
+    assert_eq!(
+        client.try_migrate(&Bytes::new(&e)).err(),
+        Some(Ok(Error::from_contract_error(
+            ContractError::AlreadyMigrated as u32
+        )))
+    );
+}
+
+#[test]
+fn test_migrate_registers_channels_enabled_before_the_registry() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, _) = create_contract(&e);
+    let (channel, asset) = (Address::generate(&e), Address::generate(&e));
+
+    // An unversioned auth enabled `channel` by event only, leaving no registry record.
+    e.as_contract(&client.address, || {
+        e.storage().instance().remove(&MigrationKey::SchemaVersion)
+    });
+    assert_eq!(client.channel_count(), 0);
+
+    assert_eq!(
+        client.try_migrate(&7_u32.to_xdr(&e)).err(),
+        Some(Ok(Error::from_contract_error(
+            ContractError::InvalidMigrationParams as u32
+        )))
+    );
+
+    client.migrate(&vec![&e, (channel.clone(), asset.clone())].to_xdr(&e));
+    let record = ChannelRecord {
+        channel: channel.clone(),
+        asset: asset.clone(),
+        enabled: true,
+        wasm_hash: None,
+    };
+    assert_eq!(client.channel(&channel), Some(record.clone()));
+    assert_eq!(client.channels(&0, &10), vec![&e, record.clone()]);
+    assert_eq!(client.asset_channels(&asset, &0, &10), vec![&e, record]);
+}
@@ -1,4 +1,7 @@
 #![cfg(test)]
 
 mod events;
+mod migration;
+mod provider_contract;
+mod registry;
 mod tests;
@@ -0,0 +1,173 @@
+//! Contract-address providers: the bundle's auth entry names the provider contract, which
+//! authorizes the payload hash through its own `__check_auth` in a second entry.
+use moonlight_client::ChannelOperationBuilder;
+use moonlight_errors::Error as MoonlightError;
+use moonlight_helpers::testutils::keys::{Ed25519Account, P256KeyPair};
+use moonlight_primitives::Condition;
+use moonlight_utxo_core::testutils::contract::{
+    create_contract as create_utxo_contract, UTXOModuleTestContractClient,
+};
+use soroban_sdk::{
+    auth::{Context, CustomAccountInterface},
+    contract, contractimpl,
+    crypto::Hash,
+    symbol_short,
+    testutils::{MockAuth, MockAuthInvoke},
+    vec,
+    xdr::{
+        HashIdPreimage, HashIdPreimageSorobanAuthorization, Limits, ScVal,
+        SorobanAddressCredentials, SorobanAuthorizationEntry, SorobanCredentials, WriteXdr,
+    },
+    Address, Bytes, BytesN, Env, IntoVal, TryFromVal, Val, Vec,
+};
+
+use super::tests::{builder, create_contract, sign_spend, utxo_operation};
+
+/// A single-owner smart wallet, standing in for a provider's custody contract.
+#[contract]
+pub struct ProviderWallet;
+
+#[contractimpl]
+impl ProviderWallet {
+    pub fn __constructor(e: &Env, owner: BytesN<32>) {
+        e.storage().instance().set(&symbol_short!("owner"), &owner);
+    }
+}
+
+#[contractimpl]
+impl CustomAccountInterface for ProviderWallet {
+    type Error = MoonlightError;
+    type Signature = BytesN<64>;
+
+    fn __check_auth(
+        e: Env,
+        payload: Hash<32>,
+        signature: BytesN<64>,
+        _contexts: Vec<Context>,
+    ) -> Result<(), MoonlightError> {
+        let owner: BytesN<32> = e.storage().instance().get(&symbol_short!("owner")).unwrap();
+        e.crypto().ed25519_verify(
+            &owner,
+            &Bytes::from_array(&e, &payload.to_array()),
+            &signature,
+        );
+        Ok(())
+    }
+}
+
+/// Registers a wallet owned by `owner` as a provider and returns a one-spend bundle, signed by the
+/// UTXO owner, that names the wallet as its provider.
+fn setup<'a>(
+    e: &'a Env,
+    owner: &Ed25519Account,
+) -> (
+    Address,
+    ChannelOperationBuilder,
+    UTXOModuleTestContractClient<'a>,
+) {
+    let (auth_client, admin) = create_contract(e);
+    let (utxo_client, _) = create_utxo_contract(e, auth_client.address.clone());
+    let wallet = e.register(ProviderWallet, (owner.public_key.clone(),));
+
+    auth_client
+        .mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &auth_client.address,
+                fn_name: "add_provider",
+                args: (&wallet,).into_val(e),
+                sub_invokes: &[],
+            },
+        }])
+        .add_provider(&wallet);
+
+    let (utxo_a, utxo_b) = (P256KeyPair::generate(e), P256KeyPair::generate(e));
+    utxo_client.mint(&vec![e, (utxo_a.public_key.clone(), 500_i128)]);
+
+    let mut op = builder(e, &utxo_client.address, &auth_client.address);
+    op.add_create(utxo_b.public_key.clone(), 500_i128).unwrap();
+    op.add_spend(
+        utxo_a.public_key.clone(),
+        vec![e, Condition::Create(utxo_b.public_key.clone(), 500_i128)],
+    )
+    .unwrap();
+
+    let live_until_ledger = e.ledger().sequence() + 1;
+    sign_spend(e, &mut op, &utxo_a, live_until_ledger);
+    op.add_provider_contract(&wallet, live_until_ledger)
+        .unwrap();
+
+    (wallet, op, utxo_client)
+}
+
+/// The wallet's entry, signed by `signer`, authorizing the bundle entry with `nonce`.
+fn wallet_entry(
+    e: &Env,
+    op: &ChannelOperationBuilder,
+    wallet: &Address,
+    signer: &Ed25519Account,
+    nonce: i64,
+    live_until_ledger: u32,
+) -> SorobanAuthorizationEntry {
+    let invocation = op.provider_invocation(e, nonce, live_until_ledger).unwrap();
+    let preimage = HashIdPreimage::SorobanAuthorization(HashIdPreimageSorobanAuthorization {
+        network_id: e.ledger().network_id().to_array().into(),
+        nonce,
+        signature_expiration_ledger: live_until_ledger,
+        invocation: invocation.clone(),
+    })
+    .to_xdr(Limits::none())
+    .unwrap();
+    let hash = e.crypto().sha256(&Bytes::from_slice(e, &preimage));
+    let signature: Val = signer.sign(e, hash).into_val(e);
+
+    SorobanAuthorizationEntry {
+        credentials: SorobanCredentials::Address(SorobanAddressCredentials {
+            address: wallet.into(),
+            nonce,
+            signature_expiration_ledger: live_until_ledger,
+            signature: ScVal::try_from_val(e, &signature).unwrap(),
+        }),
+        root_invocation: invocation,
+    }
+}
+
+#[test]
+fn test_contract_provider_authorizes_through_its_check_auth() {
+    let e = Env::default();
+    let owner = Ed25519Account::generate(&e);
+    let (wallet, op, utxo_client) = setup(&e, &owner);
+    let live_until_ledger = e.ledger().sequence() + 1;
+
+    utxo_client
+        .set_auths(&[
+            op.auth_entry(&e, 0, live_until_ledger).unwrap(),
+            wallet_entry(&e, &op, &wallet, &owner, 0, live_until_ledger),
+        ])
+        .transact(&utxo_operation(&op));
+
+    let (created, amount) = op.operation().create.get(0).unwrap();
+    assert_eq!(utxo_client.utxo_balance(&created), amount);
+}
+
+#[test]
+fn test_contract_provider_must_authorize() {
+    let e = Env::default();
+    let owner = Ed25519Account::generate(&e);
+    let (wallet, op, utxo_client) = setup(&e, &owner);
+    let live_until_ledger = e.ledger().sequence() + 1;
+
+    let without_wallet = utxo_client
+        .set_auths(&[op.auth_entry(&e, 0, live_until_ledger).unwrap()])
+        .try_transact(&utxo_operation(&op));
+    assert!(without_wallet.is_err());
+
+    let stranger = Ed25519Account::generate(&e);
+    let wrong_signer = utxo_client
+        .set_auths(&[
+            op.auth_entry(&e, 0, live_until_ledger).unwrap(),
+            wallet_entry(&e, &op, &wallet, &stranger, 0, live_until_ledger),
+        ])
+        .try_transact(&utxo_operation(&op));
+    assert!(wrong_signer.is_err());
+}
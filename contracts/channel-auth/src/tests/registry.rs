@@ -0,0 +1,130 @@
+use moonlight_errors::Error as ContractError;
+use soroban_sdk::{testutils::Address as _, vec, Address, Bytes, Env, Error};
+
+use super::tests::create_contract;
+use crate::{
+    contract::{ChannelAuthContract, ChannelAuthContractArgs},
+    registry::{ChannelRecord, MAX_PAGE_SIZE},
+};
+
+fn record(channel: &Address, asset: &Address, enabled: bool) -> ChannelRecord {
+    ChannelRecord {
+        channel: channel.clone(),
+        asset: asset.clone(),
+        enabled,
+        wasm_hash: None,
+    }
+}
+
+#[test]
+fn test_enable_and_disable_update_the_registry() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, _) = create_contract(&e);
+    let (channel, asset) = (Address::generate(&e), Address::generate(&e));
+
+    assert_eq!(client.channel(&channel), None);
+
+    client.enable_channel(&channel, &asset);
+    assert_eq!(
+        client.channel(&channel),
+        Some(record(&channel, &asset, true))
+    );
+
+    client.disable_channel(&channel, &asset);
+    client.enable_channel(&channel, &asset);
+    client.disable_channel(&channel, &asset);
+    assert_eq!(
+        client.channel(&channel),
+        Some(record(&channel, &asset, false))
+    );
+    assert_eq!(client.channel_count(), 1);
+    assert_eq!(client.asset_channel_count(&asset), 1);
+}
+
+#[test]
+fn test_channel_keeps_its_asset() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, _) = create_contract(&e);
+    let (channel, asset) = (Address::generate(&e), Address::generate(&e));
+
+    client.enable_channel(&channel, &asset);
+    assert_eq!(
+        client
+            .try_disable_channel(&channel, &Address::generate(&e))
+            .err(),
+        Some(Ok(Error::from_contract_error(
+            ContractError::ChannelAssetMismatch as u32
+        )))
+    );
+    assert_eq!(
+        client.channel(&channel),
+        Some(record(&channel, &asset, true))
+    );
+}
+
+#[test]
+fn test_record_reports_the_current_wasm_hash() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, admin) = create_contract(&e);
+    let (channel, asset) = (Address::generate(&e), Address::generate(&e));
+
+    client.enable_channel(&channel, &asset);
+    assert_eq!(client.channel(&channel).unwrap().wasm_hash, None);
+
+    // Deployed after the last state change; native test contracts run as the empty wasm.
+    e.register_at(
+        &channel,
+        ChannelAuthContract,
+        ChannelAuthContractArgs::__constructor(&admin),
+    );
+    let empty_wasm = e.crypto().sha256(&Bytes::new(&e)).to_bytes();
+    assert_eq!(
+        client.channel(&channel).unwrap().wasm_hash,
+        Some(empty_wasm.clone())
+    );
+    assert_eq!(
+        client.channels(&0, &1).get_unchecked(0).wasm_hash,
+        Some(empty_wasm)
+    );
+}
+
+#[test]
+fn test_channels_are_paged_overall_and_per_asset() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, _) = create_contract(&e);
+    let (usdc, xlm) = (Address::generate(&e), Address::generate(&e));
+    let [a, b, c] = [0; 3].map(|_| Address::generate(&e));
+
+    client.enable_channel(&a, &usdc);
+    client.enable_channel(&b, &xlm);
+    client.disable_channel(&c, &usdc);
+
+    assert_eq!(client.channel_count(), 3);
+    assert_eq!(
+        client.channels(&0, &2),
+        vec![&e, record(&a, &usdc, true), record(&b, &xlm, true)]
+    );
+    assert_eq!(
+        client.channels(&2, &MAX_PAGE_SIZE),
+        vec![&e, record(&c, &usdc, false)]
+    );
+    assert_eq!(client.channels(&3, &10), vec![&e]);
+
+    assert_eq!(client.asset_channel_count(&usdc), 2);
+    assert_eq!(
+        client.asset_channels(&usdc, &0, &u32::MAX),
+        vec![&e, record(&a, &usdc, true), record(&c, &usdc, false)]
+    );
+    assert_eq!(
+        client.asset_channels(&xlm, &0, &10),
+        vec![&e, record(&b, &xlm, true)]
+    );
+    assert_eq!(
+        client.asset_channels(&Address::generate(&e), &0, &10),
+        vec![&e]
+    );
+}
@@ -1,5 +1,6 @@
 #![cfg(test)]
 
+use moonlight_client::{ChannelContext, ChannelOperationBuilder};
 use moonlight_helpers::testutils::keys::{Ed25519Account, P256KeyPair};
 use moonlight_primitives::Condition;
 use soroban_sdk::{
@@ -8,8 +9,8 @@ use soroban_sdk::{
 };
 
 use crate::contract::{ChannelAuthContract, ChannelAuthContractArgs, ChannelAuthContractClient};
-use moonlight_utxo_core::testutils::{
-    contract::create_contract as create_utxo_contract, operation_bundle::UTXOOperationBuilder,
+use moonlight_utxo_core::{
+    core::UTXOOperation, testutils::contract::create_contract as create_utxo_contract,
 };
 
 pub fn create_contract(e: &Env) -> (ChannelAuthContractClient<'_>, Address) {
@@ -23,6 +24,33 @@ pub fn create_contract(e: &Env) -> (ChannelAuthContractClient<'_>, Address) {
     (contract, admin)
 }
 
+/// A client builder for the UTXO test contract `utxo`, which the auth contract sees as a channel.
+pub fn builder(e: &Env, utxo: &Address, auth: &Address) -> ChannelOperationBuilder {
+    ChannelOperationBuilder::new(
+        e,
+        ChannelContext {
+            network_id: e.ledger().network_id(),
+            channel: utxo.clone(),
+            auth: auth.clone(),
+            asset: Address::generate(e),
+        },
+    )
+}
+
+pub fn utxo_operation(op: &ChannelOperationBuilder) -> UTXOOperation {
+    let op = op.operation();
+    UTXOOperation {
+        spend: op.spend,
+        create: op.create,
+    }
+}
+
+pub fn sign_spend(e: &Env, op: &mut ChannelOperationBuilder, owner: &P256KeyPair, live: u32) {
+    let hash = op.spend_payload_hash(e, &owner.public_key, live).unwrap();
+    op.add_spend_signature(e, owner.public_key.clone(), owner.sign(&hash), live)
+        .unwrap();
+}
+
 #[test]
 fn test_admin_transfer_keeps_current_admin_in_control_until_acceptance() {
     let e = Env::default();
@@ -193,62 +221,41 @@ fn test_auth_module() {
         (utxo_b.public_key.clone(), 500_i128),
     ]);
 
-    let mut op = UTXOOperationBuilder::generate(
-        &e,
-        utxo_client.address.clone(),
-        auth_client.address.clone(),
-    );
+    let mut op = builder(&e, &utxo_client.address, &auth_client.address);
 
-    op.add_create(utxo_c.public_key.clone(), 700_i128);
-    op.add_create(utxo_d.public_key.clone(), 800_i128);
+    op.add_create(utxo_c.public_key.clone(), 700_i128).unwrap();
+    op.add_create(utxo_d.public_key.clone(), 800_i128).unwrap();
 
     op.add_spend(
         utxo_a.public_key.clone(),
         vec![&e, Condition::Create(utxo_c.public_key.clone(), 700_i128)],
-    );
+    )
+    .unwrap();
     op.add_spend(
         utxo_b.public_key.clone(),
         vec![&e, Condition::Create(utxo_d.public_key.clone(), 800_i128)],
-    );
+    )
+    .unwrap();
 
     let live_until_ledger = e.ledger().sequence() + 1;
 
-    let signature_a = utxo_a.sign(&op.get_auth_hash_for_spend(
-        &e,
-        utxo_a.public_key.clone(),
-        live_until_ledger.clone(),
-    ));
-
-    op.add_spend_signature(
-        &e,
-        utxo_a.public_key.clone(),
-        signature_a,
-        live_until_ledger,
-    );
-
-    let signature_b = utxo_b.sign(&op.get_auth_hash_for_spend(
-        &e,
-        utxo_b.public_key.clone(),
-        live_until_ledger.clone(),
-    ));
-    op.add_spend_signature(
-        &e,
-        utxo_b.public_key.clone(),
-        signature_b,
-        live_until_ledger,
-    );
+    sign_spend(&e, &mut op, &utxo_a, live_until_ledger);
+    sign_spend(&e, &mut op, &utxo_b, live_until_ledger);
 
     let nonce = 0;
-    let signature_provider = provider.sign(
-        &e,
-        op.get_auth_entry_payload_hash_for_bundle(&e, nonce.clone(), live_until_ledger.clone()),
-    );
-
-    op.add_provider_signature(&e, provider.address, signature_provider, live_until_ledger);
+    let hash = op
+        .bundle_payload_hash(&e, nonce, live_until_ledger)
+        .unwrap();
+    op.add_provider_signature(
+        &provider.address,
+        provider.sign(&e, hash),
+        live_until_ledger,
+    )
+    .unwrap();
 
     utxo_client
-        .set_auths(&[op.get_auth_entry(&e, nonce, live_until_ledger)])
-        .transact(&op.get_operation_bundle());
+        .set_auths(&op.auth_entries(&e, nonce, live_until_ledger).unwrap())
+        .transact(&utxo_operation(&op));
 
     assert_eq!(utxo_client.utxo_balance(&utxo_a.public_key), 0);
     assert_eq!(utxo_client.utxo_balance(&utxo_b.public_key), 0);
@@ -0,0 +1,141 @@
+//! Deploys Privacy Channels and enables them on their Channel Auth in one call, keeping one
+//! channel per asset and auth.
+
+use moonlight_errors::Error;
+use soroban_sdk::{
+    contract, contractclient, contractevent, contractimpl, contracttype, panic_with_error,
+    xdr::ToXdr, Address, BytesN, Env,
+};
+use stellar_access::ownable;
+
+/// The part of the Channel Auth interface the factory calls.
+#[contractclient(name = "ChannelAuthClient")]
+pub trait ChannelAuth {
+    fn enable_channel(e: Env, channel: Address, asset: Address);
+}
+
+#[derive(Clone)]
+#[contracttype]
+enum DataKey {
+    ChannelWasm,               //BytesN<32>
+    Channel(Address, Address), //Address, by (auth, asset)
+}
+
+#[contractevent]
+pub struct ChannelDeployed {
+    #[topic]
+    pub auth: Address,
+    #[topic]
+    pub asset: Address,
+    pub channel: Address,
+}
+
+const DAY_IN_LEDGERS: u32 = 17_280;
+const BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+const LIFETIME_THRESHOLD: u32 = BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+fn bump_instance_ttl(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(LIFETIME_THRESHOLD, BUMP_AMOUNT);
+}
+
+/// The asset address, namespaced by the auth so each auth gets its own channel per asset.
+fn channel_salt(e: &Env, auth: &Address, asset: &Address) -> BytesN<32> {
+    let mut preimage = asset.clone().to_xdr(e);
+    preimage.append(&auth.clone().to_xdr(e));
+    e.crypto().sha256(&preimage).to_bytes()
+}
+
+#[contract]
+pub struct ChannelFactoryContract;
+
+#[contractimpl]
+impl ChannelFactoryContract {
+    pub fn __constructor(e: &Env, admin: Address, channel_wasm_hash: BytesN<32>) {
+        ownable::set_owner(e, &admin);
+        e.storage()
+            .instance()
+            .set(&DataKey::ChannelWasm, &channel_wasm_hash);
+        bump_instance_ttl(e);
+    }
+
+    pub fn admin(e: &Env) -> Address {
+        ownable::get_owner(e).unwrap()
+    }
+
+    pub fn set_admin(e: &Env, new_admin: Address) {
+        ownable::transfer_ownership(e, &new_admin, e.ledger().max_live_until_ledger());
+    }
+
+    pub fn accept_admin(e: &Env) {
+        ownable::accept_ownership(e);
+    }
+
+    pub fn channel_wasm_hash(e: &Env) -> BytesN<32> {
+        e.storage().instance().get(&DataKey::ChannelWasm).unwrap()
+    }
+
+    /// Points later deployments at a new uploaded channel wasm. Channels already deployed keep
+    /// theirs. Admin only.
+    pub fn set_channel_wasm_hash(e: &Env, channel_wasm_hash: BytesN<32>) {
+        ownable::enforce_owner_auth(e);
+        e.storage()
+            .instance()
+            .set(&DataKey::ChannelWasm, &channel_wasm_hash);
+        bump_instance_ttl(e);
+    }
+
+    /// The channel deployed for `asset` under `auth`, if any.
+    pub fn channel(e: &Env, auth: Address, asset: Address) -> Option<Address> {
+        e.storage().persistent().get(&DataKey::Channel(auth, asset))
+    }
+
+    /// The address `deploy_channel(auth, asset, _)` deploys to, whether or not it has run.
+    pub fn channel_address(e: &Env, auth: Address, asset: Address) -> Address {
+        e.deployer()
+            .with_current_contract(channel_salt(e, &auth, &asset))
+            .deployed_address()
+    }
+
+    /// Deploys a Privacy Channel for `asset` owned by `channel_admin` and bound to `auth`, then
+    /// enables it on `auth`. Admin only; `channel_admin` authorizes the channel constructor and
+    /// the admin of `auth` authorizes `enable_channel`, so the factory admin is usually both.
+    ///
+    /// The address is derived from the asset and auth, and a second channel for the same pair
+    /// fails with `ChannelAlreadyDeployed`. Emits `ChannelDeployed`.
+    pub fn deploy_channel(
+        e: &Env,
+        auth: Address,
+        asset: Address,
+        channel_admin: Address,
+    ) -> Address {
+        ownable::enforce_owner_auth(e);
+        let key = DataKey::Channel(auth.clone(), asset.clone());
+        if e.storage().persistent().has(&key) {
+            panic_with_error!(e, Error::ChannelAlreadyDeployed);
+        }
+
+        let channel = e
+            .deployer()
+            .with_current_contract(channel_salt(e, &auth, &asset))
+            .deploy_v2(
+                Self::channel_wasm_hash(e),
+                (channel_admin, auth.clone(), asset.clone()),
+            );
+        ChannelAuthClient::new(e, &auth).enable_channel(&channel, &asset);
+
+        e.storage().persistent().set(&key, &channel);
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LIFETIME_THRESHOLD, BUMP_AMOUNT);
+        bump_instance_ttl(e);
+        ChannelDeployed {
+            auth,
+            asset,
+            channel: channel.clone(),
+        }
+        .publish(e);
+        channel
+    }
+}
@@ -0,0 +1,171 @@
+#![cfg(test)]
+extern crate std;
+
+use channel_auth_contract::contract::{ChannelAuthContract, ChannelAuthContractArgs};
+use soroban_sdk::{
+    testutils::{Address as _, MockAuth, MockAuthInvoke},
+    Address, BytesN, Env, IntoVal,
+};
+
+use crate::contract::{
+    ChannelFactoryContract, ChannelFactoryContractArgs, ChannelFactoryContractClient,
+};
+
+fn create_factory(
+    e: &Env,
+    wasm_hash: BytesN<32>,
+) -> (ChannelFactoryContractClient<'_>, Address, Address) {
+    let admin = Address::generate(e);
+    let auth = e.register(
+        ChannelAuthContract,
+        ChannelAuthContractArgs::__constructor(&admin),
+    );
+    let id = e.register(
+        ChannelFactoryContract,
+        ChannelFactoryContractArgs::__constructor(&admin, &wasm_hash),
+    );
+    (ChannelFactoryContractClient::new(e, &id), admin, auth)
+}
+
+#[test]
+fn test_only_admin_deploys() {
+    let e = Env::default();
+    // Authorization fails before the wasm is deployed, so no upload is needed.
+    let (factory, _, auth) = create_factory(&e, BytesN::from_array(&e, &[0; 32]));
+    let (attacker, asset) = (Address::generate(&e), Address::generate(&e));
+
+    let result = factory
+        .mock_auths(&[MockAuth {
+            address: &attacker,
+            invoke: &MockAuthInvoke {
+                contract: &factory.address,
+                fn_name: "deploy_channel",
+                args: (&auth, &asset, &attacker).into_val(&e),
+                sub_invokes: &[],
+            },
+        }])
+        .try_deploy_channel(&auth, &asset, &attacker);
+    assert!(result.is_err());
+    assert_eq!(factory.channel(&auth, &asset), None);
+}
+
+#[test]
+fn test_admin_transfer_requires_pending_admin_to_accept() {
+    let e = Env::default();
+    let (factory, admin, _) = create_factory(&e, BytesN::from_array(&e, &[0; 32]));
+    let (pending_admin, other) = (Address::generate(&e), Address::generate(&e));
+
+    factory
+        .mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &factory.address,
+                fn_name: "set_admin",
+                args: (&pending_admin,).into_val(&e),
+                sub_invokes: &[],
+            },
+        }])
+        .set_admin(&pending_admin);
+    assert_eq!(factory.admin(), admin);
+
+    let accept = |acceptor: &Address| {
+        factory
+            .mock_auths(&[MockAuth {
+                address: acceptor,
+                invoke: &MockAuthInvoke {
+                    contract: &factory.address,
+                    fn_name: "accept_admin",
+                    args: ().into_val(&e),
+                    sub_invokes: &[],
+                },
+            }])
+            .try_accept_admin()
+    };
+    assert!(accept(&other).is_err());
+    assert_eq!(factory.admin(), admin);
+
+    assert!(accept(&pending_admin).is_ok());
+    assert_eq!(factory.admin(), pending_admin);
+}
+
+/// Tests that deploy the release channel wasm. `make test-factory` builds it and runs them.
+#[cfg(feature = "wasm-tests")]
+mod deploy {
+    use channel_auth_contract::contract::ChannelAuthContractClient;
+    use moonlight_errors::Error as ContractError;
+    use soroban_sdk::{testutils::Events, Error, Event};
+
+    use super::*;
+    use crate::contract::ChannelDeployed;
+
+    mod privacy_channel {
+        soroban_sdk::contractimport!(
+            file = "../../target/wasm32v1-none/release/privacy_channel.wasm"
+        );
+    }
+
+    fn channel_wasm(e: &Env) -> BytesN<32> {
+        e.deployer().upload_contract_wasm(privacy_channel::WASM)
+    }
+
+    #[test]
+    fn test_deploys_and_records_channel() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let (factory, admin, auth) = create_factory(&e, channel_wasm(&e));
+        let asset = Address::generate(&e);
+
+        let expected = factory.channel_address(&auth, &asset);
+        assert_eq!(factory.channel(&auth, &asset), None);
+
+        let channel_id = factory.deploy_channel(&auth, &asset, &admin);
+        assert_eq!(
+            e.events().all().events().last().unwrap(),
+            &ChannelDeployed {
+                auth: auth.clone(),
+                asset: asset.clone(),
+                channel: channel_id.clone(),
+            }
+            .to_xdr(&e, &factory.address)
+        );
+        assert_eq!(channel_id, expected);
+        assert_eq!(factory.channel(&auth, &asset), Some(channel_id.clone()));
+
+        let record = ChannelAuthContractClient::new(&e, &auth)
+            .channel(&channel_id)
+            .unwrap();
+        assert!(record.enabled);
+        assert_eq!(record.asset, asset);
+        assert_eq!(record.wasm_hash, Some(factory.channel_wasm_hash()));
+
+        let channel = privacy_channel::Client::new(&e, &channel_id);
+        assert_eq!(channel.admin(), admin);
+        assert_eq!(channel.auth(), auth);
+        assert_eq!(channel.asset(), asset);
+    }
+
+    #[test]
+    fn test_one_channel_per_asset_and_auth() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let (factory, admin, auth) = create_factory(&e, channel_wasm(&e));
+        let asset = Address::generate(&e);
+
+        let first = factory.deploy_channel(&auth, &asset, &admin);
+        assert_eq!(
+            factory.try_deploy_channel(&auth, &asset, &admin).err(),
+            Some(Ok(Error::from_contract_error(
+                ContractError::ChannelAlreadyDeployed as u32
+            )))
+        );
+
+        let other_auth = e.register(
+            ChannelAuthContract,
+            ChannelAuthContractArgs::__constructor(&admin),
+        );
+        let second = factory.deploy_channel(&other_auth, &asset, &admin);
+        assert_ne!(first, second);
+        assert_eq!(factory.channel(&auth, &asset), Some(first));
+        assert_eq!(factory.channel(&other_auth, &asset), Some(second));
+    }
+}
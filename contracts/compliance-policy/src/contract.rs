@@ -0,0 +1,122 @@
+//! Reference compliance policy for the Privacy Channel: a single admin-managed address list used
+//! either as an allowlist or as a denylist, applied to deposits and withdrawals alike.
+
+use moonlight_primitives::compliance::CompliancePolicy;
+use soroban_sdk::{contract, contractevent, contractimpl, contracttype, Address, Env};
+use stellar_access::ownable;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum ListMode {
+    /// Only listed addresses pass.
+    Allow,
+    /// Every address except the listed ones passes.
+    Deny,
+}
+
+#[derive(Clone)]
+#[contracttype]
+enum DataKey {
+    Mode,            //ListMode
+    Listed(Address), //()
+}
+
+#[contractevent(data_format = "single-value")]
+pub struct ModeUpdated {
+    pub mode: ListMode,
+}
+
+#[contractevent(data_format = "single-value")]
+pub struct ListUpdated {
+    #[topic]
+    pub address: Address,
+    pub listed: bool,
+}
+
+const DAY_IN_LEDGERS: u32 = 17_280;
+const BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+const LIFETIME_THRESHOLD: u32 = BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+fn bump_instance_ttl(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(LIFETIME_THRESHOLD, BUMP_AMOUNT);
+}
+
+fn is_listed(e: &Env, address: &Address) -> bool {
+    let key = DataKey::Listed(address.clone());
+    let listed = e.storage().persistent().has(&key);
+    if listed {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LIFETIME_THRESHOLD, BUMP_AMOUNT);
+    }
+    listed
+}
+
+fn passes(e: &Env, address: &Address) -> bool {
+    bump_instance_ttl(e);
+    match CompliancePolicyContract::mode(e) {
+        ListMode::Allow => is_listed(e, address),
+        ListMode::Deny => !is_listed(e, address),
+    }
+}
+
+#[contract]
+pub struct CompliancePolicyContract;
+
+#[contractimpl]
+impl CompliancePolicyContract {
+    pub fn __constructor(e: &Env, admin: Address, mode: ListMode) {
+        ownable::set_owner(e, &admin);
+        e.storage().instance().set(&DataKey::Mode, &mode);
+        bump_instance_ttl(e);
+    }
+
+    pub fn admin(e: &Env) -> Address {
+        ownable::get_owner(e).unwrap()
+    }
+
+    pub fn mode(e: &Env) -> ListMode {
+        e.storage().instance().get(&DataKey::Mode).unwrap()
+    }
+
+    /// Switches between allowlist and denylist. The list itself is kept. Admin only.
+    pub fn set_mode(e: &Env, mode: ListMode) {
+        ownable::enforce_owner_auth(e);
+        e.storage().instance().set(&DataKey::Mode, &mode);
+        bump_instance_ttl(e);
+        ModeUpdated { mode }.publish(e);
+    }
+
+    pub fn is_listed(e: &Env, address: Address) -> bool {
+        is_listed(e, &address)
+    }
+
+    /// Adds `address` to, or with `listed == false` removes it from, the list. Admin only.
+    pub fn set_listed(e: &Env, address: Address, listed: bool) {
+        ownable::enforce_owner_auth(e);
+        let key = DataKey::Listed(address.clone());
+        if listed {
+            e.storage().persistent().set(&key, &());
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LIFETIME_THRESHOLD, BUMP_AMOUNT);
+        } else {
+            e.storage().persistent().remove(&key);
+        }
+        bump_instance_ttl(e);
+        ListUpdated { address, listed }.publish(e);
+    }
+}
+
+#[contractimpl]
+impl CompliancePolicy for CompliancePolicyContract {
+    fn check_deposit(e: Env, from: Address, _amount: i128) -> bool {
+        passes(&e, &from)
+    }
+
+    fn check_withdraw(e: Env, to: Address, _amount: i128) -> bool {
+        passes(&e, &to)
+    }
+}
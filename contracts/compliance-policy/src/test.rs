@@ -0,0 +1,81 @@
+#![cfg(test)]
+extern crate std;
+
+use moonlight_primitives::compliance::CompliancePolicyClient;
+use soroban_sdk::{
+    testutils::{Address as _, Events, MockAuth, MockAuthInvoke},
+    Address, Env, Event, IntoVal,
+};
+
+use crate::contract::{
+    CompliancePolicyContract, CompliancePolicyContractArgs, CompliancePolicyContractClient,
+    ListMode, ListUpdated,
+};
+
+fn create_policy(e: &Env, mode: ListMode) -> (CompliancePolicyContractClient<'_>, Address) {
+    let admin = Address::generate(e);
+    let id = e.register(
+        CompliancePolicyContract,
+        CompliancePolicyContractArgs::__constructor(&admin, &mode),
+    );
+    (CompliancePolicyContractClient::new(e, &id), admin)
+}
+
+#[test]
+fn test_allowlist_and_denylist() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (policy, _) = create_policy(&e, ListMode::Allow);
+    let checks = CompliancePolicyClient::new(&e, &policy.address);
+    let (listed, other) = (Address::generate(&e), Address::generate(&e));
+
+    policy.set_listed(&listed, &true);
+    assert_eq!(
+        e.events().all().events().last().unwrap(),
+        &ListUpdated {
+            address: listed.clone(),
+            listed: true,
+        }
+        .to_xdr(&e, &policy.address)
+    );
+
+    assert!(checks.check_deposit(&listed, &100));
+    assert!(checks.check_withdraw(&listed, &100));
+    assert!(!checks.check_deposit(&other, &100));
+    assert!(!checks.check_withdraw(&other, &100));
+
+    policy.set_mode(&ListMode::Deny);
+    assert!(!checks.check_withdraw(&listed, &100));
+    assert!(checks.check_withdraw(&other, &100));
+
+    policy.set_listed(&listed, &false);
+    assert!(!policy.is_listed(&listed));
+    assert!(checks.check_deposit(&listed, &100));
+}
+
+#[test]
+fn test_only_admin_manages_the_list() {
+    let e = Env::default();
+    let (policy, admin) = create_policy(&e, ListMode::Deny);
+    let (attacker, target) = (Address::generate(&e), Address::generate(&e));
+
+    let set_listed = |signer: &Address| {
+        policy
+            .mock_auths(&[MockAuth {
+                address: signer,
+                invoke: &MockAuthInvoke {
+                    contract: &policy.address,
+                    fn_name: "set_listed",
+                    args: (&target, true).into_val(&e),
+                    sub_invokes: &[],
+                },
+            }])
+            .try_set_listed(&target, &true)
+    };
+
+    assert!(set_listed(&attacker).is_err());
+    assert!(!policy.is_listed(&target));
+
+    assert!(set_listed(&admin).is_ok());
+    assert!(policy.is_listed(&target));
+}
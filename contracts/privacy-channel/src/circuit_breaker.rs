@@ -0,0 +1,104 @@
+//! Outflow circuit breaker. Withdrawn value is summed over a rolling window of ledgers; the bundle
+//! that takes it to or above the configured threshold pauses the channel until the admin or
+//! guardian resets it.
+
+use soroban_sdk::{contractevent, contracttype, Env, Vec};
+
+use crate::storage::{read_circuit_breaker, read_outflow, write_outflow, write_paused};
+
+/// The window is tracked in this many buckets, bounding the stored outflow history.
+const OUTFLOW_BUCKETS: u32 = 24;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CircuitBreaker {
+    /// The rolling window, in ledgers.
+    pub window: u32,
+    /// The value withdrawn within `window` that pauses the channel when reached or crossed.
+    pub max_outflow: i128,
+}
+
+impl CircuitBreaker {
+    pub fn is_valid(&self) -> bool {
+        self.window > 0 && self.max_outflow > 0
+    }
+
+    fn bucket_len(&self) -> u32 {
+        (self.window / OUTFLOW_BUCKETS).max(1)
+    }
+}
+
+#[contractevent]
+pub struct CircuitBreakerTripped {
+    pub outflow: i128,
+    pub max_outflow: i128,
+}
+
+/// The outflow buckets still inside the window ending at the current ledger.
+///
+/// A bucket counts while any of its ledgers is inside the window, so the sum can include up to one
+/// bucket of older outflow: the breaker may trip early, never late.
+fn live_buckets(e: &Env, breaker: &CircuitBreaker) -> Vec<(u32, i128)> {
+    let window_start = e.ledger().sequence().saturating_sub(breaker.window);
+    let mut live = Vec::new(e);
+    for (start, amount) in read_outflow(e).iter() {
+        if start.saturating_add(breaker.bucket_len()) > window_start {
+            live.push_back((start, amount));
+        }
+    }
+    live
+}
+
+fn sum(buckets: &Vec<(u32, i128)>) -> i128 {
+    buckets
+        .iter()
+        .fold(0_i128, |total, (_, amount)| total.saturating_add(amount))
+}
+
+/// The value withdrawn within the current window, `0` without a circuit breaker.
+pub fn current_outflow(e: &Env) -> i128 {
+    read_circuit_breaker(e).map_or(0, |breaker| sum(&live_buckets(e, &breaker)))
+}
+
+/// Adds `amount` to the outflow window, before any of it is transferred.
+///
+/// If the windowed outflow reaches or exceeds the threshold, pauses the channel and emits
+/// `CircuitBreakerTripped`. The tripping bundle itself completes: failing it would roll the pause
+/// back with it and leave the drain free to retry with smaller bundles. Every later `transact`
+/// fails until a reset, so the outflow past the threshold is bounded by one bundle, whose size
+/// `ChannelLimits` can cap.
+pub fn record_outflow(e: &Env, amount: i128) {
+    let Some(breaker) = read_circuit_breaker(e) else {
+        return;
+    };
+    if amount == 0 {
+        return;
+    }
+
+    let ledger = e.ledger().sequence();
+    let start = ledger - ledger % breaker.bucket_len();
+    let mut buckets = live_buckets(e, &breaker);
+    match buckets.last() {
+        Some((last, total)) if last == start => {
+            buckets.set(buckets.len() - 1, (start, total.saturating_add(amount)))
+        }
+        _ => buckets.push_back((start, amount)),
+    }
+    write_outflow(e, &buckets);
+
+    let outflow = sum(&buckets);
+    if outflow >= breaker.max_outflow {
+        write_paused(e, true);
+        CircuitBreakerTripped {
+            outflow,
+            max_outflow: breaker.max_outflow,
+        }
+        .publish(e);
+    }
+}
+
+/// Unpauses the channel and clears the outflow window.
+pub fn reset(e: &Env) {
+    write_paused(e, false);
+    write_outflow(e, &Vec::new(e));
+}
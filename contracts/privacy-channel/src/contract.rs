@@ -1,8 +1,9 @@
 use moonlight_errors::Error;
-use moonlight_utxo_core::core::UtxoHandlerTrait;
+use moonlight_primitives::{Condition, SignerKey};
+use moonlight_utxo_core::core::{hash_utxo_key, TtlPolicy, UtxoHandlerTrait, UtxoState};
 use soroban_sdk::{
-    contract, contractevent, contractimpl, panic_with_error, symbol_short, Address, BytesN, Env,
-    Symbol, Vec,
+    contract, contractevent, contractimpl, contracttype, panic_with_error, symbol_short, Address,
+    Bytes, BytesN, Env, Symbol, Vec,
 };
 use stellar_access::ownable;
 use stellar_contract_utils::upgradeable;
@@ -14,9 +15,149 @@ pub struct Upgraded {
     pub wasm_hash: BytesN<32>,
 }
 
+/// Emitted by `migrate` once storage is rewritten from schema version `from` to `to`.
+#[contractevent]
+pub struct Migrated {
+    pub from: u32,
+    pub to: u32,
+}
+
+#[contractevent]
+pub struct TtlPolicyUpdated {
+    pub threshold: u32,
+    pub extend_to: u32,
+}
+
+/// Emitted by every `transact`, regardless of the `moonlight-utxo-core` event features.
+///
+/// UTXOs appear only as `sha256(utxo_id)` and spend/create amounts are not disclosed. Deposits and
+/// withdrawals move public token balances, so they are listed in full with the resulting supply.
+/// `notes` pairs each created UTXO hash with the encrypted note the bundle attached to it, in
+/// `create` order, and `ephemeral_keys` likewise pairs them with their stealth ephemeral keys.
+/// `payload` is an opaque, typically encrypted, blob for the recipients.
+#[contractevent]
+pub struct Transact {
+    pub spent: Vec<BytesN<32>>,
+    pub created: Vec<BytesN<32>>,
+    pub notes: Vec<(BytesN<32>, Bytes)>,
+    pub ephemeral_keys: Vec<(BytesN<32>, BytesN<65>)>,
+    pub deposits: Vec<(Address, i128)>,
+    pub withdrawals: Vec<(Address, i128)>,
+    pub supply: i128,
+    pub payload: Option<Bytes>,
+}
+
+/// The channel's accounting, as returned by `reconcile`.
+///
+/// `balance` is the asset held by the channel, `supply` is deposits minus withdrawals and
+/// `unspent` is the sum of unspent UTXO amounts. A healthy channel has `unspent == supply` and
+/// `supply <= balance`; the asset above `supply` was sent to the channel directly and can be
+/// moved out with `skim`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Reconciliation {
+    pub balance: i128,
+    pub supply: i128,
+    pub unspent: i128,
+}
+
+#[contractevent]
+pub struct Reconciled {
+    pub balance: i128,
+    pub supply: i128,
+    pub unspent: i128,
+}
+
+#[contractevent]
+pub struct LimitsUpdated {
+    pub limits: ChannelLimits,
+}
+
+#[contractevent]
+pub struct CircuitBreakerUpdated {
+    pub breaker: Option<CircuitBreaker>,
+}
+
+#[contractevent]
+pub struct GuardianUpdated {
+    pub guardian: Option<Address>,
+}
+
+#[contractevent(data_format = "single-value")]
+pub struct CircuitBreakerReset {
+    #[topic]
+    pub by: Address,
+}
+
+#[contractevent]
+pub struct CompliancePolicyUpdated {
+    pub policy: Option<Address>,
+}
+
+#[contractevent]
+pub struct Skimmed {
+    #[topic]
+    pub to: Address,
+    pub amount: i128,
+}
+
+impl Transact {
+    fn new(e: &Env, op: &ChannelOperation, supply: i128, payload: Option<Bytes>) -> Self {
+        let mut spent = Vec::new(e);
+        for (utxo, _) in op.spend.iter() {
+            spent.push_back(hash_utxo_key(e, &utxo));
+        }
+        let mut created = Vec::new(e);
+        let mut notes = Vec::new(e);
+        let mut ephemeral_keys = Vec::new(e);
+        for (utxo, _) in op.create.iter() {
+            let hash = hash_utxo_key(e, &utxo);
+            if let Some(note) = op.notes.get(utxo.clone()) {
+                notes.push_back((hash.clone(), note));
+            }
+            if let Some(ephemeral_key) = op.ephemeral_keys.get(utxo) {
+                ephemeral_keys.push_back((hash.clone(), ephemeral_key));
+            }
+            created.push_back(hash);
+        }
+        let mut deposits = Vec::new(e);
+        for (from, amount, _) in op.deposit.iter() {
+            deposits.push_back((from, amount));
+        }
+        let mut withdrawals = Vec::new(e);
+        for (to, amount, _) in op.withdraw.iter() {
+            withdrawals.push_back((to, amount));
+        }
+
+        Self {
+            spent,
+            created,
+            notes,
+            ephemeral_keys,
+            deposits,
+            withdrawals,
+            supply,
+            payload,
+        }
+    }
+}
+
 use crate::{
-    storage::{read_asset, read_supply, write_asset_unchecked},
-    transact::{execute_external_operations, pre_process_channel_operation, ChannelOperation},
+    circuit_breaker::{self, current_outflow, CircuitBreaker},
+    limits::ChannelLimits,
+    migration::{self, SCHEMA_VERSION},
+    recovery::{self, PendingRecovery, RecoveryConfig},
+    storage::{
+        read_asset, read_circuit_breaker, read_compliance_policy, read_guardian, read_limits,
+        read_paused, read_schema_version, read_supply, write_asset_unchecked,
+        write_circuit_breaker, write_compliance_policy, write_guardian, write_limits,
+        write_schema_version,
+    },
+    transact::{
+        execute_external_operations, execute_netted_external_operations,
+        pre_process_channel_operation, pre_process_channel_operations, ChannelOperation,
+    },
+    treasury::{read_balance, skim_surplus},
 };
 
 #[contract]
@@ -60,6 +201,7 @@ impl PrivacyChannelContract {
         ownable::enforce_owner_auth(e);
         <Self as UtxoHandlerTrait>::set_auth(e, &auth_contract);
         write_asset_unchecked(e, asset);
+        write_schema_version(e, SCHEMA_VERSION);
         bump_instance_ttl(e);
     }
 
@@ -84,6 +226,25 @@ impl PrivacyChannelContract {
         upgradeable::upgrade(e, &wasm_hash);
     }
 
+    /// The storage schema version, `0` for channels deployed before it was stored.
+    pub fn version(e: &Env) -> u32 {
+        read_schema_version(e)
+    }
+
+    /// Migrates storage left by an older wasm to this code's schema version. Run once after each
+    /// `upgrade` that bumps the version; fails with `AlreadyMigrated` otherwise. `params` is
+    /// passed to the migration steps, none of which take any yet. Admin only. Emits `Migrated`.
+    pub fn migrate(e: &Env, params: Bytes) {
+        ownable::enforce_owner_auth(e);
+        let from = migration::migrate(e, &params);
+        bump_instance_ttl(e);
+        Migrated {
+            from,
+            to: SCHEMA_VERSION,
+        }
+        .publish(e);
+    }
+
     pub fn auth(e: &Env) -> Address {
         <Self as UtxoHandlerTrait>::auth(e)
     }
@@ -96,6 +257,116 @@ impl PrivacyChannelContract {
         <Self as UtxoHandlerTrait>::utxo_balances(e, utxos)
     }
 
+    pub fn utxo_state(e: &Env, utxo: BytesN<65>) -> UtxoState {
+        <Self as UtxoHandlerTrait>::utxo_state(e, utxo)
+    }
+
+    pub fn utxo_states(e: &Env, utxos: Vec<BytesN<65>>) -> Vec<UtxoState> {
+        <Self as UtxoHandlerTrait>::utxo_states(e, utxos)
+    }
+
+    pub fn ttl_policy(e: &Env) -> TtlPolicy {
+        <Self as UtxoHandlerTrait>::ttl_policy(e)
+    }
+
+    pub fn set_ttl_policy(e: &Env, policy: TtlPolicy) {
+        ownable::enforce_owner_auth(e);
+        <Self as UtxoHandlerTrait>::set_ttl_policy(e, &policy);
+        bump_instance_ttl(e);
+        TtlPolicyUpdated {
+            threshold: policy.threshold,
+            extend_to: policy.extend_to,
+        }
+        .publish(e);
+    }
+
+    pub fn limits(e: &Env) -> ChannelLimits {
+        read_limits(e)
+    }
+
+    /// Replaces the channel's limits. Admin only; every set limit must be strictly positive.
+    pub fn set_limits(e: &Env, limits: ChannelLimits) {
+        ownable::enforce_owner_auth(e);
+        if !limits.is_valid() {
+            panic_with_error!(e, Error::InvalidChannelLimits);
+        }
+        write_limits(e, &limits);
+        bump_instance_ttl(e);
+        LimitsUpdated { limits }.publish(e);
+    }
+
+    pub fn circuit_breaker(e: &Env) -> Option<CircuitBreaker> {
+        read_circuit_breaker(e)
+    }
+
+    /// Sets or, with `None`, removes the outflow circuit breaker. Admin only; the window and
+    /// threshold must be strictly positive. Outflow recorded so far is kept.
+    pub fn set_circuit_breaker(e: &Env, breaker: Option<CircuitBreaker>) {
+        ownable::enforce_owner_auth(e);
+        if breaker.as_ref().is_some_and(|breaker| !breaker.is_valid()) {
+            panic_with_error!(e, Error::InvalidCircuitBreaker);
+        }
+        write_circuit_breaker(e, &breaker);
+        bump_instance_ttl(e);
+        CircuitBreakerUpdated { breaker }.publish(e);
+    }
+
+    pub fn guardian(e: &Env) -> Option<Address> {
+        read_guardian(e)
+    }
+
+    /// Sets or, with `None`, removes the guardian, who may reset the circuit breaker alongside
+    /// the admin. Admin only.
+    pub fn set_guardian(e: &Env, guardian: Option<Address>) {
+        ownable::enforce_owner_auth(e);
+        write_guardian(e, &guardian);
+        bump_instance_ttl(e);
+        GuardianUpdated { guardian }.publish(e);
+    }
+
+    /// Whether the circuit breaker has tripped. A paused channel rejects every `transact`.
+    pub fn is_paused(e: &Env) -> bool {
+        read_paused(e)
+    }
+
+    /// The value withdrawn within the circuit breaker's current window.
+    pub fn outflow(e: &Env) -> i128 {
+        current_outflow(e)
+    }
+
+    /// Unpauses the channel and clears the recorded outflow. `caller` must be the admin or the
+    /// guardian.
+    pub fn reset_circuit_breaker(e: &Env, caller: Address) {
+        caller.require_auth();
+        if ownable::get_owner(e).as_ref() != Some(&caller)
+            && read_guardian(e).as_ref() != Some(&caller)
+        {
+            panic_with_error!(e, Error::NotAdminOrGuardian);
+        }
+        circuit_breaker::reset(e);
+        bump_instance_ttl(e);
+        CircuitBreakerReset { by: caller }.publish(e);
+    }
+
+    pub fn compliance_policy(e: &Env) -> Option<Address> {
+        read_compliance_policy(e)
+    }
+
+    /// Sets or, with `None`, clears the compliance-policy contract that screens every deposit and
+    /// withdrawal leg through the `CompliancePolicy` interface. Admin only.
+    pub fn set_compliance_policy(e: &Env, policy: Option<Address>) {
+        ownable::enforce_owner_auth(e);
+        write_compliance_policy(e, &policy);
+        bump_instance_ttl(e);
+        CompliancePolicyUpdated { policy }.publish(e);
+    }
+
+    /// Extends the TTL of the given unspent UTXOs. Anyone may pay to keep UTXOs alive.
+    pub fn extend_utxos(e: &Env, utxos: Vec<BytesN<65>>) {
+        bump_instance_ttl(e);
+        <Self as UtxoHandlerTrait>::extend_utxos(e, utxos);
+    }
+
     pub fn asset(e: Env) -> Address {
         read_asset(&e)
     }
@@ -104,17 +375,141 @@ impl PrivacyChannelContract {
         read_supply(&e)
     }
 
+    /// Returns the channel's asset balance, supply and unspent UTXO total, and publishes them as a
+    /// `Reconciled` event.
+    pub fn reconcile(e: &Env) -> Reconciliation {
+        let reconciliation = Reconciliation {
+            balance: read_balance(e),
+            supply: read_supply(e),
+            unspent: <Self as UtxoHandlerTrait>::unspent_value(e),
+        };
+        Reconciled {
+            balance: reconciliation.balance,
+            supply: reconciliation.supply,
+            unspent: reconciliation.unspent,
+        }
+        .publish(e);
+        reconciliation
+    }
+
+    /// Moves the asset held above `supply` to `to` and returns the amount moved. Admin only.
+    pub fn skim(e: &Env, to: Address) -> i128 {
+        ownable::enforce_owner_auth(e);
+        bump_instance_ttl(e);
+
+        let amount = skim_surplus(e, &to);
+        if amount > 0 {
+            Skimmed { to, amount }.publish(e);
+        }
+        amount
+    }
+
     pub fn transact(e: Env, op: ChannelOperation) {
+        Self::run_transact(&e, op, None, None);
+    }
+
+    /// `transact`, attaching `payload` to the `Transact` event.
+    pub fn transact_with_payload(e: Env, op: ChannelOperation, payload: Bytes) {
+        Self::run_transact(&e, op, Some(payload), None);
+    }
+
+    /// Runs independent operations under a single Channel Auth authorization.
+    ///
+    /// Each operation is checked and must balance as in `transact`; no UTXO may be spent or
+    /// created by two of them. Deposits and withdrawals are netted into one transfer per address.
+    /// Emits a `Transact` event per operation, in order.
+    pub fn transact_batch(e: Env, ops: Vec<ChannelOperation>) {
         bump_instance_ttl(&e);
         enter_reentrancy_guard(&e);
+        if read_paused(&e) {
+            panic_with_error!(&e, Error::ChannelPaused);
+        }
+        if ops.is_empty() {
+            panic_with_error!(&e, Error::EmptyBatch);
+        }
 
-        let (utxo_op, total_deposit, total_withdraw) =
-            pre_process_channel_operation(&e, op.clone());
+        let mut supply = read_supply(&e);
+        let bundles = pre_process_channel_operations(&e, &ops);
+        Self::process_bundles(&e, &bundles);
+        for op in ops.iter() {
+            recovery::clear_spent_recoveries(&e, &op);
+            recovery::arm_recoveries(&e, &op);
+        }
 
-        Self::process_bundle(&e, utxo_op.clone(), total_deposit, total_withdraw);
+        execute_netted_external_operations(&e, &ops);
 
-        execute_external_operations(&e, op.deposit, op.withdraw);
+        for (op, (_, total_deposit, total_withdraw)) in ops.iter().zip(bundles.iter()) {
+            supply = supply
+                .checked_add(total_deposit)
+                .unwrap_or_else(|| panic_with_error!(&e, Error::AmountOverflow))
+                .checked_sub(total_withdraw)
+                .unwrap_or_else(|| panic_with_error!(&e, Error::AmountUnderflow));
+            Transact::new(&e, &op, supply, None).publish(&e);
+        }
 
         exit_reentrancy_guard(&e);
     }
+
+    pub fn recovery(e: &Env, utxo: BytesN<65>) -> Option<RecoveryConfig> {
+        recovery::read_recovery(e, &utxo)
+    }
+
+    pub fn pending_recovery(e: &Env, utxo: BytesN<65>) -> Option<PendingRecovery> {
+        recovery::read_pending_recovery(e, &utxo)
+    }
+
+    /// Starts a guardian recovery of `utxo` that spends it with `conditions`. The UTXO's guardian
+    /// signs `[Condition::Recovery(utxo, guardian, delay), ..conditions]`, verified by the auth
+    /// contract. The spend runs through `recover` once the delay has passed. Emits
+    /// `RecoveryStarted`.
+    pub fn begin_recovery(e: &Env, utxo: BytesN<65>, conditions: Vec<Condition>) {
+        bump_instance_ttl(e);
+        recovery::begin_recovery(e, utxo, conditions);
+    }
+
+    /// Cancels the pending recovery of `utxo` and removes its guardian. The UTXO's own key signs
+    /// `[Condition::CancelRecovery(utxo)]`. Emits `RecoveryCancelled`.
+    pub fn cancel_recovery(e: &Env, utxo: BytesN<65>) {
+        bump_instance_ttl(e);
+        recovery::cancel_recovery(e, utxo);
+    }
+
+    /// `transact` for an operation that spends `utxo` under its matured pending recovery: that
+    /// spend needs no signature from the UTXO's key, and its conditions must be the ones the
+    /// guardian signed. Emits `Recovered`.
+    pub fn recover(e: Env, utxo: BytesN<65>, op: ChannelOperation) {
+        Self::run_transact(&e, op, None, Some(utxo));
+    }
+}
+
+impl PrivacyChannelContract {
+    fn run_transact(
+        e: &Env,
+        op: ChannelOperation,
+        payload: Option<Bytes>,
+        recovered: Option<BytesN<65>>,
+    ) {
+        bump_instance_ttl(e);
+        enter_reentrancy_guard(e);
+        if read_paused(e) {
+            panic_with_error!(e, Error::ChannelPaused);
+        }
+
+        let (mut utxo_op, total_deposit, total_withdraw) =
+            pre_process_channel_operation(e, op.clone());
+        if let Some(utxo) = recovered {
+            recovery::take_matured_recovery(e, &utxo, &op);
+            utxo_op.req.0.remove(SignerKey::P256(utxo));
+        }
+
+        Self::process_bundle(e, utxo_op.clone(), total_deposit, total_withdraw);
+        recovery::clear_spent_recoveries(e, &op);
+        recovery::arm_recoveries(e, &op);
+
+        execute_external_operations(e, &op);
+
+        Transact::new(e, &op, read_supply(e), payload).publish(e);
+
+        exit_reentrancy_guard(e);
+    }
 }
@@ -2,9 +2,13 @@
 
 extern crate alloc;
 
-mod contract;
+pub mod circuit_breaker;
+pub mod contract;
+pub mod limits;
+pub mod migration;
+pub mod recovery;
 mod storage;
 #[cfg(test)]
 mod test;
-mod transact;
+pub mod transact;
 mod treasury;
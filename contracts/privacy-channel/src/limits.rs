@@ -0,0 +1,104 @@
+use moonlight_errors::Error;
+use moonlight_utxo_core::core::{BundleItem, Violation};
+use soroban_sdk::contracttype;
+
+use crate::transact::ChannelOperation;
+
+/// Admin-configured bounds on what a single `transact` may do. `None` leaves a limit unset.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[contracttype]
+pub struct ChannelLimits {
+    /// The highest supply a bundle may raise the channel to.
+    pub max_supply: Option<i128>,
+    /// The largest amount of a single deposit entry.
+    pub max_deposit: Option<i128>,
+    /// The largest amount of a single withdraw entry.
+    pub max_withdraw: Option<i128>,
+    /// The smallest amount a created UTXO may hold, to keep dust out of the UTXO set.
+    pub min_create: Option<i128>,
+    /// The most UTXOs a bundle may spend.
+    pub max_spends: Option<u32>,
+    /// The most UTXOs a bundle may create.
+    pub max_creates: Option<u32>,
+}
+
+impl ChannelLimits {
+    /// Returns `false` if any set limit is zero or negative.
+    pub fn is_valid(&self) -> bool {
+        let amounts = [
+            self.max_supply,
+            self.max_deposit,
+            self.max_withdraw,
+            self.min_create,
+        ];
+        let counts = [self.max_spends, self.max_creates];
+
+        amounts.into_iter().flatten().all(|limit| limit > 0)
+            && counts.into_iter().flatten().all(|limit| limit > 0)
+    }
+}
+
+/// Checks an operation against the channel's limits, given the supply before the bundle and the
+/// operation's deposit and withdraw totals.
+///
+/// Every violation is passed to `report`; `pre_process_channel_operation` panics on the first. The
+/// supply ceiling only rejects bundles that raise the supply, so withdrawals keep working after the
+/// ceiling is lowered below the current supply.
+pub fn check_channel_limits(
+    op: &ChannelOperation,
+    limits: &ChannelLimits,
+    supply: i128,
+    total_deposit: i128,
+    total_withdraw: i128,
+    report: &mut impl FnMut(Violation),
+) {
+    if limits.max_spends.is_some_and(|max| op.spend.len() > max) {
+        report(Violation::new(Error::TooManySpends, BundleItem::Operation));
+    }
+    if limits.max_creates.is_some_and(|max| op.create.len() > max) {
+        report(Violation::new(Error::TooManyCreates, BundleItem::Operation));
+    }
+
+    if let Some(min) = limits.min_create {
+        for (i, (_utxo, amount)) in op.create.iter().enumerate() {
+            if amount < min {
+                report(Violation::new(
+                    Error::CreateBelowMinimum,
+                    BundleItem::Create(i as u32),
+                ));
+            }
+        }
+    }
+    if let Some(max) = limits.max_deposit {
+        for (i, (_addr, amount, _conds)) in op.deposit.iter().enumerate() {
+            if amount > max {
+                report(Violation::new(
+                    Error::DepositAboveLimit,
+                    BundleItem::Deposit(i as u32),
+                ));
+            }
+        }
+    }
+    if let Some(max) = limits.max_withdraw {
+        for (i, (_addr, amount, _conds)) in op.withdraw.iter().enumerate() {
+            if amount > max {
+                report(Violation::new(
+                    Error::WithdrawAboveLimit,
+                    BundleItem::Withdraw(i as u32),
+                ));
+            }
+        }
+    }
+
+    if let Some(max) = limits.max_supply {
+        let new_supply = supply
+            .saturating_add(total_deposit)
+            .saturating_sub(total_withdraw);
+        if new_supply > supply && new_supply > max {
+            report(Violation::new(
+                Error::SupplyCeilingExceeded,
+                BundleItem::Operation,
+            ));
+        }
+    }
+}
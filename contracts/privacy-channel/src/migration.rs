@@ -0,0 +1,42 @@
+//! Storage schema versions. A wasm upgrade replaces the code but not the storage, so every change
+//! to `PrivacyChannelDataKey`, `UTXOCoreDataKey` or the values stored under them bumps
+//! `SCHEMA_VERSION` and adds the step that rewrites the previous layout. The admin runs the steps
+//! with `migrate` right after `upgrade`.
+
+use moonlight_errors::Error;
+use moonlight_utxo_core::core::UtxoHandlerTrait;
+use soroban_sdk::{panic_with_error, Bytes, Env};
+
+use crate::{
+    contract::PrivacyChannelContract,
+    storage::{read_schema_version, read_supply, write_schema_version},
+};
+
+/// The layout this code reads and writes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Runs every step from the stored version up to `SCHEMA_VERSION` and returns the version it
+/// started from. Fails with `AlreadyMigrated` if storage is already current.
+pub fn migrate(e: &Env, params: &Bytes) -> u32 {
+    let from = read_schema_version(e);
+    if from >= SCHEMA_VERSION {
+        panic_with_error!(e, Error::AlreadyMigrated);
+    }
+    for version in from..SCHEMA_VERSION {
+        migrate_from(e, version, params);
+    }
+    write_schema_version(e, SCHEMA_VERSION);
+    from
+}
+
+/// Rewrites storage from layout `version` to `version + 1`. `params` carries whatever that step
+/// needs from the admin; it is empty unless the step says otherwise.
+fn migrate_from(e: &Env, version: u32, _params: &Bytes) {
+    match version {
+        // Unversioned deployments keep each UTXO under its own `UTXOCoreDataKey::UTXO` entry, with
+        // `0` marking a spent one, which version 1 still reads. What they lack is the unspent
+        // total. Every bundle balances, so the UTXOs still unspent hold exactly the supply.
+        0 => <PrivacyChannelContract as UtxoHandlerTrait>::set_unspent_value(e, read_supply(e)),
+        _ => panic_with_error!(e, Error::MissingMigrationStep),
+    }
+}
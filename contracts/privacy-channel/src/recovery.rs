@@ -0,0 +1,304 @@
+//! Guardian recovery for UTXOs whose key is lost.
+//!
+//! A UTXO gets a guardian at creation, when every signer of its `Condition::Create` also signs a
+//! `Condition::Recovery(utxo, guardian, delay)`. The guardian's P256 key can later begin a recovery
+//! spend with `begin_recovery`; it becomes executable through `recover` after `delay` ledgers.
+//! Until then the UTXO's own key can cancel it with a `Condition::CancelRecovery`, which also
+//! removes the guardian, or simply spend the UTXO, which removes both.
+//!
+//! The guardian is chosen by the signers of the `Create`, the sender or depositor, not by the
+//! holder of the created key. A sender can therefore make themselves guardian of a payment and
+//! claw it back `delay` ledgers after `begin_recovery` unless the recipient cancels or spends it
+//! first. Arming a guardian emits `RecoveryArmed`, and recipients should check for it (or
+//! `recovery(utxo)`) before treating a received UTXO as settled.
+
+use moonlight_errors::Error;
+use moonlight_primitives::{equal_condition_sequence, AuthRequirements, Condition, SignerKey};
+use moonlight_utxo_core::core::{hash_utxo_key, BundleItem, UtxoHandlerTrait, Violation};
+use soroban_sdk::{
+    contractevent, contracttype, panic_with_error, vec, Address, BytesN, Env, IntoVal, Map, Vec,
+};
+
+use crate::{
+    contract::PrivacyChannelContract, storage::PrivacyChannelDataKey, transact::ChannelOperation,
+};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RecoveryConfig {
+    /// SEC1 uncompressed P256 key that may begin a recovery.
+    pub guardian: BytesN<65>,
+    /// Ledgers between `begin_recovery` and the earliest `recover`.
+    pub delay: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingRecovery {
+    /// The spend conditions the guardian signed; `recover` must spend the UTXO with exactly these.
+    pub conditions: Vec<Condition>,
+    /// First ledger at which `recover` may run.
+    pub executable_at: u32,
+}
+
+#[contractevent]
+pub struct RecoveryArmed {
+    #[topic]
+    pub utxo: BytesN<32>,
+    pub guardian: BytesN<65>,
+    pub delay: u32,
+}
+
+#[contractevent(data_format = "single-value")]
+pub struct RecoveryStarted {
+    #[topic]
+    pub utxo: BytesN<32>,
+    pub executable_at: u32,
+}
+
+#[contractevent(data_format = "single-value")]
+pub struct RecoveryCancelled {
+    #[topic]
+    pub utxo: BytesN<32>,
+    pub guardian: BytesN<65>,
+}
+
+#[contractevent(data_format = "single-value")]
+pub struct Recovered {
+    #[topic]
+    pub utxo: BytesN<32>,
+    pub guardian: BytesN<65>,
+}
+
+fn bump(e: &Env, key: &PrivacyChannelDataKey) {
+    let policy = <PrivacyChannelContract as UtxoHandlerTrait>::ttl_policy(e);
+    e.storage()
+        .persistent()
+        .extend_ttl(key, policy.threshold, policy.extend_to);
+}
+
+pub fn read_recovery(e: &Env, utxo: &BytesN<65>) -> Option<RecoveryConfig> {
+    e.storage()
+        .persistent()
+        .get(&PrivacyChannelDataKey::Recovery(hash_utxo_key(e, utxo)))
+}
+
+pub fn read_pending_recovery(e: &Env, utxo: &BytesN<65>) -> Option<PendingRecovery> {
+    e.storage()
+        .persistent()
+        .get(&PrivacyChannelDataKey::PendingRecovery(hash_utxo_key(
+            e, utxo,
+        )))
+}
+
+fn clear(e: &Env, hash: &BytesN<32>) {
+    e.storage()
+        .persistent()
+        .remove(&PrivacyChannelDataKey::Recovery(hash.clone()));
+    e.storage()
+        .persistent()
+        .remove(&PrivacyChannelDataKey::PendingRecovery(hash.clone()));
+}
+
+/// Requires `signer` to have signed `conditions`, verified by the auth contract like a spend.
+fn require_p256_auth(e: &Env, signer: &BytesN<65>, conditions: Vec<Condition>) {
+    let mut req = Map::new(e);
+    req.set(SignerKey::P256(signer.clone()), conditions);
+    let auth: Address = <PrivacyChannelContract as UtxoHandlerTrait>::auth(e);
+    auth.require_auth_for_args(vec![e, AuthRequirements(req).into_val(e)]);
+}
+
+/// The signed condition lists of an operation: spend and deposit conditions. Withdraw conditions
+/// are unsigned.
+fn signed_condition_lists(e: &Env, op: &ChannelOperation) -> Vec<Vec<Condition>> {
+    let mut lists = Vec::new(e);
+    for (_, conditions) in op.spend.iter() {
+        lists.push_back(conditions);
+    }
+    for (_, _, conditions) in op.deposit.iter() {
+        lists.push_back(conditions);
+    }
+    lists
+}
+
+fn creates(conditions: &Vec<Condition>, utxo: &BytesN<65>) -> bool {
+    conditions
+        .iter()
+        .any(|c| matches!(c, Condition::Create(created, _) if &created == utxo))
+}
+
+/// Checks that every `Recovery` condition has a non-zero delay and is signed by exactly the
+/// signers of its UTXO's `Create`, and that no `CancelRecovery` condition appears in a bundle.
+///
+/// ### Violations
+/// - `InvalidRecoveryCondition` for each offending condition list.
+pub fn check_recovery_conditions(
+    e: &Env,
+    op: &ChannelOperation,
+    report: &mut impl FnMut(Violation),
+) {
+    let lists = signed_condition_lists(e, op);
+
+    let mut recoveries: Map<BytesN<65>, Condition> = Map::new(e);
+    for conditions in lists.iter() {
+        for cond in conditions.iter() {
+            if let Condition::Recovery(utxo, _, delay) = &cond {
+                if *delay == 0 || !creates(&conditions, utxo) {
+                    report(Violation::new(
+                        Error::InvalidRecoveryCondition,
+                        BundleItem::Operation,
+                    ));
+                }
+                recoveries.set(utxo.clone(), cond.clone());
+            }
+        }
+    }
+
+    // A recovery binds every signer of the create, so a co-signer cannot add a guardian alone.
+    for conditions in lists.iter() {
+        for (utxo, recovery) in recoveries.iter() {
+            if creates(&conditions, &utxo) && !conditions.contains(&recovery) {
+                report(Violation::new(
+                    Error::InvalidRecoveryCondition,
+                    BundleItem::Operation,
+                ));
+            }
+        }
+    }
+
+    // A cancel signature authorizes nothing else; in a spend it would leave the value unclaimed.
+    let withdraw_conditions = op.withdraw.iter().map(|(_, _, conditions)| conditions);
+    for conditions in lists.iter().chain(withdraw_conditions) {
+        if conditions
+            .iter()
+            .any(|c| matches!(c, Condition::CancelRecovery(..)))
+        {
+            report(Violation::new(
+                Error::InvalidRecoveryCondition,
+                BundleItem::Operation,
+            ));
+        }
+    }
+}
+
+/// Removes the guardian and any pending recovery of every UTXO the operation spends.
+///
+/// A pending recovery only exists next to a guardian, so a spend without one reads a single entry
+/// and writes none.
+pub fn clear_spent_recoveries(e: &Env, op: &ChannelOperation) {
+    for (utxo, _) in op.spend.iter() {
+        let hash = hash_utxo_key(e, &utxo);
+        if e.storage()
+            .persistent()
+            .has(&PrivacyChannelDataKey::Recovery(hash.clone()))
+        {
+            clear(e, &hash);
+        }
+    }
+}
+
+/// Stores the guardian of every UTXO the operation creates with a `Recovery` condition and emits
+/// `RecoveryArmed` for each.
+pub fn arm_recoveries(e: &Env, op: &ChannelOperation) {
+    let mut armed: Map<BytesN<32>, RecoveryConfig> = Map::new(e);
+    for conditions in signed_condition_lists(e, op).iter() {
+        for cond in conditions.iter() {
+            if let Condition::Recovery(utxo, guardian, delay) = cond {
+                armed.set(hash_utxo_key(e, &utxo), RecoveryConfig { guardian, delay });
+            }
+        }
+    }
+
+    for (utxo, config) in armed.iter() {
+        let key = PrivacyChannelDataKey::Recovery(utxo.clone());
+        e.storage().persistent().set(&key, &config);
+        bump(e, &key);
+        RecoveryArmed {
+            utxo,
+            guardian: config.guardian,
+            delay: config.delay,
+        }
+        .publish(e);
+    }
+}
+
+pub fn begin_recovery(e: &Env, utxo: BytesN<65>, conditions: Vec<Condition>) {
+    let config =
+        read_recovery(e, &utxo).unwrap_or_else(|| panic_with_error!(e, Error::RecoveryNotSet));
+    if read_pending_recovery(e, &utxo).is_some() {
+        panic_with_error!(e, Error::RecoveryPending);
+    }
+    if conditions.is_empty() {
+        panic_with_error!(e, Error::InvalidRecoveryCondition);
+    }
+    <PrivacyChannelContract as UtxoHandlerTrait>::verify_utxo_unspent(e, utxo.clone());
+
+    // The leading `Recovery` condition keeps the guardian's signature from doubling as a spend.
+    let mut signed = vec![
+        e,
+        Condition::Recovery(utxo.clone(), config.guardian.clone(), config.delay),
+    ];
+    signed.append(&conditions);
+    require_p256_auth(e, &config.guardian, signed);
+
+    let hash = hash_utxo_key(e, &utxo);
+    let executable_at = e.ledger().sequence().saturating_add(config.delay);
+    let key = PrivacyChannelDataKey::PendingRecovery(hash.clone());
+    e.storage().persistent().set(
+        &key,
+        &PendingRecovery {
+            conditions,
+            executable_at,
+        },
+    );
+    bump(e, &key);
+    bump(e, &PrivacyChannelDataKey::Recovery(hash.clone()));
+    RecoveryStarted {
+        utxo: hash,
+        executable_at,
+    }
+    .publish(e);
+}
+
+pub fn cancel_recovery(e: &Env, utxo: BytesN<65>) {
+    let config =
+        read_recovery(e, &utxo).unwrap_or_else(|| panic_with_error!(e, Error::RecoveryNotSet));
+    if read_pending_recovery(e, &utxo).is_none() {
+        panic_with_error!(e, Error::RecoveryNotPending);
+    }
+    require_p256_auth(e, &utxo, vec![e, Condition::CancelRecovery(utxo.clone())]);
+
+    let hash = hash_utxo_key(e, &utxo);
+    clear(e, &hash);
+    RecoveryCancelled {
+        utxo: hash,
+        guardian: config.guardian,
+    }
+    .publish(e);
+}
+
+/// Consumes the matured pending recovery of `utxo`, which `op` must spend with exactly the
+/// conditions the guardian signed. The caller then drops the UTXO key's own auth requirement.
+pub fn take_matured_recovery(e: &Env, utxo: &BytesN<65>, op: &ChannelOperation) {
+    let config =
+        read_recovery(e, utxo).unwrap_or_else(|| panic_with_error!(e, Error::RecoveryNotSet));
+    let pending = read_pending_recovery(e, utxo)
+        .unwrap_or_else(|| panic_with_error!(e, Error::RecoveryNotPending));
+    if e.ledger().sequence() < pending.executable_at {
+        panic_with_error!(e, Error::RecoveryNotReady);
+    }
+    let spends_as_signed = op.spend.iter().any(|(spent, conditions)| {
+        &spent == utxo && equal_condition_sequence(e, &conditions, &pending.conditions)
+    });
+    if !spends_as_signed {
+        panic_with_error!(e, Error::UnauthorizedOperation);
+    }
+
+    let hash = hash_utxo_key(e, utxo);
+    clear(e, &hash);
+    Recovered {
+        utxo: hash,
+        guardian: config.guardian,
+    }
+    .publish(e);
+}
@@ -1,10 +1,21 @@
-use soroban_sdk::{contracttype, Address, Env};
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+use crate::{circuit_breaker::CircuitBreaker, limits::ChannelLimits};
 
 #[derive(Clone)]
 #[contracttype]
 pub enum PrivacyChannelDataKey {
-    Asset,  //Address
-    Supply, //i128
+    Asset,                       //Address
+    Supply,                      //i128
+    Limits,                      //ChannelLimits
+    CircuitBreaker,              //CircuitBreaker
+    Outflow,                     //Vec<(u32, i128)>
+    Paused,                      //bool
+    Guardian,                    //Address
+    Compliance,                  //Address
+    SchemaVersion,               //u32
+    Recovery(BytesN<32>),        //RecoveryConfig, persistent, by UTXO hash
+    PendingRecovery(BytesN<32>), //PendingRecovery, persistent, by UTXO hash
 }
 
 pub fn write_asset_unchecked(e: &Env, asset: Address) {
@@ -20,6 +31,20 @@ pub fn read_asset(e: &Env) -> Address {
         .unwrap()
 }
 
+pub fn write_schema_version(e: &Env, version: u32) {
+    e.storage()
+        .instance()
+        .set(&PrivacyChannelDataKey::SchemaVersion, &version);
+}
+
+/// `0` for channels deployed before the schema version was stored.
+pub fn read_schema_version(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get(&PrivacyChannelDataKey::SchemaVersion)
+        .unwrap_or(0)
+}
+
 pub fn write_supply_unchecked(e: &Env, supply: i128) {
     e.storage()
         .instance()
@@ -32,3 +57,97 @@ pub fn read_supply(e: &Env) -> i128 {
         .get(&PrivacyChannelDataKey::Supply)
         .unwrap_or(0)
 }
+
+pub fn write_limits(e: &Env, limits: &ChannelLimits) {
+    e.storage()
+        .instance()
+        .set(&PrivacyChannelDataKey::Limits, limits);
+}
+
+pub fn read_limits(e: &Env) -> ChannelLimits {
+    e.storage()
+        .instance()
+        .get(&PrivacyChannelDataKey::Limits)
+        .unwrap_or_default()
+}
+
+pub fn write_circuit_breaker(e: &Env, breaker: &Option<CircuitBreaker>) {
+    match breaker {
+        Some(breaker) => e
+            .storage()
+            .instance()
+            .set(&PrivacyChannelDataKey::CircuitBreaker, breaker),
+        None => e
+            .storage()
+            .instance()
+            .remove(&PrivacyChannelDataKey::CircuitBreaker),
+    }
+}
+
+pub fn read_circuit_breaker(e: &Env) -> Option<CircuitBreaker> {
+    e.storage()
+        .instance()
+        .get(&PrivacyChannelDataKey::CircuitBreaker)
+}
+
+pub fn write_outflow(e: &Env, buckets: &Vec<(u32, i128)>) {
+    e.storage()
+        .instance()
+        .set(&PrivacyChannelDataKey::Outflow, buckets);
+}
+
+pub fn read_outflow(e: &Env) -> Vec<(u32, i128)> {
+    e.storage()
+        .instance()
+        .get(&PrivacyChannelDataKey::Outflow)
+        .unwrap_or_else(|| Vec::new(e))
+}
+
+pub fn write_paused(e: &Env, paused: bool) {
+    e.storage()
+        .instance()
+        .set(&PrivacyChannelDataKey::Paused, &paused);
+}
+
+pub fn read_paused(e: &Env) -> bool {
+    e.storage()
+        .instance()
+        .get(&PrivacyChannelDataKey::Paused)
+        .unwrap_or(false)
+}
+
+pub fn write_guardian(e: &Env, guardian: &Option<Address>) {
+    match guardian {
+        Some(guardian) => e
+            .storage()
+            .instance()
+            .set(&PrivacyChannelDataKey::Guardian, guardian),
+        None => e
+            .storage()
+            .instance()
+            .remove(&PrivacyChannelDataKey::Guardian),
+    }
+}
+
+pub fn read_guardian(e: &Env) -> Option<Address> {
+    e.storage().instance().get(&PrivacyChannelDataKey::Guardian)
+}
+
+pub fn write_compliance_policy(e: &Env, policy: &Option<Address>) {
+    match policy {
+        Some(policy) => e
+            .storage()
+            .instance()
+            .set(&PrivacyChannelDataKey::Compliance, policy),
+        None => e
+            .storage()
+            .instance()
+            .remove(&PrivacyChannelDataKey::Compliance),
+    }
+}
+
+pub fn read_compliance_policy(e: &Env) -> Option<Address> {
+    e.storage()
+        .instance()
+        .get(&PrivacyChannelDataKey::Compliance)
+}
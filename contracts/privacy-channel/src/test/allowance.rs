@@ -0,0 +1,288 @@
+#![cfg(test)]
+//! Allowance deposits: the channel pulls the deposit with `transfer_from`, so the depositor only
+//! authorizes its conditions and signs no nested transfer.
+extern crate std;
+
+use crate::{
+    contract::PrivacyChannelContractClient,
+    test::test::{builder, channel_operation, create_contracts, sign_deposit, sign_provider},
+    transact::ChannelOperation,
+};
+use moonlight_errors::Error as ContractError;
+use moonlight_helpers::testutils::{
+    keys::P256KeyPair,
+    snapshot::{get_env_with_g_accounts, get_snapshot_g_accounts},
+};
+use moonlight_primitives::Condition;
+use soroban_sdk::{
+    contract, contractimpl,
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, MockAuth, MockAuthInvoke},
+    token::TokenClient,
+    vec, Address, BytesN, Env, Error, IntoVal, Map, Symbol, Vec,
+};
+
+/// A contract account that deposits its own funds: it approves the channel and calls `transact`
+/// itself, which is all the authorization it can give.
+#[contract]
+pub struct Vault;
+
+#[contractimpl]
+impl Vault {
+    pub fn deposit(e: Env, channel: Address, amount: i128, op: ChannelOperation) {
+        let channel = PrivacyChannelContractClient::new(&e, &channel);
+        TokenClient::new(&e, &channel.asset()).approve(
+            &e.current_contract_address(),
+            &channel.address,
+            &amount,
+            &(e.ledger().sequence() + 1),
+        );
+        channel.transact(&op);
+    }
+}
+
+fn deposit_operation(
+    e: &Env,
+    from: &Address,
+    utxo: &BytesN<65>,
+    amount: i128,
+    allowance_deposits: Vec<Address>,
+) -> ChannelOperation {
+    ChannelOperation {
+        spend: vec![e],
+        create: vec![e, (utxo.clone(), amount)],
+        deposit: vec![
+            e,
+            (
+                from.clone(),
+                amount,
+                vec![
+                    e,
+                    Condition::ExtDeposit(from.clone(), amount),
+                    Condition::Create(utxo.clone(), amount),
+                ],
+            ),
+        ],
+        withdraw: vec![e],
+        notes: Map::new(e),
+        ephemeral_keys: Map::new(e),
+        allowance_deposits,
+    }
+}
+
+#[test]
+fn test_contract_account_deposits_from_allowance() {
+    let e = Env::default();
+    let (channel, auth, token, _) = create_contracts(&e);
+    let vault = e.register(Vault, ());
+    let vault_client = VaultClient::new(&e, &vault);
+    token.mock_all_auths().mint(&vault, &500_i128);
+    let utxo = P256KeyPair::generate(&e).public_key;
+
+    // Only the provider's Channel Auth entry is supplied: the vault signs nothing.
+    let provider_only = |op: &ChannelOperation| {
+        vault_client
+            .mock_auths(&[MockAuth {
+                address: &auth.address,
+                invoke: &MockAuthInvoke {
+                    contract: &channel.address,
+                    fn_name: "transact",
+                    args: ().into_val(&e),
+                    sub_invokes: &[],
+                },
+            }])
+            .try_deposit(&channel.address, &500_i128, op)
+            .is_ok()
+    };
+
+    // A plain deposit needs the vault to authorize the channel's nested `transfer`.
+    assert!(!provider_only(&deposit_operation(
+        &e,
+        &vault,
+        &utxo,
+        500,
+        vec![&e]
+    )));
+
+    assert!(provider_only(&deposit_operation(
+        &e,
+        &vault,
+        &utxo,
+        500,
+        vec![&e, vault.clone()],
+    )));
+    assert_eq!(token.balance(&vault), 0);
+    assert_eq!(token.balance(&channel.address), 500);
+    assert_eq!(token.allowance(&vault, &channel.address), 0);
+    assert_eq!(channel.utxo_balance(&utxo), 500);
+    assert_eq!(channel.supply(), 500);
+}
+
+#[test]
+fn test_allowance_deposit_still_binds_conditions() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let depositor = Address::generate(&e);
+    let utxo = P256KeyPair::generate(&e).public_key;
+    token.mint(&depositor, &500_i128);
+    token.approve(&depositor, &channel.address, &500_i128, &100);
+
+    let op = deposit_operation(&e, &depositor, &utxo, 500, vec![&e, depositor.clone()]);
+    channel.transact(&op);
+
+    // The depositor authorizes its conditions, with no transfer beneath them.
+    let depositor_auths: std::vec::Vec<_> = e
+        .auths()
+        .into_iter()
+        .filter(|(address, _)| *address == depositor)
+        .collect();
+    assert_eq!(
+        depositor_auths,
+        std::vec![(
+            depositor.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    channel.address.clone(),
+                    Symbol::new(&e, "transact"),
+                    (
+                        vec![
+                            &e,
+                            Condition::ExtDeposit(depositor.clone(), 500_i128),
+                            Condition::Create(utxo.clone(), 500_i128),
+                        ],
+                        true,
+                    )
+                        .into_val(&e),
+                )),
+                sub_invocations: std::vec![],
+            }
+        )]
+    );
+    assert_eq!(token.balance(&depositor), 0);
+    assert_eq!(channel.utxo_balance(&utxo), 500);
+}
+
+#[test]
+fn test_allowance_deposit_rejections() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let (depositor, stranger) = (Address::generate(&e), Address::generate(&e));
+    let utxo = P256KeyPair::generate(&e).public_key;
+    token.mint(&depositor, &500_i128);
+
+    assert_eq!(
+        channel
+            .try_transact(&deposit_operation(
+                &e,
+                &depositor,
+                &utxo,
+                500,
+                vec![&e, stranger],
+            ))
+            .err(),
+        Some(Ok(Error::from_contract_error(
+            ContractError::AllowanceWithoutDeposit as u32
+        )))
+    );
+
+    // Without a signed `ExtDeposit`, the provider could raise the amount pulled.
+    let mut unbound = deposit_operation(&e, &depositor, &utxo, 500, vec![&e, depositor.clone()]);
+    let (from, amount, _) = unbound.deposit.get_unchecked(0);
+    unbound.deposit.set(
+        0,
+        (
+            from,
+            amount,
+            vec![&e, Condition::Create(utxo.clone(), 500_i128)],
+        ),
+    );
+    assert_eq!(
+        channel.try_transact(&unbound).err(),
+        Some(Ok(Error::from_contract_error(
+            ContractError::UnauthorizedOperation as u32
+        )))
+    );
+
+    // An allowance below the deposit fails in the token.
+    token.approve(&depositor, &channel.address, &499_i128, &100);
+    let op = deposit_operation(&e, &depositor, &utxo, 500, vec![&e, depositor.clone()]);
+    assert!(channel.try_transact(&op).is_err());
+    assert_eq!(token.balance(&depositor), 500);
+    assert_eq!(channel.utxo_balance(&utxo), -1);
+}
+
+/// Real signatures: the provider cannot inflate a signed allowance deposit, nor turn a signed
+/// plain deposit into an allowance deposit.
+#[test]
+fn test_allowance_deposit_binds_the_signed_amount() {
+    let e = get_env_with_g_accounts();
+    let (provider, depositor, _, _, _) = get_snapshot_g_accounts(&e);
+    let (channel, auth, token, _) = create_contracts(&e);
+    auth.mock_all_auths().add_provider(&provider.address);
+    token.mock_all_auths().mint(&depositor.address, &1000_i128);
+    let live = e.ledger().sequence() + 100;
+    token
+        .mock_all_auths()
+        .approve(&depositor.address, &channel.address, &1000_i128, &live);
+    e.set_auths(&[]);
+    let (utxo, attacker_utxo) = (
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+    );
+    let conditions = vec![
+        &e,
+        Condition::ExtDeposit(depositor.address.clone(), 500_i128),
+        Condition::Create(utxo.clone(), 500_i128),
+    ];
+
+    let mut op = builder(&e, &channel, &auth, &token.address);
+    op.add_allowance_deposit(&e, depositor.address.clone(), 500, conditions.clone())
+        .unwrap();
+    op.add_create(utxo.clone(), 500).unwrap();
+    sign_provider(&e, &mut op, &provider, 0, live);
+    sign_deposit(&e, &mut op, &depositor, 0, live);
+    let entries = op.auth_entries(&e, 0, live).unwrap();
+
+    // Pull the whole allowance and keep the surplus in an extra create. Every entry still
+    // matches: the bundle spends nothing and the depositor's conditions are unchanged.
+    let mut inflated = channel_operation(&e, &op.operation());
+    inflated.deposit.set(
+        0,
+        (depositor.address.clone(), 1000_i128, conditions.clone()),
+    );
+    inflated.create.push_back((attacker_utxo.clone(), 500_i128));
+    assert_eq!(
+        channel.set_auths(&entries).try_transact(&inflated).err(),
+        Some(Ok(Error::from_contract_error(
+            ContractError::UnauthorizedOperation as u32
+        )))
+    );
+
+    // A plain deposit's signature does not authorize spending the allowance.
+    let mut plain = builder(&e, &channel, &auth, &token.address);
+    plain
+        .add_deposit(&e, depositor.address.clone(), 500, conditions)
+        .unwrap();
+    plain.add_create(utxo.clone(), 500).unwrap();
+    sign_provider(&e, &mut plain, &provider, 0, live);
+    sign_deposit(&e, &mut plain, &depositor, 0, live);
+    let mut switched = channel_operation(&e, &plain.operation());
+    switched
+        .allowance_deposits
+        .push_back(depositor.address.clone());
+    assert!(channel
+        .set_auths(&plain.auth_entries(&e, 0, live).unwrap())
+        .try_transact(&switched)
+        .is_err());
+
+    assert_eq!(token.balance(&depositor.address), 1000);
+    assert_eq!(channel.utxo_balance(&attacker_utxo), -1);
+
+    channel
+        .set_auths(&entries)
+        .transact(&channel_operation(&e, &op.operation()));
+    assert_eq!(token.balance(&depositor.address), 500);
+    assert_eq!(channel.utxo_balance(&utxo), 500);
+    assert_eq!(token.allowance(&depositor.address, &channel.address), 500);
+}
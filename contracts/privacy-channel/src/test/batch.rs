@@ -0,0 +1,457 @@
+#![cfg(test)]
+//! `transact_batch`: independent operations under one Channel Auth authorization, with the token
+//! transfers netted per address.
+extern crate std;
+
+use crate::{
+    contract::{PrivacyChannelContractClient, Transact},
+    test::test::{builder, channel_operation, context, create_contracts, sign_deposit, sign_spend},
+    transact::ChannelOperation,
+};
+use channel_auth_contract::contract::ChannelAuthContractClient;
+use moonlight_client::{ChannelBatchBuilder, ChannelOperationBuilder};
+use moonlight_errors::Error as ContractError;
+use moonlight_helpers::testutils::{
+    keys::{Ed25519Account, P256KeyPair},
+    snapshot::{get_env_with_g_accounts, get_snapshot_g_accounts},
+};
+use moonlight_primitives::Condition;
+use moonlight_utxo_core::core::hash_utxo_key;
+use soroban_sdk::{
+    testutils::{Address as _, Events, Ledger},
+    vec, Address, BytesN, Env, Error, Event, Map, Vec,
+};
+use token_contract::TestTokenClient as TokenClient;
+
+const OPS: u32 = 4;
+
+fn operation(
+    e: &Env,
+    spend: Vec<(BytesN<65>, Vec<Condition>)>,
+    create: Vec<(BytesN<65>, i128)>,
+    deposit: Vec<(Address, i128, Vec<Condition>)>,
+    withdraw: Vec<(Address, i128, Vec<Condition>)>,
+) -> ChannelOperation {
+    ChannelOperation {
+        spend,
+        create,
+        deposit,
+        withdraw,
+        notes: Map::new(e),
+        ephemeral_keys: Map::new(e),
+        allowance_deposits: vec![e],
+    }
+}
+
+/// A channel whose provider is registered and that holds one UTXO of 100 per returned key pair.
+fn funded_channel(
+    e: &Env,
+) -> (
+    PrivacyChannelContractClient<'_>,
+    ChannelAuthContractClient<'_>,
+    Ed25519Account,
+    std::vec::Vec<P256KeyPair>,
+) {
+    let (provider, depositor, _, _, _) = get_snapshot_g_accounts(e);
+    let (channel, auth, token, _) = create_contracts(e);
+    let owners: std::vec::Vec<P256KeyPair> = (0..OPS).map(|_| P256KeyPair::generate(e)).collect();
+
+    e.mock_all_auths();
+    auth.add_provider(&provider.address);
+    token.mint(&depositor.address, &(100 * OPS as i128));
+    let mut create = vec![e];
+    for owner in owners.iter() {
+        create.push_back((owner.public_key.clone(), 100_i128));
+    }
+    channel.transact(&operation(
+        e,
+        vec![e],
+        create,
+        vec![e, (depositor.address, 100 * OPS as i128, vec![e])],
+        vec![e],
+    ));
+    e.set_auths(&[]);
+    e.ledger().set_sequence_number(3);
+
+    (channel, auth, provider, owners)
+}
+
+/// Adds a transfer of `owner`'s UTXO to a fresh one to `op`, signed by the owner.
+fn add_transfer(e: &Env, op: &mut ChannelOperationBuilder, owner: &P256KeyPair, live: u32) {
+    let to = P256KeyPair::generate(e).public_key;
+    op.add_create(to.clone(), 100_i128).unwrap();
+    op.add_spend(
+        owner.public_key.clone(),
+        vec![e, Condition::Create(to, 100_i128)],
+    )
+    .unwrap();
+    sign_spend(e, op, owner, live);
+}
+
+/// The batch's budget against the same transfers submitted one `transact` at a time, with real
+/// spend and provider signatures so every `__check_auth` runs.
+#[test]
+fn test_batch_saves_per_operation() {
+    let e = get_env_with_g_accounts();
+    let (channel, auth, provider, owners) = funded_channel(&e);
+    let asset = Address::generate(&e);
+    let live = e.ledger().sequence() + 100;
+    let mut sequential = 0_i64;
+    for (nonce, owner) in owners.iter().enumerate() {
+        let mut op = builder(&e, &channel, &auth, &asset);
+        add_transfer(&e, &mut op, owner, live);
+        let hash = op.bundle_payload_hash(&e, nonce as i64, live).unwrap();
+        op.add_provider_signature(&provider.address, provider.sign(&e, hash), live)
+            .unwrap();
+
+        channel
+            .set_auths(&op.auth_entries(&e, nonce as i64, live).unwrap())
+            .transact(&channel_operation(&e, &op.operation()));
+        sequential += e.cost_estimate().resources().instructions;
+    }
+
+    let e = get_env_with_g_accounts();
+    let (channel, auth, provider, owners) = funded_channel(&e);
+    let asset = Address::generate(&e);
+    let live = e.ledger().sequence() + 100;
+    // The auth entry covers every spend of the batch; each operation carries its own.
+    let mut batch = ChannelBatchBuilder::new(&e, context(&e, &channel, &auth, &asset));
+    for owner in owners.iter() {
+        let mut op = batch.operation_builder(&e);
+        add_transfer(&e, &mut op, owner, live);
+        batch.push(op).unwrap();
+    }
+    let hash = batch.bundle_payload_hash(&e, 0, live).unwrap();
+    batch
+        .add_provider_signature(&provider.address, provider.sign(&e, hash), live)
+        .unwrap();
+
+    let mut ops = vec![&e];
+    for op in batch.operations(&e).iter() {
+        ops.push_back(channel_operation(&e, &op));
+    }
+    channel
+        .set_auths(&batch.auth_entries(&e, 0, live).unwrap())
+        .transact_batch(&ops);
+    let batch = e.cost_estimate().resources().instructions;
+
+    for op in ops.iter() {
+        let (created, _) = op.create.get_unchecked(0);
+        assert_eq!(channel.utxo_balance(&created), 100);
+    }
+    // Each operation after the first skips the instance reads, the token client setup and the
+    // provider's `__check_auth`.
+    let saving_per_op = (sequential - batch) / (OPS as i64 - 1);
+    assert!(
+        saving_per_op > sequential / OPS as i64 / 10,
+        "{saving_per_op}"
+    );
+}
+
+#[test]
+fn test_batch_nets_transfers_per_address() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let (alice, bob) = (Address::generate(&e), Address::generate(&e));
+    let (a, b) = (
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+    );
+    token.mint(&alice, &300_i128);
+    token.mint(&bob, &100_i128);
+    channel.transact(&operation(
+        &e,
+        vec![&e],
+        vec![&e, (b.clone(), 100_i128)],
+        vec![&e, (bob.clone(), 100_i128, vec![&e])],
+        vec![&e],
+    ));
+
+    // Alice deposits 300 while Bob withdraws 100 and deposits 40 back from his allowance.
+    let live = e.ledger().sequence() + 100;
+    token.approve(&bob, &channel.address, &40_i128, &live);
+    let mut deposit_back = operation(
+        &e,
+        vec![&e],
+        vec![&e, (P256KeyPair::generate(&e).public_key, 40_i128)],
+        vec![
+            &e,
+            (
+                bob.clone(),
+                40_i128,
+                vec![&e, Condition::ExtDeposit(bob.clone(), 40_i128)],
+            ),
+        ],
+        vec![&e],
+    );
+    deposit_back.allowance_deposits = vec![&e, bob.clone()];
+    let ops = vec![
+        &e,
+        operation(
+            &e,
+            vec![&e],
+            vec![&e, (a.clone(), 300_i128)],
+            vec![&e, (alice.clone(), 300_i128, vec![&e])],
+            vec![&e],
+        ),
+        operation(
+            &e,
+            vec![&e, (b.clone(), vec![&e])],
+            vec![&e],
+            vec![&e],
+            vec![&e, (bob.clone(), 100_i128, vec![&e])],
+        ),
+        deposit_back,
+    ];
+    channel.transact_batch(&ops);
+    let events = e.events().all();
+
+    assert_eq!(token.balance(&alice), 0);
+    assert_eq!(token.balance(&bob), 60);
+    // Bob's legs netted to a single transfer out, so his allowance was never drawn.
+    assert_eq!(token.allowance(&bob, &channel.address), 40);
+    assert_eq!(token.balance(&channel.address), 340);
+    assert_eq!(channel.supply(), 340);
+    assert_eq!(channel.utxo_balance(&a), 300);
+    assert_eq!(channel.utxo_balance(&b), 0);
+
+    // One `Transact` per operation, each with the supply it left.
+    let events = events.events();
+    assert_eq!(
+        events.last().unwrap(),
+        &Transact {
+            spent: vec![&e],
+            created: vec![
+                &e,
+                hash_utxo_key(&e, &ops.get_unchecked(2).create.get_unchecked(0).0)
+            ],
+            notes: vec![&e],
+            ephemeral_keys: vec![&e],
+            deposits: vec![&e, (bob.clone(), 40_i128)],
+            withdrawals: vec![&e],
+            supply: 340,
+            payload: None,
+        }
+        .to_xdr(&e, &channel.address)
+    );
+    assert!(events.contains(
+        &Transact {
+            spent: vec![&e, hash_utxo_key(&e, &b)],
+            created: vec![&e],
+            notes: vec![&e],
+            ephemeral_keys: vec![&e],
+            deposits: vec![&e],
+            withdrawals: vec![&e, (bob, 100_i128)],
+            supply: 300,
+            payload: None,
+        }
+        .to_xdr(&e, &channel.address)
+    ));
+}
+
+/// Adds a deposit of `amount` from `depositor` into a fresh UTXO to `op`, signed by the
+/// depositor with `nonce`.
+fn add_signed_deposit(
+    e: &Env,
+    op: &mut ChannelOperationBuilder,
+    depositor: &Ed25519Account,
+    amount: i128,
+    nonce: i64,
+    live: u32,
+) {
+    let to = P256KeyPair::generate(e).public_key;
+    op.add_create(to.clone(), amount).unwrap();
+    op.add_deposit(
+        e,
+        depositor.address.clone(),
+        amount,
+        vec![e, Condition::Create(to, amount)],
+    )
+    .unwrap();
+    sign_deposit(e, op, depositor, nonce, live);
+}
+
+/// Adds a withdrawal of `owner`'s UTXO of 100 to `to` to `op`, signed by the owner.
+fn add_withdraw(
+    e: &Env,
+    op: &mut ChannelOperationBuilder,
+    owner: &P256KeyPair,
+    to: &Address,
+    live: u32,
+) {
+    op.add_withdraw(e, to.clone(), 100, vec![e]).unwrap();
+    op.add_spend(
+        owner.public_key.clone(),
+        vec![e, Condition::ExtWithdraw(to.clone(), 100_i128)],
+    )
+    .unwrap();
+    sign_spend(e, op, owner, live);
+}
+
+/// Signs `batch` as the provider with `nonce` and submits it with every entry.
+fn submit(
+    e: &Env,
+    channel: &PrivacyChannelContractClient,
+    mut batch: ChannelBatchBuilder,
+    provider: &Ed25519Account,
+    nonce: i64,
+    live: u32,
+) {
+    let hash = batch.bundle_payload_hash(e, nonce, live).unwrap();
+    batch
+        .add_provider_signature(&provider.address, provider.sign(e, hash), live)
+        .unwrap();
+
+    let mut ops = vec![e];
+    for op in batch.operations(e).iter() {
+        ops.push_back(channel_operation(e, &op));
+    }
+    channel
+        .set_auths(&batch.auth_entries(e, nonce, live).unwrap())
+        .transact_batch(&ops);
+}
+
+/// A depositor that signs a transfer in two operations, or deposits in one and is paid in another,
+/// is not netted: each transfer must match the amount it signed.
+#[test]
+fn test_batch_keeps_each_signed_deposit_transfer() {
+    let e = get_env_with_g_accounts();
+    let (channel, auth, provider, owners) = funded_channel(&e);
+    let (_, _, depositor, _, _) = get_snapshot_g_accounts(&e);
+    let token = TokenClient::new(&e, &channel.asset());
+    token.mock_all_auths().mint(&depositor.address, &160_i128);
+    e.set_auths(&[]);
+    let live = e.ledger().sequence() + 100;
+    let context = context(&e, &channel, &auth, &token.address);
+
+    // Batch nonce 0: the depositor's operations sign with nonces 1 and 2.
+    let mut batch = ChannelBatchBuilder::new(&e, context.clone());
+    for (i, amount) in [100_i128, 60].into_iter().enumerate() {
+        let mut op = batch.operation_builder(&e);
+        add_signed_deposit(&e, &mut op, &depositor, amount, 1 + i as i64, live);
+        batch.push(op).unwrap();
+    }
+    submit(&e, &channel, batch, &provider, 0, live);
+    assert_eq!(token.balance(&depositor.address), 0);
+    assert_eq!(channel.supply(), 100 * OPS as i128 + 160);
+
+    // The depositor's 100 in and 100 out would net to nothing, but it signed the transfer in.
+    // Batch nonce 3: its first operation signs with nonce 4.
+    token.mock_all_auths().mint(&depositor.address, &100_i128);
+    e.set_auths(&[]);
+    let mut batch = ChannelBatchBuilder::new(&e, context);
+    let mut op = batch.operation_builder(&e);
+    add_signed_deposit(&e, &mut op, &depositor, 100, 4, live);
+    batch.push(op).unwrap();
+    let mut op = batch.operation_builder(&e);
+    add_withdraw(&e, &mut op, &owners[0], &depositor.address, live);
+    batch.push(op).unwrap();
+    submit(&e, &channel, batch, &provider, 3, live);
+
+    assert_eq!(token.balance(&depositor.address), 100);
+    assert_eq!(channel.utxo_balance(&owners[0].public_key), 0);
+    assert_eq!(channel.supply(), 100 * OPS as i128 + 160);
+    assert_eq!(token.balance(&channel.address), channel.supply());
+}
+
+#[test]
+fn test_batch_operations_are_independent() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let depositor = Address::generate(&e);
+    let (a, b) = (
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+    );
+    token.mint(&depositor, &200_i128);
+    channel.transact(&operation(
+        &e,
+        vec![&e],
+        vec![&e, (a.clone(), 100_i128), (b.clone(), 100_i128)],
+        vec![&e, (depositor, 200_i128, vec![&e])],
+        vec![&e],
+    ));
+    let error = |error: ContractError| Some(Ok(Error::from_contract_error(error as u32)));
+
+    assert_eq!(
+        channel.try_transact_batch(&vec![&e]).err(),
+        error(ContractError::EmptyBatch)
+    );
+
+    // Value cannot move between operations, even when the batch as a whole balances.
+    let (x, y) = (
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+    );
+    let ops = vec![
+        &e,
+        operation(
+            &e,
+            vec![&e, (a.clone(), vec![&e])],
+            vec![&e, (x.clone(), 150_i128)],
+            vec![&e],
+            vec![&e],
+        ),
+        operation(
+            &e,
+            vec![&e, (b.clone(), vec![&e])],
+            vec![&e, (y.clone(), 50_i128)],
+            vec![&e],
+            vec![&e],
+        ),
+    ];
+    assert_eq!(
+        channel.try_transact_batch(&ops).err(),
+        error(ContractError::UnbalancedBundle)
+    );
+
+    // A UTXO is spent at most once per batch.
+    let ops = vec![
+        &e,
+        operation(
+            &e,
+            vec![&e, (a.clone(), vec![&e])],
+            vec![&e, (x.clone(), 100_i128)],
+            vec![&e],
+            vec![&e],
+        ),
+        operation(
+            &e,
+            vec![&e, (a.clone(), vec![&e])],
+            vec![&e, (y.clone(), 100_i128)],
+            vec![&e],
+            vec![&e],
+        ),
+    ];
+    assert_eq!(
+        channel.try_transact_batch(&ops).err(),
+        error(ContractError::RepeatedSpendUtxo)
+    );
+
+    // Nor can an operation spend a UTXO created earlier in the batch.
+    let ops = vec![
+        &e,
+        operation(
+            &e,
+            vec![&e, (a.clone(), vec![&e])],
+            vec![&e, (x.clone(), 100_i128)],
+            vec![&e],
+            vec![&e],
+        ),
+        operation(
+            &e,
+            vec![&e, (x.clone(), vec![&e])],
+            vec![&e, (y, 100_i128)],
+            vec![&e],
+            vec![&e],
+        ),
+    ];
+    assert_eq!(
+        channel.try_transact_batch(&ops).err(),
+        error(ContractError::UtxoDoesNotExist)
+    );
+    assert_eq!(channel.utxo_balance(&a), 100);
+    assert_eq!(channel.utxo_balance(&b), 100);
+}
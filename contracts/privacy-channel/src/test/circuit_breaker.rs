@@ -0,0 +1,305 @@
+#![cfg(test)]
+//! Outflow circuit breaker: withdrawals are summed over a rolling window of ledgers, a bundle that
+//! would exceed the threshold fails and the channel pauses once it is reached, until the admin or
+//! the guardian resets it.
+extern crate std;
+
+use crate::{
+    circuit_breaker::{CircuitBreaker, CircuitBreakerTripped},
+    contract::{CircuitBreakerReset, CircuitBreakerUpdated, PrivacyChannelContractClient},
+    test::test::{create_contracts, withdraw_op},
+    transact::ChannelOperation,
+};
+use moonlight_errors::Error as ContractError;
+use moonlight_helpers::testutils::keys::P256KeyPair;
+use moonlight_primitives::Condition;
+use soroban_sdk::{
+    testutils::{Address as _, Events, Ledger, MockAuth, MockAuthInvoke},
+    vec, Address, BytesN, Env, Error, Event, IntoVal, Map,
+};
+use std::vec::Vec;
+
+/// Deposits `count` UTXOs of 100 each.
+fn fund(
+    e: &Env,
+    channel: &PrivacyChannelContractClient,
+    from: &Address,
+    count: u32,
+) -> Vec<BytesN<65>> {
+    let utxos: Vec<_> = (0..count)
+        .map(|_| P256KeyPair::generate(e).public_key)
+        .collect();
+    let mut create = vec![e];
+    for utxo in &utxos {
+        create.push_back((utxo.clone(), 100_i128));
+    }
+    channel.transact(&ChannelOperation {
+        spend: vec![e],
+        create,
+        deposit: vec![e, (from.clone(), 100 * count as i128, vec![e])],
+        withdraw: vec![e],
+        notes: Map::new(e),
+        ephemeral_keys: Map::new(e),
+        allowance_deposits: vec![e],
+    });
+    utxos
+}
+
+fn assert_error<T: core::fmt::Debug>(
+    result: Result<T, Result<Error, soroban_sdk::InvokeError>>,
+    error: ContractError,
+) {
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(error as u32)))
+    );
+}
+
+fn setup(
+    e: &Env,
+    breaker: CircuitBreaker,
+    utxos: u32,
+) -> (
+    PrivacyChannelContractClient<'_>,
+    Address,
+    Vec<BytesN<65>>,
+    Address,
+) {
+    let (channel, _, token, admin) = create_contracts(e);
+    e.mock_all_auths();
+    let depositor = Address::generate(e);
+    token.mint(&depositor, &(100 * utxos as i128));
+    channel.set_circuit_breaker(&Some(breaker));
+    let utxos = fund(e, &channel, &depositor, utxos);
+    (channel, admin, utxos, Address::generate(e))
+}
+
+#[test]
+fn test_admin_sets_circuit_breaker() {
+    let e = Env::default();
+    let (channel, _, _, admin) = create_contracts(&e);
+    let breaker = Some(CircuitBreaker {
+        window: 17_280,
+        max_outflow: 1_000,
+    });
+
+    assert_eq!(channel.circuit_breaker(), None);
+
+    channel
+        .mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &channel.address,
+                fn_name: "set_circuit_breaker",
+                args: (&breaker,).into_val(&e),
+                sub_invokes: &[],
+            },
+        }])
+        .set_circuit_breaker(&breaker);
+
+    assert_eq!(
+        e.events().all().events().last().unwrap(),
+        &CircuitBreakerUpdated {
+            breaker: breaker.clone(),
+        }
+        .to_xdr(&e, &channel.address)
+    );
+    assert_eq!(channel.circuit_breaker(), breaker);
+
+    let attacker = Address::generate(&e);
+    let result = channel
+        .mock_auths(&[MockAuth {
+            address: &attacker,
+            invoke: &MockAuthInvoke {
+                contract: &channel.address,
+                fn_name: "set_circuit_breaker",
+                args: (None::<CircuitBreaker>,).into_val(&e),
+                sub_invokes: &[],
+            },
+        }])
+        .try_set_circuit_breaker(&None);
+    assert!(result.is_err());
+    assert_eq!(channel.circuit_breaker(), breaker);
+
+    e.mock_all_auths();
+    for invalid in [
+        CircuitBreaker {
+            window: 0,
+            max_outflow: 1_000,
+        },
+        CircuitBreaker {
+            window: 100,
+            max_outflow: 0,
+        },
+    ] {
+        assert_error(
+            channel.try_set_circuit_breaker(&Some(invalid)),
+            ContractError::InvalidCircuitBreaker,
+        );
+    }
+}
+
+#[test]
+fn test_breaker_trips_and_pauses_transact() {
+    let e = Env::default();
+    let (channel, _, utxos, recipient) = setup(
+        &e,
+        CircuitBreaker {
+            window: 100,
+            max_outflow: 300,
+        },
+        4,
+    );
+
+    channel.transact(&withdraw_op(&e, &utxos[0], &recipient));
+    channel.transact(&withdraw_op(&e, &utxos[1], &recipient));
+    assert_eq!(channel.outflow(), 200);
+    assert!(!channel.is_paused());
+
+    // The bundle reaching the threshold completes and pauses the channel.
+    channel.transact(&withdraw_op(&e, &utxos[2], &recipient));
+    assert!(e.events().all().events().contains(
+        &CircuitBreakerTripped {
+            outflow: 300,
+            max_outflow: 300,
+        }
+        .to_xdr(&e, &channel.address)
+    ));
+    assert!(channel.is_paused());
+    assert_eq!(channel.supply(), 100);
+
+    assert_error(
+        channel.try_transact(&withdraw_op(&e, &utxos[3], &recipient)),
+        ContractError::ChannelPaused,
+    );
+}
+
+#[test]
+fn test_bundle_exceeding_threshold_pauses() {
+    let e = Env::default();
+    let (channel, _, utxos, recipient) = setup(
+        &e,
+        CircuitBreaker {
+            window: 100,
+            max_outflow: 100,
+        },
+        10,
+    );
+
+    // One bundle withdrawing ten times the threshold.
+    let mut drain = withdraw_op(&e, &utxos[0], &recipient);
+    drain.spend = vec![&e];
+    for utxo in &utxos {
+        drain.spend.push_back((
+            utxo.clone(),
+            vec![&e, Condition::ExtWithdraw(recipient.clone(), 1_000)],
+        ));
+    }
+    drain.withdraw = vec![&e, (recipient.clone(), 1_000_i128, vec![&e])];
+    channel.transact(&drain);
+
+    // The pause is not rolled back: no smaller bundle can follow.
+    assert!(e.events().all().events().contains(
+        &CircuitBreakerTripped {
+            outflow: 1_000,
+            max_outflow: 100,
+        }
+        .to_xdr(&e, &channel.address)
+    ));
+    assert!(channel.is_paused());
+    assert_eq!(channel.outflow(), 1_000);
+    assert_eq!(channel.supply(), 0);
+}
+
+#[test]
+fn test_batch_exceeding_threshold_pauses() {
+    let e = Env::default();
+    let (channel, _, utxos, recipient) = setup(
+        &e,
+        CircuitBreaker {
+            window: 100,
+            max_outflow: 250,
+        },
+        4,
+    );
+
+    let mut ops = vec![&e];
+    for utxo in &utxos[..3] {
+        ops.push_back(withdraw_op(&e, utxo, &recipient));
+    }
+    channel.transact_batch(&ops);
+    assert!(e.events().all().events().contains(
+        &CircuitBreakerTripped {
+            outflow: 300,
+            max_outflow: 250,
+        }
+        .to_xdr(&e, &channel.address)
+    ));
+    assert!(channel.is_paused());
+
+    assert_error(
+        channel.try_transact(&withdraw_op(&e, &utxos[3], &recipient)),
+        ContractError::ChannelPaused,
+    );
+    assert_eq!(channel.supply(), 100);
+}
+
+#[test]
+fn test_outflow_rolls_out_of_the_window() {
+    let e = Env::default();
+    let (channel, _, utxos, recipient) = setup(
+        &e,
+        CircuitBreaker {
+            window: 48,
+            max_outflow: 250,
+        },
+        4,
+    );
+
+    channel.transact(&withdraw_op(&e, &utxos[0], &recipient));
+    channel.transact(&withdraw_op(&e, &utxos[1], &recipient));
+
+    e.ledger().with_mut(|l| l.sequence_number += 25);
+    assert_eq!(channel.outflow(), 200);
+    e.ledger().with_mut(|l| l.sequence_number += 25);
+    assert_eq!(channel.outflow(), 0);
+
+    channel.transact(&withdraw_op(&e, &utxos[2], &recipient));
+    channel.transact(&withdraw_op(&e, &utxos[3], &recipient));
+    assert_eq!(channel.outflow(), 200);
+    assert!(!channel.is_paused());
+}
+
+#[test]
+fn test_admin_or_guardian_resets_breaker() {
+    let e = Env::default();
+    let (channel, admin, utxos, recipient) = setup(
+        &e,
+        CircuitBreaker {
+            window: 100,
+            max_outflow: 100,
+        },
+        2,
+    );
+    let guardian = Address::generate(&e);
+    channel.set_guardian(&Some(guardian.clone()));
+    assert_eq!(channel.guardian(), Some(guardian.clone()));
+
+    let stranger = Address::generate(&e);
+    for (utxo, caller) in utxos.iter().zip([&admin, &guardian]) {
+        channel.transact(&withdraw_op(&e, utxo, &recipient));
+        assert!(channel.is_paused());
+        assert_error(
+            channel.try_reset_circuit_breaker(&stranger),
+            ContractError::NotAdminOrGuardian,
+        );
+
+        channel.reset_circuit_breaker(caller);
+        assert_eq!(
+            e.events().all().events().last().unwrap(),
+            &CircuitBreakerReset { by: caller.clone() }.to_xdr(&e, &channel.address)
+        );
+        assert!(!channel.is_paused());
+        assert_eq!(channel.outflow(), 0);
+    }
+}
@@ -0,0 +1,145 @@
+#![cfg(test)]
+//! Compliance hook: with a policy set, every deposit and withdrawal leg is screened through the
+//! `CompliancePolicy` interface and a rejected leg reverts the bundle.
+extern crate std;
+
+use crate::{
+    contract::{CompliancePolicyUpdated, PrivacyChannelContractClient},
+    test::test::{create_contracts, withdraw_op},
+    transact::ChannelOperation,
+};
+use compliance_policy_contract::contract::{
+    CompliancePolicyContract, CompliancePolicyContractArgs, CompliancePolicyContractClient,
+    ListMode,
+};
+use moonlight_errors::Error as ContractError;
+use moonlight_helpers::testutils::keys::P256KeyPair;
+use soroban_sdk::{
+    testutils::{Address as _, Events, MockAuth, MockAuthInvoke},
+    vec, Address, BytesN, Env, Error, Event, IntoVal, Map,
+};
+
+fn create_policy<'a>(e: &Env, mode: ListMode) -> CompliancePolicyContractClient<'a> {
+    let id = e.register(
+        CompliancePolicyContract,
+        CompliancePolicyContractArgs::__constructor(&Address::generate(e), &mode),
+    );
+    CompliancePolicyContractClient::new(e, &id)
+}
+
+fn deposit_op(e: &Env, from: &Address, utxo: &BytesN<65>) -> ChannelOperation {
+    ChannelOperation {
+        spend: vec![e],
+        create: vec![e, (utxo.clone(), 100_i128)],
+        deposit: vec![e, (from.clone(), 100_i128, vec![e])],
+        withdraw: vec![e],
+        notes: Map::new(e),
+        ephemeral_keys: Map::new(e),
+        allowance_deposits: vec![e],
+    }
+}
+
+fn assert_rejected(channel: &PrivacyChannelContractClient, op: &ChannelOperation) {
+    assert_eq!(
+        channel.try_transact(op).err(),
+        Some(Ok(Error::from_contract_error(
+            ContractError::ComplianceCheckFailed as u32
+        )))
+    );
+}
+
+#[test]
+fn test_admin_sets_and_clears_compliance_policy() {
+    let e = Env::default();
+    let (channel, _, _, admin) = create_contracts(&e);
+    let policy = Some(Address::generate(&e));
+
+    assert_eq!(channel.compliance_policy(), None);
+
+    channel
+        .mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &channel.address,
+                fn_name: "set_compliance_policy",
+                args: (&policy,).into_val(&e),
+                sub_invokes: &[],
+            },
+        }])
+        .set_compliance_policy(&policy);
+    assert_eq!(
+        e.events().all().events().last().unwrap(),
+        &CompliancePolicyUpdated {
+            policy: policy.clone(),
+        }
+        .to_xdr(&e, &channel.address)
+    );
+    assert_eq!(channel.compliance_policy(), policy);
+
+    let attacker = Address::generate(&e);
+    let result = channel
+        .mock_auths(&[MockAuth {
+            address: &attacker,
+            invoke: &MockAuthInvoke {
+                contract: &channel.address,
+                fn_name: "set_compliance_policy",
+                args: (None::<Address>,).into_val(&e),
+                sub_invokes: &[],
+            },
+        }])
+        .try_set_compliance_policy(&None);
+    assert!(result.is_err());
+
+    e.mock_all_auths();
+    channel.set_compliance_policy(&None);
+    assert_eq!(channel.compliance_policy(), None);
+}
+
+#[test]
+fn test_allowlist_screens_deposits() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let policy = create_policy(&e, ListMode::Allow);
+    channel.set_compliance_policy(&Some(policy.address.clone()));
+
+    let (allowed, unknown) = (Address::generate(&e), Address::generate(&e));
+    token.mint(&allowed, &100_i128);
+    token.mint(&unknown, &100_i128);
+    policy.set_listed(&allowed, &true);
+
+    assert_rejected(
+        &channel,
+        &deposit_op(&e, &unknown, &P256KeyPair::generate(&e).public_key),
+    );
+    channel.transact(&deposit_op(
+        &e,
+        &allowed,
+        &P256KeyPair::generate(&e).public_key,
+    ));
+    assert_eq!(channel.supply(), 100);
+    assert_eq!(token.balance(&unknown), 100);
+}
+
+#[test]
+fn test_denylist_screens_withdrawals() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let depositor = Address::generate(&e);
+    token.mint(&depositor, &100_i128);
+    let utxo = P256KeyPair::generate(&e).public_key;
+    channel.transact(&deposit_op(&e, &depositor, &utxo));
+
+    let policy = create_policy(&e, ListMode::Deny);
+    channel.set_compliance_policy(&Some(policy.address.clone()));
+    let (denied, recipient) = (Address::generate(&e), Address::generate(&e));
+    policy.set_listed(&denied, &true);
+
+    assert_rejected(&channel, &withdraw_op(&e, &utxo, &denied));
+    assert_eq!(channel.utxo_balance(&utxo), 100);
+
+    channel.transact(&withdraw_op(&e, &utxo, &recipient));
+    assert_eq!(token.balance(&recipient), 100);
+    assert_eq!(channel.supply(), 0);
+}
@@ -0,0 +1,125 @@
+#![cfg(test)]
+//! Every `transact` emits one `Transact` event with hashed UTXO ids and public external legs.
+extern crate std;
+
+use crate::{contract::Transact, test::test::create_contracts, transact::ChannelOperation};
+use moonlight_helpers::testutils::keys::P256KeyPair;
+use moonlight_primitives::Condition;
+use moonlight_utxo_core::core::hash_utxo_key;
+use soroban_sdk::{
+    testutils::{Address as _, Events},
+    vec, Address, Bytes, Env, Event, Map,
+};
+
+#[test]
+fn test_transact_emits_hashed_utxos_and_external_legs() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+
+    let depositor = Address::generate(&e);
+    let recipient = Address::generate(&e);
+    token.mint(&depositor, &500_i128);
+    let utxo_a = P256KeyPair::generate(&e);
+    let utxo_b = P256KeyPair::generate(&e);
+
+    channel.transact(&ChannelOperation {
+        spend: vec![&e],
+        create: vec![&e, (utxo_a.public_key.clone(), 500_i128)],
+        deposit: vec![&e, (depositor.clone(), 500_i128, vec![&e])],
+        withdraw: vec![&e],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
+    });
+
+    let event = Transact {
+        spent: vec![&e],
+        created: vec![&e, hash_utxo_key(&e, &utxo_a.public_key)],
+        notes: vec![&e],
+        ephemeral_keys: vec![&e],
+        deposits: vec![&e, (depositor, 500_i128)],
+        withdrawals: vec![&e],
+        supply: 500_i128,
+        payload: None,
+    };
+    assert_eq!(
+        e.events().all().events().last().unwrap(),
+        &event.to_xdr(&e, &channel.address)
+    );
+
+    channel.transact(&ChannelOperation {
+        spend: vec![
+            &e,
+            (
+                utxo_a.public_key.clone(),
+                vec![
+                    &e,
+                    Condition::Create(utxo_b.public_key.clone(), 300_i128),
+                    Condition::ExtWithdraw(recipient.clone(), 200_i128),
+                ],
+            ),
+        ],
+        create: vec![&e, (utxo_b.public_key.clone(), 300_i128)],
+        deposit: vec![&e],
+        withdraw: vec![&e, (recipient.clone(), 200_i128, vec![&e])],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
+    });
+
+    let event = Transact {
+        spent: vec![&e, hash_utxo_key(&e, &utxo_a.public_key)],
+        created: vec![&e, hash_utxo_key(&e, &utxo_b.public_key)],
+        notes: vec![&e],
+        ephemeral_keys: vec![&e],
+        deposits: vec![&e],
+        withdrawals: vec![&e, (recipient, 200_i128)],
+        supply: 300_i128,
+        payload: None,
+    };
+    assert_eq!(
+        e.events().all().events().last().unwrap(),
+        &event.to_xdr(&e, &channel.address)
+    );
+}
+
+#[test]
+fn test_transact_with_payload_attaches_payload() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+
+    let depositor = Address::generate(&e);
+    token.mint(&depositor, &100_i128);
+    let utxo = P256KeyPair::generate(&e);
+    let payload = Bytes::from_array(&e, &[7; 48]);
+
+    channel.transact_with_payload(
+        &ChannelOperation {
+            spend: vec![&e],
+            create: vec![&e, (utxo.public_key.clone(), 100_i128)],
+            deposit: vec![&e, (depositor.clone(), 100_i128, vec![&e])],
+            withdraw: vec![&e],
+            notes: Map::new(&e),
+            ephemeral_keys: Map::new(&e),
+            allowance_deposits: vec![&e],
+        },
+        &payload,
+    );
+
+    let event = Transact {
+        spent: vec![&e],
+        created: vec![&e, hash_utxo_key(&e, &utxo.public_key)],
+        notes: vec![&e],
+        ephemeral_keys: vec![&e],
+        deposits: vec![&e, (depositor, 100_i128)],
+        withdrawals: vec![&e],
+        supply: 100_i128,
+        payload: Some(payload),
+    };
+    assert_eq!(
+        e.events().all().events().last().unwrap(),
+        &event.to_xdr(&e, &channel.address)
+    );
+}
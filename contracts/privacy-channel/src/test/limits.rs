@@ -0,0 +1,266 @@
+#![cfg(test)]
+//! Admin-configured channel limits: supply ceiling, per-deposit and per-withdraw maximums, minimum
+//! create amount and bundle size, each rejected with its own error before any UTXO is touched.
+extern crate std;
+
+use crate::{
+    contract::{LimitsUpdated, PrivacyChannelContractClient},
+    limits::ChannelLimits,
+    test::test::create_contracts,
+    transact::ChannelOperation,
+};
+use moonlight_errors::Error as ContractError;
+use moonlight_helpers::testutils::keys::P256KeyPair;
+use moonlight_primitives::Condition;
+use soroban_sdk::{
+    testutils::{Address as _, Events, MockAuth, MockAuthInvoke},
+    vec, Address, BytesN, Env, Error, Event, IntoVal, Map,
+};
+
+fn key(e: &Env) -> BytesN<65> {
+    P256KeyPair::generate(e).public_key
+}
+
+fn deposit_op(e: &Env, from: &Address, creates: &[i128]) -> ChannelOperation {
+    let mut create = vec![e];
+    for amount in creates {
+        create.push_back((key(e), *amount));
+    }
+    ChannelOperation {
+        spend: vec![e],
+        create,
+        deposit: vec![e, (from.clone(), creates.iter().sum(), vec![e])],
+        withdraw: vec![e],
+        notes: Map::new(e),
+        ephemeral_keys: Map::new(e),
+        allowance_deposits: vec![e],
+    }
+}
+
+fn assert_error(
+    channel: &PrivacyChannelContractClient,
+    op: &ChannelOperation,
+    error: ContractError,
+) {
+    assert_eq!(
+        channel.try_transact(op).err(),
+        Some(Ok(Error::from_contract_error(error as u32)))
+    );
+}
+
+#[test]
+fn test_admin_sets_limits() {
+    let e = Env::default();
+    let (channel, _, _, admin) = create_contracts(&e);
+    let limits = ChannelLimits {
+        max_supply: Some(1_000),
+        min_create: Some(10),
+        max_spends: Some(8),
+        ..ChannelLimits::default()
+    };
+
+    assert_eq!(channel.limits(), ChannelLimits::default());
+
+    channel
+        .mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &channel.address,
+                fn_name: "set_limits",
+                args: (&limits,).into_val(&e),
+                sub_invokes: &[],
+            },
+        }])
+        .set_limits(&limits);
+
+    assert_eq!(
+        e.events().all().events().last().unwrap(),
+        &LimitsUpdated {
+            limits: limits.clone(),
+        }
+        .to_xdr(&e, &channel.address)
+    );
+    assert_eq!(channel.limits(), limits);
+}
+
+#[test]
+fn test_non_admin_cannot_set_limits() {
+    let e = Env::default();
+    let (channel, _, _, _) = create_contracts(&e);
+    let attacker = Address::generate(&e);
+    let limits = ChannelLimits {
+        max_deposit: Some(1),
+        ..ChannelLimits::default()
+    };
+
+    let result = channel
+        .mock_auths(&[MockAuth {
+            address: &attacker,
+            invoke: &MockAuthInvoke {
+                contract: &channel.address,
+                fn_name: "set_limits",
+                args: (&limits,).into_val(&e),
+                sub_invokes: &[],
+            },
+        }])
+        .try_set_limits(&limits);
+
+    assert!(result.is_err());
+    assert_eq!(channel.limits(), ChannelLimits::default());
+}
+
+#[test]
+fn test_set_limits_rejects_non_positive_limits() {
+    let e = Env::default();
+    let (channel, _, _, _) = create_contracts(&e);
+    e.mock_all_auths();
+
+    for limits in [
+        ChannelLimits {
+            max_supply: Some(0),
+            ..ChannelLimits::default()
+        },
+        ChannelLimits {
+            min_create: Some(-5),
+            ..ChannelLimits::default()
+        },
+        ChannelLimits {
+            max_creates: Some(0),
+            ..ChannelLimits::default()
+        },
+    ] {
+        assert_eq!(
+            channel.try_set_limits(&limits).err(),
+            Some(Ok(Error::from_contract_error(
+                ContractError::InvalidChannelLimits as u32
+            )))
+        );
+    }
+}
+
+#[test]
+fn test_transact_enforces_each_limit() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let depositor = Address::generate(&e);
+    token.mint(&depositor, &10_000_i128);
+
+    channel.set_limits(&ChannelLimits {
+        max_supply: Some(1_000),
+        max_deposit: Some(600),
+        max_withdraw: Some(150),
+        min_create: Some(10),
+        max_spends: Some(1),
+        max_creates: Some(2),
+    });
+
+    assert_error(
+        &channel,
+        &deposit_op(&e, &depositor, &[300, 301]),
+        ContractError::DepositAboveLimit,
+    );
+    assert_error(
+        &channel,
+        &deposit_op(&e, &depositor, &[100, 9]),
+        ContractError::CreateBelowMinimum,
+    );
+    assert_error(
+        &channel,
+        &deposit_op(&e, &depositor, &[10, 10, 10]),
+        ContractError::TooManyCreates,
+    );
+
+    let utxo = key(&e);
+    channel.transact(&ChannelOperation {
+        spend: vec![&e],
+        create: vec![&e, (utxo.clone(), 600)],
+        deposit: vec![&e, (depositor.clone(), 600, vec![&e])],
+        withdraw: vec![&e],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
+    });
+    channel.transact(&deposit_op(&e, &depositor, &[200, 200]));
+    assert_eq!(channel.supply(), 1_000);
+    assert_error(
+        &channel,
+        &deposit_op(&e, &depositor, &[10]),
+        ContractError::SupplyCeilingExceeded,
+    );
+
+    let recipient = Address::generate(&e);
+    let change = key(&e);
+    let withdraw = |amount: i128| ChannelOperation {
+        spend: vec![
+            &e,
+            (
+                utxo.clone(),
+                vec![
+                    &e,
+                    Condition::ExtWithdraw(recipient.clone(), amount),
+                    Condition::Create(change.clone(), 600 - amount),
+                ],
+            ),
+        ],
+        create: vec![&e, (change.clone(), 600 - amount)],
+        deposit: vec![&e],
+        withdraw: vec![&e, (recipient.clone(), amount, vec![&e])],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
+    };
+    assert_error(&channel, &withdraw(151), ContractError::WithdrawAboveLimit);
+
+    let mut two_spends = withdraw(100);
+    two_spends.spend.push_back((key(&e), vec![&e]));
+    assert_error(&channel, &two_spends, ContractError::TooManySpends);
+}
+
+#[test]
+fn test_withdrawals_work_above_a_lowered_supply_ceiling() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let depositor = Address::generate(&e);
+    let recipient = Address::generate(&e);
+    token.mint(&depositor, &500_i128);
+
+    let (utxo, change) = (key(&e), key(&e));
+    channel.transact(&ChannelOperation {
+        spend: vec![&e],
+        create: vec![&e, (utxo.clone(), 500)],
+        deposit: vec![&e, (depositor, 500, vec![&e])],
+        withdraw: vec![&e],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
+    });
+    channel.set_limits(&ChannelLimits {
+        max_supply: Some(100),
+        ..ChannelLimits::default()
+    });
+
+    channel.transact(&ChannelOperation {
+        spend: vec![
+            &e,
+            (
+                utxo,
+                vec![
+                    &e,
+                    Condition::ExtWithdraw(recipient.clone(), 200),
+                    Condition::Create(change.clone(), 300),
+                ],
+            ),
+        ],
+        create: vec![&e, (change, 300)],
+        deposit: vec![&e],
+        withdraw: vec![&e, (recipient.clone(), 200, vec![&e])],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
+    });
+
+    assert_eq!(channel.supply(), 300);
+    assert_eq!(token.balance(&recipient), 200);
+}
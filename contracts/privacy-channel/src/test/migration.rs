@@ -0,0 +1,202 @@
+#![cfg(test)]
+//! Schema versioning: `migrate` brings a channel deployed before the version was stored up to the
+//! current layout exactly once, seeding its unspent total, and its UTXOs stay spendable.
+extern crate std;
+
+use crate::{
+    contract::{Migrated, Reconciliation},
+    migration::SCHEMA_VERSION,
+    storage::{write_supply_unchecked, PrivacyChannelDataKey},
+    test::test::create_contracts,
+    transact::ChannelOperation,
+};
+use moonlight_errors::Error as ContractError;
+use moonlight_helpers::testutils::keys::P256KeyPair;
+use moonlight_primitives::Condition;
+use moonlight_storage::{hash_utxo_key, StorageConfigKey, UTXOCoreDataKey, UtxoState};
+use soroban_sdk::{
+    testutils::{Address as _, Events, MockAuth, MockAuthInvoke},
+    vec, Address, Bytes, BytesN, Env, Error, Event, IntoVal, Map,
+};
+
+// `upgrade` swaps the code and leaves storage as it is, so a channel upgraded from an unversioned
+// wasm looks like this one: no schema version and no unspent total, and each UTXO in its own
+// `UTXOCoreDataKey::UTXO` entry holding its amount, or `0` once spent.
+fn seed_unversioned(e: &Env, channel: &Address, utxos: &[(BytesN<65>, i128)], supply: i128) {
+    e.as_contract(channel, || {
+        e.storage()
+            .instance()
+            .remove(&PrivacyChannelDataKey::SchemaVersion);
+        e.storage()
+            .instance()
+            .remove(&StorageConfigKey::UnspentValue);
+        for (utxo, amount) in utxos {
+            e.storage()
+                .persistent()
+                .set(&UTXOCoreDataKey::UTXO(hash_utxo_key(e, utxo)), amount);
+        }
+        write_supply_unchecked(e, supply);
+    });
+}
+
+fn assert_error<T: core::fmt::Debug>(
+    result: Result<T, Result<Error, soroban_sdk::InvokeError>>,
+    error: ContractError,
+) {
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(error as u32)))
+    );
+}
+
+#[test]
+fn test_migrated_channel_keeps_its_utxos_spendable() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+
+    let depositor = Address::generate(&e);
+    let recipient = Address::generate(&e);
+    let legacy = P256KeyPair::generate(&e);
+    let tombstoned = P256KeyPair::generate(&e);
+    let utxo_b = P256KeyPair::generate(&e);
+
+    token.mint(&channel.address, &500_i128);
+    seed_unversioned(
+        &e,
+        &channel.address,
+        &[
+            (legacy.public_key.clone(), 500_i128),
+            (tombstoned.public_key.clone(), 0_i128),
+        ],
+        500,
+    );
+    assert_eq!(channel.version(), 0);
+    assert_eq!(channel.reconcile().unspent, 0);
+
+    channel.migrate(&Bytes::new(&e));
+    assert_eq!(
+        e.events().all().events().last().unwrap(),
+        &Migrated {
+            from: 0,
+            to: SCHEMA_VERSION,
+        }
+        .to_xdr(&e, &channel.address)
+    );
+    assert_eq!(channel.version(), SCHEMA_VERSION);
+    assert_eq!(
+        channel.reconcile(),
+        Reconciliation {
+            balance: 500,
+            supply: 500,
+            unspent: 500,
+        }
+    );
+    assert_eq!(
+        channel.utxo_state(&legacy.public_key),
+        UtxoState::Unspent(500)
+    );
+    assert_eq!(channel.utxo_state(&tombstoned.public_key), UtxoState::Spent);
+
+    // The tombstone still blocks re-spending and re-creating its key.
+    assert_error(
+        channel.try_transact(&ChannelOperation {
+            spend: vec![
+                &e,
+                (
+                    tombstoned.public_key.clone(),
+                    vec![&e, Condition::ExtWithdraw(recipient.clone(), 100_i128)],
+                ),
+            ],
+            create: vec![&e],
+            deposit: vec![&e],
+            withdraw: vec![&e, (recipient.clone(), 100_i128, vec![&e])],
+            notes: Map::new(&e),
+            ephemeral_keys: Map::new(&e),
+            allowance_deposits: vec![&e],
+        }),
+        ContractError::UtxoAlreadySpent,
+    );
+    token.mint(&depositor, &100_i128);
+    assert_error(
+        channel.try_transact(&ChannelOperation {
+            spend: vec![&e],
+            create: vec![&e, (tombstoned.public_key.clone(), 100_i128)],
+            deposit: vec![&e, (depositor, 100_i128, vec![&e])],
+            withdraw: vec![&e],
+            notes: Map::new(&e),
+            ephemeral_keys: Map::new(&e),
+            allowance_deposits: vec![&e],
+        }),
+        ContractError::UtxoAlreadyExists,
+    );
+
+    channel.transact(&ChannelOperation {
+        spend: vec![
+            &e,
+            (
+                legacy.public_key.clone(),
+                vec![
+                    &e,
+                    Condition::Create(utxo_b.public_key.clone(), 320_i128),
+                    Condition::ExtWithdraw(recipient.clone(), 180_i128),
+                ],
+            ),
+        ],
+        create: vec![&e, (utxo_b.public_key.clone(), 320_i128)],
+        deposit: vec![&e],
+        withdraw: vec![&e, (recipient.clone(), 180_i128, vec![&e])],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
+    });
+    assert_eq!(channel.utxo_state(&legacy.public_key), UtxoState::Spent);
+    assert_eq!(channel.utxo_balance(&utxo_b.public_key), 320);
+    assert_eq!(token.balance(&recipient), 180);
+    assert_eq!(
+        channel.reconcile(),
+        Reconciliation {
+            balance: 320,
+            supply: 320,
+            unspent: 320,
+        }
+    );
+}
+
+#[test]
+fn test_migrate_runs_once_per_version() {
+    let e = Env::default();
+    let (channel, _, _, admin) = create_contracts(&e);
+    let outsider = Address::generate(&e);
+    let params = Bytes::new(&e);
+
+    // New channels start at the current version.
+    assert_eq!(channel.version(), SCHEMA_VERSION);
+    assert_eq!(
+        channel.mock_all_auths().try_migrate(&params).err(),
+        Some(Ok(Error::from_contract_error(
+            ContractError::AlreadyMigrated as u32
+        )))
+    );
+
+    seed_unversioned(&e, &channel.address, &[], 0);
+    let migrate_as = |signer: &Address| {
+        channel
+            .mock_auths(&[MockAuth {
+                address: signer,
+                invoke: &MockAuthInvoke {
+                    contract: &channel.address,
+                    fn_name: "migrate",
+                    args: (&params,).into_val(&e),
+                    sub_invokes: &[],
+                },
+            }])
+            .try_migrate(&params)
+    };
+    assert!(migrate_as(&outsider).is_err());
+    assert_eq!(channel.version(), 0);
+
+    assert!(migrate_as(&admin).is_ok());
+    assert_eq!(channel.version(), SCHEMA_VERSION);
+    assert!(migrate_as(&admin).is_err());
+}
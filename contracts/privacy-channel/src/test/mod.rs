@@ -1,4 +1,17 @@
-pub mod channel_operation_builder;
+#[cfg(test)]
+pub mod allowance;
+#[cfg(test)]
+pub mod batch;
+#[cfg(test)]
+pub mod circuit_breaker;
+#[cfg(test)]
+pub mod compliance;
+#[cfg(test)]
+pub mod events;
+#[cfg(test)]
+pub mod limits;
+#[cfg(test)]
+pub mod migration;
 #[cfg(test)]
 pub mod moon01;
 #[cfg(test)]
@@ -6,4 +19,14 @@ pub mod moon05;
 #[cfg(test)]
 pub mod moon06;
 #[cfg(test)]
+pub mod notes;
+#[cfg(test)]
+pub mod reconcile;
+#[cfg(test)]
+pub mod recovery;
+#[cfg(test)]
+pub mod stealth;
+#[cfg(test)]
 pub mod test;
+#[cfg(test)]
+pub mod ttl;
@@ -10,7 +10,9 @@ extern crate std;
 
 use crate::{
     contract::PrivacyChannelContractClient,
-    test::{channel_operation_builder::ChannelOperationBuilder, test::create_contracts},
+    test::test::{
+        builder, channel_operation, create_contracts, sign_deposit, sign_provider, sign_spend,
+    },
 };
 use channel_auth_contract::contract::ChannelAuthContractClient;
 use moonlight_errors::Error as ContractError;
@@ -45,34 +47,22 @@ fn fund_utxos(
         conditions.push_back(Condition::Create(utxo.clone(), amount));
     }
 
-    let mut op = ChannelOperationBuilder::generate(
-        e,
-        channel.address.clone(),
-        auth.address.clone(),
-        token.address.clone(),
-    );
-    op.add_deposit(e, depositor.address.clone(), total, conditions);
+    let mut op = builder(e, channel, auth, &token.address);
+    op.add_deposit(e, depositor.address.clone(), total, conditions)
+        .unwrap();
     for (utxo, amount) in creates.iter() {
-        op.add_create(utxo.clone(), amount);
+        op.add_create(utxo.clone(), amount).unwrap();
     }
 
     let live = e.ledger().sequence() + 100;
 
-    let provider_sig = provider.sign(e, op.get_auth_entry_payload_hash_for_bundle(e, nonce, live));
-    op.add_provider_signature(e, provider.address.clone(), provider_sig, live);
+    sign_provider(e, &mut op, provider, nonce, live);
 
-    let depositor_sig = depositor.sign_for_transaction(
-        e,
-        op.get_auth_entry_payload_hash_for_deposit(e, depositor.address.clone(), nonce, live),
-    );
-    op.add_deposit_signature(depositor.address.clone(), depositor_sig);
+    sign_deposit(e, &mut op, depositor, nonce, live);
 
     channel
-        .set_auths(&[
-            op.get_auth_entry(e, nonce, live),
-            op.get_auth_entry_for_deposit(e, depositor.address.clone(), nonce, live),
-        ])
-        .transact(&op.get_operation_bundle());
+        .set_auths(&op.auth_entries(e, nonce, live).unwrap())
+        .transact(&channel_operation(e, &op.operation()));
 }
 
 fn unauthorized(res_err: Option<Result<Error, soroban_sdk::InvokeError>>) {
@@ -128,12 +118,7 @@ fn test_moon01_redirect_spend_output_to_attacker_withdraw_is_rejected() {
     let attacker = Address::generate(&e);
     let utxo_change = P256KeyPair::generate(&e); // the create the victim THINKS they authorize
 
-    let mut atk = ChannelOperationBuilder::generate(
-        &e,
-        channel.address.clone(),
-        auth.address.clone(),
-        token.address.clone(),
-    );
+    let mut atk = builder(&e, &channel, &auth, &token.address);
 
     // Victim authorizes an INTERNAL change-create of 500 to utxo_change...
     atk.add_spend(
@@ -142,24 +127,20 @@ fn test_moon01_redirect_spend_output_to_attacker_withdraw_is_rejected() {
             &e,
             Condition::Create(utxo_change.public_key.clone(), 500_i128),
         ],
-    );
+    )
+    .unwrap();
     // ...but the bundle drops the create and redirects 500 OUT to the attacker. Balanced (500==500).
-    atk.add_withdraw(&e, attacker.clone(), 500_i128, vec![&e]);
+    atk.add_withdraw(&e, attacker.clone(), 500_i128, vec![&e])
+        .unwrap();
 
-    let p_sig = provider_a.sign(
-        &e,
-        atk.get_auth_entry_payload_hash_for_bundle(&e, nonce, live),
-    );
-    atk.add_provider_signature(&e, provider_a.address.clone(), p_sig, live);
+    sign_provider(&e, &mut atk, &provider_a, nonce, live);
 
     // The victim's P256 signature is over the UNCHANGED conditions and still verifies.
-    let v_sig =
-        utxo_victim.sign(&atk.get_auth_hash_for_spend(&e, utxo_victim.public_key.clone(), live));
-    atk.add_spend_signature(&e, utxo_victim.public_key.clone(), v_sig, live);
+    sign_spend(&e, &mut atk, &utxo_victim, live);
 
     let res = channel
-        .set_auths(&[atk.get_auth_entry(&e, nonce, live)])
-        .try_transact(&atk.get_operation_bundle());
+        .set_auths(&atk.auth_entries(&e, nonce, live).unwrap())
+        .try_transact(&channel_operation(&e, &atk.operation()));
 
     // POST-FIX: rejected, and no funds moved.
     unauthorized(res.err());
@@ -198,34 +179,25 @@ fn test_moon01_redirect_spend_output_to_attacker_create_is_rejected() {
     let utxo_intended = P256KeyPair::generate(&e);
     let utxo_attacker = P256KeyPair::generate(&e);
 
-    let mut atk = ChannelOperationBuilder::generate(
-        &e,
-        channel.address.clone(),
-        auth.address.clone(),
-        token.address.clone(),
-    );
+    let mut atk = builder(&e, &channel, &auth, &token.address);
     atk.add_spend(
         utxo_victim.public_key.clone(),
         vec![
             &e,
             Condition::Create(utxo_intended.public_key.clone(), 500_i128),
         ],
-    );
+    )
+    .unwrap();
     // Substitute the create to an attacker-owned UTXO, same amount → balanced.
-    atk.add_create(utxo_attacker.public_key.clone(), 500_i128);
+    atk.add_create(utxo_attacker.public_key.clone(), 500_i128)
+        .unwrap();
 
-    let p_sig = provider_a.sign(
-        &e,
-        atk.get_auth_entry_payload_hash_for_bundle(&e, nonce, live),
-    );
-    atk.add_provider_signature(&e, provider_a.address.clone(), p_sig, live);
-    let v_sig =
-        utxo_victim.sign(&atk.get_auth_hash_for_spend(&e, utxo_victim.public_key.clone(), live));
-    atk.add_spend_signature(&e, utxo_victim.public_key.clone(), v_sig, live);
+    sign_provider(&e, &mut atk, &provider_a, nonce, live);
+    sign_spend(&e, &mut atk, &utxo_victim, live);
 
     let res = channel
-        .set_auths(&[atk.get_auth_entry(&e, nonce, live)])
-        .try_transact(&atk.get_operation_bundle());
+        .set_auths(&atk.auth_entries(&e, nonce, live).unwrap())
+        .try_transact(&channel_operation(&e, &atk.operation()));
 
     unauthorized(res.err());
     assert_eq!(channel.utxo_balance(&utxo_victim.public_key), 500);
@@ -275,30 +247,22 @@ fn test_moon01_multispend_full_outputset_is_accepted() {
         Condition::Create(utxo_y.public_key.clone(), 300_i128),
     ];
 
-    let mut op = ChannelOperationBuilder::generate(
-        &e,
-        channel.address.clone(),
-        auth.address.clone(),
-        token.address.clone(),
-    );
-    op.add_create(utxo_x.public_key.clone(), 200_i128);
-    op.add_create(utxo_y.public_key.clone(), 300_i128);
-    op.add_spend(utxo_a.public_key.clone(), full.clone());
-    op.add_spend(utxo_b.public_key.clone(), full.clone());
+    let mut op = builder(&e, &channel, &auth, &token.address);
+    op.add_create(utxo_x.public_key.clone(), 200_i128).unwrap();
+    op.add_create(utxo_y.public_key.clone(), 300_i128).unwrap();
+    op.add_spend(utxo_a.public_key.clone(), full.clone())
+        .unwrap();
+    op.add_spend(utxo_b.public_key.clone(), full.clone())
+        .unwrap();
 
-    let p_sig = provider_b.sign(
-        &e,
-        op.get_auth_entry_payload_hash_for_bundle(&e, nonce, live),
-    );
-    op.add_provider_signature(&e, provider_b.address.clone(), p_sig, live);
+    sign_provider(&e, &mut op, &provider_b, nonce, live);
     for kp in [&utxo_a, &utxo_b] {
-        let sig = kp.sign(&op.get_auth_hash_for_spend(&e, kp.public_key.clone(), live));
-        op.add_spend_signature(&e, kp.public_key.clone(), sig, live);
+        sign_spend(&e, &mut op, kp, live);
     }
 
     channel
-        .set_auths(&[op.get_auth_entry(&e, nonce, live)])
-        .transact(&op.get_operation_bundle());
+        .set_auths(&op.auth_entries(&e, nonce, live).unwrap())
+        .transact(&channel_operation(&e, &op.operation()));
 
     assert_eq!(channel.utxo_balance(&utxo_a.public_key), 0);
     assert_eq!(channel.utxo_balance(&utxo_b.public_key), 0);
@@ -338,12 +302,7 @@ fn test_moon01_legit_withdraw_is_accepted() {
     let destination = Address::generate(&e);
     let utxo_change = P256KeyPair::generate(&e);
 
-    let mut op = ChannelOperationBuilder::generate(
-        &e,
-        channel.address.clone(),
-        auth.address.clone(),
-        token.address.clone(),
-    );
+    let mut op = builder(&e, &channel, &auth, &token.address);
     // Spend authorizes: withdraw 400 to `destination` + keep 100 as change.
     op.add_spend(
         utxo_a.public_key.clone(),
@@ -352,21 +311,19 @@ fn test_moon01_legit_withdraw_is_accepted() {
             Condition::ExtWithdraw(destination.clone(), 400_i128),
             Condition::Create(utxo_change.public_key.clone(), 100_i128),
         ],
-    );
-    op.add_create(utxo_change.public_key.clone(), 100_i128);
-    op.add_withdraw(&e, destination.clone(), 400_i128, vec![&e]);
+    )
+    .unwrap();
+    op.add_create(utxo_change.public_key.clone(), 100_i128)
+        .unwrap();
+    op.add_withdraw(&e, destination.clone(), 400_i128, vec![&e])
+        .unwrap();
 
-    let p_sig = provider_a.sign(
-        &e,
-        op.get_auth_entry_payload_hash_for_bundle(&e, nonce, live),
-    );
-    op.add_provider_signature(&e, provider_a.address.clone(), p_sig, live);
-    let v_sig = utxo_a.sign(&op.get_auth_hash_for_spend(&e, utxo_a.public_key.clone(), live));
-    op.add_spend_signature(&e, utxo_a.public_key.clone(), v_sig, live);
+    sign_provider(&e, &mut op, &provider_a, nonce, live);
+    sign_spend(&e, &mut op, &utxo_a, live);
 
     channel
-        .set_auths(&[op.get_auth_entry(&e, nonce, live)])
-        .transact(&op.get_operation_bundle());
+        .set_auths(&op.auth_entries(&e, nonce, live).unwrap())
+        .transact(&channel_operation(&e, &op.operation()));
 
     assert_eq!(token.balance(&destination), 400); // funds reached the intended recipient
     assert_eq!(channel.utxo_balance(&utxo_change.public_key), 100);
@@ -404,33 +361,25 @@ fn test_moon01_provider_fee_within_residual_is_accepted() {
     let utxo_dest = P256KeyPair::generate(&e);
     let utxo_opex = P256KeyPair::generate(&e);
 
-    let mut op = ChannelOperationBuilder::generate(
-        &e,
-        channel.address.clone(),
-        auth.address.clone(),
-        token.address.clone(),
-    );
+    let mut op = builder(&e, &channel, &auth, &token.address);
     op.add_spend(
         utxo_src.public_key.clone(),
         vec![
             &e,
             Condition::Create(utxo_dest.public_key.clone(), 995_i128),
         ], // signer claims 995
-    );
-    op.add_create(utxo_dest.public_key.clone(), 995_i128);
-    op.add_create(utxo_opex.public_key.clone(), 5_i128); // provider fee (unsigned), == residual
+    )
+    .unwrap();
+    op.add_create(utxo_dest.public_key.clone(), 995_i128)
+        .unwrap();
+    op.add_create(utxo_opex.public_key.clone(), 5_i128).unwrap(); // provider fee (unsigned), == residual
 
-    let p_sig = provider_a.sign(
-        &e,
-        op.get_auth_entry_payload_hash_for_bundle(&e, nonce, live),
-    );
-    op.add_provider_signature(&e, provider_a.address.clone(), p_sig, live);
-    let s_sig = utxo_src.sign(&op.get_auth_hash_for_spend(&e, utxo_src.public_key.clone(), live));
-    op.add_spend_signature(&e, utxo_src.public_key.clone(), s_sig, live);
+    sign_provider(&e, &mut op, &provider_a, nonce, live);
+    sign_spend(&e, &mut op, &utxo_src, live);
 
     channel
-        .set_auths(&[op.get_auth_entry(&e, nonce, live)])
-        .transact(&op.get_operation_bundle());
+        .set_auths(&op.auth_entries(&e, nonce, live).unwrap())
+        .transact(&channel_operation(&e, &op.operation()));
 
     assert_eq!(channel.utxo_balance(&utxo_src.public_key), 0);
     assert_eq!(channel.utxo_balance(&utxo_dest.public_key), 995); // signer's output delivered exactly
@@ -467,33 +416,26 @@ fn test_moon01_provider_fee_exceeding_residual_is_rejected() {
     let utxo_dest = P256KeyPair::generate(&e);
     let utxo_opex = P256KeyPair::generate(&e);
 
-    let mut op = ChannelOperationBuilder::generate(
-        &e,
-        channel.address.clone(),
-        auth.address.clone(),
-        token.address.clone(),
-    );
+    let mut op = builder(&e, &channel, &auth, &token.address);
     op.add_spend(
         utxo_src.public_key.clone(),
         vec![
             &e,
             Condition::Create(utxo_dest.public_key.clone(), 995_i128),
         ],
-    );
-    op.add_create(utxo_dest.public_key.clone(), 995_i128);
-    op.add_create(utxo_opex.public_key.clone(), 50_i128); // exceeds the 5 residual
+    )
+    .unwrap();
+    op.add_create(utxo_dest.public_key.clone(), 995_i128)
+        .unwrap();
+    op.add_create(utxo_opex.public_key.clone(), 50_i128)
+        .unwrap(); // exceeds the 5 residual
 
-    let p_sig = provider_a.sign(
-        &e,
-        op.get_auth_entry_payload_hash_for_bundle(&e, nonce, live),
-    );
-    op.add_provider_signature(&e, provider_a.address.clone(), p_sig, live);
-    let s_sig = utxo_src.sign(&op.get_auth_hash_for_spend(&e, utxo_src.public_key.clone(), live));
-    op.add_spend_signature(&e, utxo_src.public_key.clone(), s_sig, live);
+    sign_provider(&e, &mut op, &provider_a, nonce, live);
+    sign_spend(&e, &mut op, &utxo_src, live);
 
     let res = channel
-        .set_auths(&[op.get_auth_entry(&e, nonce, live)])
-        .try_transact(&op.get_operation_bundle());
+        .set_auths(&op.auth_entries(&e, nonce, live).unwrap())
+        .try_transact(&channel_operation(&e, &op.operation()));
 
     unbalanced(res.err());
     assert_eq!(channel.utxo_balance(&utxo_src.public_key), 1000); // nothing moved
@@ -529,33 +471,25 @@ fn test_moon01_internal_transfer_extra_create_is_rejected() {
     let utxo_dest = P256KeyPair::generate(&e);
     let utxo_opex = P256KeyPair::generate(&e);
 
-    let mut op = ChannelOperationBuilder::generate(
-        &e,
-        channel.address.clone(),
-        auth.address.clone(),
-        token.address.clone(),
-    );
+    let mut op = builder(&e, &channel, &auth, &token.address);
     op.add_spend(
         utxo_src.public_key.clone(),
         vec![
             &e,
             Condition::Create(utxo_dest.public_key.clone(), 500_i128),
         ], // claims all 500
-    );
-    op.add_create(utxo_dest.public_key.clone(), 500_i128);
-    op.add_create(utxo_opex.public_key.clone(), 1_i128); // no residual to fund this
+    )
+    .unwrap();
+    op.add_create(utxo_dest.public_key.clone(), 500_i128)
+        .unwrap();
+    op.add_create(utxo_opex.public_key.clone(), 1_i128).unwrap(); // no residual to fund this
 
-    let p_sig = provider_a.sign(
-        &e,
-        op.get_auth_entry_payload_hash_for_bundle(&e, nonce, live),
-    );
-    op.add_provider_signature(&e, provider_a.address.clone(), p_sig, live);
-    let s_sig = utxo_src.sign(&op.get_auth_hash_for_spend(&e, utxo_src.public_key.clone(), live));
-    op.add_spend_signature(&e, utxo_src.public_key.clone(), s_sig, live);
+    sign_provider(&e, &mut op, &provider_a, nonce, live);
+    sign_spend(&e, &mut op, &utxo_src, live);
 
     let res = channel
-        .set_auths(&[op.get_auth_entry(&e, nonce, live)])
-        .try_transact(&op.get_operation_bundle());
+        .set_auths(&op.auth_entries(&e, nonce, live).unwrap())
+        .try_transact(&channel_operation(&e, &op.operation()));
 
     unbalanced(res.err());
     assert_eq!(channel.utxo_balance(&utxo_src.public_key), 500);
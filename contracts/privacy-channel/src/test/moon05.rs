@@ -6,7 +6,7 @@ extern crate std;
 use crate::{test::test::create_contracts, transact::ChannelOperation};
 use moonlight_errors::Error as ContractError;
 use moonlight_helpers::testutils::snapshot::get_env_with_g_accounts;
-use soroban_sdk::{testutils::Address as _, vec, Address, Error};
+use soroban_sdk::{testutils::Address as _, vec, Address, Error, Map};
 
 fn assert_invalid_amount(res_err: Option<Result<Error, soroban_sdk::InvokeError>>) {
     assert_eq!(
@@ -28,6 +28,9 @@ fn test_moon05_zero_withdraw_amount_is_rejected() {
         create: vec![&e],
         deposit: vec![&e],
         withdraw: vec![&e, (to, 0_i128, vec![&e])],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
     };
 
     assert_invalid_amount(channel.try_transact(&op).err());
@@ -44,6 +47,9 @@ fn test_moon05_negative_deposit_amount_is_rejected() {
         create: vec![&e],
         deposit: vec![&e, (from, -1_i128, vec![&e])],
         withdraw: vec![&e],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
     };
 
     assert_invalid_amount(channel.try_transact(&op).err());
@@ -11,7 +11,8 @@ use channel_auth_contract::contract::{ChannelAuthContract, ChannelAuthContractAr
 use moonlight_helpers::testutils::{keys::P256KeyPair, snapshot::get_env_with_g_accounts};
 use moonlight_primitives::Condition;
 use soroban_sdk::{
-    contract, contractimpl, symbol_short, testutils::Address as _, vec, Address, Env, MuxedAddress,
+    contract, contractimpl, symbol_short, testutils::Address as _, vec, Address, Env, Map,
+    MuxedAddress,
 };
 
 /// A malicious "asset" whose `transfer` re-enters the channel's `transact`.
@@ -32,6 +33,9 @@ impl ReentrantAsset {
             create: vec![&e],
             deposit: vec![&e],
             withdraw: vec![&e],
+            notes: Map::new(&e),
+            ephemeral_keys: Map::new(&e),
+            allowance_deposits: vec![&e],
         };
         PrivacyChannelContractClient::new(&e, &channel).transact(&empty);
     }
@@ -71,6 +75,9 @@ fn test_moon06_reentrant_asset_call_is_rejected() {
             ),
         ],
         withdraw: vec![&e],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
     };
 
     let res = channel.try_transact(&op);
@@ -0,0 +1,175 @@
+#![cfg(test)]
+//! Encrypted notes: a `create` entry may carry a note, published in the `Transact` event, and a
+//! spender may bind it with a signed `Condition::Note`.
+extern crate std;
+
+use crate::{contract::Transact, test::test::create_contracts, transact::ChannelOperation};
+use moonlight_errors::Error as ContractError;
+use moonlight_helpers::{
+    notes::{decrypt_note, encrypt_note, NotePlaintext, ViewingKey},
+    testutils::keys::P256KeyPair,
+};
+use moonlight_primitives::Condition;
+use moonlight_utxo_core::core::hash_utxo_key;
+use rand::rngs::OsRng;
+use soroban_sdk::{
+    testutils::{Address as _, Events},
+    vec, Address, Bytes, BytesN, Env, Event, Map,
+};
+
+fn note_hash(e: &Env, note: &Bytes) -> BytesN<32> {
+    e.crypto().sha256(note).into()
+}
+
+#[test]
+fn test_recipient_decrypts_note_from_transact_event() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let depositor = Address::generate(&e);
+    token.mint(&depositor, &300_i128);
+
+    let (utxo_a, utxo_b) = (
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+    );
+    let viewing_key = ViewingKey::generate(&mut OsRng);
+    let plaintext = NotePlaintext {
+        amount: 200,
+        blinding: [3; 32],
+        memo: b"rent".to_vec(),
+    };
+    let note = encrypt_note(
+        &mut OsRng,
+        &viewing_key.public_key(),
+        &utxo_b.to_array(),
+        &plaintext,
+    )
+    .unwrap();
+    let note = Bytes::from_slice(&e, &note);
+
+    let mut notes = Map::new(&e);
+    notes.set(utxo_b.clone(), note.clone());
+    channel.transact(&ChannelOperation {
+        spend: vec![&e],
+        create: vec![&e, (utxo_a.clone(), 100_i128), (utxo_b.clone(), 200_i128)],
+        deposit: vec![&e, (depositor.clone(), 300_i128, vec![&e])],
+        withdraw: vec![&e],
+        notes,
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
+    });
+
+    let event = Transact {
+        spent: vec![&e],
+        created: vec![&e, hash_utxo_key(&e, &utxo_a), hash_utxo_key(&e, &utxo_b)],
+        notes: vec![&e, (hash_utxo_key(&e, &utxo_b), note.clone())],
+        ephemeral_keys: vec![&e],
+        deposits: vec![&e, (depositor, 300_i128)],
+        withdrawals: vec![&e],
+        supply: 300_i128,
+        payload: None,
+    };
+    assert_eq!(
+        e.events().all().events().last().unwrap(),
+        &event.to_xdr(&e, &channel.address)
+    );
+
+    let decrypted = decrypt_note(&viewing_key, &utxo_b.to_array(), &note.to_alloc_vec());
+    assert_eq!(decrypted, Ok(plaintext));
+}
+
+#[test]
+fn test_note_requires_a_created_utxo() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let depositor = Address::generate(&e);
+    token.mint(&depositor, &100_i128);
+
+    let mut notes = Map::new(&e);
+    notes.set(
+        P256KeyPair::generate(&e).public_key,
+        Bytes::from_array(&e, &[1; 8]),
+    );
+    let result = channel.try_transact(&ChannelOperation {
+        spend: vec![&e],
+        create: vec![&e, (P256KeyPair::generate(&e).public_key, 100_i128)],
+        deposit: vec![&e, (depositor, 100_i128, vec![&e])],
+        withdraw: vec![&e],
+        notes,
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
+    });
+
+    assert_eq!(
+        result.err(),
+        Some(Ok(soroban_sdk::Error::from_contract_error(
+            ContractError::NoteWithoutCreate as u32
+        )))
+    );
+}
+
+#[test]
+fn test_signed_note_condition_binds_the_note() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let depositor = Address::generate(&e);
+    token.mint(&depositor, &100_i128);
+
+    let (utxo_a, utxo_b) = (
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+    );
+    channel.transact(&ChannelOperation {
+        spend: vec![&e],
+        create: vec![&e, (utxo_a.clone(), 100_i128)],
+        deposit: vec![&e, (depositor, 100_i128, vec![&e])],
+        withdraw: vec![&e],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
+    });
+
+    let signed_note = Bytes::from_array(&e, &[5; 16]);
+    let transfer = |note: Option<Bytes>| {
+        let mut notes = Map::new(&e);
+        if let Some(note) = note {
+            notes.set(utxo_b.clone(), note);
+        }
+        ChannelOperation {
+            spend: vec![
+                &e,
+                (
+                    utxo_a.clone(),
+                    vec![
+                        &e,
+                        Condition::Create(utxo_b.clone(), 100_i128),
+                        Condition::Note(utxo_b.clone(), note_hash(&e, &signed_note)),
+                    ],
+                ),
+            ],
+            create: vec![&e, (utxo_b.clone(), 100_i128)],
+            deposit: vec![&e],
+            withdraw: vec![&e],
+            notes,
+            ephemeral_keys: Map::new(&e),
+            allowance_deposits: vec![&e],
+        }
+    };
+
+    let unauthorized = Some(Ok(soroban_sdk::Error::from_contract_error(
+        ContractError::UnauthorizedOperation as u32,
+    )));
+    assert_eq!(channel.try_transact(&transfer(None)).err(), unauthorized);
+    assert_eq!(
+        channel
+            .try_transact(&transfer(Some(Bytes::from_array(&e, &[6; 16]))))
+            .err(),
+        unauthorized
+    );
+
+    channel.transact(&transfer(Some(signed_note.clone())));
+    assert_eq!(channel.utxo_balance(&utxo_b), 100);
+}
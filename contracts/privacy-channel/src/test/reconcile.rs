@@ -0,0 +1,170 @@
+#![cfg(test)]
+//! `reconcile` compares the asset balance with the supply and unspent UTXO total, and `skim` lets
+//! the admin move tokens sent to the channel directly.
+extern crate std;
+
+use crate::{
+    contract::{Reconciled, Reconciliation, Skimmed},
+    test::test::create_contracts,
+    transact::ChannelOperation,
+};
+use moonlight_helpers::testutils::keys::P256KeyPair;
+use moonlight_primitives::Condition;
+use soroban_sdk::{
+    testutils::{Address as _, Events, MockAuth, MockAuthInvoke},
+    vec, Address, Env, Event, IntoVal, Map,
+};
+
+#[test]
+fn test_reconcile_reports_balance_supply_and_unspent_value() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+
+    let depositor = Address::generate(&e);
+    let recipient = Address::generate(&e);
+    token.mint(&depositor, &500_i128);
+    let utxo_a = P256KeyPair::generate(&e);
+    let utxo_b = P256KeyPair::generate(&e);
+
+    channel.transact(&ChannelOperation {
+        spend: vec![&e],
+        create: vec![&e, (utxo_a.public_key.clone(), 500_i128)],
+        deposit: vec![&e, (depositor, 500_i128, vec![&e])],
+        withdraw: vec![&e],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
+    });
+    channel.transact(&ChannelOperation {
+        spend: vec![
+            &e,
+            (
+                utxo_a.public_key.clone(),
+                vec![
+                    &e,
+                    Condition::Create(utxo_b.public_key.clone(), 320_i128),
+                    Condition::ExtWithdraw(recipient.clone(), 180_i128),
+                ],
+            ),
+        ],
+        create: vec![&e, (utxo_b.public_key.clone(), 320_i128)],
+        deposit: vec![&e],
+        withdraw: vec![&e, (recipient, 180_i128, vec![&e])],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
+    });
+
+    let expected = Reconciliation {
+        balance: 320,
+        supply: 320,
+        unspent: 320,
+    };
+    assert_eq!(channel.reconcile(), expected);
+    assert_eq!(
+        e.events().all().events().last().unwrap(),
+        &Reconciled {
+            balance: 320,
+            supply: 320,
+            unspent: 320,
+        }
+        .to_xdr(&e, &channel.address)
+    );
+
+    token.mint(&channel.address, &75_i128);
+    assert_eq!(
+        channel.reconcile(),
+        Reconciliation {
+            balance: 395,
+            ..expected
+        }
+    );
+}
+
+#[test]
+fn test_admin_skims_surplus_above_supply() {
+    let e = Env::default();
+    let (channel, _, token, admin) = create_contracts(&e);
+    let depositor = Address::generate(&e);
+    let treasury = Address::generate(&e);
+
+    e.mock_all_auths();
+    token.mint(&depositor, &200_i128);
+    channel.transact(&ChannelOperation {
+        spend: vec![&e],
+        create: vec![&e, (P256KeyPair::generate(&e).public_key, 200_i128)],
+        deposit: vec![&e, (depositor, 200_i128, vec![&e])],
+        withdraw: vec![&e],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
+    });
+    token.mint(&channel.address, &40_i128);
+    e.set_auths(&[]);
+
+    let skimmed = channel
+        .mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &channel.address,
+                fn_name: "skim",
+                args: (&treasury,).into_val(&e),
+                sub_invokes: &[],
+            },
+        }])
+        .skim(&treasury);
+
+    assert_eq!(skimmed, 40);
+    assert_eq!(
+        e.events().all().events().last().unwrap(),
+        &Skimmed {
+            to: treasury.clone(),
+            amount: 40,
+        }
+        .to_xdr(&e, &channel.address)
+    );
+    assert_eq!(token.balance(&treasury), 40);
+    assert_eq!(token.balance(&channel.address), 200);
+    assert_eq!(channel.supply(), 200);
+
+    let again = channel
+        .mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &channel.address,
+                fn_name: "skim",
+                args: (&treasury,).into_val(&e),
+                sub_invokes: &[],
+            },
+        }])
+        .skim(&treasury);
+    assert_eq!(again, 0);
+    assert_eq!(token.balance(&channel.address), 200);
+}
+
+#[test]
+fn test_non_admin_cannot_skim() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    let attacker = Address::generate(&e);
+
+    e.mock_all_auths();
+    token.mint(&channel.address, &40_i128);
+    e.set_auths(&[]);
+
+    let result = channel
+        .mock_auths(&[MockAuth {
+            address: &attacker,
+            invoke: &MockAuthInvoke {
+                contract: &channel.address,
+                fn_name: "skim",
+                args: (&attacker,).into_val(&e),
+                sub_invokes: &[],
+            },
+        }])
+        .try_skim(&attacker);
+
+    assert!(result.is_err());
+    assert_eq!(token.balance(&channel.address), 40);
+}
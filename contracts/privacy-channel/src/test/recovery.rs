@@ -0,0 +1,403 @@
+#![cfg(test)]
+//! Guardian recovery: a UTXO created with a signed `Condition::Recovery` can be spent by its
+//! guardian after a delay, unless its own key cancels the recovery first.
+extern crate std;
+
+use crate::{
+    contract::PrivacyChannelContractClient,
+    recovery::{Recovered, RecoveryArmed, RecoveryCancelled, RecoveryConfig},
+    test::test::create_contracts,
+    transact::ChannelOperation,
+};
+use moonlight_errors::Error as ContractError;
+use moonlight_helpers::testutils::keys::P256KeyPair;
+use moonlight_primitives::{AuthRequirements, Condition, SignerKey};
+use moonlight_utxo_core::core::hash_utxo_key;
+use soroban_sdk::{
+    testutils::{Address as _, Events, Ledger, MockAuth, MockAuthInvoke},
+    vec, Address, BytesN, Env, Error, Event, IntoVal, Map, Val, Vec,
+};
+
+const DELAY: u32 = 100;
+
+fn operation(
+    e: &Env,
+    spend: Vec<(BytesN<65>, Vec<Condition>)>,
+    create: Vec<(BytesN<65>, i128)>,
+    deposit: Vec<(Address, i128, Vec<Condition>)>,
+) -> ChannelOperation {
+    ChannelOperation {
+        spend,
+        create,
+        deposit,
+        withdraw: vec![e],
+        notes: Map::new(e),
+        ephemeral_keys: Map::new(e),
+        allowance_deposits: vec![e],
+    }
+}
+
+/// Deposits 500 into `utxo` with `guardian` as its recovery guardian.
+fn create_guarded_utxo(
+    e: &Env,
+    channel: &PrivacyChannelContractClient,
+    token: &token_contract::TestTokenClient,
+    utxo: &BytesN<65>,
+    guardian: &BytesN<65>,
+) {
+    let depositor = Address::generate(e);
+    token.mint(&depositor, &500_i128);
+    channel.transact(&operation(
+        e,
+        vec![e],
+        vec![e, (utxo.clone(), 500_i128)],
+        vec![
+            e,
+            (
+                depositor,
+                500_i128,
+                vec![
+                    e,
+                    Condition::Create(utxo.clone(), 500_i128),
+                    Condition::Recovery(utxo.clone(), guardian.clone(), DELAY),
+                ],
+            ),
+        ],
+    ));
+}
+
+/// The auth-contract arguments requiring `signer` to sign `conditions`.
+fn p256_requirement(e: &Env, signer: &BytesN<65>, conditions: Vec<Condition>) -> Vec<Val> {
+    let mut req = Map::new(e);
+    req.set(SignerKey::P256(signer.clone()), conditions);
+    (AuthRequirements(req),).into_val(e)
+}
+
+#[test]
+fn test_guardian_recovers_after_delay() {
+    let e = Env::default();
+    let (channel, auth, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let (utxo, guardian, rescued) = (
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+    );
+    create_guarded_utxo(&e, &channel, &token, &utxo, &guardian);
+    // The recipient's signal that a guardian can claw the UTXO back.
+    assert!(e.events().all().events().contains(
+        &RecoveryArmed {
+            utxo: hash_utxo_key(&e, &utxo),
+            guardian: guardian.clone(),
+            delay: DELAY,
+        }
+        .to_xdr(&e, &channel.address)
+    ));
+    assert_eq!(
+        channel.recovery(&utxo),
+        Some(RecoveryConfig {
+            guardian: guardian.clone(),
+            delay: DELAY,
+        })
+    );
+
+    // The guardian signs the recovery spend behind a leading `Recovery` condition.
+    let conditions = vec![&e, Condition::Create(rescued.clone(), 500_i128)];
+    let mut signed = vec![
+        &e,
+        Condition::Recovery(utxo.clone(), guardian.clone(), DELAY),
+    ];
+    signed.append(&conditions);
+    channel
+        .mock_auths(&[MockAuth {
+            address: &auth.address,
+            invoke: &MockAuthInvoke {
+                contract: &channel.address,
+                fn_name: "begin_recovery",
+                args: p256_requirement(&e, &guardian, signed),
+                sub_invokes: &[],
+            },
+        }])
+        .begin_recovery(&utxo, &conditions);
+    let executable_at = e.ledger().sequence() + DELAY;
+    assert_eq!(
+        channel.pending_recovery(&utxo).unwrap().executable_at,
+        executable_at
+    );
+    assert_eq!(
+        channel
+            .mock_all_auths()
+            .try_begin_recovery(&utxo, &conditions)
+            .err(),
+        Some(Ok(Error::from_contract_error(
+            ContractError::RecoveryPending as u32
+        )))
+    );
+
+    let recovery_op = operation(
+        &e,
+        vec![&e, (utxo.clone(), conditions.clone())],
+        vec![&e, (rescued.clone(), 500_i128)],
+        vec![&e],
+    );
+    assert_eq!(
+        channel.try_recover(&utxo, &recovery_op).err(),
+        Some(Ok(Error::from_contract_error(
+            ContractError::RecoveryNotReady as u32
+        )))
+    );
+
+    // Once matured, the spend needs no signature from the lost key: the bundle carries no UTXO
+    // requirements, only the provider's.
+    e.ledger().with_mut(|l| l.sequence_number = executable_at);
+    channel
+        .mock_auths(&[MockAuth {
+            address: &auth.address,
+            invoke: &MockAuthInvoke {
+                contract: &channel.address,
+                fn_name: "recover",
+                args: ().into_val(&e),
+                sub_invokes: &[],
+            },
+        }])
+        .recover(&utxo, &recovery_op);
+    let recovered = Recovered {
+        utxo: hash_utxo_key(&e, &utxo),
+        guardian,
+    };
+    assert!(e
+        .events()
+        .all()
+        .events()
+        .contains(&recovered.to_xdr(&e, &channel.address)));
+    assert_eq!(channel.utxo_balance(&utxo), 0);
+    assert_eq!(channel.utxo_balance(&rescued), 500);
+    assert_eq!(channel.recovery(&utxo), None);
+    assert!(channel.pending_recovery(&utxo).is_none());
+}
+
+#[test]
+fn test_recovery_spend_must_match_what_the_guardian_signed() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let (utxo, guardian, rescued, thief) = (
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+    );
+    create_guarded_utxo(&e, &channel, &token, &utxo, &guardian);
+    channel.begin_recovery(&utxo, &vec![&e, Condition::Create(rescued, 500_i128)]);
+    e.ledger().with_mut(|l| l.sequence_number += DELAY);
+
+    let conditions = vec![&e, Condition::Create(thief.clone(), 500_i128)];
+    let redirected = operation(
+        &e,
+        vec![&e, (utxo.clone(), conditions)],
+        vec![&e, (thief, 500_i128)],
+        vec![&e],
+    );
+    assert_eq!(
+        channel.try_recover(&utxo, &redirected).err(),
+        Some(Ok(Error::from_contract_error(
+            ContractError::UnauthorizedOperation as u32
+        )))
+    );
+}
+
+#[test]
+fn test_owner_cancels_pending_recovery() {
+    let e = Env::default();
+    let (channel, auth, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let (utxo, guardian, rescued) = (
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+    );
+    create_guarded_utxo(&e, &channel, &token, &utxo, &guardian);
+
+    assert_eq!(
+        channel.try_cancel_recovery(&utxo).err(),
+        Some(Ok(Error::from_contract_error(
+            ContractError::RecoveryNotPending as u32
+        )))
+    );
+
+    let conditions = vec![&e, Condition::Create(rescued.clone(), 500_i128)];
+    channel.begin_recovery(&utxo, &conditions);
+    channel
+        .mock_auths(&[MockAuth {
+            address: &auth.address,
+            invoke: &MockAuthInvoke {
+                contract: &channel.address,
+                fn_name: "cancel_recovery",
+                args: p256_requirement(
+                    &e,
+                    &utxo,
+                    vec![&e, Condition::CancelRecovery(utxo.clone())],
+                ),
+                sub_invokes: &[],
+            },
+        }])
+        .cancel_recovery(&utxo);
+    assert_eq!(
+        e.events().all().events().last().unwrap(),
+        &RecoveryCancelled {
+            utxo: hash_utxo_key(&e, &utxo),
+            guardian,
+        }
+        .to_xdr(&e, &channel.address)
+    );
+    assert_eq!(channel.recovery(&utxo), None);
+
+    // Cancelling removes the guardian, so the signed recovery cannot be replayed.
+    e.ledger().with_mut(|l| l.sequence_number += DELAY);
+    let recovery_op = operation(
+        &e,
+        vec![&e, (utxo.clone(), conditions.clone())],
+        vec![&e, (rescued, 500_i128)],
+        vec![&e],
+    );
+    assert_eq!(
+        channel.try_recover(&utxo, &recovery_op).err(),
+        Some(Ok(Error::from_contract_error(
+            ContractError::RecoveryNotSet as u32
+        )))
+    );
+    assert_eq!(
+        channel.try_begin_recovery(&utxo, &conditions).err(),
+        Some(Ok(Error::from_contract_error(
+            ContractError::RecoveryNotSet as u32
+        )))
+    );
+    assert_eq!(channel.utxo_balance(&utxo), 500);
+}
+
+#[test]
+fn test_spending_clears_the_recovery() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let (utxo, guardian, rescued, next) = (
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+    );
+    create_guarded_utxo(&e, &channel, &token, &utxo, &guardian);
+    channel.begin_recovery(
+        &utxo,
+        &vec![&e, Condition::Create(rescued.clone(), 500_i128)],
+    );
+    assert!(channel.pending_recovery(&utxo).is_some());
+
+    // The owner spends the UTXO with its own key before the recovery matures.
+    channel.transact(&operation(
+        &e,
+        vec![
+            &e,
+            (
+                utxo.clone(),
+                vec![&e, Condition::Create(next.clone(), 500_i128)],
+            ),
+        ],
+        vec![&e, (next, 500_i128)],
+        vec![&e],
+    ));
+    assert_eq!(channel.recovery(&utxo), None);
+    assert!(channel.pending_recovery(&utxo).is_none());
+}
+
+#[test]
+fn test_recovery_conditions_are_bound_to_the_create() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let owner = P256KeyPair::generate(&e).public_key;
+    let (utxo, guardian) = (
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+    );
+    let depositor = Address::generate(&e);
+    token.mint(&depositor, &1_000_i128);
+    channel.transact(&operation(
+        &e,
+        vec![&e],
+        vec![&e, (owner.clone(), 500_i128)],
+        vec![&e, (depositor.clone(), 500_i128, vec![&e])],
+    ));
+    let invalid = Some(Ok(Error::from_contract_error(
+        ContractError::InvalidRecoveryCondition as u32,
+    )));
+    let recovery = Condition::Recovery(utxo.clone(), guardian.clone(), DELAY);
+    let create = Condition::Create(utxo.clone(), 500_i128);
+
+    // A recovery without the create it guards.
+    let op = operation(
+        &e,
+        vec![&e, (owner.clone(), vec![&e, recovery.clone()])],
+        vec![&e, (utxo.clone(), 500_i128)],
+        vec![&e],
+    );
+    assert_eq!(channel.try_transact(&op).err(), invalid);
+
+    // A zero delay leaves no time to cancel.
+    let op = operation(
+        &e,
+        vec![
+            &e,
+            (
+                owner.clone(),
+                vec![
+                    &e,
+                    create.clone(),
+                    Condition::Recovery(utxo.clone(), guardian.clone(), 0),
+                ],
+            ),
+        ],
+        vec![&e, (utxo.clone(), 500_i128)],
+        vec![&e],
+    );
+    assert_eq!(channel.try_transact(&op).err(), invalid);
+
+    // A co-signer of the create cannot add a guardian the spender did not sign.
+    let op = operation(
+        &e,
+        vec![&e, (owner.clone(), vec![&e, create.clone()])],
+        vec![&e, (utxo.clone(), 500_i128), (guardian.clone(), 500_i128)],
+        vec![
+            &e,
+            (
+                depositor.clone(),
+                500_i128,
+                vec![
+                    &e,
+                    create.clone(),
+                    recovery.clone(),
+                    Condition::Create(guardian.clone(), 500_i128),
+                ],
+            ),
+        ],
+    );
+    assert_eq!(channel.try_transact(&op).err(), invalid);
+
+    // A cancel signature is not a spend authorization.
+    let op = operation(
+        &e,
+        vec![
+            &e,
+            (
+                owner.clone(),
+                vec![&e, Condition::CancelRecovery(owner.clone())],
+            ),
+        ],
+        vec![&e, (utxo.clone(), 500_i128)],
+        vec![&e],
+    );
+    assert_eq!(channel.try_transact(&op).err(), invalid);
+
+    assert_eq!(channel.utxo_balance(&owner), 500);
+    assert_eq!(channel.recovery(&utxo), None);
+}
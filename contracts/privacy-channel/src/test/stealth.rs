@@ -0,0 +1,189 @@
+#![cfg(test)]
+//! Stealth UTXOs: a `create` entry may carry the ephemeral key of a one-time UTXO key, published in
+//! the `Transact` event for the recipient to scan, and a spender may bind it with a signed
+//! `Condition::EphemeralKey`.
+extern crate std;
+
+use crate::{
+    contract::Transact,
+    test::test::{builder, channel_operation, create_contracts, sign_provider, sign_spend},
+    transact::ChannelOperation,
+};
+use moonlight_errors::Error as ContractError;
+use moonlight_helpers::{
+    stealth::{derive_stealth_utxo, scan, StealthKeys},
+    testutils::{
+        keys::P256KeyPair,
+        snapshot::{get_env_with_g_accounts, get_snapshot_g_accounts},
+    },
+};
+use moonlight_primitives::Condition;
+use moonlight_utxo_core::core::hash_utxo_key;
+use rand::rngs::OsRng;
+use soroban_sdk::{
+    testutils::{Address as _, Events},
+    vec, Address, BytesN, Env, Event, Map,
+};
+
+#[test]
+fn test_recipient_scans_stealth_utxo_from_transact_event() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let depositor = Address::generate(&e);
+    token.mint(&depositor, &300_i128);
+
+    let recipient = StealthKeys::generate(&mut OsRng);
+    let meta_key = recipient.meta_key();
+    let payment = derive_stealth_utxo(&mut OsRng, &meta_key).unwrap();
+    let (change, stealth_utxo, ephemeral_key) = (
+        P256KeyPair::generate(&e).public_key,
+        BytesN::from_array(&e, &payment.utxo),
+        BytesN::from_array(&e, &payment.ephemeral_key),
+    );
+
+    let mut ephemeral_keys = Map::new(&e);
+    ephemeral_keys.set(stealth_utxo.clone(), ephemeral_key.clone());
+    channel.transact(&ChannelOperation {
+        spend: vec![&e],
+        create: vec![
+            &e,
+            (change.clone(), 100_i128),
+            (stealth_utxo.clone(), 200_i128),
+        ],
+        deposit: vec![&e, (depositor.clone(), 300_i128, vec![&e])],
+        withdraw: vec![&e],
+        notes: Map::new(&e),
+        ephemeral_keys,
+        allowance_deposits: vec![&e],
+    });
+
+    let event = Transact {
+        spent: vec![&e],
+        created: vec![
+            &e,
+            hash_utxo_key(&e, &change),
+            hash_utxo_key(&e, &stealth_utxo),
+        ],
+        notes: vec![&e],
+        ephemeral_keys: vec![
+            &e,
+            (hash_utxo_key(&e, &stealth_utxo), ephemeral_key.clone()),
+        ],
+        deposits: vec![&e, (depositor, 300_i128)],
+        withdrawals: vec![&e],
+        supply: 300_i128,
+        payload: None,
+    };
+    assert_eq!(
+        e.events().all().events().last().unwrap(),
+        &event.to_xdr(&e, &channel.address)
+    );
+
+    let found = scan(
+        recipient.viewing_key(),
+        &meta_key.spend,
+        &hash_utxo_key(&e, &stealth_utxo).to_array(),
+        &ephemeral_key.to_array(),
+    );
+    assert_eq!(found, Ok(Some(payment.utxo)));
+    assert_eq!(channel.utxo_balance(&stealth_utxo), 200);
+}
+
+#[test]
+fn test_ephemeral_key_requires_a_created_utxo() {
+    let e = Env::default();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+    let depositor = Address::generate(&e);
+    token.mint(&depositor, &100_i128);
+
+    let mut ephemeral_keys = Map::new(&e);
+    ephemeral_keys.set(
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+    );
+    let result = channel.try_transact(&ChannelOperation {
+        spend: vec![&e],
+        create: vec![&e, (P256KeyPair::generate(&e).public_key, 100_i128)],
+        deposit: vec![&e, (depositor, 100_i128, vec![&e])],
+        withdraw: vec![&e],
+        notes: Map::new(&e),
+        ephemeral_keys,
+        allowance_deposits: vec![&e],
+    });
+
+    assert_eq!(
+        result.err(),
+        Some(Ok(soroban_sdk::Error::from_contract_error(
+            ContractError::EphemeralKeyWithoutCreate as u32
+        )))
+    );
+}
+
+#[test]
+fn test_signed_ephemeral_key_condition_binds_the_key() {
+    let e = get_env_with_g_accounts();
+    let (provider, depositor, _, _, _) = get_snapshot_g_accounts(&e);
+    let (channel, auth, token, _) = create_contracts(&e);
+    auth.mock_all_auths().add_provider(&provider.address);
+    token.mock_all_auths().mint(&depositor.address, &100_i128);
+
+    let owner = P256KeyPair::generate(&e);
+    channel.mock_all_auths().transact(&ChannelOperation {
+        spend: vec![&e],
+        create: vec![&e, (owner.public_key.clone(), 100_i128)],
+        deposit: vec![&e, (depositor.address.clone(), 100_i128, vec![&e])],
+        withdraw: vec![&e],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
+    });
+    e.set_auths(&[]);
+
+    let payment =
+        derive_stealth_utxo(&mut OsRng, &StealthKeys::generate(&mut OsRng).meta_key()).unwrap();
+    let (stealth_utxo, ephemeral_key) = (
+        BytesN::from_array(&e, &payment.utxo),
+        BytesN::from_array(&e, &payment.ephemeral_key),
+    );
+    let live = e.ledger().sequence() + 100;
+    let mut op = builder(&e, &channel, &auth, &token.address);
+    op.add_create(stealth_utxo.clone(), 100).unwrap();
+    let bound = op
+        .add_ephemeral_key(stealth_utxo.clone(), ephemeral_key.clone())
+        .unwrap();
+    op.add_spend(
+        owner.public_key.clone(),
+        vec![&e, Condition::Create(stealth_utxo.clone(), 100), bound],
+    )
+    .unwrap();
+    sign_spend(&e, &mut op, &owner, live);
+    sign_provider(&e, &mut op, &provider, 0, live);
+    let entries = op.auth_entries(&e, 0, live).unwrap();
+
+    // The provider swaps in a key the recipient cannot scan, or drops it. The signatures cover
+    // only the conditions, so only the binding stops it.
+    let unauthorized = Some(Ok(soroban_sdk::Error::from_contract_error(
+        ContractError::UnauthorizedOperation as u32,
+    )));
+    let mut swapped = channel_operation(&e, &op.operation());
+    swapped
+        .ephemeral_keys
+        .set(stealth_utxo.clone(), P256KeyPair::generate(&e).public_key);
+    assert_eq!(
+        channel.set_auths(&entries).try_transact(&swapped).err(),
+        unauthorized
+    );
+    let mut dropped = channel_operation(&e, &op.operation());
+    dropped.ephemeral_keys = Map::new(&e);
+    assert_eq!(
+        channel.set_auths(&entries).try_transact(&dropped).err(),
+        unauthorized
+    );
+
+    channel
+        .set_auths(&entries)
+        .transact(&channel_operation(&e, &op.operation()));
+    assert_eq!(channel.utxo_balance(&stealth_utxo), 100);
+}
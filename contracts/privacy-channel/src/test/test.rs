@@ -3,21 +3,22 @@ extern crate std;
 
 use crate::{
     contract::{PrivacyChannelContract, PrivacyChannelContractArgs, PrivacyChannelContractClient},
-    test::channel_operation_builder::ChannelOperationBuilder,
+    transact::ChannelOperation,
 };
 
 use channel_auth_contract::contract::{
     ChannelAuthContract, ChannelAuthContractArgs, ChannelAuthContractClient,
 };
 
+use moonlight_client::{AccountSignature, ChannelContext, ChannelOperationBuilder};
 use moonlight_helpers::testutils::{
-    keys::P256KeyPair,
+    keys::{Ed25519Account, P256KeyPair},
     snapshot::{get_env_with_g_accounts, get_snapshot_g_accounts},
 };
 use moonlight_primitives::Condition;
 use soroban_sdk::{
     testutils::{Address as _, Ledger, MockAuth, MockAuthInvoke},
-    vec, Address, Env, FromVal, IntoVal, String,
+    vec, Address, BytesN, Env, FromVal, IntoVal, Map, String, TryFromVal, Val,
 };
 
 use token_contract::{TestToken as Token, TestTokenClient as TokenClient};
@@ -65,6 +66,94 @@ pub fn create_contracts(
     (privacy_channel_contract, auth_contract, token, admin)
 }
 
+/// Withdraws the whole of a 100 UTXO to `to`.
+pub fn withdraw_op(e: &Env, utxo: &BytesN<65>, to: &Address) -> ChannelOperation {
+    ChannelOperation {
+        spend: vec![
+            e,
+            (
+                utxo.clone(),
+                vec![e, Condition::ExtWithdraw(to.clone(), 100)],
+            ),
+        ],
+        create: vec![e],
+        deposit: vec![e],
+        withdraw: vec![e, (to.clone(), 100, vec![e])],
+        notes: Map::new(e),
+        ephemeral_keys: Map::new(e),
+        allowance_deposits: vec![e],
+    }
+}
+
+/// A `moonlight-client` builder for `channel`, whose asset is `asset`.
+pub fn builder(
+    e: &Env,
+    channel: &PrivacyChannelContractClient,
+    auth: &ChannelAuthContractClient,
+    asset: &Address,
+) -> ChannelOperationBuilder {
+    ChannelOperationBuilder::new(e, context(e, channel, auth, asset))
+}
+
+pub fn context(
+    e: &Env,
+    channel: &PrivacyChannelContractClient,
+    auth: &ChannelAuthContractClient,
+    asset: &Address,
+) -> ChannelContext {
+    ChannelContext {
+        network_id: e.ledger().network_id(),
+        channel: channel.address.clone(),
+        auth: auth.address.clone(),
+        asset: asset.clone(),
+    }
+}
+
+/// The client links its own build of this crate, so its `ChannelOperation` is converted
+/// through a `Val`.
+pub fn channel_operation(e: &Env, op: &moonlight_client::ChannelOperation) -> ChannelOperation {
+    let val: Val = op.into_val(e);
+    ChannelOperation::try_from_val(e, &val).unwrap()
+}
+
+pub fn sign_spend(e: &Env, op: &mut ChannelOperationBuilder, owner: &P256KeyPair, live: u32) {
+    let hash = op.spend_payload_hash(e, &owner.public_key, live).unwrap();
+    op.add_spend_signature(e, owner.public_key.clone(), owner.sign(&hash), live)
+        .unwrap();
+}
+
+pub fn sign_provider(
+    e: &Env,
+    op: &mut ChannelOperationBuilder,
+    provider: &Ed25519Account,
+    nonce: i64,
+    live: u32,
+) {
+    let hash = op.bundle_payload_hash(e, nonce, live).unwrap();
+    op.add_provider_signature(&provider.address, provider.sign(e, hash), live)
+        .unwrap();
+}
+
+pub fn sign_deposit(
+    e: &Env,
+    op: &mut ChannelOperationBuilder,
+    depositor: &Ed25519Account,
+    nonce: i64,
+    live: u32,
+) {
+    let hash = op
+        .deposit_payload_hash(e, &depositor.address, nonce, live)
+        .unwrap();
+    op.add_deposit_signature(
+        depositor.address.clone(),
+        AccountSignature {
+            public_key: depositor.public_key.clone(),
+            signature: depositor.sign(e, hash),
+        },
+    )
+    .unwrap();
+}
+
 #[test]
 fn test_admin_transfer_keeps_current_admin_until_acceptance() {
     let e = Env::default();
@@ -202,61 +291,33 @@ fn test_single_deposit_with_auth() {
 
     let nonce = 0;
 
-    let mut deposit_op = ChannelOperationBuilder::generate(
-        &e,
-        channel.address.clone(),
-        auth.address.clone(),
-        token.address.clone(),
-    );
+    let mut deposit_op = builder(&e, &channel, &auth, &token.address);
 
-    // no conditions as we cant properly test the G address signing mixed with the mocked address
-    deposit_op.add_deposit(
-        &e,
-        john.address.clone(),
-        500_i128,
-        vec![&e, Condition::Create(utxo_a.public_key.clone(), 500_i128)],
-    );
+    deposit_op
+        .add_deposit(
+            &e,
+            john.address.clone(),
+            500_i128,
+            vec![&e, Condition::Create(utxo_a.public_key.clone(), 500_i128)],
+        )
+        .unwrap();
 
-    deposit_op.add_create(utxo_a.public_key.clone(), 500_i128);
+    deposit_op
+        .add_create(utxo_a.public_key.clone(), 500_i128)
+        .unwrap();
 
     let live_until_ledger = e.ledger().sequence() + 100;
 
-    let provider_a_signature = provider_a.sign(
-        &e,
-        deposit_op.get_auth_entry_payload_hash_for_bundle(&e, nonce, live_until_ledger),
-    );
-
-    deposit_op.add_provider_signature(
-        &e,
-        provider_a.address.clone(),
-        provider_a_signature,
-        live_until_ledger,
-    );
-
-    let john_deposit_sub_signature = john.sign_for_transaction(
-        &e,
-        deposit_op.get_auth_entry_payload_hash_for_deposit(
-            &e,
-            john.address.clone(),
-            nonce,
-            live_until_ledger,
-        ),
-    );
-
-    deposit_op.add_deposit_signature(john.address.clone(), john_deposit_sub_signature);
+    sign_provider(&e, &mut deposit_op, &provider_a, nonce, live_until_ledger);
+    sign_deposit(&e, &mut deposit_op, &john, nonce, live_until_ledger);
 
     channel
-        // .mock_all_auths()
-        .set_auths(&[
-            deposit_op.get_auth_entry(&e, nonce, live_until_ledger.clone()),
-            deposit_op.get_auth_entry_for_deposit(
-                &e,
-                john.address.clone(),
-                nonce,
-                live_until_ledger,
-            ),
-        ])
-        .transact(&deposit_op.get_operation_bundle());
+        .set_auths(
+            &deposit_op
+                .auth_entries(&e, nonce, live_until_ledger)
+                .unwrap(),
+        )
+        .transact(&channel_operation(&e, &deposit_op.operation()));
 
     assert_eq!(token.balance(&john.address), 500_i128);
 
@@ -327,95 +388,56 @@ fn test_auth_module() {
 
     let mut nonce = 0;
 
-    let mut deposit_op = ChannelOperationBuilder::generate(
-        &e,
-        channel.address.clone(),
-        auth.address.clone(),
-        token.address.clone(),
-    );
+    let mut deposit_op = builder(&e, &channel, &auth, &token.address);
 
-    // no conditions as we cant properly test the G address signing mixed with the mocked address
-    deposit_op.add_deposit(
-        &e,
-        john.address.clone(),
-        500_i128,
-        vec![
-            &e,
-            Condition::Create(utxo_a.public_key.clone(), 200_i128),
-            Condition::Create(utxo_c.public_key.clone(), 300_i128),
-        ],
-    );
-    deposit_op.add_deposit(
-        &e,
-        jane.address.clone(),
-        600_i128,
-        vec![
-            &e,
-            Condition::Create(utxo_b.public_key.clone(), 300_i128),
-            Condition::Create(utxo_d.public_key.clone(), 300_i128),
-        ],
-    );
-    deposit_op.add_create(utxo_a.public_key.clone(), 200_i128);
-    deposit_op.add_create(utxo_b.public_key.clone(), 300_i128);
-    deposit_op.add_create(utxo_c.public_key.clone(), 300_i128);
-    deposit_op.add_create(utxo_d.public_key.clone(), 300_i128);
-
-    let live_until_ledger = e.ledger().sequence() + 100;
-
-    let provider_a_signature = provider_a.sign(
-        &e,
-        deposit_op.get_auth_entry_payload_hash_for_bundle(&e, nonce, live_until_ledger),
-    );
-
-    deposit_op.add_provider_signature(
-        &e,
-        provider_a.address.clone(),
-        provider_a_signature,
-        live_until_ledger,
-    );
-
-    let john_deposit_sub_signature = john.sign_for_transaction(
-        &e,
-        deposit_op.get_auth_entry_payload_hash_for_deposit(
+    deposit_op
+        .add_deposit(
             &e,
             john.address.clone(),
-            nonce,
-            live_until_ledger,
-        ),
-    );
-
-    deposit_op.add_deposit_signature(john.address.clone(), john_deposit_sub_signature);
-
-    let jane_deposit_sub_signature = jane.sign_for_transaction(
-        &e,
-        deposit_op.get_auth_entry_payload_hash_for_deposit(
+            500_i128,
+            vec![
+                &e,
+                Condition::Create(utxo_a.public_key.clone(), 200_i128),
+                Condition::Create(utxo_c.public_key.clone(), 300_i128),
+            ],
+        )
+        .unwrap();
+    deposit_op
+        .add_deposit(
             &e,
             jane.address.clone(),
-            nonce,
-            live_until_ledger,
-        ),
-    );
+            600_i128,
+            vec![
+                &e,
+                Condition::Create(utxo_b.public_key.clone(), 300_i128),
+                Condition::Create(utxo_d.public_key.clone(), 300_i128),
+            ],
+        )
+        .unwrap();
+    for (utxo, amount) in [
+        (&utxo_a, 200_i128),
+        (&utxo_b, 300_i128),
+        (&utxo_c, 300_i128),
+        (&utxo_d, 300_i128),
+    ] {
+        deposit_op
+            .add_create(utxo.public_key.clone(), amount)
+            .unwrap();
+    }
+
+    let live_until_ledger = e.ledger().sequence() + 100;
 
-    deposit_op.add_deposit_signature(jane.address.clone(), jane_deposit_sub_signature);
+    sign_provider(&e, &mut deposit_op, &provider_a, nonce, live_until_ledger);
+    sign_deposit(&e, &mut deposit_op, &john, nonce, live_until_ledger);
+    sign_deposit(&e, &mut deposit_op, &jane, nonce, live_until_ledger);
 
     channel
-        // .mock_all_auths()
-        .set_auths(&[
-            deposit_op.get_auth_entry(&e, nonce, live_until_ledger.clone()),
-            deposit_op.get_auth_entry_for_deposit(
-                &e,
-                john.address.clone(),
-                nonce,
-                live_until_ledger,
-            ),
-            deposit_op.get_auth_entry_for_deposit(
-                &e,
-                jane.address.clone(),
-                nonce,
-                live_until_ledger,
-            ),
-        ])
-        .transact(&deposit_op.get_operation_bundle());
+        .set_auths(
+            &deposit_op
+                .auth_entries(&e, nonce, live_until_ledger)
+                .unwrap(),
+        )
+        .transact(&channel_operation(&e, &deposit_op.operation()));
 
     assert_eq!(token.balance(&john.address), 500_i128);
     assert_eq!(token.balance(&jane.address), 1400_i128);
@@ -434,124 +456,82 @@ fn test_auth_module() {
     let utxo_h = P256KeyPair::generate(&e);
     let utxo_i = P256KeyPair::generate(&e);
 
-    let mut transfer_op = ChannelOperationBuilder::generate(
-        &e,
-        channel.address.clone(),
-        auth.address.clone(),
-        token.address.clone(),
-    );
+    let mut transfer_op = builder(&e, &channel, &auth, &token.address);
 
     nonce = 1;
 
-    transfer_op.add_create(utxo_e.public_key.clone(), 100_i128);
-    transfer_op.add_create(utxo_f.public_key.clone(), 200_i128);
-    transfer_op.add_create(utxo_g.public_key.clone(), 70_i128);
-    transfer_op.add_create(utxo_h.public_key.clone(), 130_i128);
-    transfer_op.add_create(utxo_i.public_key.clone(), 600_i128);
-
-    transfer_op.add_spend(
-        utxo_a.public_key.clone(),
-        vec![
-            &e,
-            Condition::Create(utxo_e.public_key.clone(), 100_i128),
-            Condition::Create(utxo_f.public_key.clone(), 200_i128),
-            Condition::Create(utxo_g.public_key.clone(), 70_i128),
-        ],
-    );
-
-    transfer_op.add_spend(
-        utxo_b.public_key.clone(),
-        vec![
-            &e,
-            Condition::Create(utxo_f.public_key.clone(), 200_i128),
-            Condition::Create(utxo_g.public_key.clone(), 70_i128),
-            Condition::Create(utxo_h.public_key.clone(), 130_i128),
-        ],
-    );
-
-    transfer_op.add_spend(
-        utxo_c.public_key.clone(),
-        vec![
-            &e,
-            Condition::Create(utxo_g.public_key.clone(), 70_i128),
-            Condition::Create(utxo_h.public_key.clone(), 130_i128),
-            Condition::Create(utxo_i.public_key.clone(), 600_i128),
-        ],
-    );
-
-    transfer_op.add_spend(
-        utxo_d.public_key.clone(),
-        vec![
-            &e,
-            Condition::Create(utxo_e.public_key.clone(), 100_i128),
-            Condition::Create(utxo_g.public_key.clone(), 70_i128),
-            Condition::Create(utxo_i.public_key.clone(), 600_i128),
-        ],
-    );
-
-    let provider_b_signature = provider_b.sign(
-        &e,
-        transfer_op.get_auth_entry_payload_hash_for_bundle(&e, nonce, live_until_ledger),
-    );
-
-    transfer_op.add_provider_signature(
-        &e,
-        provider_b.address.clone(),
-        provider_b_signature,
-        live_until_ledger,
-    );
-
-    let utxo_a_signature = utxo_a.sign(&transfer_op.get_auth_hash_for_spend(
-        &e,
-        utxo_a.public_key.clone(),
-        live_until_ledger,
-    ));
-
-    transfer_op.add_spend_signature(
-        &e,
-        utxo_a.public_key.clone(),
-        utxo_a_signature,
-        live_until_ledger,
-    );
-
-    let utxo_b_signature = utxo_b.sign(&transfer_op.get_auth_hash_for_spend(
-        &e,
-        utxo_b.public_key.clone(),
-        live_until_ledger,
-    ));
-    transfer_op.add_spend_signature(
-        &e,
-        utxo_b.public_key.clone(),
-        utxo_b_signature,
-        live_until_ledger,
-    );
-    let utxo_c_signature = utxo_c.sign(&transfer_op.get_auth_hash_for_spend(
-        &e,
-        utxo_c.public_key.clone(),
-        live_until_ledger,
-    ));
-    transfer_op.add_spend_signature(
-        &e,
-        utxo_c.public_key.clone(),
-        utxo_c_signature,
-        live_until_ledger,
-    );
-    let utxo_d_signature = utxo_d.sign(&transfer_op.get_auth_hash_for_spend(
-        &e,
-        utxo_d.public_key.clone(),
-        live_until_ledger,
-    ));
-    transfer_op.add_spend_signature(
-        &e,
-        utxo_d.public_key.clone(),
-        utxo_d_signature,
-        live_until_ledger,
-    );
+    for (utxo, amount) in [
+        (&utxo_e, 100_i128),
+        (&utxo_f, 200_i128),
+        (&utxo_g, 70_i128),
+        (&utxo_h, 130_i128),
+        (&utxo_i, 600_i128),
+    ] {
+        transfer_op
+            .add_create(utxo.public_key.clone(), amount)
+            .unwrap();
+    }
+
+    transfer_op
+        .add_spend(
+            utxo_a.public_key.clone(),
+            vec![
+                &e,
+                Condition::Create(utxo_e.public_key.clone(), 100_i128),
+                Condition::Create(utxo_f.public_key.clone(), 200_i128),
+                Condition::Create(utxo_g.public_key.clone(), 70_i128),
+            ],
+        )
+        .unwrap();
+
+    transfer_op
+        .add_spend(
+            utxo_b.public_key.clone(),
+            vec![
+                &e,
+                Condition::Create(utxo_f.public_key.clone(), 200_i128),
+                Condition::Create(utxo_g.public_key.clone(), 70_i128),
+                Condition::Create(utxo_h.public_key.clone(), 130_i128),
+            ],
+        )
+        .unwrap();
+
+    transfer_op
+        .add_spend(
+            utxo_c.public_key.clone(),
+            vec![
+                &e,
+                Condition::Create(utxo_g.public_key.clone(), 70_i128),
+                Condition::Create(utxo_h.public_key.clone(), 130_i128),
+                Condition::Create(utxo_i.public_key.clone(), 600_i128),
+            ],
+        )
+        .unwrap();
+
+    transfer_op
+        .add_spend(
+            utxo_d.public_key.clone(),
+            vec![
+                &e,
+                Condition::Create(utxo_e.public_key.clone(), 100_i128),
+                Condition::Create(utxo_g.public_key.clone(), 70_i128),
+                Condition::Create(utxo_i.public_key.clone(), 600_i128),
+            ],
+        )
+        .unwrap();
+
+    sign_provider(&e, &mut transfer_op, &provider_b, nonce, live_until_ledger);
+    for utxo in [&utxo_a, &utxo_b, &utxo_c, &utxo_d] {
+        sign_spend(&e, &mut transfer_op, utxo, live_until_ledger);
+    }
 
     channel
-        // .mock_all_auths()
-        .set_auths(&[transfer_op.get_auth_entry(&e, nonce, live_until_ledger.clone())])
-        .transact(&transfer_op.get_operation_bundle());
+        .set_auths(
+            &transfer_op
+                .auth_entries(&e, nonce, live_until_ledger)
+                .unwrap(),
+        )
+        .transact(&channel_operation(&e, &transfer_op.operation()));
 
     assert_eq!(channel.supply(), 1100_i128);
 
@@ -0,0 +1,149 @@
+#![cfg(test)]
+//! UTXO TTL policy is channel configuration: only the admin may change it, and anyone may pay to
+//! extend dormant UTXOs under the configured policy via `extend_utxos`.
+extern crate std;
+
+use crate::{test::test::create_contracts, transact::ChannelOperation};
+use moonlight_errors::Error as ContractError;
+use moonlight_helpers::testutils::{keys::P256KeyPair, snapshot::get_env_with_g_accounts};
+use moonlight_primitives::Condition;
+use moonlight_storage::UTXOCoreDataKey;
+use moonlight_utxo_core::core::TtlPolicy;
+use soroban_sdk::{
+    testutils::{storage::Persistent as _, Address as _, Ledger, MockAuth, MockAuthInvoke},
+    vec, Address, Bytes, BytesN, Env, Error, IntoVal, Map,
+};
+
+const DAY_IN_LEDGERS: u32 = 17_280;
+
+fn utxo_ttl(e: &Env, channel: &Address, utxo: &BytesN<65>) -> u32 {
+    let hash = e
+        .crypto()
+        .sha256(&Bytes::from_slice(e, utxo.to_array().as_ref()));
+    let key = UTXOCoreDataKey::UTXO(hash.into());
+    e.as_contract(channel, || e.storage().persistent().get_ttl(&key))
+}
+
+#[test]
+fn test_admin_sets_ttl_policy() {
+    let e = Env::default();
+    let (channel, _, _, admin) = create_contracts(&e);
+    let policy = TtlPolicy {
+        threshold: 60 * DAY_IN_LEDGERS,
+        extend_to: 90 * DAY_IN_LEDGERS,
+    };
+
+    channel
+        .mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &channel.address,
+                fn_name: "set_ttl_policy",
+                args: (&policy,).into_val(&e),
+                sub_invokes: &[],
+            },
+        }])
+        .set_ttl_policy(&policy);
+
+    assert_eq!(channel.ttl_policy(), policy);
+}
+
+#[test]
+fn test_non_admin_cannot_set_ttl_policy() {
+    let e = Env::default();
+    let (channel, _, _, _) = create_contracts(&e);
+    let outsider = Address::generate(&e);
+    let default_policy = channel.ttl_policy();
+    let policy = TtlPolicy {
+        threshold: DAY_IN_LEDGERS,
+        extend_to: 2 * DAY_IN_LEDGERS,
+    };
+
+    let res = channel
+        .mock_auths(&[MockAuth {
+            address: &outsider,
+            invoke: &MockAuthInvoke {
+                contract: &channel.address,
+                fn_name: "set_ttl_policy",
+                args: (&policy,).into_val(&e),
+                sub_invokes: &[],
+            },
+        }])
+        .try_set_ttl_policy(&policy);
+
+    assert!(res.is_err());
+    assert_eq!(channel.ttl_policy(), default_policy);
+}
+
+#[test]
+fn test_invalid_ttl_policy_is_rejected() {
+    let e = Env::default();
+    let (channel, _, _, _) = create_contracts(&e);
+    let policy = TtlPolicy {
+        threshold: 2 * DAY_IN_LEDGERS,
+        extend_to: DAY_IN_LEDGERS,
+    };
+
+    let res = channel.mock_all_auths().try_set_ttl_policy(&policy);
+
+    assert_eq!(
+        res.err(),
+        Some(Ok(Error::from_contract_error(
+            ContractError::InvalidTtlPolicy as u32
+        )))
+    );
+}
+
+#[test]
+fn test_extend_utxos_keeps_dormant_utxos_alive() {
+    let e = get_env_with_g_accounts();
+    let (channel, _, token, _) = create_contracts(&e);
+    e.mock_all_auths();
+
+    // A short policy so the UTXO would archive within the test without a keep-alive.
+    channel.set_ttl_policy(&TtlPolicy {
+        threshold: 2 * DAY_IN_LEDGERS,
+        extend_to: 3 * DAY_IN_LEDGERS,
+    });
+
+    let depositor = Address::generate(&e);
+    token.mint(&depositor, &500_i128);
+
+    let utxo = P256KeyPair::generate(&e);
+    let missing = P256KeyPair::generate(&e);
+    channel.transact(&ChannelOperation {
+        spend: vec![&e],
+        create: vec![&e, (utxo.public_key.clone(), 500_i128)],
+        deposit: vec![
+            &e,
+            (
+                depositor,
+                500_i128,
+                vec![&e, Condition::Create(utxo.public_key.clone(), 500_i128)],
+            ),
+        ],
+        withdraw: vec![&e],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
+    });
+
+    e.ledger()
+        .with_mut(|l| l.sequence_number += 2 * DAY_IN_LEDGERS);
+    assert!(utxo_ttl(&e, &channel.address, &utxo.public_key) < 2 * DAY_IN_LEDGERS);
+
+    // No auth is needed, and missing keys are skipped rather than failing the whole batch.
+    e.set_auths(&[]);
+    channel.extend_utxos(&vec![
+        &e,
+        utxo.public_key.clone(),
+        missing.public_key.clone(),
+    ]);
+
+    assert_eq!(
+        utxo_ttl(&e, &channel.address, &utxo.public_key),
+        3 * DAY_IN_LEDGERS
+    );
+    assert_eq!(channel.utxo_balance(&utxo.public_key), 500_i128);
+    assert_eq!(channel.utxo_balance(&missing.public_key), -1_i128);
+}
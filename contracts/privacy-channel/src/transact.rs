@@ -1,13 +1,14 @@
 use moonlight_errors::Error;
 use moonlight_primitives::{
-    condition_does_not_conflict_with_set, equal_condition_sequence, no_duplicate_addresses,
-    Condition,
+    compliance::CompliancePolicyClient, condition_does_not_conflict_with_set,
+    equal_condition_sequence, no_duplicate_addresses, Condition,
+};
+use moonlight_utxo_core::core::{
+    calculate_auth_requirements, BundleItem, InternalBundle, Violation,
 };
-use moonlight_utxo_core::core::{calculate_auth_requirements, InternalBundle};
 use soroban_sdk::{
-    assert_with_error,
     auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
-    contracttype, panic_with_error,
+    panic_with_error,
     token::TokenClient,
     vec,
     xdr::ToXdr,
@@ -15,49 +16,124 @@ use soroban_sdk::{
 };
 
 use crate::{
-    storage::read_asset,
+    circuit_breaker::record_outflow,
+    limits::check_channel_limits,
+    recovery::check_recovery_conditions,
+    storage::{read_asset, read_compliance_policy, read_limits, read_supply},
     treasury::{decrease_supply, increase_supply},
 };
 
-#[derive(Clone)]
-#[contracttype]
-pub struct ChannelOperation {
-    pub spend: Vec<(BytesN<65>, Vec<Condition>)>,
-    pub create: Vec<(BytesN<65>, i128)>,
-    pub deposit: Vec<(Address, i128, Vec<Condition>)>,
-    pub withdraw: Vec<(Address, i128, Vec<Condition>)>,
-}
+pub use moonlight_primitives::ChannelOperation;
 
 pub fn pre_process_channel_operation(
     e: &Env,
     op: ChannelOperation,
 ) -> (InternalBundle, i128, i128) {
-    assert_with_error!(
-        &e,
-        op_has_no_conflicting_conditions(&e, &op),
-        Error::BundleHasConflictingConditions
+    let fail_fast = &mut |v: Violation| panic_with_error!(e, v.error);
+    let (total_deposit, total_withdraw) = check_channel_operation(e, &op, fail_fast);
+    check_channel_limits(
+        &op,
+        &read_limits(e),
+        read_supply(e),
+        total_deposit,
+        total_withdraw,
+        fail_fast,
     );
 
-    let mut total_deposit: i128 = 0;
-    for (_addr, amt, _conds) in op.deposit.iter() {
-        // MOON-05: reject non-positive amounts in-contract rather than relying on the asset SAC.
-        assert_with_error!(&e, amt > 0, Error::InvalidExternalAmount);
-        total_deposit = match total_deposit.checked_add(amt) {
-            Some(v) => v,
-            None => panic_with_error!(&e, Error::AmountOverflow),
-        };
+    (to_internal_bundle(e, op), total_deposit, total_withdraw)
+}
+
+/// `pre_process_channel_operation` for each operation of a batch, checking the channel limits
+/// against the supply left by the operations before it.
+pub fn pre_process_channel_operations(
+    e: &Env,
+    ops: &Vec<ChannelOperation>,
+) -> Vec<(InternalBundle, i128, i128)> {
+    let fail_fast = &mut |v: Violation| panic_with_error!(e, v.error);
+    let limits = read_limits(e);
+    let mut supply = read_supply(e);
+
+    let mut bundles = Vec::new(e);
+    for op in ops.iter() {
+        let (total_deposit, total_withdraw) = check_channel_operation(e, &op, fail_fast);
+        check_channel_limits(
+            &op,
+            &limits,
+            supply,
+            total_deposit,
+            total_withdraw,
+            fail_fast,
+        );
+        supply = supply
+            .saturating_add(total_deposit)
+            .saturating_sub(total_withdraw);
+
+        bundles.push_back((to_internal_bundle(e, op), total_deposit, total_withdraw));
     }
+    bundles
+}
 
-    let mut total_withdraw: i128 = 0;
-    for (_addr, amt, _conds) in op.withdraw.iter() {
-        assert_with_error!(&e, amt > 0, Error::InvalidExternalAmount);
-        total_withdraw = match total_withdraw.checked_add(amt) {
-            Some(v) => v,
-            None => panic_with_error!(&e, Error::AmountOverflow),
+/// Checks every channel-level rule of an operation and returns its total deposit and withdraw.
+///
+/// Every violation is passed to `report`, in the order the contract enforces them;
+/// `pre_process_channel_operation` panics on the first. UTXO-level rules are checked by
+/// `moonlight_utxo_core::core::{check_bundle_keys, apply_bundle}`.
+pub fn check_channel_operation(
+    e: &Env,
+    op: &ChannelOperation,
+    report: &mut impl FnMut(Violation),
+) -> (i128, i128) {
+    if !op_has_no_conflicting_conditions(e, op) {
+        report(Violation::new(
+            Error::BundleHasConflictingConditions,
+            BundleItem::Operation,
+        ));
+    }
+
+    let total_deposit = sum_external_amounts(&op.deposit, BundleItem::Deposit, report);
+    let total_withdraw = sum_external_amounts(&op.withdraw, BundleItem::Withdraw, report);
+
+    verify_external_operations(e, &op.deposit, &op.withdraw, report);
+
+    for utxo in op.notes.keys().iter() {
+        if !op.create.iter().any(|(create_utxo, _)| create_utxo == utxo) {
+            report(Violation::new(
+                Error::NoteWithoutCreate,
+                BundleItem::Operation,
+            ));
+        }
+    }
+    for utxo in op.ephemeral_keys.keys().iter() {
+        if !op.create.iter().any(|(create_utxo, _)| create_utxo == utxo) {
+            report(Violation::new(
+                Error::EphemeralKeyWithoutCreate,
+                BundleItem::Operation,
+            ));
+        }
+    }
+
+    for address in op.allowance_deposits.iter() {
+        let deposit = op
+            .deposit
+            .iter()
+            .enumerate()
+            .find(|(_, (from, _, _))| *from == address);
+        let Some((i, (from, amount, conditions))) = deposit else {
+            report(Violation::new(
+                Error::AllowanceWithoutDeposit,
+                BundleItem::Operation,
+            ));
+            continue;
         };
+        if !conditions.contains(Condition::ExtDeposit(from, amount)) {
+            report(Violation::new(
+                Error::UnauthorizedOperation,
+                BundleItem::Deposit(i as u32),
+            ));
+        }
     }
 
-    verify_external_operations(&e, op.deposit.clone(), op.withdraw.clone());
+    check_recovery_conditions(e, op, report);
 
     // MOON-01: bind owner-signed conditions to executed effects. The balance check in
     // `process_bundle` only guarantees value conservation, not *where* the value goes; without
@@ -66,37 +142,63 @@ pub fn pre_process_channel_operation(
     // that every Create/ExtWithdraw condition signed by a spend owner (P256) or depositor (Ed25519)
     // is executed exactly (same utxo/addr + amount). Extra executed creates/withdraws are allowed
     // (the provider fee); the balance check bounds them to the residual the signers left.
-    assert_signed_effects_are_executed(&e, &op);
+    assert_signed_effects_are_executed(e, op, report);
+
+    (total_deposit, total_withdraw)
+}
 
-    let auth_req = calculate_auth_requirements(e, &op.spend); // &vec![&e]);
+/// Builds the UTXO bundle processed by `moonlight-utxo-core` from a channel operation.
+pub fn to_internal_bundle(e: &Env, op: ChannelOperation) -> InternalBundle {
+    let auth_req = calculate_auth_requirements(e, &op.spend);
 
     //get the spend array without conditions
-    let mut spend: Vec<BytesN<65>> = Vec::new(&e);
+    let mut spend: Vec<BytesN<65>> = Vec::new(e);
     for (spend_utxo, _conditions) in op.spend.iter() {
         spend.push_back(spend_utxo.clone());
     }
 
-    let utxo_op = InternalBundle {
-        spend: spend,
+    InternalBundle {
+        spend,
         create: op.create,
         req: auth_req,
-    };
+    }
+}
 
-    return (utxo_op, total_deposit, total_withdraw);
+fn sum_external_amounts(
+    external: &Vec<(Address, i128, Vec<Condition>)>,
+    item: fn(u32) -> BundleItem,
+    report: &mut impl FnMut(Violation),
+) -> i128 {
+    let mut total: i128 = 0;
+    for (i, (_addr, amt, _conds)) in external.iter().enumerate() {
+        // MOON-05: reject non-positive amounts in-contract rather than relying on the asset SAC.
+        if amt <= 0 {
+            report(Violation::new(Error::InvalidExternalAmount, item(i as u32)));
+        }
+        total = match total.checked_add(amt) {
+            Some(v) => v,
+            None => {
+                report(Violation::new(Error::AmountOverflow, item(i as u32)));
+                total.saturating_add(amt)
+            }
+        };
+    }
+    total
 }
 
-/// MOON-01 binding: enforce that every cryptographically-signed `Create` / `ExtWithdraw` condition
-/// is executed exactly by the bundle (subset direction `authorized ⊆ executed`).
+/// MOON-01 binding: enforce that every cryptographically-signed `Create` / `ExtWithdraw` / `Note`
+/// condition is executed exactly by the bundle (subset direction `authorized ⊆ executed`).
 ///
-/// Authorized set = every `Condition::Create` / `Condition::ExtWithdraw` found in the spend
-/// conditions (P256-signed by the UTXO owner, verified via the auth contract) and the deposit
-/// conditions (Ed25519-signed by the depositor via `require_auth_for_args`). Withdraw-tuple
+/// Authorized set = every `Condition::Create` / `Condition::ExtWithdraw` / `Condition::Note` found
+/// in the spend conditions (P256-signed by the UTXO owner, verified via the auth contract) and the
+/// deposit conditions (Ed25519-signed by the depositor via `require_auth_for_args`). Withdraw-tuple
 /// conditions are unsigned and are intentionally NOT a source of authorization. `ExtDeposit`
-/// (already bound by the depositor's SAC-transfer auth) and `ExtIntegration` (never executed) are
-/// not execution-bound and are ignored.
+/// (already bound by the depositor's SAC-transfer auth, or checked against the deposit for an
+/// allowance deposit) and `ExtIntegration` (never executed) are not execution-bound and are
+/// ignored.
 ///
-/// Executed set = `op.create` rendered as `Condition::Create` and `op.withdraw` rendered as
-/// `Condition::ExtWithdraw`.
+/// Executed set = `op.create` rendered as `Condition::Create`, `op.withdraw` rendered as
+/// `Condition::ExtWithdraw` and `op.notes` rendered as `Condition::Note` over the note's `sha256`.
 ///
 /// Compared by canonical XDR bytes with set (dedup) semantics, so a multi-spend bundle where each
 /// spend repeats (or partitions) the output set is accepted as long as every signed effect appears
@@ -108,9 +210,14 @@ pub fn pre_process_channel_operation(
 /// the signers left unallocated (deposit over-funded / spend under-claimed); for a pure internal
 /// transfer the residual is zero, so no extra create can balance.
 ///
-/// ### Panics
-/// - `UnauthorizedOperation` if a signed create/withdraw condition is not executed.
-fn assert_signed_effects_are_executed(e: &Env, op: &ChannelOperation) {
+/// ### Violations
+/// - `UnauthorizedOperation` for each signed create/withdraw/note/ephemeral key condition that is
+///   not executed.
+fn assert_signed_effects_are_executed(
+    e: &Env,
+    op: &ChannelOperation,
+    report: &mut impl FnMut(Violation),
+) {
     let mut authorized: Map<Bytes, ()> = Map::new(e);
     collect_authorized_effects(e, &mut authorized, &op.spend);
     collect_authorized_effects_from_external(e, &mut authorized, &op.deposit);
@@ -122,10 +229,22 @@ fn assert_signed_effects_are_executed(e: &Env, op: &ChannelOperation) {
     for (addr, amount, _conds) in op.withdraw.iter() {
         executed.set(Condition::ExtWithdraw(addr, amount).to_xdr(e), ());
     }
+    for (utxo, note) in op.notes.iter() {
+        let hash = e.crypto().sha256(&note).to_bytes();
+        executed.set(Condition::Note(utxo, hash).to_xdr(e), ());
+    }
+    for (utxo, ephemeral_key) in op.ephemeral_keys.iter() {
+        executed.set(Condition::EphemeralKey(utxo, ephemeral_key).to_xdr(e), ());
+    }
 
     // Subset: every signed effect must be executed exactly. Extra executed effects are allowed.
     for key in authorized.keys().iter() {
-        assert_with_error!(e, executed.contains_key(key), Error::UnauthorizedOperation);
+        if !executed.contains_key(key) {
+            report(Violation::new(
+                Error::UnauthorizedOperation,
+                BundleItem::Operation,
+            ));
+        }
     }
 }
 
@@ -157,79 +276,244 @@ fn collect_authorized_effects_from_external(
     }
 }
 
-/// Only `Create` and `ExtWithdraw` conditions describe on-ledger value movement the bundle
-/// executes; they are the effects this binding governs.
+/// `Create` and `ExtWithdraw` conditions describe on-ledger value movement the bundle executes, and
+/// `Note` and `EphemeralKey` the data it publishes for a created UTXO; they are the effects this
+/// binding governs.
 fn is_execution_bound(cond: &Condition) -> bool {
-    matches!(cond, Condition::Create(..) | Condition::ExtWithdraw(..))
+    matches!(
+        cond,
+        Condition::Create(..)
+            | Condition::ExtWithdraw(..)
+            | Condition::Note(..)
+            | Condition::EphemeralKey(..)
+    )
 }
 
 fn verify_external_operations(
     e: &Env,
-    deposit: Vec<(Address, i128, Vec<Condition>)>,
-    withdraw: Vec<(Address, i128, Vec<Condition>)>,
+    deposit: &Vec<(Address, i128, Vec<Condition>)>,
+    withdraw: &Vec<(Address, i128, Vec<Condition>)>,
+    report: &mut impl FnMut(Violation),
 ) {
-    if !no_duplicate_addresses(&e, deposit.iter(), |(addr, _amount, _conditions)| {
+    if !no_duplicate_addresses(e, deposit.iter(), |(addr, _amount, _conditions)| {
         addr.clone()
     }) {
-        panic_with_error!(&e, Error::RepeatedAccountForDeposit);
+        report(Violation::new(
+            Error::RepeatedAccountForDeposit,
+            BundleItem::Operation,
+        ));
     }
-    if !no_duplicate_addresses(&e, withdraw.iter(), |(addr, _amount, _conditions)| {
+    if !no_duplicate_addresses(e, withdraw.iter(), |(addr, _amount, _conditions)| {
         addr.clone()
     }) {
-        panic_with_error!(&e, Error::RepeatedAccountForWithdraw);
+        report(Violation::new(
+            Error::RepeatedAccountForWithdraw,
+            BundleItem::Operation,
+        ));
     }
 
     // If an address is both depositing and withdrawing, the condition sequences must be identical (order + content).
     for (dep_addr, _, dep_conds) in deposit.iter() {
-        for (with_addr, _amt, with_conds) in withdraw.iter() {
-            if dep_addr == with_addr {
-                if !equal_condition_sequence(&e, &dep_conds, &with_conds) {
-                    panic_with_error!(&e, Error::ConflictingConditionsForAccount);
-                }
+        for (i, (with_addr, _amt, with_conds)) in withdraw.iter().enumerate() {
+            if dep_addr == with_addr && !equal_condition_sequence(e, &dep_conds, &with_conds) {
+                report(Violation::new(
+                    Error::ConflictingConditionsForAccount,
+                    BundleItem::Withdraw(i as u32),
+                ));
             }
         }
     }
 }
 
-pub fn execute_external_operations(
+pub fn execute_external_operations(e: &Env, op: &ChannelOperation) {
+    let asset_client = TokenClient::new(e, &read_asset(e));
+    let compliance =
+        read_compliance_policy(e).map(|policy| CompliancePolicyClient::new(e, &policy));
+
+    for (from, amount, deposit_conditions) in op.deposit.iter() {
+        let allowance = op.allowance_deposits.contains(&from);
+        authorize_deposit(
+            e,
+            &compliance,
+            &from,
+            amount,
+            &deposit_conditions,
+            allowance,
+        );
+        transfer_in(e, &asset_client, &from, amount, allowance);
+        increase_supply(e, amount);
+    }
+
+    let mut outflow = 0_i128;
+    for (_, amount, _) in op.withdraw.iter() {
+        outflow = outflow
+            .checked_add(amount)
+            .unwrap_or_else(|| panic_with_error!(e, Error::AmountOverflow));
+    }
+    record_outflow(e, outflow);
+
+    for (to, amount, _) in op.withdraw.iter() {
+        authorize_withdraw(e, &compliance, &to, amount);
+        transfer_out(e, &asset_client, &to, amount);
+        decrease_supply(e, amount);
+    }
+}
+
+/// `execute_external_operations` for a batch, with the token transfers netted per address.
+///
+/// Every deposit is still authorized by its depositor and screened by the compliance policy, and
+/// every withdrawal screened, leg by leg. An address then makes or receives a single transfer of
+/// its net amount, and none if its legs cancel out; a net deposit is pulled from its allowance.
+/// An address with a deposit outside `allowance_deposits` is not netted: its depositor signed a
+/// `transfer` of each leg's amount, so each of its legs makes its own transfer. The circuit
+/// breaker records the gross withdrawals, as if the operations ran one by one.
+pub fn execute_netted_external_operations(e: &Env, ops: &Vec<ChannelOperation>) {
+    let asset_client = TokenClient::new(e, &read_asset(e));
+    let compliance =
+        read_compliance_policy(e).map(|policy| CompliancePolicyClient::new(e, &policy));
+
+    let mut signed: Map<Address, ()> = Map::new(e);
+    for op in ops.iter() {
+        for (from, _, _) in op.deposit.iter() {
+            if !op.allowance_deposits.contains(&from) {
+                signed.set(from, ());
+            }
+        }
+    }
+
+    // Legs of addresses with a signed transfer, in order, as (address, amount, from_allowance);
+    // a negative amount is a withdrawal.
+    let mut legs: Vec<(Address, i128, bool)> = Vec::new(e);
+    let mut net: Map<Address, i128> = Map::new(e);
+    let mut outflow = 0_i128;
+    for op in ops.iter() {
+        for (from, amount, deposit_conditions) in op.deposit.iter() {
+            let from_allowance = op.allowance_deposits.contains(&from);
+            authorize_deposit(
+                e,
+                &compliance,
+                &from,
+                amount,
+                &deposit_conditions,
+                from_allowance,
+            );
+            if signed.contains_key(from.clone()) {
+                legs.push_back((from, amount, from_allowance));
+            } else {
+                let total = net.get(from.clone()).unwrap_or(0).checked_add(amount);
+                net.set(
+                    from,
+                    total.unwrap_or_else(|| panic_with_error!(e, Error::AmountOverflow)),
+                );
+            }
+            increase_supply(e, amount);
+        }
+        for (to, amount, _) in op.withdraw.iter() {
+            authorize_withdraw(e, &compliance, &to, amount);
+            if signed.contains_key(to.clone()) {
+                legs.push_back((to, -amount, false));
+            } else {
+                let total = net.get(to.clone()).unwrap_or(0).checked_sub(amount);
+                net.set(
+                    to,
+                    total.unwrap_or_else(|| panic_with_error!(e, Error::AmountUnderflow)),
+                );
+            }
+            decrease_supply(e, amount);
+            outflow = outflow
+                .checked_add(amount)
+                .unwrap_or_else(|| panic_with_error!(e, Error::AmountOverflow));
+        }
+    }
+
+    record_outflow(e, outflow);
+
+    for (address, amount, from_allowance) in legs.iter() {
+        if amount > 0 {
+            transfer_in(e, &asset_client, &address, amount, from_allowance);
+        } else {
+            transfer_out(e, &asset_client, &address, -amount);
+        }
+    }
+    // Every deposit of a netted address is an allowance deposit.
+    for (address, amount) in net.iter() {
+        if amount > 0 {
+            transfer_in(e, &asset_client, &address, amount, true);
+        } else if amount < 0 {
+            transfer_out(e, &asset_client, &address, -amount);
+        }
+    }
+}
+
+/// `from` authorizes its conditions; an allowance depositor authorizes them followed by `true`, so
+/// a signature for a plain deposit cannot be replayed to spend its allowance.
+fn authorize_deposit(
     e: &Env,
-    deposit: Vec<(Address, i128, Vec<Condition>)>,
-    withdraw: Vec<(Address, i128, Vec<Condition>)>,
+    compliance: &Option<CompliancePolicyClient>,
+    from: &Address,
+    amount: i128,
+    conditions: &Vec<Condition>,
+    allowance: bool,
 ) {
-    let asset = read_asset(e);
-
-    let asset_client = TokenClient::new(e, &asset);
+    if allowance {
+        from.require_auth_for_args(vec![e, conditions.into_val(e), true.into_val(e)]);
+    } else {
+        from.require_auth_for_args(vec![e, conditions.into_val(e)]);
+    }
+    if let Some(compliance) = compliance {
+        if !compliance.check_deposit(from, &amount) {
+            panic_with_error!(e, Error::ComplianceCheckFailed);
+        }
+    }
+}
 
-    for (from, amount, deposit_conditions) in deposit.iter() {
-        from.require_auth_for_args(vec![&e, deposit_conditions.into_val(e)]);
-        asset_client.transfer(&from, &e.current_contract_address(), &amount);
-        increase_supply(&e, amount);
+fn authorize_withdraw(
+    e: &Env,
+    compliance: &Option<CompliancePolicyClient>,
+    to: &Address,
+    amount: i128,
+) {
+    if let Some(compliance) = compliance {
+        if !compliance.check_withdraw(to, &amount) {
+            panic_with_error!(e, Error::ComplianceCheckFailed);
+        }
     }
+}
 
-    for (to, amount, _) in withdraw.iter() {
-        let args_val: Vec<Val> = vec![
-            e,
-            (&e.current_contract_address()).into_val(e),
-            (&to).into_val(e),
-            (&amount).into_val(e),
-        ];
-
-        e.authorize_as_current_contract(vec![
-            &e,
-            InvokerContractAuthEntry::Contract(SubContractInvocation {
-                context: ContractContext {
-                    contract: asset.clone(),
-                    fn_name: Symbol::new(e, "transfer"),
-                    args: args_val.clone(),
-                },
-                sub_invocations: vec![e],
-            }),
-        ]);
-        asset_client.transfer(&e.current_contract_address(), &to, &amount);
-        decrease_supply(&e, amount);
+/// Transfers `amount` of the asset from `from` to the channel. With `allowance`, the channel spends
+/// an allowance `from` approved for it, so `from` authorizes no transfer.
+fn transfer_in(e: &Env, asset_client: &TokenClient, from: &Address, amount: i128, allowance: bool) {
+    let channel = e.current_contract_address();
+    if allowance {
+        asset_client.transfer_from(&channel, from, &channel, &amount);
+    } else {
+        asset_client.transfer(from, &channel, &amount);
     }
 }
 
+/// Transfers `amount` of the asset from the channel to `to`, authorizing it as the channel.
+fn transfer_out(e: &Env, asset_client: &TokenClient, to: &Address, amount: i128) {
+    let args_val: Vec<Val> = vec![
+        e,
+        e.current_contract_address().into_val(e),
+        to.into_val(e),
+        amount.into_val(e),
+    ];
+
+    e.authorize_as_current_contract(vec![
+        e,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: asset_client.address.clone(),
+                fn_name: Symbol::new(e, "transfer"),
+                args: args_val.clone(),
+            },
+            sub_invocations: vec![e],
+        }),
+    ]);
+    asset_client.transfer(&e.current_contract_address(), to, &amount);
+}
+
 pub fn op_has_no_conflicting_conditions(e: &Env, op: &ChannelOperation) -> bool {
     let mut verified_conditions: Vec<Condition> = Vec::new(&e);
 
@@ -1,7 +1,7 @@
 use moonlight_errors::Error;
-use soroban_sdk::{panic_with_error, Env};
+use soroban_sdk::{panic_with_error, token::TokenClient, Address, Env};
 
-use crate::storage::{read_supply, write_supply_unchecked};
+use crate::storage::{read_asset, read_supply, write_supply_unchecked};
 
 pub fn increase_supply(e: &Env, amount: i128) {
     let supply = read_supply(e);
@@ -22,3 +22,20 @@ pub fn decrease_supply(e: &Env, amount: i128) {
         None => panic_with_error!(e, Error::AmountUnderflow),
     }
 }
+
+/// The channel's own balance of its asset.
+pub fn read_balance(e: &Env) -> i128 {
+    TokenClient::new(e, &read_asset(e)).balance(&e.current_contract_address())
+}
+
+/// Transfers the asset held above the supply to `to` and returns the amount, `0` if there is no
+/// surplus.
+pub fn skim_surplus(e: &Env, to: &Address) -> i128 {
+    let surplus = read_balance(e) - read_supply(e);
+    if surplus <= 0 {
+        return 0;
+    }
+
+    TokenClient::new(e, &read_asset(e)).transfer(&e.current_contract_address(), to, &surplus);
+    surplus
+}
@@ -4,8 +4,8 @@ use moonlight_primitives::{
     hash_payload, AuthPayload, AuthRequirements, Condition, Signature, Signatures, SignerKey,
 };
 use soroban_sdk::{
-    assert_with_error, auth::Context, contracttype, crypto::Hash, Address, Bytes, BytesN, Env, Map,
-    TryIntoVal, Vec,
+    assert_with_error, auth::Context, contracttype, crypto::Hash, vec, Address, Bytes, BytesN, Env,
+    IntoVal, Map, TryIntoVal, Vec,
 };
 
 /// Verify a secp256r1 (P-256) signature.
@@ -51,6 +51,21 @@ fn verify_ed25519_signature(
     Ok(())
 }
 
+/// Delegate to a contract signer's own `__check_auth`.
+///
+/// The signer's authorization entry must authorize `__check_auth` of the calling contract with the
+/// payload hash as its only argument. Like the wrappers above, `require_auth_for_args` panics when
+/// the signer does not authorize.
+fn verify_delegated_signature(
+    e: &Env,
+    signer: &Address,
+    payload_hash: &Hash<32>,
+) -> Result<(), Error> {
+    signer.require_auth_for_args(vec![e, payload_hash.to_bytes().into_val(e)]);
+
+    Ok(())
+}
+
 pub fn verify_signature(
     e: &Env,
     signer: &SignerKey,
@@ -67,6 +82,9 @@ pub fn verify_signature(
         (SignerKey::Ed25519(pk), Signature::Ed25519(sig)) => {
             verify_ed25519_signature(e, pk, sig, payload_hash)
         }
+        (SignerKey::ProviderContract(address), Signature::Delegated) => {
+            verify_delegated_signature(e, address, payload_hash)
+        }
         _ => Err(Error::InvalidSignatureFormat),
     }
 }
@@ -194,6 +212,9 @@ pub trait ProviderAuthorizable {
     /// Requires that the given provider is registered
     ///  and that the transaction is authorized by the provider.
     ///
+    /// Native providers sign the payload with their Ed25519 key; contract providers authorize it
+    /// through their own `__check_auth`.
+    ///
     /// ### Panics
     /// - Panics if the provider is not registered.
     /// - Panics if the transaction is not authorized by the provider.
@@ -204,9 +225,12 @@ pub trait ProviderAuthorizable {
         const PROVIDER_THRESHOLD: u32 = 1; // For now we require only one exact provider signature
 
         for signer in sig_map.keys().iter() {
-            if let SignerKey::Provider(pk32) = signer.clone() {
-                let provider_addr: Address = address_from_ed25519_pk_bytes(&e, &pk32);
-
+            let provider_addr: Option<Address> = match signer.clone() {
+                SignerKey::Provider(pk32) => Some(address_from_ed25519_pk_bytes(e, &pk32)),
+                SignerKey::ProviderContract(address) => Some(address),
+                _ => None,
+            };
+            if let Some(provider_addr) = provider_addr {
                 assert_with_error!(
                     e,
                     Self::is_provider(&e, provider_addr.clone()),
@@ -1,3 +1,4 @@
+use moonlight_client::{ChannelContext, ChannelOperationBuilder};
 use moonlight_errors::Error as MoonlightError;
 use moonlight_helpers::testutils::keys::{Ed25519Account, P256KeyPair};
 use moonlight_primitives::{Condition, Signature, Signatures, SignerKey};
@@ -11,10 +12,49 @@ use crate::{
     core::{verify_signature, UtxoAuthorizable},
     testutils::contract::{create_contract, AuthModuleTestContract},
 };
-use moonlight_utxo_core::testutils::{
-    contract::create_contract as create_utxo_contract, operation_bundle::UTXOOperationBuilder,
+use moonlight_utxo_core::{
+    core::UTXOOperation, testutils::contract::create_contract as create_utxo_contract,
 };
 
+/// A client builder for the UTXO test contract `utxo`, which the auth contract sees as a channel.
+fn builder(e: &Env, utxo: &Address, auth: &Address) -> ChannelOperationBuilder {
+    ChannelOperationBuilder::new(
+        e,
+        ChannelContext {
+            network_id: e.ledger().network_id(),
+            channel: utxo.clone(),
+            auth: auth.clone(),
+            asset: Address::generate(e),
+        },
+    )
+}
+
+fn utxo_operation(op: &ChannelOperationBuilder) -> UTXOOperation {
+    let op = op.operation();
+    UTXOOperation {
+        spend: op.spend,
+        create: op.create,
+    }
+}
+
+fn sign_spend(e: &Env, op: &mut ChannelOperationBuilder, owner: &P256KeyPair, live: u32) {
+    let hash = op.spend_payload_hash(e, &owner.public_key, live).unwrap();
+    op.add_spend_signature(e, owner.public_key.clone(), owner.sign(&hash), live)
+        .unwrap();
+}
+
+fn sign_provider(
+    e: &Env,
+    op: &mut ChannelOperationBuilder,
+    provider: &Ed25519Account,
+    nonce: i64,
+    live: u32,
+) {
+    let hash = op.bundle_payload_hash(e, nonce, live).unwrap();
+    op.add_provider_signature(&provider.address, provider.sign(e, hash), live)
+        .unwrap();
+}
+
 #[test]
 fn test_auth_module() {
     let e = Env::default();
@@ -40,62 +80,33 @@ fn test_auth_module() {
         (utxo_b.public_key.clone(), 500_i128),
     ]);
 
-    let mut op = UTXOOperationBuilder::generate(
-        &e,
-        utxo_client.address.clone(),
-        auth_client.address.clone(),
-    );
+    let mut op = builder(&e, &utxo_client.address, &auth_client.address);
 
-    op.add_create(utxo_c.public_key.clone(), 700_i128);
-    op.add_create(utxo_d.public_key.clone(), 800_i128);
+    op.add_create(utxo_c.public_key.clone(), 700_i128).unwrap();
+    op.add_create(utxo_d.public_key.clone(), 800_i128).unwrap();
 
     op.add_spend(
         utxo_a.public_key.clone(),
         vec![&e, Condition::Create(utxo_c.public_key.clone(), 700_i128)],
-    );
+    )
+    .unwrap();
     op.add_spend(
         utxo_b.public_key.clone(),
         vec![&e, Condition::Create(utxo_d.public_key.clone(), 800_i128)],
-    );
+    )
+    .unwrap();
 
     let live_until_ledger = e.ledger().sequence() + 1;
 
-    let signature_a = utxo_a.sign(&op.get_auth_hash_for_spend(
-        &e,
-        utxo_a.public_key.clone(),
-        live_until_ledger.clone(),
-    ));
-
-    op.add_spend_signature(
-        &e,
-        utxo_a.public_key.clone(),
-        signature_a,
-        live_until_ledger,
-    );
-
-    let signature_b = utxo_b.sign(&op.get_auth_hash_for_spend(
-        &e,
-        utxo_b.public_key.clone(),
-        live_until_ledger.clone(),
-    ));
-    op.add_spend_signature(
-        &e,
-        utxo_b.public_key.clone(),
-        signature_b,
-        live_until_ledger,
-    );
+    sign_spend(&e, &mut op, &utxo_a, live_until_ledger);
+    sign_spend(&e, &mut op, &utxo_b, live_until_ledger);
 
     let nonce = 0;
-    let signature_provider = provider.sign(
-        &e,
-        op.get_auth_entry_payload_hash_for_bundle(&e, nonce.clone(), live_until_ledger.clone()),
-    );
-
-    op.add_provider_signature(&e, provider.address, signature_provider, live_until_ledger);
+    sign_provider(&e, &mut op, &provider, nonce, live_until_ledger);
 
     utxo_client
-        .set_auths(&[op.get_auth_entry(&e, nonce, live_until_ledger)])
-        .transact(&op.get_operation_bundle());
+        .set_auths(&op.auth_entries(&e, nonce, live_until_ledger).unwrap())
+        .transact(&utxo_operation(&op));
 
     assert_eq!(utxo_client.utxo_balance(&utxo_a.public_key), 0);
     assert_eq!(utxo_client.utxo_balance(&utxo_b.public_key), 0);
@@ -122,55 +133,30 @@ fn test_auth_module_errors() {
 
     utxo_client.mint(&vec![&e, (utxo_a.public_key.clone(), 1000_i128)]);
 
-    let mut op = UTXOOperationBuilder::generate(
-        &e,
-        utxo_client.address.clone(),
-        auth_client.address.clone(),
-    );
+    let mut op = builder(&e, &utxo_client.address, &auth_client.address);
 
-    op.add_create(utxo_b.public_key.clone(), 1000_i128);
+    op.add_create(utxo_b.public_key.clone(), 1000_i128).unwrap();
 
     op.add_spend(
         utxo_a.public_key.clone(),
         vec![&e, Condition::Create(utxo_b.public_key.clone(), 1000_i128)],
-    );
+    )
+    .unwrap();
 
     e.ledger().set_sequence_number(10);
     let expired_live_until_ledger = 9;
 
-    let signature_a = utxo_a.sign(&op.get_auth_hash_for_spend(
-        &e,
-        utxo_a.public_key.clone(),
-        expired_live_until_ledger.clone(),
-    ));
-
-    op.add_spend_signature(
-        &e,
-        utxo_a.public_key.clone(),
-        signature_a,
-        expired_live_until_ledger,
-    );
+    sign_spend(&e, &mut op, &utxo_a, expired_live_until_ledger);
 
     let nonce = 0;
-    let signature_provider = provider.sign(
-        &e,
-        op.get_auth_entry_payload_hash_for_bundle(
-            &e,
-            nonce.clone(),
-            expired_live_until_ledger.clone(),
-        ),
-    );
-
-    op.add_provider_signature(
-        &e,
-        provider.address,
-        signature_provider,
-        expired_live_until_ledger,
-    );
+    sign_provider(&e, &mut op, &provider, nonce, expired_live_until_ledger);
 
     let expected_expired_error = utxo_client
-        .set_auths(&[op.get_auth_entry(&e, nonce, expired_live_until_ledger)])
-        .try_transact(&op.get_operation_bundle());
+        .set_auths(
+            &op.auth_entries(&e, nonce, expired_live_until_ledger)
+                .unwrap(),
+        )
+        .try_transact(&utxo_operation(&op));
 
     // assert_eq!(
     //     expected_expired_error.err(),
@@ -235,13 +221,13 @@ fn test_handle_utxo_auth_empty_context_does_not_skip_later_spend_context() {
     let utxo = P256KeyPair::generate(&e);
 
     // A spend context that DOES require a P256 signature for `utxo`.
-    let mut builder =
-        UTXOOperationBuilder::generate(&e, channel.clone(), auth_client.address.clone());
-    builder.add_spend(
+    let mut op = builder(&e, &channel, &auth_client.address);
+    op.add_spend(
         utxo.public_key.clone(),
         vec![&e, Condition::Create(utxo.public_key.clone(), 100_i128)],
-    );
-    let spend_args = builder.get_contract_auth_args(&e);
+    )
+    .unwrap();
+    let spend_args = op.bundle_auth_args(&e);
 
     let empty_ctx = Context::Contract(ContractContext {
         contract: channel.clone(),
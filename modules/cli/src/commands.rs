@@ -0,0 +1,141 @@
+use moonlight_client::{LocalP256Signer, P256Signer};
+use moonlight_primitives::{
+    hash_payload,
+    json::{from_json_str, from_xdr_base64, parse_address, to_json_string, to_xdr_base64},
+    AuthPayload, ChannelOperation, Signatures,
+};
+use moonlight_utxo_core::core::{check_bundle_keys, Violation};
+use privacy_channel::transact::{check_channel_operation, to_internal_bundle};
+use rand::{rngs::OsRng, RngCore};
+use soroban_sdk::{crypto::Hash, Env};
+
+use crate::CliError;
+
+/// The XDR types `encode` and `decode` understand.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum XdrType {
+    Operation,
+    Signatures,
+}
+
+impl XdrType {
+    pub fn parse(value: &str) -> Result<Self, CliError> {
+        match value {
+            "operation" => Ok(Self::Operation),
+            "signatures" => Ok(Self::Signatures),
+            other => Err(CliError::new(format!(
+                "unknown type `{other}`, expected `operation` or `signatures`"
+            ))),
+        }
+    }
+}
+
+/// Generates a P256 UTXO keypair.
+pub fn keygen() -> Result<String, CliError> {
+    loop {
+        let mut secret = [0u8; 32];
+        OsRng.fill_bytes(&mut secret);
+
+        // Out-of-range scalars are rejected; draw again.
+        if let Ok(signer) = LocalP256Signer::from_bytes(&secret) {
+            return Ok(serde_json::json!({
+                "secret_key": hex::encode(secret),
+                "public_key": hex::encode(signer.public_key()),
+            })
+            .to_string());
+        }
+    }
+}
+
+/// Parses an operation, checks it against the channel's stateless rules and encodes it.
+///
+/// Rules that depend on UTXO state or signatures are not checked.
+pub fn build(e: &Env, operation: &str) -> Result<String, CliError> {
+    let op: ChannelOperation = from_json_str(e, operation)?;
+
+    let mut violations: Vec<Violation> = Vec::new();
+    let report = &mut |v: Violation| violations.push(v);
+    check_channel_operation(e, &op, report);
+    check_bundle_keys(e, &to_internal_bundle(e, op.clone()), report);
+
+    if !violations.is_empty() {
+        let lines: Vec<String> = violations
+            .iter()
+            .map(|v| format!("{:?} ({}) at {:?}", v.error, v.error as u32, v.item))
+            .collect();
+        return Err(CliError::new(format!(
+            "operation breaks channel rules:\n  {}",
+            lines.join("\n  ")
+        )));
+    }
+
+    Ok(to_xdr_base64(e, &op)?)
+}
+
+/// Returns the hex `hash_payload` digest a spend owner signs for `conditions`.
+pub fn hash(
+    e: &Env,
+    channel: &str,
+    live_until_ledger: u32,
+    conditions: &str,
+) -> Result<String, CliError> {
+    Ok(hex::encode(
+        payload_hash(e, channel, live_until_ledger, conditions)?.to_array(),
+    ))
+}
+
+/// Signs the `hash_payload` digest for `conditions` with a hex P256 secret key.
+pub fn sign(
+    e: &Env,
+    secret_key: &str,
+    channel: &str,
+    live_until_ledger: u32,
+    conditions: &str,
+) -> Result<String, CliError> {
+    let mut secret = [0u8; 32];
+    hex::decode_to_slice(secret_key, &mut secret)
+        .map_err(|err| CliError::new(format!("invalid secret key: {err}")))?;
+    let signer = LocalP256Signer::from_bytes(&secret)?;
+
+    let hash = payload_hash(e, channel, live_until_ledger, conditions)?;
+    Ok(hex::encode(signer.sign_prehash(&hash.to_array())?))
+}
+
+/// Encodes a JSON operation or signature list as base64 XDR, without checking any rule.
+pub fn encode(e: &Env, ty: XdrType, input: &str) -> Result<String, CliError> {
+    let xdr = match ty {
+        XdrType::Operation => to_xdr_base64(e, &from_json_str::<ChannelOperation>(e, input)?)?,
+        XdrType::Signatures => to_xdr_base64(e, &from_json_str::<Signatures>(e, input)?)?,
+    };
+    Ok(xdr)
+}
+
+/// Decodes base64 XDR into the JSON accepted by [`encode`].
+pub fn decode(e: &Env, ty: XdrType, input: &str) -> Result<String, CliError> {
+    let mismatch = |_| CliError::new(format!("XDR value is not a {ty:?}"));
+
+    let json = match ty {
+        XdrType::Operation => {
+            to_json_string(&from_xdr_base64::<ChannelOperation>(e, input).map_err(mismatch)?)?
+        }
+        XdrType::Signatures => {
+            to_json_string(&from_xdr_base64::<Signatures>(e, input).map_err(mismatch)?)?
+        }
+    };
+    Ok(json)
+}
+
+fn payload_hash(
+    e: &Env,
+    channel: &str,
+    live_until_ledger: u32,
+    conditions: &str,
+) -> Result<Hash<32>, CliError> {
+    let channel = parse_address(e, channel)?;
+    let payload = AuthPayload {
+        conditions: from_json_str(e, conditions)?,
+        live_until_ledger,
+    };
+
+    Ok(hash_payload(e, &payload, &channel.to_string().to_bytes()))
+}
@@ -0,0 +1,185 @@
+//! `moonlight`: keys, bundles and payload hashes for Privacy Channel operators.
+
+use std::{fmt, io::Read, process::ExitCode};
+
+use soroban_sdk::Env;
+
+use crate::commands::XdrType;
+
+mod commands;
+
+#[cfg(test)]
+mod test;
+
+const USAGE: &str = "\
+usage: moonlight <command> [args]
+
+commands:
+  keygen
+      Generate a P256 UTXO keypair.
+  build <OPERATION>
+      Check a JSON operation against the channel's stateless rules and print it as base64 XDR.
+  hash --channel <C...> --live-until <LEDGER> <CONDITIONS>
+      Print the hash_payload digest a spend owner signs for JSON conditions.
+  sign --key <SECRET_HEX> --channel <C...> --live-until <LEDGER> <CONDITIONS>
+      Sign the hash_payload digest for JSON conditions with a P256 secret key.
+  encode <operation|signatures> <JSON>
+      Encode JSON as base64 XDR without checking any rule.
+  decode <operation|signatures> <BASE64>
+      Decode base64 XDR into JSON.
+
+JSON and base64 arguments are read from a file path, or from stdin when given as `-`.";
+
+#[derive(Debug)]
+pub struct CliError(String);
+
+impl CliError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<moonlight_client::ClientError> for CliError {
+    fn from(err: moonlight_client::ClientError) -> Self {
+        Self(err.to_string())
+    }
+}
+
+impl From<moonlight_primitives::json::JsonError> for CliError {
+    fn from(err: moonlight_primitives::json::JsonError) -> Self {
+        Self(err.to_string())
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match run(&args) {
+        Ok(output) => {
+            println!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<String, CliError> {
+    let e = Env::default();
+    let (command, rest) = args.split_first().ok_or_else(|| CliError::new(USAGE))?;
+    let mut args = Args::parse(rest)?;
+
+    let output = match command.as_str() {
+        "keygen" => commands::keygen(),
+        "build" => commands::build(&e, &read_input(&args.positional(0)?)?),
+        "hash" => commands::hash(
+            &e,
+            &args.flag("--channel")?,
+            args.ledger()?,
+            &read_input(&args.positional(0)?)?,
+        ),
+        "sign" => commands::sign(
+            &e,
+            &args.flag("--key")?,
+            &args.flag("--channel")?,
+            args.ledger()?,
+            &read_input(&args.positional(0)?)?,
+        ),
+        "encode" => commands::encode(
+            &e,
+            XdrType::parse(&args.positional(0)?)?,
+            &read_input(&args.positional(1)?)?,
+        ),
+        "decode" => commands::decode(
+            &e,
+            XdrType::parse(&args.positional(0)?)?,
+            &read_input(&args.positional(1)?)?,
+        ),
+        "help" | "--help" | "-h" => Ok(USAGE.to_string()),
+        other => Err(CliError::new(format!(
+            "unknown command `{other}`\n\n{USAGE}"
+        ))),
+    }?;
+
+    args.finish()?;
+    Ok(output)
+}
+
+/// `--flag value` pairs and positional arguments of a subcommand.
+struct Args {
+    flags: Vec<(String, String)>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, CliError> {
+        let mut flags = Vec::new();
+        let mut positional = Vec::new();
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            if arg.starts_with("--") {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| CliError::new(format!("missing value for `{arg}`")))?;
+                flags.push((arg.clone(), value.clone()));
+            } else {
+                positional.push(arg.clone());
+            }
+        }
+
+        Ok(Self { flags, positional })
+    }
+
+    fn flag(&mut self, name: &str) -> Result<String, CliError> {
+        let index = self
+            .flags
+            .iter()
+            .position(|(flag, _)| flag == name)
+            .ok_or_else(|| CliError::new(format!("missing `{name}`")))?;
+        Ok(self.flags.remove(index).1)
+    }
+
+    fn ledger(&mut self) -> Result<u32, CliError> {
+        let value = self.flag("--live-until")?;
+        value
+            .parse()
+            .map_err(|_| CliError::new(format!("invalid ledger `{value}`")))
+    }
+
+    fn positional(&self, index: usize) -> Result<String, CliError> {
+        self.positional
+            .get(index)
+            .cloned()
+            .ok_or_else(|| CliError::new(format!("missing argument\n\n{USAGE}")))
+    }
+
+    /// Rejects flags that no command consumed.
+    fn finish(self) -> Result<(), CliError> {
+        match self.flags.first() {
+            Some((flag, _)) => Err(CliError::new(format!("unexpected `{flag}`"))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn read_input(source: &str) -> Result<String, CliError> {
+    if source == "-" {
+        let mut input = String::new();
+        std::io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|err| CliError::new(format!("cannot read stdin: {err}")))?;
+        return Ok(input);
+    }
+
+    std::fs::read_to_string(source)
+        .map_err(|err| CliError::new(format!("cannot read `{source}`: {err}")))
+}
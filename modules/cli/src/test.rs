@@ -0,0 +1,163 @@
+use moonlight_client::{LocalP256Signer, P256Signer};
+use moonlight_primitives::{
+    hash_payload,
+    json::{address_string as strkey, JSON_SCHEMA_VERSION},
+    AuthPayload, Condition,
+};
+use soroban_sdk::{testutils::Address as _, vec, Address, BytesN, Env};
+
+use crate::commands::{self, XdrType};
+
+fn versioned(value: &str) -> String {
+    format!(r#"{{ "version": {JSON_SCHEMA_VERSION}, "value": {value} }}"#)
+}
+
+fn utxo_hex(seed: u8) -> String {
+    hex::encode(
+        LocalP256Signer::from_bytes(&[seed; 32])
+            .unwrap()
+            .public_key(),
+    )
+}
+
+fn operation_json(e: &Env) -> String {
+    let depositor = strkey(&Address::generate(e));
+    versioned(&format!(
+        r#"{{
+            "create": [{{ "utxo": "{a}", "amount": "300" }}, {{ "utxo": "{b}", "amount": "200" }}],
+            "deposit": [{{
+                "address": "{depositor}",
+                "amount": "500",
+                "conditions": [{{ "create": {{ "utxo": "{a}", "amount": "300" }} }}]
+            }}]
+        }}"#,
+        a = utxo_hex(1),
+        b = utxo_hex(2),
+    ))
+}
+
+#[test]
+fn keygen_prints_a_matching_keypair() {
+    let output: serde_json::Value = serde_json::from_str(&commands::keygen().unwrap()).unwrap();
+
+    let mut secret = [0u8; 32];
+    hex::decode_to_slice(output["secret_key"].as_str().unwrap(), &mut secret).unwrap();
+
+    assert_eq!(
+        output["public_key"].as_str().unwrap(),
+        hex::encode(LocalP256Signer::from_bytes(&secret).unwrap().public_key())
+    );
+}
+
+#[test]
+fn hash_and_sign_match_the_auth_payload() {
+    let e = Env::default();
+    let channel = Address::generate(&e);
+    let conditions = versioned(&format!(
+        r#"[{{ "create": {{ "utxo": "{}", "amount": "70" }} }}]"#,
+        utxo_hex(2)
+    ));
+
+    let hash = commands::hash(&e, &strkey(&channel), 1_000, &conditions).unwrap();
+
+    let expected = hash_payload(
+        &e,
+        &AuthPayload {
+            conditions: vec![
+                &e,
+                Condition::Create(BytesN::from_array(&e, &public_key(2)), 70),
+            ],
+            live_until_ledger: 1_000,
+        },
+        &channel.to_string().to_bytes(),
+    );
+    assert_eq!(hash, hex::encode(expected.to_array()));
+
+    let signature = commands::sign(
+        &e,
+        &hex::encode([1u8; 32]),
+        &strkey(&channel),
+        1_000,
+        &conditions,
+    )
+    .unwrap();
+
+    let mut signature_bytes = [0u8; 64];
+    hex::decode_to_slice(&signature, &mut signature_bytes).unwrap();
+    e.crypto().secp256r1_verify(
+        &BytesN::from_array(&e, &public_key(1)),
+        &expected,
+        &BytesN::from_array(&e, &signature_bytes),
+    );
+}
+
+#[test]
+fn operation_round_trips_through_xdr() {
+    let e = Env::default();
+    let json = operation_json(&e);
+
+    let built = commands::build(&e, &json).unwrap();
+    assert_eq!(
+        built,
+        commands::encode(&e, XdrType::Operation, &json).unwrap()
+    );
+
+    let decoded = commands::decode(&e, XdrType::Operation, &built).unwrap();
+    assert_eq!(
+        commands::encode(&e, XdrType::Operation, &decoded).unwrap(),
+        built
+    );
+}
+
+#[test]
+fn signatures_round_trip_through_xdr() {
+    let e = Env::default();
+    let json = versioned(&format!(
+        r#"[
+            {{ "signer": {{ "p256": "{}" }}, "signature": {{ "p256": "{}" }}, "live_until_ledger": 10 }},
+            {{ "signer": {{ "provider": "{}" }}, "signature": {{ "ed25519": "{}" }}, "live_until_ledger": 20 }}
+        ]"#,
+        utxo_hex(1),
+        "11".repeat(64),
+        "22".repeat(32),
+        "33".repeat(64),
+    ));
+
+    let encoded = commands::encode(&e, XdrType::Signatures, &json).unwrap();
+    let decoded = commands::decode(&e, XdrType::Signatures, &encoded).unwrap();
+
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&decoded).unwrap(),
+        serde_json::from_str::<serde_json::Value>(&json).unwrap()
+    );
+    assert!(commands::decode(&e, XdrType::Operation, &encoded).is_err());
+}
+
+#[test]
+fn build_lists_every_broken_rule() {
+    let e = Env::default();
+    let json = versioned(&format!(
+        r#"{{
+            "spend": [{{ "utxo": "{a}" }}, {{ "utxo": "{a}" }}],
+            "deposit": [{{ "address": "{depositor}", "amount": "0" }}]
+        }}"#,
+        a = utxo_hex(1),
+        depositor = strkey(&Address::generate(&e)),
+    ));
+
+    let err = commands::build(&e, &json).unwrap_err().to_string();
+
+    assert!(
+        err.contains("InvalidExternalAmount (3007) at Deposit(0)"),
+        "{err}"
+    );
+    assert!(err.contains("RepeatedSpendUtxo"), "{err}");
+    // Unchecked encoding still works, to reproduce rejected bundles.
+    assert!(commands::encode(&e, XdrType::Operation, &json).is_ok());
+}
+
+fn public_key(seed: u8) -> [u8; 65] {
+    LocalP256Signer::from_bytes(&[seed; 32])
+        .unwrap()
+        .public_key()
+}
@@ -0,0 +1,142 @@
+use soroban_sdk::{
+    crypto::Hash,
+    xdr::{SorobanAuthorizationEntry, SorobanAuthorizedInvocation},
+    Address, BytesN, Env, Vec,
+};
+
+use crate::{
+    ChannelContext, ChannelOperation, ChannelOperationBuilder, ClientError, Ed25519Signer,
+};
+
+/// Assembles a `transact_batch` call: its operations and the auth contract's single entry,
+/// which authorizes the spends of every operation.
+///
+/// Operations come from [`Self::operation_builder`] and join the batch with [`Self::push`] once
+/// their spends are signed. Pushing an operation drops the provider signature. Depositors sign
+/// their entries on the operation's own builder; those of the operation at index `i` sign with
+/// nonce `nonce + 1 + i`, so an address that deposits in several operations uses a distinct
+/// nonce in each.
+#[derive(Clone)]
+pub struct ChannelBatchBuilder {
+    auth: ChannelOperationBuilder,
+    operations: std::vec::Vec<ChannelOperationBuilder>,
+}
+
+impl ChannelBatchBuilder {
+    pub fn new(e: &Env, context: ChannelContext) -> Self {
+        Self {
+            auth: ChannelOperationBuilder::new_batched(e, context),
+            operations: std::vec::Vec::new(),
+        }
+    }
+
+    /// Starts an operation of this batch.
+    pub fn operation_builder(&self, e: &Env) -> ChannelOperationBuilder {
+        ChannelOperationBuilder::new_batched(e, self.auth.context().clone())
+    }
+
+    /// Adds an operation. Fails if it spends a UTXO another operation spends or one of its
+    /// spends is not signed.
+    pub fn push(&mut self, operation: ChannelOperationBuilder) -> Result<(), ClientError> {
+        self.auth.merge_spends(&operation)?;
+
+        self.operations.push(operation);
+        Ok(())
+    }
+
+    pub fn operations(&self, e: &Env) -> Vec<ChannelOperation> {
+        let mut operations = Vec::new(e);
+        for operation in self.operations.iter() {
+            operations.push_back(operation.operation());
+        }
+        operations
+    }
+
+    /// See [`ChannelOperationBuilder::bundle_payload_hash`].
+    pub fn bundle_payload_hash(
+        &self,
+        e: &Env,
+        nonce: i64,
+        signature_expiration_ledger: u32,
+    ) -> Result<Hash<32>, ClientError> {
+        self.auth
+            .bundle_payload_hash(e, nonce, signature_expiration_ledger)
+    }
+
+    /// See [`ChannelOperationBuilder::sign_provider`].
+    pub fn sign_provider(
+        &mut self,
+        e: &Env,
+        signer: &impl Ed25519Signer,
+        nonce: i64,
+        signature_expiration_ledger: u32,
+    ) -> Result<(), ClientError> {
+        self.auth
+            .sign_provider(e, signer, nonce, signature_expiration_ledger)
+    }
+
+    /// See [`ChannelOperationBuilder::add_provider_signature`].
+    pub fn add_provider_signature(
+        &mut self,
+        provider: &Address,
+        signature: BytesN<64>,
+        live_until_ledger: u32,
+    ) -> Result<(), ClientError> {
+        self.auth
+            .add_provider_signature(provider, signature, live_until_ledger)
+    }
+
+    /// See [`ChannelOperationBuilder::add_provider_contract`].
+    pub fn add_provider_contract(
+        &mut self,
+        provider: &Address,
+        live_until_ledger: u32,
+    ) -> Result<(), ClientError> {
+        self.auth.add_provider_contract(provider, live_until_ledger)
+    }
+
+    /// See [`ChannelOperationBuilder::provider_invocation`].
+    pub fn provider_invocation(
+        &self,
+        e: &Env,
+        nonce: i64,
+        signature_expiration_ledger: u32,
+    ) -> Result<SorobanAuthorizedInvocation, ClientError> {
+        self.auth
+            .provider_invocation(e, nonce, signature_expiration_ledger)
+    }
+
+    /// Builds the auth contract's entry, which authorizes the spends of every operation.
+    pub fn auth_entry(
+        &self,
+        e: &Env,
+        nonce: i64,
+        signature_expiration_ledger: u32,
+    ) -> Result<SorobanAuthorizationEntry, ClientError> {
+        self.auth.auth_entry(e, nonce, signature_expiration_ledger)
+    }
+
+    /// Builds every entry `transact_batch` needs: the auth contract's entry, then each
+    /// operation's depositor entries.
+    pub fn auth_entries(
+        &self,
+        e: &Env,
+        nonce: i64,
+        signature_expiration_ledger: u32,
+    ) -> Result<std::vec::Vec<SorobanAuthorizationEntry>, ClientError> {
+        let mut entries = std::vec![self.auth_entry(e, nonce, signature_expiration_ledger)?];
+        for (i, operation) in self.operations.iter().enumerate() {
+            let deposit_nonce = nonce + 1 + i as i64;
+            for (depositor, _, _) in operation.operation().deposit.iter() {
+                entries.push(operation.deposit_auth_entry(
+                    e,
+                    &depositor,
+                    deposit_nonce,
+                    signature_expiration_ledger,
+                )?);
+            }
+        }
+
+        Ok(entries)
+    }
+}
@@ -0,0 +1,798 @@
+use moonlight_primitives::{
+    condition_does_not_conflict_with_set, equal_condition_sequence,
+    has_no_conflicting_conditions_in_sets, hash_payload, AuthPayload, ChannelOperation, Condition,
+    Signature, Signatures, SignerKey,
+};
+use moonlight_utxo_core::core::calculate_auth_requirements;
+use soroban_sdk::{
+    address_payload::AddressPayload,
+    contracttype,
+    crypto::Hash,
+    vec,
+    xdr::{
+        HashIdPreimage, HashIdPreimageSorobanAuthorization, InvokeContractArgs, Limits, ScVal,
+        SorobanAddressCredentials, SorobanAuthorizationEntry, SorobanAuthorizedFunction,
+        SorobanAuthorizedInvocation, SorobanCredentials, VecM, WriteXdr,
+    },
+    Address, Bytes, BytesN, Env, IntoVal, Map, TryFromVal, Val, Vec,
+};
+
+use crate::{ClientError, Ed25519Signer, P256Signer};
+
+const TRANSACT: &str = "transact";
+const TRANSACT_BATCH: &str = "transact_batch";
+
+/// A signature in the format Stellar accounts expect in their auth entries.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct AccountSignature {
+    pub public_key: BytesN<32>,
+    pub signature: BytesN<64>,
+}
+
+/// The network and deployed contracts an operation targets.
+#[derive(Clone, Debug)]
+pub struct ChannelContext {
+    pub network_id: BytesN<32>,
+    pub channel: Address,
+    pub auth: Address,
+    pub asset: Address,
+}
+
+impl ChannelContext {
+    /// Builds a context for the network identified by `network_passphrase`.
+    pub fn new(
+        e: &Env,
+        network_passphrase: &str,
+        channel: Address,
+        auth: Address,
+        asset: Address,
+    ) -> Self {
+        let network_id = e
+            .crypto()
+            .sha256(&Bytes::from_slice(e, network_passphrase.as_bytes()))
+            .into();
+
+        Self {
+            network_id,
+            channel,
+            auth,
+            asset,
+        }
+    }
+}
+
+/// Assembles a [`ChannelOperation`], its signatures and its authorization entries.
+///
+/// Adding an entry the contract would reject (a repeated key, a non-positive amount or a
+/// conflicting condition) fails with a [`ClientError`] and leaves the builder unchanged.
+/// Adding any entry drops the provider signature, which must then be signed again.
+#[derive(Clone)]
+pub struct ChannelOperationBuilder {
+    context: ChannelContext,
+    function: &'static str,
+    spend: Vec<(BytesN<65>, Vec<Condition>)>,
+    create: Vec<(BytesN<65>, i128)>,
+    deposit: Vec<(Address, i128, Vec<Condition>)>,
+    withdraw: Vec<(Address, i128, Vec<Condition>)>,
+    notes: Map<BytesN<65>, Bytes>,
+    ephemeral_keys: Map<BytesN<65>, BytesN<65>>,
+    allowance_deposits: Vec<Address>,
+    sign_map: Map<SignerKey, (Signature, u32)>,
+    deposit_signatures: Map<Address, AccountSignature>,
+}
+
+impl ChannelOperationBuilder {
+    pub fn new(e: &Env, context: ChannelContext) -> Self {
+        Self::for_function(e, context, TRANSACT)
+    }
+
+    /// Builds an operation submitted through `transact_batch`, whose deposits are authorized
+    /// for that entry point. Add it to a [`ChannelBatchBuilder`] once its spends are signed.
+    pub fn new_batched(e: &Env, context: ChannelContext) -> Self {
+        Self::for_function(e, context, TRANSACT_BATCH)
+    }
+
+    fn for_function(e: &Env, context: ChannelContext, function: &'static str) -> Self {
+        Self {
+            context,
+            function,
+            spend: Vec::new(e),
+            create: Vec::new(e),
+            deposit: Vec::new(e),
+            withdraw: Vec::new(e),
+            notes: Map::new(e),
+            ephemeral_keys: Map::new(e),
+            allowance_deposits: Vec::new(e),
+            sign_map: Map::new(e),
+            deposit_signatures: Map::new(e),
+        }
+    }
+
+    pub fn context(&self) -> &ChannelContext {
+        &self.context
+    }
+
+    pub fn operation(&self) -> ChannelOperation {
+        ChannelOperation {
+            spend: self.spend.clone(),
+            create: self.create.clone(),
+            deposit: self.deposit.clone(),
+            withdraw: self.withdraw.clone(),
+            notes: self.notes.clone(),
+            ephemeral_keys: self.ephemeral_keys.clone(),
+            allowance_deposits: self.allowance_deposits.clone(),
+        }
+    }
+
+    pub fn add_spend(
+        &mut self,
+        utxo: BytesN<65>,
+        conditions: Vec<Condition>,
+    ) -> Result<(), ClientError> {
+        for (existing_utxo, existing_conditions) in self.spend.iter() {
+            if existing_utxo == utxo {
+                return Err(ClientError::RepeatedSpend(utxo));
+            }
+            if !has_no_conflicting_conditions_in_sets(&conditions, &existing_conditions) {
+                return Err(ClientError::ConflictingConditions);
+            }
+        }
+
+        self.spend.push_back((utxo, conditions));
+        self.drop_provider_signature();
+        Ok(())
+    }
+
+    pub fn add_create(&mut self, utxo: BytesN<65>, amount: i128) -> Result<(), ClientError> {
+        if amount <= 0 {
+            return Err(ClientError::InvalidAmount(amount));
+        }
+        if self.create.iter().any(|(existing, _)| existing == utxo) {
+            return Err(ClientError::RepeatedCreate(utxo));
+        }
+
+        let condition = Condition::Create(utxo.clone(), amount);
+        for (_, existing_conditions) in self.spend.iter() {
+            if !condition_does_not_conflict_with_set(&condition, &existing_conditions) {
+                return Err(ClientError::ConflictingConditions);
+            }
+        }
+
+        self.create.push_back((utxo, amount));
+        self.drop_provider_signature();
+        Ok(())
+    }
+
+    /// Attaches an encrypted note to a UTXO created by this operation and returns the
+    /// `Condition::Note` a spender adds to its conditions to bind the note to its signature.
+    pub fn add_note(
+        &mut self,
+        e: &Env,
+        utxo: BytesN<65>,
+        note: Bytes,
+    ) -> Result<Condition, ClientError> {
+        if !self.create.iter().any(|(existing, _)| existing == utxo) {
+            return Err(ClientError::UnknownCreate(utxo));
+        }
+        if self.notes.contains_key(utxo.clone()) {
+            return Err(ClientError::RepeatedNote(utxo));
+        }
+
+        let condition = Condition::Note(utxo.clone(), e.crypto().sha256(&note).to_bytes());
+        self.notes.set(utxo, note);
+        self.drop_provider_signature();
+        Ok(condition)
+    }
+
+    /// Publishes the stealth ephemeral key of a UTXO created by this operation, from
+    /// `moonlight_helpers::stealth::derive_stealth_utxo`, and returns the
+    /// `Condition::EphemeralKey` a spender adds to its conditions to bind the key to its signature.
+    pub fn add_ephemeral_key(
+        &mut self,
+        utxo: BytesN<65>,
+        ephemeral_key: BytesN<65>,
+    ) -> Result<Condition, ClientError> {
+        if !self.create.iter().any(|(existing, _)| existing == utxo) {
+            return Err(ClientError::UnknownCreate(utxo));
+        }
+        if self.ephemeral_keys.contains_key(utxo.clone()) {
+            return Err(ClientError::RepeatedEphemeralKey(utxo));
+        }
+
+        let condition = Condition::EphemeralKey(utxo.clone(), ephemeral_key.clone());
+        self.ephemeral_keys.set(utxo, ephemeral_key);
+        self.drop_provider_signature();
+        Ok(condition)
+    }
+
+    pub fn add_deposit(
+        &mut self,
+        e: &Env,
+        address: Address,
+        amount: i128,
+        conditions: Vec<Condition>,
+    ) -> Result<(), ClientError> {
+        if self
+            .deposit
+            .iter()
+            .any(|(existing, _, _)| existing == address)
+        {
+            return Err(ClientError::RepeatedDeposit(address));
+        }
+        Self::check_external(
+            e,
+            &self.deposit,
+            &self.withdraw,
+            &address,
+            amount,
+            &conditions,
+        )?;
+
+        self.deposit.push_back((address, amount, conditions));
+        self.drop_provider_signature();
+        Ok(())
+    }
+
+    /// Adds a deposit the channel pulls from an allowance `address` approved for it, so
+    /// `address` only authorizes its conditions. Suits contract accounts that cannot sign the
+    /// nested transfer of [`Self::add_deposit`].
+    ///
+    /// The conditions must include `Condition::ExtDeposit(address, amount)`, which binds the
+    /// amount in place of the transfer.
+    pub fn add_allowance_deposit(
+        &mut self,
+        e: &Env,
+        address: Address,
+        amount: i128,
+        conditions: Vec<Condition>,
+    ) -> Result<(), ClientError> {
+        if !conditions.contains(Condition::ExtDeposit(address.clone(), amount)) {
+            return Err(ClientError::MissingDepositCondition(address));
+        }
+        self.add_deposit(e, address.clone(), amount, conditions)?;
+
+        self.allowance_deposits.push_back(address);
+        Ok(())
+    }
+
+    pub fn add_withdraw(
+        &mut self,
+        e: &Env,
+        address: Address,
+        amount: i128,
+        conditions: Vec<Condition>,
+    ) -> Result<(), ClientError> {
+        if self
+            .withdraw
+            .iter()
+            .any(|(existing, _, _)| existing == address)
+        {
+            return Err(ClientError::RepeatedWithdraw(address));
+        }
+        Self::check_external(
+            e,
+            &self.withdraw,
+            &self.deposit,
+            &address,
+            amount,
+            &conditions,
+        )?;
+
+        self.withdraw.push_back((address, amount, conditions));
+        self.drop_provider_signature();
+        Ok(())
+    }
+
+    /// Returns the payload the owner of `utxo` signs to authorize its spend.
+    pub fn spend_payload(
+        &self,
+        utxo: &BytesN<65>,
+        live_until_ledger: u32,
+    ) -> Result<AuthPayload, ClientError> {
+        let (_, conditions) = self
+            .spend
+            .iter()
+            .find(|(existing, _)| existing == utxo)
+            .ok_or_else(|| ClientError::UnknownSpend(utxo.clone()))?;
+
+        Ok(AuthPayload {
+            conditions,
+            live_until_ledger,
+        })
+    }
+
+    pub fn spend_payload_hash(
+        &self,
+        e: &Env,
+        utxo: &BytesN<65>,
+        live_until_ledger: u32,
+    ) -> Result<Hash<32>, ClientError> {
+        let payload = self.spend_payload(utxo, live_until_ledger)?;
+        Ok(hash_payload(
+            e,
+            &payload,
+            &self.context.channel.to_string().to_bytes(),
+        ))
+    }
+
+    /// Signs the spend of the UTXO owned by `signer`.
+    pub fn sign_spend(
+        &mut self,
+        e: &Env,
+        signer: &impl P256Signer,
+        live_until_ledger: u32,
+    ) -> Result<(), ClientError> {
+        let utxo = BytesN::from_array(e, &signer.public_key());
+        let hash = self.spend_payload_hash(e, &utxo, live_until_ledger)?;
+        let signature = signer.sign_prehash(&hash.to_array())?;
+
+        self.add_spend_signature(e, utxo, signature, live_until_ledger)
+    }
+
+    /// Adds a spend signature produced outside the builder.
+    pub fn add_spend_signature(
+        &mut self,
+        e: &Env,
+        utxo: BytesN<65>,
+        signature: [u8; 64],
+        live_until_ledger: u32,
+    ) -> Result<(), ClientError> {
+        self.spend_payload(&utxo, live_until_ledger)?;
+
+        self.insert_signature(
+            SignerKey::P256(utxo),
+            Signature::P256(BytesN::from_array(e, &signature)),
+            live_until_ledger,
+        )
+    }
+
+    /// Returns the hash the provider signs for the auth contract's entry.
+    pub fn bundle_payload_hash(
+        &self,
+        e: &Env,
+        nonce: i64,
+        signature_expiration_ledger: u32,
+    ) -> Result<Hash<32>, ClientError> {
+        self.entry_payload_hash(
+            e,
+            nonce,
+            signature_expiration_ledger,
+            self.bundle_invocation(e)?,
+        )
+    }
+
+    /// Signs the auth contract's entry as the provider.
+    ///
+    /// `nonce` and `signature_expiration_ledger` must match the ones passed to
+    /// [`Self::auth_entry`].
+    pub fn sign_provider(
+        &mut self,
+        e: &Env,
+        signer: &impl Ed25519Signer,
+        nonce: i64,
+        signature_expiration_ledger: u32,
+    ) -> Result<(), ClientError> {
+        let hash = self.bundle_payload_hash(e, nonce, signature_expiration_ledger)?;
+        let signature = signer.sign(&hash.to_array())?;
+        let provider = Address::from_payload(
+            e,
+            AddressPayload::AccountIdPublicKeyEd25519(BytesN::from_array(e, &signer.public_key())),
+        );
+
+        self.add_provider_signature(
+            &provider,
+            BytesN::from_array(e, &signature),
+            signature_expiration_ledger,
+        )
+    }
+
+    /// Adds a provider signature produced outside the builder.
+    pub fn add_provider_signature(
+        &mut self,
+        provider: &Address,
+        signature: BytesN<64>,
+        live_until_ledger: u32,
+    ) -> Result<(), ClientError> {
+        // The auth contract currently requires exactly one provider.
+        if self.has_provider_signature() {
+            return Err(ClientError::SignatureAlreadyAdded);
+        }
+        let provider_key = ed25519_public_key(provider)?;
+
+        self.insert_signature(
+            SignerKey::Provider(provider_key),
+            Signature::Ed25519(signature),
+            live_until_ledger,
+        )
+    }
+
+    /// Names a contract (C-address) provider. It signs nothing here: it authorizes the auth
+    /// contract's entry through its own `__check_auth`, in an entry rooted at
+    /// [`Self::provider_invocation`] that the provider's custody builds and signs.
+    pub fn add_provider_contract(
+        &mut self,
+        provider: &Address,
+        live_until_ledger: u32,
+    ) -> Result<(), ClientError> {
+        if self.has_provider_signature() {
+            return Err(ClientError::SignatureAlreadyAdded);
+        }
+        if !matches!(
+            provider.to_payload(),
+            Some(AddressPayload::ContractIdHash(_))
+        ) {
+            return Err(ClientError::NotContract(provider.clone()));
+        }
+
+        self.insert_signature(
+            SignerKey::ProviderContract(provider.clone()),
+            Signature::Delegated,
+            live_until_ledger,
+        )
+    }
+
+    /// The root invocation of a contract provider's entry: the auth contract's `__check_auth`
+    /// with [`Self::bundle_payload_hash`].
+    ///
+    /// `nonce` and `signature_expiration_ledger` must match the ones passed to
+    /// [`Self::auth_entry`].
+    pub fn provider_invocation(
+        &self,
+        e: &Env,
+        nonce: i64,
+        signature_expiration_ledger: u32,
+    ) -> Result<SorobanAuthorizedInvocation, ClientError> {
+        let payload = self
+            .bundle_payload_hash(e, nonce, signature_expiration_ledger)?
+            .to_bytes();
+
+        Ok(SorobanAuthorizedInvocation {
+            function: contract_fn(
+                &self.context.auth,
+                "__check_auth",
+                vec![e, payload.into_val(e)],
+            )?,
+            sub_invocations: VecM::default(),
+        })
+    }
+
+    /// Returns the hash `depositor` signs for its own auth entry.
+    pub fn deposit_payload_hash(
+        &self,
+        e: &Env,
+        depositor: &Address,
+        nonce: i64,
+        signature_expiration_ledger: u32,
+    ) -> Result<Hash<32>, ClientError> {
+        self.entry_payload_hash(
+            e,
+            nonce,
+            signature_expiration_ledger,
+            self.deposit_invocation(e, depositor)?,
+        )
+    }
+
+    /// Signs the deposit of the G account owned by `signer`.
+    ///
+    /// `nonce` and `signature_expiration_ledger` must match the ones passed to
+    /// [`Self::deposit_auth_entry`].
+    pub fn sign_deposit(
+        &mut self,
+        e: &Env,
+        signer: &impl Ed25519Signer,
+        nonce: i64,
+        signature_expiration_ledger: u32,
+    ) -> Result<(), ClientError> {
+        let public_key = BytesN::from_array(e, &signer.public_key());
+        let depositor = Address::from_payload(
+            e,
+            AddressPayload::AccountIdPublicKeyEd25519(public_key.clone()),
+        );
+        let hash = self.deposit_payload_hash(e, &depositor, nonce, signature_expiration_ledger)?;
+        let signature = BytesN::from_array(e, &signer.sign(&hash.to_array())?);
+
+        self.add_deposit_signature(
+            depositor,
+            AccountSignature {
+                public_key,
+                signature,
+            },
+        )
+    }
+
+    /// Adds a depositor signature produced outside the builder.
+    pub fn add_deposit_signature(
+        &mut self,
+        depositor: Address,
+        signature: AccountSignature,
+    ) -> Result<(), ClientError> {
+        if ed25519_public_key(&depositor)? != signature.public_key {
+            return Err(ClientError::SignerMismatch);
+        }
+        if !self.deposit.iter().any(|(addr, _, _)| addr == depositor) {
+            return Err(ClientError::UnknownDepositor(depositor));
+        }
+        if self.deposit_signatures.contains_key(depositor.clone()) {
+            return Err(ClientError::SignatureAlreadyAdded);
+        }
+
+        self.deposit_signatures.set(depositor, signature);
+        Ok(())
+    }
+
+    /// Returns the signatures for the auth contract's entry.
+    ///
+    /// Fails unless every spend is signed and a provider signature was added.
+    pub fn signatures(&self) -> Result<Signatures, ClientError> {
+        for (utxo, _) in self.spend.iter() {
+            if !self.sign_map.contains_key(SignerKey::P256(utxo.clone())) {
+                return Err(ClientError::MissingSpendSignature(utxo));
+            }
+        }
+        if !self.has_provider_signature() {
+            return Err(ClientError::MissingProviderSignature);
+        }
+
+        Ok(Signatures(self.sign_map.clone()))
+    }
+
+    /// Builds the auth contract's entry, which authorizes the bundle's spends.
+    pub fn auth_entry(
+        &self,
+        e: &Env,
+        nonce: i64,
+        signature_expiration_ledger: u32,
+    ) -> Result<SorobanAuthorizationEntry, ClientError> {
+        let signatures: Val = self.signatures()?.into_val(e);
+
+        Ok(SorobanAuthorizationEntry {
+            credentials: SorobanCredentials::Address(SorobanAddressCredentials {
+                address: (&self.context.auth).into(),
+                nonce,
+                signature_expiration_ledger,
+                signature: ScVal::try_from_val(e, &signatures).map_err(|_| ClientError::Xdr)?,
+            }),
+            root_invocation: self.bundle_invocation(e)?,
+        })
+    }
+
+    /// Builds `depositor`'s entry, which authorizes its conditions and its asset transfer.
+    pub fn deposit_auth_entry(
+        &self,
+        e: &Env,
+        depositor: &Address,
+        nonce: i64,
+        signature_expiration_ledger: u32,
+    ) -> Result<SorobanAuthorizationEntry, ClientError> {
+        let signature = self
+            .deposit_signatures
+            .get(depositor.clone())
+            .ok_or_else(|| ClientError::MissingDepositSignature(depositor.clone()))?;
+
+        Ok(SorobanAuthorizationEntry {
+            credentials: SorobanCredentials::Address(SorobanAddressCredentials {
+                address: depositor.into(),
+                nonce,
+                signature_expiration_ledger,
+                signature: vec![e, signature].into(),
+            }),
+            root_invocation: self.deposit_invocation(e, depositor)?,
+        })
+    }
+
+    /// Builds every entry `transact` needs: the auth contract's entry, then one per depositor.
+    pub fn auth_entries(
+        &self,
+        e: &Env,
+        nonce: i64,
+        signature_expiration_ledger: u32,
+    ) -> Result<std::vec::Vec<SorobanAuthorizationEntry>, ClientError> {
+        let mut entries = std::vec![self.auth_entry(e, nonce, signature_expiration_ledger)?];
+        for (depositor, _, _) in self.deposit.iter() {
+            entries.push(self.deposit_auth_entry(
+                e,
+                &depositor,
+                nonce,
+                signature_expiration_ledger,
+            )?);
+        }
+
+        Ok(entries)
+    }
+
+    /// Adds `other`'s signed spends to this builder's, for an auth contract entry that
+    /// authorizes several operations at once.
+    pub(crate) fn merge_spends(&mut self, other: &Self) -> Result<(), ClientError> {
+        if other.function != self.function {
+            return Err(ClientError::NotBatched);
+        }
+        for (utxo, _) in other.spend.iter() {
+            if self.spend.iter().any(|(existing, _)| existing == utxo) {
+                return Err(ClientError::RepeatedSpend(utxo));
+            }
+            if !other.sign_map.contains_key(SignerKey::P256(utxo.clone())) {
+                return Err(ClientError::MissingSpendSignature(utxo));
+            }
+        }
+
+        for (utxo, conditions) in other.spend.iter() {
+            let key = SignerKey::P256(utxo.clone());
+            self.sign_map
+                .set(key.clone(), other.sign_map.get_unchecked(key));
+            self.spend.push_back((utxo, conditions));
+        }
+        self.drop_provider_signature();
+        Ok(())
+    }
+
+    fn check_external(
+        e: &Env,
+        same_side: &Vec<(Address, i128, Vec<Condition>)>,
+        other_side: &Vec<(Address, i128, Vec<Condition>)>,
+        address: &Address,
+        amount: i128,
+        conditions: &Vec<Condition>,
+    ) -> Result<(), ClientError> {
+        if amount <= 0 {
+            return Err(ClientError::InvalidAmount(amount));
+        }
+        for (_, _, existing_conditions) in same_side.iter() {
+            if !has_no_conflicting_conditions_in_sets(conditions, &existing_conditions) {
+                return Err(ClientError::ConflictingConditions);
+            }
+        }
+        for (existing_address, _, existing_conditions) in other_side.iter() {
+            if existing_address == *address {
+                // An address on both sides must repeat the exact same conditions.
+                if !equal_condition_sequence(e, &existing_conditions, conditions) {
+                    return Err(ClientError::AccountConditionsMismatch(address.clone()));
+                }
+            } else if !has_no_conflicting_conditions_in_sets(conditions, &existing_conditions) {
+                return Err(ClientError::ConflictingConditions);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn insert_signature(
+        &mut self,
+        key: SignerKey,
+        signature: Signature,
+        live_until_ledger: u32,
+    ) -> Result<(), ClientError> {
+        if self.sign_map.contains_key(key.clone()) {
+            return Err(ClientError::SignatureAlreadyAdded);
+        }
+
+        self.sign_map.set(key, (signature, live_until_ledger));
+        Ok(())
+    }
+
+    /// The provider signs the bundle as it stood when signing, so any later entry voids it.
+    fn drop_provider_signature(&mut self) {
+        for key in self.sign_map.keys().iter() {
+            if matches!(key, SignerKey::Provider(_) | SignerKey::ProviderContract(_)) {
+                self.sign_map.remove(key);
+            }
+        }
+    }
+
+    fn has_provider_signature(&self) -> bool {
+        self.sign_map
+            .keys()
+            .iter()
+            .any(|key| matches!(key, SignerKey::Provider(_) | SignerKey::ProviderContract(_)))
+    }
+
+    /// The arguments the channel passes to the auth contract's `require_auth_for_args`: the
+    /// spends and their conditions, or nothing when the bundle spends no UTXO.
+    pub fn bundle_auth_args(&self, e: &Env) -> Vec<Val> {
+        let auth_req = calculate_auth_requirements(e, &self.spend);
+        if auth_req.0.is_empty() {
+            vec![e]
+        } else {
+            vec![e, auth_req.into_val(e)]
+        }
+    }
+
+    /// The channel's entry point as invoked by the auth contract's `require_auth_for_args`.
+    fn bundle_invocation(&self, e: &Env) -> Result<SorobanAuthorizedInvocation, ClientError> {
+        Ok(SorobanAuthorizedInvocation {
+            function: contract_fn(
+                &self.context.channel,
+                self.function,
+                self.bundle_auth_args(e),
+            )?,
+            sub_invocations: VecM::default(),
+        })
+    }
+
+    /// The channel's entry point with the depositor's conditions, followed by its asset transfer.
+    /// An allowance deposit authorizes no transfer.
+    fn deposit_invocation(
+        &self,
+        e: &Env,
+        depositor: &Address,
+    ) -> Result<SorobanAuthorizedInvocation, ClientError> {
+        let (conditions, amount) = self
+            .deposit
+            .iter()
+            .find_map(|(addr, amount, conditions)| {
+                (addr == *depositor).then_some((conditions, amount))
+            })
+            .ok_or_else(|| ClientError::UnknownDepositor(depositor.clone()))?;
+
+        // The channel passes the conditions even when there are none, and marks an allowance
+        // deposit with a trailing `true`.
+        if self.allowance_deposits.contains(depositor) {
+            let root_args: Vec<Val> = vec![e, conditions.into_val(e), true.into_val(e)];
+            let function = contract_fn(&self.context.channel, self.function, root_args)?;
+            return Ok(SorobanAuthorizedInvocation {
+                function,
+                sub_invocations: VecM::default(),
+            });
+        }
+
+        let root_args: Vec<Val> = vec![e, conditions.into_val(e)];
+        let function = contract_fn(&self.context.channel, self.function, root_args)?;
+        let transfer_args: Vec<Val> = vec![
+            e,
+            depositor.into_val(e),
+            self.context.channel.into_val(e),
+            amount.into_val(e),
+        ];
+
+        let transfer = SorobanAuthorizedInvocation {
+            function: contract_fn(&self.context.asset, "transfer", transfer_args)?,
+            sub_invocations: VecM::default(),
+        };
+
+        Ok(SorobanAuthorizedInvocation {
+            function,
+            sub_invocations: std::vec![transfer]
+                .try_into()
+                .map_err(|_| ClientError::Xdr)?,
+        })
+    }
+
+    fn entry_payload_hash(
+        &self,
+        e: &Env,
+        nonce: i64,
+        signature_expiration_ledger: u32,
+        invocation: SorobanAuthorizedInvocation,
+    ) -> Result<Hash<32>, ClientError> {
+        let preimage = HashIdPreimage::SorobanAuthorization(HashIdPreimageSorobanAuthorization {
+            network_id: self.context.network_id.to_array().into(),
+            nonce,
+            signature_expiration_ledger,
+            invocation,
+        });
+        let preimage_xdr = preimage
+            .to_xdr(Limits::none())
+            .map_err(|_| ClientError::Xdr)?;
+
+        Ok(e.crypto().sha256(&Bytes::from_slice(e, &preimage_xdr)))
+    }
+}
+
+fn contract_fn(
+    contract: &Address,
+    function_name: &str,
+    args: Vec<Val>,
+) -> Result<SorobanAuthorizedFunction, ClientError> {
+    Ok(SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
+        contract_address: contract.into(),
+        function_name: function_name.try_into().map_err(|_| ClientError::Xdr)?,
+        args: args.into(),
+    }))
+}
+
+fn ed25519_public_key(address: &Address) -> Result<BytesN<32>, ClientError> {
+    match address.to_payload() {
+        Some(AddressPayload::AccountIdPublicKeyEd25519(public_key)) => Ok(public_key),
+        _ => Err(ClientError::NotEd25519Account(address.clone())),
+    }
+}
@@ -0,0 +1,236 @@
+//! Resource cost estimates for `transact` bundles.
+//!
+//! [`CostModel::NATIVE_ESTIMATE`] was fitted to `Env::cost_estimate()` measurements of signed
+//! `transact` calls against the native test contracts (see `src/test.rs`), and rounded up so it
+//! never underestimates them. It is not calibrated against the deployed Wasm: the native
+//! contracts skip VM instantiation and execution and leave the contract code entries out of the
+//! footprint, so on-chain instructions, memory and read entries are higher. Treat its results as
+//! estimates and simulate bundles before submitting them, or build a [`CostModel`] from
+//! measurements of the deployed contracts.
+
+use moonlight_primitives::ChannelOperation;
+
+/// The entry counts of a bundle, the inputs of a [`CostModel`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BundleShape {
+    pub spends: u32,
+    pub creates: u32,
+    pub deposits: u32,
+    pub withdrawals: u32,
+    /// Conditions across all spends, deposits and withdrawals.
+    pub conditions: u32,
+}
+
+impl BundleShape {
+    pub fn of(op: &ChannelOperation) -> Self {
+        let spend_conditions: u32 = op.spend.iter().map(|(_, c)| c.len()).sum();
+        let external_conditions: u32 = op
+            .deposit
+            .iter()
+            .chain(op.withdraw.iter())
+            .map(|(_, _, c)| c.len())
+            .sum();
+
+        Self {
+            spends: op.spend.len(),
+            creates: op.create.len(),
+            deposits: op.deposit.len(),
+            withdrawals: op.withdraw.len(),
+            conditions: spend_conditions + external_conditions,
+        }
+    }
+
+    /// `n` copies of this shape in one bundle.
+    pub fn scaled(&self, n: u32) -> Self {
+        Self {
+            spends: self.spends.saturating_mul(n),
+            creates: self.creates.saturating_mul(n),
+            deposits: self.deposits.saturating_mul(n),
+            withdrawals: self.withdrawals.saturating_mul(n),
+            conditions: self.conditions.saturating_mul(n),
+        }
+    }
+
+    /// Spends, creates, deposits and withdrawals; conditions are not entries.
+    pub fn entries(&self) -> u32 {
+        self.spends
+            .saturating_add(self.creates)
+            .saturating_add(self.deposits)
+            .saturating_add(self.withdrawals)
+    }
+}
+
+/// The resources of one `transact` invocation.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CostEstimate {
+    pub instructions: u64,
+    pub memory_bytes: u64,
+    /// Ledger entries read, including the ones that are also written.
+    pub read_entries: u64,
+    pub write_entries: u64,
+    pub event_bytes: u64,
+}
+
+impl CostEstimate {
+    pub fn fits(&self, limits: &ResourceLimits) -> bool {
+        self.instructions <= limits.instructions
+            && self.memory_bytes <= limits.memory_bytes
+            && self.read_entries.saturating_add(self.write_entries) <= limits.ledger_entries
+            && self.write_entries <= limits.write_entries
+            && self.event_bytes <= limits.event_bytes
+    }
+
+    fn add_scaled(self, other: &CostEstimate, n: u64) -> Self {
+        Self {
+            instructions: self
+                .instructions
+                .saturating_add(other.instructions.saturating_mul(n)),
+            memory_bytes: self
+                .memory_bytes
+                .saturating_add(other.memory_bytes.saturating_mul(n)),
+            read_entries: self
+                .read_entries
+                .saturating_add(other.read_entries.saturating_mul(n)),
+            write_entries: self
+                .write_entries
+                .saturating_add(other.write_entries.saturating_mul(n)),
+            event_bytes: self
+                .event_bytes
+                .saturating_add(other.event_bytes.saturating_mul(n)),
+        }
+    }
+}
+
+/// Per-transaction resource limits of a network.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ResourceLimits {
+    pub instructions: u64,
+    pub memory_bytes: u64,
+    /// Read plus written entries, the size of the footprint.
+    pub ledger_entries: u64,
+    pub write_entries: u64,
+    pub event_bytes: u64,
+}
+
+impl ResourceLimits {
+    /// Stellar Mainnet, as enforced by the `soroban-sdk` 25 test environment.
+    pub const MAINNET: Self = Self {
+        instructions: 600_000_000,
+        memory_bytes: 41_943_040,
+        ledger_entries: 100,
+        write_entries: 50,
+        event_bytes: 16_384,
+    };
+}
+
+/// A linear cost model with a quadratic term for the pairwise checks between entries.
+///
+/// `estimate = base + Σ count × per_item + entries² × per_entry_pair`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CostModel {
+    /// The auth contract call, provider signature, channel overhead and `Transact` event.
+    pub base: CostEstimate,
+    /// Dominated by the P256 signature check.
+    pub per_spend: CostEstimate,
+    pub per_create: CostEstimate,
+    /// Includes the depositor's Ed25519 check and token transfer and its event.
+    pub per_deposit: CostEstimate,
+    pub per_withdrawal: CostEstimate,
+    pub per_condition: CostEstimate,
+    pub per_entry_pair: CostEstimate,
+}
+
+impl CostModel {
+    /// Fitted to the native test contracts; see the module docs.
+    pub const NATIVE_ESTIMATE: Self = Self {
+        base: CostEstimate {
+            instructions: 700_000,
+            memory_bytes: 34_000,
+            read_entries: 6,
+            write_entries: 3,
+            event_bytes: 316,
+        },
+        per_spend: CostEstimate {
+            instructions: 3_200_000,
+            memory_bytes: 25_000,
+            read_entries: 3,
+            write_entries: 2,
+            event_bytes: 40,
+        },
+        per_create: CostEstimate {
+            instructions: 100_000,
+            memory_bytes: 16_600,
+            read_entries: 2,
+            write_entries: 1,
+            event_bytes: 40,
+        },
+        per_deposit: CostEstimate {
+            instructions: 600_000,
+            memory_bytes: 30_000,
+            read_entries: 4,
+            write_entries: 3,
+            event_bytes: 300,
+        },
+        per_withdrawal: CostEstimate {
+            instructions: 165_000,
+            memory_bytes: 28_500,
+            read_entries: 2,
+            write_entries: 2,
+            event_bytes: 300,
+        },
+        per_condition: CostEstimate {
+            instructions: 52_000,
+            memory_bytes: 10_500,
+            read_entries: 0,
+            write_entries: 0,
+            event_bytes: 0,
+        },
+        per_entry_pair: CostEstimate {
+            instructions: 2_100,
+            memory_bytes: 400,
+            read_entries: 0,
+            write_entries: 0,
+            event_bytes: 0,
+        },
+    };
+
+    pub fn estimate(&self, shape: &BundleShape) -> CostEstimate {
+        let entries = u64::from(shape.entries());
+
+        self.base
+            .add_scaled(&self.per_spend, shape.spends.into())
+            .add_scaled(&self.per_create, shape.creates.into())
+            .add_scaled(&self.per_deposit, shape.deposits.into())
+            .add_scaled(&self.per_withdrawal, shape.withdrawals.into())
+            .add_scaled(&self.per_condition, shape.conditions.into())
+            .add_scaled(&self.per_entry_pair, entries.saturating_mul(entries))
+    }
+
+    /// The most copies of `unit` that fit in one bundle under `limits`, according to this model.
+    ///
+    /// For example, a `unit` of one spend, one create and one condition gives the largest
+    /// one-to-one transfer. Returns `0` if a single copy does not fit or `unit` has no entries.
+    pub fn max_bundle_size(&self, unit: &BundleShape, limits: &ResourceLimits) -> u32 {
+        let fits = |n: u32| self.estimate(&unit.scaled(n)).fits(limits);
+
+        if unit.entries() == 0 || !fits(1) {
+            return 0;
+        }
+        if fits(u32::MAX) {
+            return u32::MAX;
+        }
+
+        // Estimates never decrease with `n`, so binary search between a size that fits and one
+        // that does not. A model with zero per-item costs returns above instead of looping.
+        let (mut low, mut high) = (1, u32::MAX);
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if fits(mid) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+}
@@ -0,0 +1,132 @@
+use core::fmt;
+
+use soroban_sdk::{Address, BytesN};
+
+/// Errors returned while building, signing or assembling a channel operation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ClientError {
+    /// The UTXO is already in the spend list.
+    RepeatedSpend(BytesN<65>),
+    /// The UTXO is already in the create list.
+    RepeatedCreate(BytesN<65>),
+    /// The address already has a deposit.
+    RepeatedDeposit(Address),
+    /// The address already has a withdraw.
+    RepeatedWithdraw(Address),
+    /// Create, deposit and withdraw amounts must be positive.
+    InvalidAmount(i128),
+    /// The new entry's conditions conflict with conditions already in the operation.
+    ConflictingConditions,
+    /// An address that both deposits and withdraws must use the same condition sequence.
+    AccountConditionsMismatch(Address),
+    /// The UTXO is not in the spend list.
+    UnknownSpend(BytesN<65>),
+    /// The UTXO is not in the create list.
+    UnknownCreate(BytesN<65>),
+    /// The UTXO already has a note.
+    RepeatedNote(BytesN<65>),
+    /// The UTXO already has an ephemeral key.
+    RepeatedEphemeralKey(BytesN<65>),
+    /// The address has no deposit.
+    UnknownDepositor(Address),
+    /// An allowance deposit's conditions must include `Condition::ExtDeposit` of its amount.
+    MissingDepositCondition(Address),
+    /// A signature for this signer was already added.
+    SignatureAlreadyAdded,
+    /// The spend UTXO has no P256 signature.
+    MissingSpendSignature(BytesN<65>),
+    /// The depositor has no signature.
+    MissingDepositSignature(Address),
+    /// No provider signature was added.
+    MissingProviderSignature,
+    /// Only operations built with `ChannelOperationBuilder::new_batched` join a batch.
+    NotBatched,
+    /// The address is not an Ed25519 (G) account.
+    NotEd25519Account(Address),
+    /// The address is not a contract (C) address.
+    NotContract(Address),
+    /// The signer's public key does not match the key it is signing for.
+    SignerMismatch,
+    /// A signer failed to produce a signature.
+    Signer(String),
+    /// A value could not be converted to XDR.
+    Xdr,
+    /// HD seeds must be 16 to 64 bytes long.
+    InvalidSeed,
+    /// Only hardened indices below 2^31 can be derived.
+    InvalidIndex(u32),
+    /// A gap-limit scan needs a gap limit of at least one.
+    InvalidGapLimit,
+    /// A scan query returned a different number of results than keys it was given.
+    BatchSizeMismatch,
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RepeatedSpend(utxo) => {
+                write!(f, "UTXO {utxo:?} is already spent in this operation")
+            }
+            Self::RepeatedCreate(utxo) => {
+                write!(f, "UTXO {utxo:?} is already created in this operation")
+            }
+            Self::RepeatedDeposit(addr) => write!(f, "{addr:?} already deposits in this operation"),
+            Self::RepeatedWithdraw(addr) => {
+                write!(f, "{addr:?} already withdraws in this operation")
+            }
+            Self::InvalidAmount(amount) => write!(f, "amount {amount} must be positive"),
+            Self::ConflictingConditions => {
+                write!(
+                    f,
+                    "conditions conflict with the operation's existing conditions"
+                )
+            }
+            Self::AccountConditionsMismatch(addr) => write!(
+                f,
+                "{addr:?} deposits and withdraws with different conditions"
+            ),
+            Self::UnknownSpend(utxo) => write!(f, "UTXO {utxo:?} is not spent in this operation"),
+            Self::UnknownCreate(utxo) => {
+                write!(f, "UTXO {utxo:?} is not created in this operation")
+            }
+            Self::RepeatedNote(utxo) => write!(f, "UTXO {utxo:?} already has a note"),
+            Self::RepeatedEphemeralKey(utxo) => {
+                write!(f, "UTXO {utxo:?} already has an ephemeral key")
+            }
+            Self::UnknownDepositor(addr) => {
+                write!(f, "{addr:?} does not deposit in this operation")
+            }
+            Self::MissingDepositCondition(addr) => {
+                write!(
+                    f,
+                    "allowance deposit of {addr:?} does not sign its ExtDeposit"
+                )
+            }
+            Self::SignatureAlreadyAdded => {
+                write!(f, "a signature for this signer was already added")
+            }
+            Self::MissingSpendSignature(utxo) => write!(f, "UTXO {utxo:?} has no spend signature"),
+            Self::MissingDepositSignature(addr) => {
+                write!(f, "depositor {addr:?} has no signature")
+            }
+            Self::MissingProviderSignature => write!(f, "no provider signature was added"),
+            Self::NotBatched => write!(f, "operation was not built for transact_batch"),
+            Self::NotEd25519Account(addr) => write!(f, "{addr:?} is not an Ed25519 account"),
+            Self::NotContract(addr) => write!(f, "{addr:?} is not a contract"),
+            Self::SignerMismatch => write!(f, "signer key does not match the key being signed for"),
+            Self::Signer(reason) => write!(f, "signer failed: {reason}"),
+            Self::Xdr => write!(f, "value could not be converted to XDR"),
+            Self::InvalidSeed => write!(f, "seed must be 16 to 64 bytes long"),
+            Self::InvalidIndex(index) => write!(f, "index {index} cannot be hardened"),
+            Self::InvalidGapLimit => write!(f, "gap limit must be at least one"),
+            Self::BatchSizeMismatch => {
+                write!(
+                    f,
+                    "scan query returned a result count that does not match its batch"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
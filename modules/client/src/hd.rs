@@ -0,0 +1,261 @@
+//! Hierarchical deterministic derivation of P256 UTXO keys.
+//!
+//! Keys follow [SLIP-0010] for the NIST P-256 curve, which only defines hardened derivation.
+//! UTXO keys live at `m/44'/148'/<account>'/0'/<index>'`; every UTXO of a wallet is recovered
+//! from its seed and account number alone.
+//!
+//! [SLIP-0010]: https://github.com/satoshilabs/slips/blob/master/slip-0010.md
+
+use hmac::{Hmac, Mac};
+use p256::{
+    elliptic_curve::{Field, PrimeField},
+    FieldBytes, Scalar,
+};
+use sha2::Sha512;
+
+use crate::{ClientError, LocalP256Signer, P256Signer};
+
+/// Indices at or above this offset are hardened.
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// BIP-44 purpose and the Stellar coin type, the prefix of every UTXO key path.
+pub const UTXO_PATH_PREFIX: [u32; 2] = [44, 148];
+
+/// The branch under an account that holds UTXO keys.
+pub const UTXO_BRANCH: u32 = 0;
+
+const CURVE_SEED_KEY: &[u8] = b"Nist256p1 seed";
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// A P256 secret key and chain code at some derivation path.
+#[derive(Clone)]
+pub struct ExtendedKey {
+    secret_key: [u8; 32],
+    chain_code: [u8; 32],
+}
+
+impl ExtendedKey {
+    /// Derives the master key from a 16 to 64-byte seed.
+    pub fn master(seed: &[u8]) -> Result<Self, ClientError> {
+        if !(16..=64).contains(&seed.len()) {
+            return Err(ClientError::InvalidSeed);
+        }
+
+        let mut i = hmac_sha512(CURVE_SEED_KEY, &[seed]);
+        loop {
+            let (il, ir) = split(&i);
+            if let Some(scalar) = nonzero_scalar(&il) {
+                return Ok(Self {
+                    secret_key: scalar.to_bytes().into(),
+                    chain_code: ir,
+                });
+            }
+            i = hmac_sha512(CURVE_SEED_KEY, &[&i]);
+        }
+    }
+
+    /// Derives the hardened child `index'`. `index` is given without the hardened offset.
+    pub fn derive(&self, index: u32) -> Result<Self, ClientError> {
+        if index >= HARDENED_OFFSET {
+            return Err(ClientError::InvalidIndex(index));
+        }
+        let index_bytes = (index | HARDENED_OFFSET).to_be_bytes();
+        let parent = nonzero_scalar(&self.secret_key).expect("extended keys hold valid scalars");
+
+        let mut i = hmac_sha512(&self.chain_code, &[&[0], &self.secret_key, &index_bytes]);
+        loop {
+            let (il, ir) = split(&i);
+            if let Some(tweak) = Option::<Scalar>::from(Scalar::from_repr(FieldBytes::from(il))) {
+                let child = tweak + parent;
+                if !bool::from(child.is_zero()) {
+                    return Ok(Self {
+                        secret_key: child.to_bytes().into(),
+                        chain_code: ir,
+                    });
+                }
+            }
+            i = hmac_sha512(&self.chain_code, &[&[1], &ir, &index_bytes]);
+        }
+    }
+
+    /// Derives a chain of hardened children, e.g. `[44, 148, 0]` for `m/44'/148'/0'`.
+    pub fn derive_path(&self, path: &[u32]) -> Result<Self, ClientError> {
+        path.iter()
+            .try_fold(self.clone(), |key, index| key.derive(*index))
+    }
+
+    pub fn secret_key(&self) -> [u8; 32] {
+        self.secret_key
+    }
+
+    pub fn chain_code(&self) -> [u8; 32] {
+        self.chain_code
+    }
+
+    pub fn signer(&self) -> LocalP256Signer {
+        LocalP256Signer::from_bytes(&self.secret_key)
+            .expect("derived keys are valid non-zero scalars")
+    }
+}
+
+/// The UTXO keys of one wallet account, `m/44'/148'/<account>'/0'/<index>'`.
+#[derive(Clone)]
+pub struct UtxoKeychain {
+    branch: ExtendedKey,
+}
+
+/// A UTXO found by [`UtxoKeychain::recover`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecoveredUtxo {
+    pub index: u32,
+    pub public_key: [u8; 65],
+    /// As returned by `utxo_balances`: the amount if unspent, `0` if spent.
+    pub balance: i128,
+}
+
+/// The result of [`UtxoKeychain::recover`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Recovery {
+    /// Every derived UTXO with an on-chain record, in index order.
+    pub utxos: Vec<RecoveredUtxo>,
+    /// The first index after the last used one; the next fresh UTXO key.
+    pub next_index: u32,
+}
+
+impl UtxoKeychain {
+    pub fn new(seed: &[u8], account: u32) -> Result<Self, ClientError> {
+        let branch = ExtendedKey::master(seed)?.derive_path(&[
+            UTXO_PATH_PREFIX[0],
+            UTXO_PATH_PREFIX[1],
+            account,
+            UTXO_BRANCH,
+        ])?;
+
+        Ok(Self { branch })
+    }
+
+    pub fn key(&self, index: u32) -> Result<ExtendedKey, ClientError> {
+        self.branch.derive(index)
+    }
+
+    pub fn signer(&self, index: u32) -> Result<LocalP256Signer, ClientError> {
+        Ok(self.key(index)?.signer())
+    }
+
+    /// The UTXO id of the key at `index`.
+    pub fn public_key(&self, index: u32) -> Result<[u8; 65], ClientError> {
+        Ok(self.signer(index)?.public_key())
+    }
+
+    /// The UTXO ids of `count` consecutive keys starting at `start`.
+    pub fn public_keys(&self, start: u32, count: u32) -> Result<Vec<[u8; 65]>, ClientError> {
+        (0..count)
+            .map(|offset| {
+                let index = start
+                    .checked_add(offset)
+                    .ok_or(ClientError::InvalidIndex(u32::MAX))?;
+                self.public_key(index)
+            })
+            .collect()
+    }
+
+    /// Scans keys in batches of `gap_limit` until `gap_limit` consecutive keys are unused.
+    ///
+    /// `is_used` receives the index of each batch's first key and the batch, and returns one
+    /// flag per key, in order. Returns the indices of every used key.
+    pub fn scan<E: From<ClientError>>(
+        &self,
+        gap_limit: u32,
+        mut is_used: impl FnMut(u32, &[[u8; 65]]) -> Result<Vec<bool>, E>,
+    ) -> Result<Vec<u32>, E> {
+        if gap_limit == 0 {
+            return Err(ClientError::InvalidGapLimit.into());
+        }
+
+        let mut used = Vec::new();
+        let mut start = 0;
+        let mut gap = 0;
+
+        while gap < gap_limit {
+            let batch = self.public_keys(start, gap_limit)?;
+            let flags = is_used(start, &batch)?;
+            if flags.len() != batch.len() {
+                return Err(ClientError::BatchSizeMismatch.into());
+            }
+
+            for (offset, flag) in flags.into_iter().enumerate() {
+                if flag {
+                    used.push(start + offset as u32);
+                    gap = 0;
+                } else {
+                    gap += 1;
+                }
+            }
+            start = start
+                .checked_add(gap_limit)
+                .ok_or(ClientError::InvalidIndex(start))?;
+        }
+
+        Ok(used)
+    }
+
+    /// Rediscovers the account's UTXOs from `utxo_balances` results.
+    ///
+    /// `utxo_balances` receives each batch of derived UTXO ids and returns the channel's
+    /// `utxo_balances` for them. Keys with a record (`>= 0`) count as used.
+    pub fn recover<E: From<ClientError>>(
+        &self,
+        gap_limit: u32,
+        mut utxo_balances: impl FnMut(&[[u8; 65]]) -> Result<Vec<i128>, E>,
+    ) -> Result<Recovery, E> {
+        let mut utxos = Vec::new();
+
+        self.scan(gap_limit, |start, batch| -> Result<Vec<bool>, E> {
+            let balances = utxo_balances(batch)?;
+            if balances.len() != batch.len() {
+                return Err(ClientError::BatchSizeMismatch.into());
+            }
+
+            let mut flags = Vec::with_capacity(balances.len());
+
+            for (offset, balance) in balances.into_iter().enumerate() {
+                let used = balance >= 0;
+                if used {
+                    utxos.push(RecoveredUtxo {
+                        index: start + offset as u32,
+                        public_key: batch[offset],
+                        balance,
+                    });
+                }
+                flags.push(used);
+            }
+            Ok(flags)
+        })?;
+
+        let next_index = utxos.last().map_or(0, |utxo| utxo.index + 1);
+        Ok(Recovery { utxos, next_index })
+    }
+}
+
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> [u8; 64] {
+    let mut mac = HmacSha512::new_from_slice(key).expect("HMAC accepts keys of any length");
+    for chunk in data {
+        mac.update(chunk);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+fn split(i: &[u8; 64]) -> ([u8; 32], [u8; 32]) {
+    let mut il = [0u8; 32];
+    let mut ir = [0u8; 32];
+    il.copy_from_slice(&i[..32]);
+    ir.copy_from_slice(&i[32..]);
+    (il, ir)
+}
+
+/// Parses `bytes` as a scalar in `[1, n)`.
+fn nonzero_scalar(bytes: &[u8; 32]) -> Option<Scalar> {
+    let scalar: Option<Scalar> = Scalar::from_repr(FieldBytes::from(*bytes)).into();
+    scalar.filter(|s| !bool::from(s.is_zero()))
+}
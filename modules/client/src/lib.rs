@@ -0,0 +1,31 @@
+//! Client SDK for building Privacy Channel transactions.
+//!
+//! [`ChannelOperationBuilder`] assembles a [`ChannelOperation`], collects the
+//! P256, depositor and provider signatures it needs through the [`P256Signer`]
+//! and [`Ed25519Signer`] traits, and produces the Soroban authorization
+//! entries to attach to the `transact` invocation. Every step that the
+//! contract would reject returns a [`ClientError`] instead of panicking.
+//! [`ChannelBatchBuilder`] does the same for `transact_batch`.
+
+mod batch;
+mod builder;
+mod cost;
+mod error;
+mod hd;
+mod signer;
+
+#[cfg(test)]
+mod test;
+
+pub use batch::ChannelBatchBuilder;
+pub use builder::{AccountSignature, ChannelContext, ChannelOperationBuilder};
+pub use cost::{BundleShape, CostEstimate, CostModel, ResourceLimits};
+pub use error::ClientError;
+pub use hd::{
+    ExtendedKey, RecoveredUtxo, Recovery, UtxoKeychain, HARDENED_OFFSET, UTXO_BRANCH,
+    UTXO_PATH_PREFIX,
+};
+pub use signer::{Ed25519Signer, LocalEd25519Signer, LocalP256Signer, P256Signer};
+
+pub use moonlight_primitives::ChannelOperation;
+pub use moonlight_primitives::Condition;
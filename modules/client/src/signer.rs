@@ -0,0 +1,86 @@
+use ed25519_dalek::{Signer as _, SigningKey as Ed25519SigningKey};
+use p256::ecdsa::{signature::hazmat::PrehashSigner, Signature as P256Signature, SigningKey};
+
+use crate::ClientError;
+
+/// Signs UTXO spend payloads with a secp256r1 key.
+///
+/// Implement this to keep UTXO keys in an HSM, a remote signer or a wallet.
+pub trait P256Signer {
+    /// The SEC1 uncompressed public key, which is also the UTXO id.
+    fn public_key(&self) -> [u8; 65];
+
+    /// Signs a 32-byte prehashed payload and returns `r || s`.
+    ///
+    /// The host rejects high-S signatures, so implementations must return the low-S form.
+    fn sign_prehash(&self, hash: &[u8; 32]) -> Result<[u8; 64], ClientError>;
+}
+
+/// Signs authorization payloads with an Ed25519 key.
+///
+/// Used for depositor (G account) auth entries and for provider signatures.
+pub trait Ed25519Signer {
+    fn public_key(&self) -> [u8; 32];
+
+    fn sign(&self, payload: &[u8; 32]) -> Result<[u8; 64], ClientError>;
+}
+
+/// A [`P256Signer`] holding its secret key in memory.
+pub struct LocalP256Signer {
+    secret_key: SigningKey,
+}
+
+impl LocalP256Signer {
+    pub fn from_bytes(secret: &[u8; 32]) -> Result<Self, ClientError> {
+        let secret_key = SigningKey::from_bytes(secret.into())
+            .map_err(|err| ClientError::Signer(err.to_string()))?;
+
+        Ok(Self { secret_key })
+    }
+}
+
+impl P256Signer for LocalP256Signer {
+    fn public_key(&self) -> [u8; 65] {
+        let mut public_key = [0u8; 65];
+        public_key.copy_from_slice(
+            self.secret_key
+                .verifying_key()
+                .to_encoded_point(false)
+                .as_bytes(),
+        );
+        public_key
+    }
+
+    fn sign_prehash(&self, hash: &[u8; 32]) -> Result<[u8; 64], ClientError> {
+        let signature: P256Signature = self
+            .secret_key
+            .sign_prehash(hash)
+            .map_err(|err| ClientError::Signer(err.to_string()))?;
+        let signature = signature.normalize_s().unwrap_or(signature);
+
+        Ok(signature.to_bytes().into())
+    }
+}
+
+/// An [`Ed25519Signer`] holding its secret key in memory.
+pub struct LocalEd25519Signer {
+    signing_key: Ed25519SigningKey,
+}
+
+impl LocalEd25519Signer {
+    pub fn from_bytes(secret: &[u8; 32]) -> Self {
+        Self {
+            signing_key: Ed25519SigningKey::from_bytes(secret),
+        }
+    }
+}
+
+impl Ed25519Signer for LocalEd25519Signer {
+    fn public_key(&self) -> [u8; 32] {
+        self.signing_key.verifying_key().to_bytes()
+    }
+
+    fn sign(&self, payload: &[u8; 32]) -> Result<[u8; 64], ClientError> {
+        Ok(self.signing_key.sign(payload).to_bytes())
+    }
+}
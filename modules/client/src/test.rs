@@ -0,0 +1,836 @@
+use channel_auth_contract::contract::{
+    ChannelAuthContract, ChannelAuthContractArgs, ChannelAuthContractClient,
+};
+use moonlight_helpers::testutils::snapshot::{get_env_with_g_accounts, get_snapshot_g_accounts};
+use privacy_channel::contract::{
+    PrivacyChannelContract, PrivacyChannelContractArgs, PrivacyChannelContractClient,
+};
+use soroban_sdk::{
+    testutils::Address as _,
+    vec,
+    xdr::{ScAddress, ScBytes, ScVal, SorobanAuthorizedFunction},
+    Address, BytesN, Env, FromVal, String,
+};
+use token_contract::{TestToken as Token, TestTokenClient as TokenClient};
+
+use crate::{
+    BundleShape, ChannelBatchBuilder, ChannelContext, ChannelOperationBuilder, ClientError,
+    Condition, CostEstimate, CostModel, ExtendedKey, LocalEd25519Signer, LocalP256Signer,
+    P256Signer, ResourceLimits, UtxoKeychain,
+};
+
+fn create_contracts(
+    e: &Env,
+) -> (
+    PrivacyChannelContractClient<'_>,
+    ChannelAuthContractClient<'_>,
+    TokenClient<'_>,
+) {
+    let admin = Address::generate(e);
+    let auth_id = e.register(
+        ChannelAuthContract,
+        ChannelAuthContractArgs::__constructor(&admin),
+    );
+    let token_id = e.register(
+        Token,
+        (
+            admin.clone(),
+            7_u32,
+            String::from_val(e, &"Moon Token"),
+            String::from_val(e, &"MOON"),
+        ),
+    );
+
+    e.mock_all_auths();
+    let channel_id = e.register(
+        PrivacyChannelContract,
+        PrivacyChannelContractArgs::__constructor(&admin, &auth_id, &token_id),
+    );
+    let auth = ChannelAuthContractClient::new(e, &auth_id);
+    auth.add_provider(&provider_address(e));
+    TokenClient::new(e, &token_id).mint(&depositor_address(e), &1_000_i128);
+    e.set_auths(&[]);
+
+    (
+        PrivacyChannelContractClient::new(e, &channel_id),
+        auth,
+        TokenClient::new(e, &token_id),
+    )
+}
+
+fn provider_address(e: &Env) -> Address {
+    get_snapshot_g_accounts(e).0.address
+}
+
+fn depositor_address(e: &Env) -> Address {
+    get_snapshot_g_accounts(e).1.address
+}
+
+fn signers(e: &Env) -> (LocalEd25519Signer, LocalEd25519Signer) {
+    let (provider, depositor, _, _, _) = get_snapshot_g_accounts(e);
+    (
+        LocalEd25519Signer::from_bytes(&provider.signing_key.to_bytes()),
+        LocalEd25519Signer::from_bytes(&depositor.signing_key.to_bytes()),
+    )
+}
+
+fn utxo(e: &Env, signer: &LocalP256Signer) -> BytesN<65> {
+    BytesN::from_array(e, &signer.public_key())
+}
+
+fn builder(
+    e: &Env,
+    channel: &PrivacyChannelContractClient,
+    auth: &ChannelAuthContractClient,
+    token: &TokenClient,
+) -> ChannelOperationBuilder {
+    ChannelOperationBuilder::new(
+        e,
+        ChannelContext {
+            network_id: e.ledger().network_id(),
+            channel: channel.address.clone(),
+            auth: auth.address.clone(),
+            asset: token.address.clone(),
+        },
+    )
+}
+
+#[test]
+fn deposit_and_transfer_with_client_signers() {
+    let e = get_env_with_g_accounts();
+    let (channel, auth, token) = create_contracts(&e);
+    let (provider, depositor) = signers(&e);
+    let owner_a = LocalP256Signer::from_bytes(&[1; 32]).unwrap();
+    let (utxo_a, utxo_b, utxo_c) = (
+        utxo(&e, &owner_a),
+        utxo(&e, &LocalP256Signer::from_bytes(&[2; 32]).unwrap()),
+        utxo(&e, &LocalP256Signer::from_bytes(&[3; 32]).unwrap()),
+    );
+    let live_until_ledger = e.ledger().sequence() + 100;
+
+    let mut deposit = builder(&e, &channel, &auth, &token);
+    deposit
+        .add_deposit(
+            &e,
+            depositor_address(&e),
+            500,
+            vec![&e, Condition::Create(utxo_a.clone(), 500)],
+        )
+        .unwrap();
+    deposit.add_create(utxo_a.clone(), 500).unwrap();
+    deposit
+        .sign_provider(&e, &provider, 0, live_until_ledger)
+        .unwrap();
+    deposit
+        .sign_deposit(&e, &depositor, 0, live_until_ledger)
+        .unwrap();
+
+    channel
+        .set_auths(&deposit.auth_entries(&e, 0, live_until_ledger).unwrap())
+        .transact(&deposit.operation());
+
+    assert_eq!(channel.utxo_balance(&utxo_a), 500);
+    assert_eq!(token.balance(&depositor_address(&e)), 500);
+
+    let mut transfer = builder(&e, &channel, &auth, &token);
+    transfer.add_create(utxo_b.clone(), 300).unwrap();
+    transfer.add_create(utxo_c.clone(), 200).unwrap();
+    transfer
+        .add_spend(
+            utxo_a.clone(),
+            vec![
+                &e,
+                Condition::Create(utxo_b.clone(), 300),
+                Condition::Create(utxo_c.clone(), 200),
+            ],
+        )
+        .unwrap();
+    transfer
+        .sign_spend(&e, &owner_a, live_until_ledger)
+        .unwrap();
+    transfer
+        .sign_provider(&e, &provider, 1, live_until_ledger)
+        .unwrap();
+
+    channel
+        .set_auths(&transfer.auth_entries(&e, 1, live_until_ledger).unwrap())
+        .transact(&transfer.operation());
+
+    assert_eq!(channel.utxo_balance(&utxo_a), 0);
+    assert_eq!(channel.utxo_balance(&utxo_b), 300);
+    assert_eq!(channel.utxo_balance(&utxo_c), 200);
+}
+
+#[test]
+fn allowance_deposit_signs_only_its_conditions() {
+    let e = get_env_with_g_accounts();
+    let (channel, auth, token) = create_contracts(&e);
+    let (provider, depositor) = signers(&e);
+    let utxo_a = utxo(&e, &LocalP256Signer::from_bytes(&[1; 32]).unwrap());
+    let live_until_ledger = e.ledger().sequence() + 100;
+    token.mock_all_auths().approve(
+        &depositor_address(&e),
+        &channel.address,
+        &500,
+        &live_until_ledger,
+    );
+
+    let mut deposit = builder(&e, &channel, &auth, &token);
+    assert_eq!(
+        deposit.add_allowance_deposit(
+            &e,
+            depositor_address(&e),
+            500,
+            vec![&e, Condition::Create(utxo_a.clone(), 500)],
+        ),
+        Err(ClientError::MissingDepositCondition(depositor_address(&e)))
+    );
+    deposit
+        .add_allowance_deposit(
+            &e,
+            depositor_address(&e),
+            500,
+            vec![
+                &e,
+                Condition::ExtDeposit(depositor_address(&e), 500),
+                Condition::Create(utxo_a.clone(), 500),
+            ],
+        )
+        .unwrap();
+    deposit.add_create(utxo_a.clone(), 500).unwrap();
+    deposit
+        .sign_provider(&e, &provider, 0, live_until_ledger)
+        .unwrap();
+    deposit
+        .sign_deposit(&e, &depositor, 0, live_until_ledger)
+        .unwrap();
+
+    let entry = deposit
+        .deposit_auth_entry(&e, &depositor_address(&e), 0, live_until_ledger)
+        .unwrap();
+    assert!(entry.root_invocation.sub_invocations.is_empty());
+
+    channel
+        .set_auths(&deposit.auth_entries(&e, 0, live_until_ledger).unwrap())
+        .transact(&deposit.operation());
+
+    assert_eq!(channel.utxo_balance(&utxo_a), 500);
+    assert_eq!(token.balance(&depositor_address(&e)), 500);
+    assert_eq!(token.allowance(&depositor_address(&e), &channel.address), 0);
+}
+
+#[test]
+fn rejects_entries_the_contract_would_reject() {
+    let e = Env::default();
+    let (channel, auth, token) = create_contracts(&e);
+    let (a, b) = (
+        utxo(&e, &LocalP256Signer::from_bytes(&[1; 32]).unwrap()),
+        utxo(&e, &LocalP256Signer::from_bytes(&[2; 32]).unwrap()),
+    );
+    let account = Address::generate(&e);
+    let mut op = builder(&e, &channel, &auth, &token);
+
+    op.add_spend(a.clone(), vec![&e, Condition::Create(b.clone(), 10)])
+        .unwrap();
+    assert_eq!(
+        op.add_spend(a.clone(), vec![&e]),
+        Err(ClientError::RepeatedSpend(a.clone()))
+    );
+    assert_eq!(
+        op.add_create(b.clone(), 20),
+        Err(ClientError::ConflictingConditions)
+    );
+    assert_eq!(
+        op.add_create(b.clone(), 0),
+        Err(ClientError::InvalidAmount(0))
+    );
+
+    op.add_withdraw(&e, account.clone(), 10, vec![&e]).unwrap();
+    assert_eq!(
+        op.add_deposit(
+            &e,
+            account.clone(),
+            10,
+            vec![&e, Condition::Create(b.clone(), 10)]
+        ),
+        Err(ClientError::AccountConditionsMismatch(account.clone()))
+    );
+
+    // A rejected entry leaves the operation unchanged.
+    assert_eq!(op.operation().spend.len(), 1);
+    assert_eq!(op.operation().create.len(), 0);
+    assert_eq!(op.operation().deposit.len(), 0);
+}
+
+#[test]
+fn auth_entries_require_every_signature() {
+    let e = Env::default();
+    let (channel, auth, token) = create_contracts(&e);
+    let (provider, depositor) = signers(&e);
+    let owner = LocalP256Signer::from_bytes(&[1; 32]).unwrap();
+    let a = utxo(&e, &owner);
+
+    let mut op = builder(&e, &channel, &auth, &token);
+    op.add_spend(a.clone(), vec![&e]).unwrap();
+
+    assert_eq!(
+        op.auth_entry(&e, 0, 100),
+        Err(ClientError::MissingSpendSignature(a.clone()))
+    );
+
+    op.sign_spend(&e, &owner, 100).unwrap();
+    assert_eq!(
+        op.auth_entry(&e, 0, 100),
+        Err(ClientError::MissingProviderSignature)
+    );
+    assert_eq!(
+        op.sign_spend(&e, &owner, 100),
+        Err(ClientError::SignatureAlreadyAdded)
+    );
+
+    op.sign_provider(&e, &provider, 0, 100).unwrap();
+    assert!(op.auth_entry(&e, 0, 100).is_ok());
+
+    // Depositors can only sign for deposits in the operation.
+    assert_eq!(
+        op.sign_deposit(&e, &depositor, 0, 100),
+        Err(ClientError::UnknownDepositor(depositor_address(&e)))
+    );
+}
+
+#[test]
+fn adding_an_entry_drops_the_provider_signature() {
+    let e = Env::default();
+    let (channel, auth, token) = create_contracts(&e);
+    let (provider, _) = signers(&e);
+    let owner = LocalP256Signer::from_bytes(&[1; 32]).unwrap();
+    let (a, b) = (
+        utxo(&e, &owner),
+        utxo(&e, &LocalP256Signer::from_bytes(&[2; 32]).unwrap()),
+    );
+
+    let mut op = builder(&e, &channel, &auth, &token);
+    op.add_spend(a.clone(), vec![&e]).unwrap();
+    op.sign_spend(&e, &owner, 100).unwrap();
+    op.sign_provider(&e, &provider, 0, 100).unwrap();
+    assert!(op.auth_entry(&e, 0, 100).is_ok());
+
+    // The provider signed a bundle without this create.
+    op.add_create(b, 10).unwrap();
+    assert_eq!(
+        op.auth_entry(&e, 0, 100),
+        Err(ClientError::MissingProviderSignature)
+    );
+
+    // Spend signatures cover only their own conditions and are kept.
+    op.sign_provider(&e, &provider, 0, 100).unwrap();
+    assert!(op.auth_entry(&e, 0, 100).is_ok());
+}
+
+#[test]
+fn batch_builder_authorizes_every_operation_in_one_entry() {
+    let e = get_env_with_g_accounts();
+    let (channel, auth, token) = create_contracts(&e);
+    let (provider, depositor) = signers(&e);
+    let owners = [
+        LocalP256Signer::from_bytes(&[1; 32]).unwrap(),
+        LocalP256Signer::from_bytes(&[2; 32]).unwrap(),
+    ];
+    let (x, y) = (
+        utxo(&e, &LocalP256Signer::from_bytes(&[3; 32]).unwrap()),
+        utxo(&e, &LocalP256Signer::from_bytes(&[4; 32]).unwrap()),
+    );
+    let live_until_ledger = e.ledger().sequence() + 100;
+
+    let mut deposit = builder(&e, &channel, &auth, &token);
+    deposit
+        .add_deposit(&e, depositor_address(&e), 200, vec![&e])
+        .unwrap();
+    for owner in owners.iter() {
+        deposit.add_create(utxo(&e, owner), 100).unwrap();
+    }
+    deposit
+        .sign_provider(&e, &provider, 0, live_until_ledger)
+        .unwrap();
+    deposit
+        .sign_deposit(&e, &depositor, 0, live_until_ledger)
+        .unwrap();
+    channel
+        .set_auths(&deposit.auth_entries(&e, 0, live_until_ledger).unwrap())
+        .transact(&deposit.operation());
+
+    let context = builder(&e, &channel, &auth, &token).context().clone();
+    let mut batch = ChannelBatchBuilder::new(&e, context);
+    for (owner, to) in owners.iter().zip([&x, &y]) {
+        let mut op = batch.operation_builder(&e);
+        op.add_create(to.clone(), 100).unwrap();
+        op.add_spend(
+            utxo(&e, owner),
+            vec![&e, Condition::Create(to.clone(), 100)],
+        )
+        .unwrap();
+        assert_eq!(
+            batch.push(op.clone()).err(),
+            Some(ClientError::MissingSpendSignature(utxo(&e, owner)))
+        );
+
+        op.sign_spend(&e, owner, live_until_ledger).unwrap();
+        batch.push(op.clone()).unwrap();
+        assert_eq!(
+            batch.push(op).err(),
+            Some(ClientError::RepeatedSpend(utxo(&e, owner)))
+        );
+    }
+    assert_eq!(
+        batch.push(builder(&e, &channel, &auth, &token)).err(),
+        Some(ClientError::NotBatched)
+    );
+
+    batch
+        .sign_provider(&e, &provider, 1, live_until_ledger)
+        .unwrap();
+    channel
+        .set_auths(&batch.auth_entries(&e, 1, live_until_ledger).unwrap())
+        .transact_batch(&batch.operations(&e));
+
+    assert_eq!(channel.utxo_balance(&x), 100);
+    assert_eq!(channel.utxo_balance(&y), 100);
+}
+
+#[test]
+fn contract_provider_authorizes_in_its_own_entry() {
+    let e = Env::default();
+    let (channel, auth, token) = create_contracts(&e);
+    let (provider, _) = signers(&e);
+    let wallet = Address::generate(&e);
+
+    let mut op = builder(&e, &channel, &auth, &token);
+    op.add_create(BytesN::from_array(&e, &[4; 65]), 10).unwrap();
+
+    assert_eq!(
+        op.add_provider_contract(&provider_address(&e), 100),
+        Err(ClientError::NotContract(provider_address(&e)))
+    );
+    op.add_provider_contract(&wallet, 100).unwrap();
+    assert_eq!(
+        op.sign_provider(&e, &provider, 0, 100),
+        Err(ClientError::SignatureAlreadyAdded)
+    );
+    assert!(op.auth_entry(&e, 0, 100).is_ok());
+
+    let invocation = op.provider_invocation(&e, 0, 100).unwrap();
+    let SorobanAuthorizedFunction::ContractFn(function) = invocation.function else {
+        panic!("provider invocation is not a contract call");
+    };
+    assert_eq!(function.contract_address, ScAddress::from(&auth.address));
+    assert_eq!(
+        function.function_name.to_utf8_string().unwrap(),
+        "__check_auth"
+    );
+    assert_eq!(
+        function.args.to_vec(),
+        std::vec![ScVal::Bytes(ScBytes(
+            op.bundle_payload_hash(&e, 0, 100)
+                .unwrap()
+                .to_array()
+                .try_into()
+                .unwrap()
+        ))]
+    );
+}
+
+#[test]
+fn context_derives_network_id_from_passphrase() {
+    let e = Env::default();
+    let context = ChannelContext::new(
+        &e,
+        "Test SDF Network ; September 2015",
+        Address::generate(&e),
+        Address::generate(&e),
+        Address::generate(&e),
+    );
+
+    let mut expected = [0u8; 32];
+    let hex = "cee0302d59844d32bdca915c8203dd44b33fbb7edc19051ea37abedf28ecd472";
+    for (i, byte) in expected.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).unwrap();
+    }
+    assert_eq!(context.network_id, BytesN::from_array(&e, &expected));
+}
+
+fn hex32(value: &str) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&value[i * 2..i * 2 + 2], 16).unwrap();
+    }
+    bytes
+}
+
+#[test]
+fn hd_derivation_matches_slip10_nist256p1_vectors() {
+    let seed = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f,
+    ];
+    let master = ExtendedKey::master(&seed).unwrap();
+    assert_eq!(
+        master.chain_code(),
+        hex32("beeb672fe4621673f722f38529c07392fecaa61015c80c34f29ce8b41b3cb6ea")
+    );
+    assert_eq!(
+        master.secret_key(),
+        hex32("612091aaa12e22dd2abef664f8a01a82cae99ad7441b7ef8110424915c268bc2")
+    );
+
+    let child = master.derive(0).unwrap();
+    assert_eq!(
+        child.chain_code(),
+        hex32("3460cea53e6a6bb5fb391eeef3237ffd8724bf0a40e94943c98b83825342ee11")
+    );
+    assert_eq!(
+        child.secret_key(),
+        hex32("6939694369114c67917a182c59ddb8cafc3004e63ca5d3b84403ba8613debc0c")
+    );
+
+    // SLIP-0010 "derivation retry" vector: IL >= n on the first attempt.
+    let retry = master.derive(28578).unwrap();
+    assert_eq!(
+        retry.chain_code(),
+        hex32("e94c8ebe30c2250a14713212f6449b20f3329105ea15b652ca5bdfc68f6c65c2")
+    );
+    assert_eq!(
+        retry.secret_key(),
+        hex32("06f0db126f023755d0b8d86d4591718a5210dd8d024e3e14b6159d63f53aa669")
+    );
+}
+
+/// Moonlight UTXO path vectors, `m/44'/148'/<account>'/0'/<index>'` from a seed of 32 `0x4d`
+/// bytes. Other SDKs must reproduce these.
+#[test]
+fn hd_derivation_matches_utxo_path_vectors() {
+    let vectors = [
+        (
+            0,
+            "94829e6669619aff23720b047b92de3ca5954ffd89946c47ec54c51e672ddc9c",
+            "042c0df0eb4b226ead4305d7de58d69595768638e17be6eb79b5378697dd35f064c880f06b21cf7bcf54456215378d67ac1e9c0f426397deca0ef532c1a0e93f48",
+        ),
+        (
+            1,
+            "eeb6754d4ad63514d7ebd86aca0972d7a4880974eb58484c8cbb79098a0065a3",
+            "04c18cc058d0bb9901387d3572b3304e8a8ea827798c964737ff24e19a47afaed483e5adab98e556e4ed8f71d193e0ad1d1507d2538c9f2bf15d8d19afbf6d19d2",
+        ),
+        (
+            2,
+            "49209a66e1bd11ea248972436f0240052c80e9c495d7001502574d1f1f52d8d4",
+            "0489d3eb0604d458f27101bb987934aa67a5a6026e519152d12e2d9ab4495b021d5653f2cd4f35463680660ad9ae8f176e000c9530b67a1438feb710bbea197ec7",
+        ),
+    ];
+
+    let keychain = UtxoKeychain::new(&[0x4d; 32], 0).unwrap();
+    for (index, secret_key, public_key) in vectors {
+        assert_eq!(
+            hex_string(&keychain.key(index).unwrap().secret_key()),
+            secret_key
+        );
+        assert_eq!(hex_string(&keychain.public_key(index).unwrap()), public_key);
+    }
+
+    assert_eq!(
+        hex_string(
+            &UtxoKeychain::new(&[0x4d; 32], 1)
+                .unwrap()
+                .key(0)
+                .unwrap()
+                .secret_key()
+        ),
+        "20f863dc50abe72c792f4ee296f1f2c32e076c868e8e71a7476400ca53479164"
+    );
+}
+
+#[test]
+fn hd_rejects_invalid_inputs() {
+    assert!(matches!(
+        ExtendedKey::master(&[0; 15]),
+        Err(ClientError::InvalidSeed)
+    ));
+    assert!(matches!(
+        UtxoKeychain::new(&[0; 32], 1 << 31),
+        Err(ClientError::InvalidIndex(_))
+    ));
+
+    let keychain = UtxoKeychain::new(&[0; 32], 0).unwrap();
+    assert_eq!(
+        keychain.scan(0, |_, batch| Ok::<_, ClientError>(
+            std::vec![false; batch.len()]
+        )),
+        Err(ClientError::InvalidGapLimit)
+    );
+    assert_eq!(
+        keychain.recover(5, |_| Ok::<_, ClientError>(std::vec![-1])),
+        Err(ClientError::BatchSizeMismatch)
+    );
+}
+
+#[test]
+fn hd_recovery_rediscovers_utxos_from_utxo_balances() {
+    let e = Env::default();
+    let (channel, _, token) = create_contracts(&e);
+    let keychain = UtxoKeychain::new(&[7; 32], 0).unwrap();
+    let key = |index| BytesN::from_array(&e, &keychain.public_key(index).unwrap());
+
+    // Indices 0 and 2 hold funds, 5 is spent into 2; 1, 3 and 4 were never used.
+    e.mock_all_auths();
+    let depositor = Address::generate(&e);
+    token.mint(&depositor, &1_000);
+    let mut op = builder(&e, &channel, &channel_auth(&e, &channel), &token);
+    op.add_deposit(&e, depositor, 1_000, vec![&e]).unwrap();
+    op.add_create(key(0), 400).unwrap();
+    op.add_create(key(5), 600).unwrap();
+    channel.transact(&op.operation());
+
+    let mut op = builder(&e, &channel, &channel_auth(&e, &channel), &token);
+    op.add_spend(key(5), vec![&e]).unwrap();
+    op.add_create(key(2), 600).unwrap();
+    channel.transact(&op.operation());
+
+    let mut queries = 0;
+    let recovery = keychain
+        .recover(3, |batch| {
+            queries += 1;
+            let mut keys = soroban_sdk::Vec::new(&e);
+            for public_key in batch {
+                keys.push_back(BytesN::from_array(&e, public_key));
+            }
+            Ok::<_, ClientError>(channel.utxo_balances(&keys).iter().collect())
+        })
+        .unwrap();
+
+    let found: std::vec::Vec<(u32, i128)> = recovery
+        .utxos
+        .iter()
+        .map(|utxo| (utxo.index, utxo.balance))
+        .collect();
+    assert_eq!(found, std::vec![(0, 400), (2, 600), (5, 0)]);
+    assert_eq!(recovery.next_index, 6);
+    // Batches [0, 3), [3, 6) and [6, 9); the last one closes a gap of three.
+    assert_eq!(queries, 3);
+}
+
+/// Runs a signed `transact` with `spends` funded UTXOs, `deposits` G-account depositors (at most
+/// four), and `creates` and `withdrawals` splitting the total. Every output is a condition of a
+/// spend, or of a deposit when there are no spends. Returns the shape and the measured resources.
+fn measure_transact(
+    spends: u32,
+    creates: u32,
+    deposits: u32,
+    withdrawals: u32,
+) -> (BundleShape, CostEstimate) {
+    let e = get_env_with_g_accounts();
+    let (channel, auth, token) = create_contracts(&e);
+    let (provider, _) = signers(&e);
+    let (_, a, b, c, d) = get_snapshot_g_accounts(&e);
+    let depositors = [a, b, c, d];
+    let owners: std::vec::Vec<LocalP256Signer> = (0..spends)
+        .map(|i| LocalP256Signer::from_bytes(&[10 + i as u8; 32]).unwrap())
+        .collect();
+    let live_until_ledger = e.ledger().sequence() + 100;
+
+    e.mock_all_auths();
+    for depositor in depositors.iter() {
+        token.mint(&depositor.address, &1_000);
+    }
+    if spends > 0 {
+        let funder = Address::generate(&e);
+        token.mint(&funder, &(1_000 * spends as i128));
+        let mut funding = builder(&e, &channel, &auth, &token);
+        funding
+            .add_deposit(&e, funder, 1_000 * spends as i128, vec![&e])
+            .unwrap();
+        for owner in owners.iter() {
+            funding.add_create(utxo(&e, owner), 1_000).unwrap();
+        }
+        channel.transact(&funding.operation());
+    }
+    e.set_auths(&[]);
+
+    let total = 1_000 * (spends + deposits) as i128;
+    let outputs = (creates + withdrawals) as i128;
+    let amount = |i: u32| {
+        let share = total / outputs;
+        if i == 0 {
+            total - share * (outputs - 1)
+        } else {
+            share
+        }
+    };
+
+    let mut op = builder(&e, &channel, &auth, &token);
+    let mut conditions = std::vec::Vec::new();
+    for i in 0..creates {
+        let key = utxo(
+            &e,
+            &LocalP256Signer::from_bytes(&[100 + i as u8; 32]).unwrap(),
+        );
+        op.add_create(key.clone(), amount(i)).unwrap();
+        conditions.push(Condition::Create(key, amount(i)));
+    }
+    for i in 0..withdrawals {
+        let to = Address::generate(&e);
+        let value = amount(creates + i);
+        op.add_withdraw(&e, to.clone(), value, vec![&e]).unwrap();
+        conditions.push(Condition::ExtWithdraw(to, value));
+    }
+
+    let holders = spends.max(deposits) as usize;
+    let mut held: std::vec::Vec<soroban_sdk::Vec<Condition>> =
+        (0..holders).map(|_| vec![&e]).collect();
+    for (i, condition) in conditions.into_iter().enumerate() {
+        held[i % holders].push_back(condition);
+    }
+    for (owner, conditions) in owners.iter().zip(held.iter()) {
+        op.add_spend(utxo(&e, owner), conditions.clone()).unwrap();
+    }
+    for (i, depositor) in depositors.iter().take(deposits as usize).enumerate() {
+        let conditions = if spends == 0 {
+            held[i].clone()
+        } else {
+            vec![&e]
+        };
+        op.add_deposit(&e, depositor.address.clone(), 1_000, conditions)
+            .unwrap();
+    }
+
+    for owner in owners.iter() {
+        op.sign_spend(&e, owner, live_until_ledger).unwrap();
+    }
+    for depositor in depositors.iter().take(deposits as usize) {
+        let signer = LocalEd25519Signer::from_bytes(&depositor.signing_key.to_bytes());
+        op.sign_deposit(&e, &signer, 0, live_until_ledger).unwrap();
+    }
+    op.sign_provider(&e, &provider, 0, live_until_ledger)
+        .unwrap();
+
+    channel
+        .set_auths(&op.auth_entries(&e, 0, live_until_ledger).unwrap())
+        .transact(&op.operation());
+
+    let resources = e.cost_estimate().resources();
+    let measured = CostEstimate {
+        instructions: resources.instructions as u64,
+        memory_bytes: resources.mem_bytes as u64,
+        read_entries: (resources.memory_read_entries + resources.disk_read_entries).into(),
+        write_entries: resources.write_entries.into(),
+        event_bytes: resources.contract_events_size_bytes.into(),
+    };
+    (BundleShape::of(&op.operation()), measured)
+}
+
+#[test]
+fn cost_model_bounds_measured_bundles() {
+    for (spends, creates, deposits, withdrawals) in
+        [(1, 1, 0, 0), (3, 2, 0, 2), (2, 6, 0, 0), (0, 3, 3, 0)]
+    {
+        let (shape, measured) = measure_transact(spends, creates, deposits, withdrawals);
+        let estimate = CostModel::NATIVE_ESTIMATE.estimate(&shape);
+
+        assert!(estimate.instructions >= measured.instructions, "{shape:?}");
+        assert!(estimate.memory_bytes >= measured.memory_bytes, "{shape:?}");
+        assert!(estimate.read_entries >= measured.read_entries, "{shape:?}");
+        assert!(
+            estimate.write_entries >= measured.write_entries,
+            "{shape:?}"
+        );
+        assert!(estimate.event_bytes >= measured.event_bytes, "{shape:?}");
+        // Within 10% on CPU and memory: the model is a bound, not a guess.
+        assert!(
+            estimate.instructions * 10 <= measured.instructions * 11,
+            "{shape:?}"
+        );
+        assert!(
+            estimate.memory_bytes * 10 <= measured.memory_bytes * 11,
+            "{shape:?}"
+        );
+    }
+}
+
+#[test]
+fn max_bundle_size_runs_under_mainnet_limits() {
+    let transfer = BundleShape {
+        spends: 1,
+        creates: 1,
+        conditions: 1,
+        ..BundleShape::default()
+    };
+    let model = CostModel::NATIVE_ESTIMATE;
+    let max = model.max_bundle_size(&transfer, &ResourceLimits::MAINNET);
+
+    // The footprint, not CPU, bounds one-to-one transfers.
+    assert_eq!(max, 11);
+    assert!(!model
+        .estimate(&transfer.scaled(max + 1))
+        .fits(&ResourceLimits::MAINNET));
+    assert_eq!(
+        model.max_bundle_size(&BundleShape::default(), &ResourceLimits::MAINNET),
+        0
+    );
+
+    // The test environment enforces the Mainnet limits, so this fails if the estimate is too low
+    // for the native contracts.
+    let (shape, measured) = measure_transact(max, max, 0, 0);
+    assert_eq!(shape, transfer.scaled(max));
+    assert!(measured.fits(&ResourceLimits::MAINNET));
+}
+
+#[test]
+fn max_bundle_size_is_bounded_without_per_item_costs() {
+    let base = CostModel::NATIVE_ESTIMATE.base;
+    let free = CostModel {
+        base,
+        per_spend: CostEstimate::default(),
+        per_create: CostEstimate::default(),
+        per_deposit: CostEstimate::default(),
+        per_withdrawal: CostEstimate::default(),
+        per_condition: CostEstimate::default(),
+        per_entry_pair: CostEstimate::default(),
+    };
+    let transfer = BundleShape {
+        spends: 1,
+        creates: 1,
+        ..BundleShape::default()
+    };
+
+    assert_eq!(
+        free.max_bundle_size(&transfer, &ResourceLimits::MAINNET),
+        u32::MAX
+    );
+
+    // Only the pairwise term grows: 1000² × 100 instructions is the last size under 10⁸.
+    let pairwise = CostModel {
+        per_entry_pair: CostEstimate {
+            instructions: 100,
+            ..CostEstimate::default()
+        },
+        base: CostEstimate::default(),
+        ..free
+    };
+    let limits = ResourceLimits {
+        instructions: 100_000_000,
+        ..ResourceLimits::MAINNET
+    };
+    let single = BundleShape {
+        spends: 1,
+        ..BundleShape::default()
+    };
+    assert_eq!(pairwise.max_bundle_size(&single, &limits), 1000);
+}
+
+fn channel_auth<'a>(
+    e: &'a Env,
+    channel: &PrivacyChannelContractClient,
+) -> ChannelAuthContractClient<'a> {
+    ChannelAuthContractClient::new(e, &channel.auth())
+}
+
+fn hex_string(bytes: &[u8]) -> std::string::String {
+    bytes.iter().map(|b| std::format!("{b:02x}")).collect()
+}
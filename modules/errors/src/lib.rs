@@ -13,6 +13,10 @@ pub const CHANNEL_ERROR_RANGE_START: u32 = 3_000;
 pub const CHANNEL_ERROR_RANGE_END: u32 = 3_099;
 pub const HELPER_ERROR_RANGE_START: u32 = 4_000;
 pub const HELPER_ERROR_RANGE_END: u32 = 4_099;
+pub const FACTORY_ERROR_RANGE_START: u32 = 5_000;
+pub const FACTORY_ERROR_RANGE_END: u32 = 5_099;
+pub const MIGRATION_ERROR_RANGE_START: u32 = 6_000;
+pub const MIGRATION_ERROR_RANGE_END: u32 = 6_099;
 
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
@@ -47,6 +51,8 @@ pub enum MoonlightError {
     ProviderAlreadyRegistered = 1_012,
     /// The provider account is not registered.
     ProviderNotRegistered = 1_013,
+    /// The channel is already registered under a different asset.
+    ChannelAssetMismatch = 1_014,
 
     // UTXO Module errors: 2000-2099.
     /// A UTXO creation attempted to write an output identifier that already exists.
@@ -67,6 +73,10 @@ pub enum MoonlightError {
     UtxoNotFound = 2_007,
     /// The UTXO module cannot authorize transactions because no authorization contract is configured.
     AuthContractNotSet = 2_008,
+    /// A UTXO TTL policy must extend entries beyond its threshold and within the network's maximum TTL.
+    InvalidTtlPolicy = 2_009,
+    /// A UTXO's storage entry is archived and must be restored before the UTXO can be used.
+    UtxoArchived = 2_010,
 
     // Privacy channel errors: 3000-3099.
     /// The same account appears more than once in the deposit list.
@@ -88,12 +98,65 @@ pub enum MoonlightError {
     InvalidExternalAmount = 3_007,
     /// `transact` was re-entered while a call was already in progress.
     ReentrantCall = 3_008,
+    /// A bundle would raise the channel supply above its configured maximum.
+    SupplyCeilingExceeded = 3_009,
+    /// A deposit amount is above the channel's per-deposit maximum.
+    DepositAboveLimit = 3_010,
+    /// A withdraw amount is above the channel's per-withdraw maximum.
+    WithdrawAboveLimit = 3_011,
+    /// A UTXO creation amount is below the channel's minimum.
+    CreateBelowMinimum = 3_012,
+    /// The bundle spends more UTXOs than the channel allows.
+    TooManySpends = 3_013,
+    /// The bundle creates more UTXOs than the channel allows.
+    TooManyCreates = 3_014,
+    /// A channel limit must be strictly positive.
+    InvalidChannelLimits = 3_015,
+    /// The channel's outflow circuit breaker has tripped; it must be reset before `transact`.
+    ChannelPaused = 3_016,
+    /// A circuit breaker needs a non-zero window and a strictly positive outflow threshold.
+    InvalidCircuitBreaker = 3_017,
+    /// Only the admin or the guardian may reset the circuit breaker.
+    NotAdminOrGuardian = 3_018,
+    /// The channel's compliance policy rejected a deposit or withdrawal leg.
+    ComplianceCheckFailed = 3_019,
+    /// A note is attached to a UTXO the bundle does not create.
+    NoteWithoutCreate = 3_020,
+    /// A stealth ephemeral key is attached to a UTXO the bundle does not create.
+    EphemeralKeyWithoutCreate = 3_021,
+    /// The UTXO has no recovery guardian.
+    RecoveryNotSet = 3_022,
+    /// A recovery of the UTXO is already pending.
+    RecoveryPending = 3_023,
+    /// The UTXO has no pending recovery.
+    RecoveryNotPending = 3_024,
+    /// The pending recovery's delay has not elapsed yet.
+    RecoveryNotReady = 3_025,
+    /// A recovery condition has a zero delay or is not signed together with its UTXO's create by
+    /// every signer of that create, a recovery spend is empty, or a cancel condition is in a bundle.
+    InvalidRecoveryCondition = 3_026,
+    /// `transact_batch` was called without operations.
+    EmptyBatch = 3_027,
+    /// An address in `allowance_deposits` does not deposit in the operation.
+    AllowanceWithoutDeposit = 3_028,
 
     // Helper errors: 4000-4099.
     /// An address payload was expected to be an Ed25519 account address but was not.
     NotEd25519AccountAddress = 4_000,
     /// The address payload type is not supported by this helper.
     UnsupportedAddressPayload = 4_001,
+
+    // Channel factory errors: 5000-5099.
+    /// The factory already deployed a channel for this asset under this Channel Auth.
+    ChannelAlreadyDeployed = 5_000,
+
+    // Migration errors: 6000-6099.
+    /// Storage is already at the schema version of the running code; there is nothing to migrate.
+    AlreadyMigrated = 6_000,
+    /// The `migrate` parameters do not decode to what a migration step expects.
+    InvalidMigrationParams = 6_001,
+    /// No migration step exists from the stored schema version.
+    MissingMigrationStep = 6_002,
 }
 
 pub use MoonlightError as Error;
@@ -22,6 +22,7 @@ fn keeps_auth_errors_in_their_reserved_range() {
         Error::ProviderThresholdNotMet.code(),
         Error::ProviderAlreadyRegistered.code(),
         Error::ProviderNotRegistered.code(),
+        Error::ChannelAssetMismatch.code(),
     ] {
         assert!((1_000..=1_099).contains(&code));
     }
@@ -39,6 +40,8 @@ fn keeps_utxo_errors_in_their_reserved_range() {
         Error::RepeatedSpendUtxo.code(),
         Error::UtxoNotFound.code(),
         Error::AuthContractNotSet.code(),
+        Error::InvalidTtlPolicy.code(),
+        Error::UtxoArchived.code(),
     ] {
         assert!((2_000..=2_099).contains(&code));
     }
@@ -53,6 +56,26 @@ fn keeps_channel_errors_in_their_reserved_range() {
         Error::AmountOverflow.code(),
         Error::BundleHasConflictingConditions.code(),
         Error::AmountUnderflow.code(),
+        Error::SupplyCeilingExceeded.code(),
+        Error::DepositAboveLimit.code(),
+        Error::WithdrawAboveLimit.code(),
+        Error::CreateBelowMinimum.code(),
+        Error::TooManySpends.code(),
+        Error::TooManyCreates.code(),
+        Error::InvalidChannelLimits.code(),
+        Error::ChannelPaused.code(),
+        Error::InvalidCircuitBreaker.code(),
+        Error::NotAdminOrGuardian.code(),
+        Error::ComplianceCheckFailed.code(),
+        Error::NoteWithoutCreate.code(),
+        Error::EphemeralKeyWithoutCreate.code(),
+        Error::RecoveryNotSet.code(),
+        Error::RecoveryPending.code(),
+        Error::RecoveryNotPending.code(),
+        Error::RecoveryNotReady.code(),
+        Error::InvalidRecoveryCondition.code(),
+        Error::EmptyBatch.code(),
+        Error::AllowanceWithoutDeposit.code(),
     ] {
         assert!((3_000..=3_099).contains(&code));
     }
@@ -67,3 +90,19 @@ fn keeps_helper_errors_in_their_reserved_range() {
         assert!((4_000..=4_099).contains(&code));
     }
 }
+
+#[test]
+fn keeps_factory_errors_in_their_reserved_range() {
+    assert!((5_000..=5_099).contains(&Error::ChannelAlreadyDeployed.code()));
+}
+
+#[test]
+fn keeps_migration_errors_in_their_reserved_range() {
+    for code in [
+        Error::AlreadyMigrated.code(),
+        Error::InvalidMigrationParams.code(),
+        Error::MissingMigrationStep.code(),
+    ] {
+        assert!((6_000..=6_099).contains(&code));
+    }
+}
@@ -1,6 +1,13 @@
 #![no_std]
 
+#[cfg(feature = "notes")]
+extern crate alloc;
+
+#[cfg(feature = "notes")]
+pub mod notes;
 pub mod parser;
+#[cfg(feature = "stealth")]
+pub mod stealth;
 #[cfg(feature = "testutils")]
 pub mod testutils;
 
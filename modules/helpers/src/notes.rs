@@ -0,0 +1,223 @@
+//! Encrypted notes for created UTXOs, enabled by the `notes` feature.
+//!
+//! A note is encrypted to the recipient's P256 viewing key with ECIES: an ephemeral key agrees a
+//! secret with the viewing key (ECDH), HKDF-SHA256 derives an AES-256-GCM key from it, and the
+//! created UTXO's key is the associated data, so a note cannot be moved to another UTXO.
+//!
+//! Encoded note: `version (1) || ephemeral public key (65, SEC1 uncompressed) || ciphertext || tag
+//! (16)`. Every note has a fresh ephemeral key and so a fresh AES key, which makes the fixed zero
+//! nonce safe.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use hkdf::Hkdf;
+use p256::{ecdh::diffie_hellman, elliptic_curve::sec1::ToEncodedPoint, PublicKey, SecretKey};
+use rand_core::CryptoRngCore;
+use sha2::Sha256;
+
+pub const NOTE_VERSION: u8 = 1;
+
+const PUBLIC_KEY_LEN: usize = 65;
+const TAG_LEN: usize = 16;
+const HKDF_INFO: &[u8] = b"moonlight-note-v1";
+
+/// Bytes an encrypted note adds to its plaintext.
+pub const NOTE_OVERHEAD: usize = 1 + PUBLIC_KEY_LEN + TAG_LEN;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NoteError {
+    /// The viewing public key is not a valid SEC1 P256 point.
+    InvalidPublicKey,
+    /// The viewing secret key is not a valid P256 scalar.
+    InvalidSecretKey,
+    /// The note was written with a version this crate does not read.
+    UnsupportedVersion(u8),
+    /// The note or its plaintext is too short.
+    Malformed,
+    /// The note was not encrypted to this viewing key, for this UTXO, or was altered.
+    Decryption,
+}
+
+impl fmt::Display for NoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPublicKey => write!(f, "invalid viewing public key"),
+            Self::InvalidSecretKey => write!(f, "invalid viewing secret key"),
+            Self::UnsupportedVersion(version) => write!(f, "unsupported note version {version}"),
+            Self::Malformed => write!(f, "malformed note"),
+            Self::Decryption => write!(f, "note cannot be decrypted with this key"),
+        }
+    }
+}
+
+/// What the sender tells the recipient about a created UTXO.
+///
+/// `blinding` is sender-chosen data the recipient needs to recognise or derive the UTXO, such as a
+/// key-derivation seed; `memo` is free-form.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NotePlaintext {
+    pub amount: i128,
+    pub blinding: [u8; 32],
+    pub memo: Vec<u8>,
+}
+
+impl NotePlaintext {
+    /// `amount (16, little-endian) || blinding (32) || memo`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(48 + self.memo.len());
+        bytes.extend_from_slice(&self.amount.to_le_bytes());
+        bytes.extend_from_slice(&self.blinding);
+        bytes.extend_from_slice(&self.memo);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, NoteError> {
+        if bytes.len() < 48 {
+            return Err(NoteError::Malformed);
+        }
+        let (amount, rest) = bytes.split_at(16);
+        let (blinding, memo) = rest.split_at(32);
+
+        Ok(Self {
+            amount: i128::from_le_bytes(amount.try_into().unwrap()),
+            blinding: blinding.try_into().unwrap(),
+            memo: memo.to_vec(),
+        })
+    }
+}
+
+/// The P256 key a recipient scans and decrypts notes with. It cannot spend UTXOs; with the
+/// `stealth` feature it also finds stealth UTXOs.
+#[derive(Clone)]
+pub struct ViewingKey(SecretKey);
+
+impl ViewingKey {
+    pub fn generate(rng: &mut impl CryptoRngCore) -> Self {
+        Self(SecretKey::random(rng))
+    }
+
+    pub fn from_bytes(secret: &[u8; 32]) -> Result<Self, NoteError> {
+        SecretKey::from_slice(secret)
+            .map(Self)
+            .map_err(|_| NoteError::InvalidSecretKey)
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes().into()
+    }
+
+    #[cfg(feature = "stealth")]
+    pub(crate) fn secret(&self) -> &SecretKey {
+        &self.0
+    }
+
+    /// The SEC1 uncompressed public key senders encrypt notes to.
+    pub fn public_key(&self) -> [u8; 65] {
+        encode_public_key(&self.0.public_key())
+    }
+}
+
+/// Encrypts `note` for the holder of `viewing_public_key`, bound to the created `utxo`.
+pub fn encrypt_note(
+    rng: &mut impl CryptoRngCore,
+    viewing_public_key: &[u8; 65],
+    utxo: &[u8; 65],
+    note: &NotePlaintext,
+) -> Result<Vec<u8>, NoteError> {
+    let recipient =
+        PublicKey::from_sec1_bytes(viewing_public_key).map_err(|_| NoteError::InvalidPublicKey)?;
+    let ephemeral = SecretKey::random(rng);
+    let ephemeral_public = encode_public_key(&ephemeral.public_key());
+
+    let cipher = note_cipher(
+        &ephemeral,
+        &recipient,
+        &ephemeral_public,
+        viewing_public_key,
+    );
+    let ciphertext = cipher
+        .encrypt(
+            &Nonce::default(),
+            Payload {
+                msg: &note.to_bytes(),
+                aad: utxo,
+            },
+        )
+        .map_err(|_| NoteError::Malformed)?;
+
+    let mut encoded = Vec::with_capacity(1 + PUBLIC_KEY_LEN + ciphertext.len());
+    encoded.push(NOTE_VERSION);
+    encoded.extend_from_slice(&ephemeral_public);
+    encoded.extend_from_slice(&ciphertext);
+    Ok(encoded)
+}
+
+/// Decrypts a note created for `utxo`. Fails with [`NoteError::Decryption`] for notes meant for
+/// another viewing key, which is how a scanner recognises its own notes.
+pub fn decrypt_note(
+    viewing_key: &ViewingKey,
+    utxo: &[u8; 65],
+    encoded: &[u8],
+) -> Result<NotePlaintext, NoteError> {
+    if encoded.len() < NOTE_OVERHEAD {
+        return Err(NoteError::Malformed);
+    }
+    if encoded[0] != NOTE_VERSION {
+        return Err(NoteError::UnsupportedVersion(encoded[0]));
+    }
+    let (ephemeral_public, ciphertext) = encoded[1..].split_at(PUBLIC_KEY_LEN);
+    let ephemeral =
+        PublicKey::from_sec1_bytes(ephemeral_public).map_err(|_| NoteError::Malformed)?;
+
+    let cipher = note_cipher(
+        &viewing_key.0,
+        &ephemeral,
+        ephemeral_public,
+        &viewing_key.public_key(),
+    );
+    let plaintext = cipher
+        .decrypt(
+            &Nonce::default(),
+            Payload {
+                msg: ciphertext,
+                aad: utxo,
+            },
+        )
+        .map_err(|_| NoteError::Decryption)?;
+
+    NotePlaintext::from_bytes(&plaintext)
+}
+
+/// The AES-256-GCM cipher keyed by `HKDF-SHA256(ECDH(secret, public))`, salted with both public
+/// keys.
+fn note_cipher(
+    secret: &SecretKey,
+    public: &PublicKey,
+    ephemeral_public: &[u8],
+    viewing_public: &[u8],
+) -> Aes256Gcm {
+    let shared = diffie_hellman(secret.to_nonzero_scalar(), public.as_affine());
+
+    let mut salt = [0u8; 2 * PUBLIC_KEY_LEN];
+    salt[..PUBLIC_KEY_LEN].copy_from_slice(ephemeral_public);
+    salt[PUBLIC_KEY_LEN..].copy_from_slice(viewing_public);
+
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(&salt), shared.raw_secret_bytes())
+        .expand(HKDF_INFO, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    Aes256Gcm::new(&key.into())
+}
+
+fn encode_public_key(public: &PublicKey) -> [u8; 65] {
+    public
+        .to_encoded_point(false)
+        .as_bytes()
+        .try_into()
+        .expect("an uncompressed P256 point is 65 bytes")
+}
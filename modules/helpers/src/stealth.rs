@@ -0,0 +1,216 @@
+//! Stealth UTXO keys, enabled by the `stealth` feature.
+//!
+//! A recipient publishes one [`StealthMetaKey`]: a spend public key `S` and a viewing public key
+//! `V`. For every payment the sender picks an ephemeral key `r`, publishes `R = r·G` with the
+//! create, and creates the one-time UTXO key `P = S + h·G`, where
+//! `h = SHA-256("moonlight-stealth-v1" || x(r·V) || R) mod n`.
+//!
+//! The recipient recomputes `h` from `x(v·R)` with the viewing key alone, so a scanner can find
+//! payments without being able to spend them; the one-time secret `s + h` also needs the spend
+//! key. Without the viewing key, `P` and `R` cannot be linked to `S`, `V` or each other.
+
+use core::fmt;
+
+use p256::{
+    ecdh::diffie_hellman,
+    elliptic_curve::{ops::Reduce, sec1::ToEncodedPoint, Field, Group},
+    ProjectivePoint, PublicKey, Scalar, SecretKey, U256,
+};
+use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha256};
+
+use crate::notes::ViewingKey;
+
+const PUBLIC_KEY_LEN: usize = 65;
+const DOMAIN: &[u8] = b"moonlight-stealth-v1";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StealthError {
+    /// A public key is not a valid SEC1 P256 point.
+    InvalidPublicKey,
+    /// A secret key is not a valid P256 scalar.
+    InvalidSecretKey,
+    /// The derived one-time key is the identity or zero; pick another ephemeral key.
+    DegenerateKey,
+}
+
+impl fmt::Display for StealthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPublicKey => write!(f, "invalid P256 public key"),
+            Self::InvalidSecretKey => write!(f, "invalid P256 secret key"),
+            Self::DegenerateKey => write!(f, "degenerate one-time key"),
+        }
+    }
+}
+
+/// What a recipient publishes once to receive stealth payments.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StealthMetaKey {
+    /// SEC1 uncompressed spend public key.
+    pub spend: [u8; 65],
+    /// SEC1 uncompressed viewing public key.
+    pub view: [u8; 65],
+}
+
+impl StealthMetaKey {
+    /// `spend (65) || view (65)`.
+    pub fn to_bytes(&self) -> [u8; 130] {
+        let mut bytes = [0u8; 2 * PUBLIC_KEY_LEN];
+        bytes[..PUBLIC_KEY_LEN].copy_from_slice(&self.spend);
+        bytes[PUBLIC_KEY_LEN..].copy_from_slice(&self.view);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; 130]) -> Result<Self, StealthError> {
+        let (spend, view) = bytes.split_at(PUBLIC_KEY_LEN);
+        let meta_key = Self {
+            spend: spend.try_into().unwrap(),
+            view: view.try_into().unwrap(),
+        };
+        decode_public_key(&meta_key.spend)?;
+        decode_public_key(&meta_key.view)?;
+        Ok(meta_key)
+    }
+}
+
+/// The recipient's secret keys behind a [`StealthMetaKey`].
+#[derive(Clone)]
+pub struct StealthKeys {
+    spend: SecretKey,
+    view: ViewingKey,
+}
+
+impl StealthKeys {
+    pub fn generate(rng: &mut impl CryptoRngCore) -> Self {
+        Self {
+            spend: SecretKey::random(rng),
+            view: ViewingKey::generate(rng),
+        }
+    }
+
+    pub fn from_bytes(spend: &[u8; 32], view: &[u8; 32]) -> Result<Self, StealthError> {
+        Ok(Self {
+            spend: SecretKey::from_slice(spend).map_err(|_| StealthError::InvalidSecretKey)?,
+            view: ViewingKey::from_bytes(view).map_err(|_| StealthError::InvalidSecretKey)?,
+        })
+    }
+
+    pub fn meta_key(&self) -> StealthMetaKey {
+        StealthMetaKey {
+            spend: encode_public_key(&self.spend.public_key()),
+            view: self.view.public_key(),
+        }
+    }
+
+    /// The key to hand to a scanner, which finds payments but cannot spend them. It also
+    /// decrypts notes encrypted to `meta_key().view`.
+    pub fn viewing_key(&self) -> &ViewingKey {
+        &self.view
+    }
+
+    /// The P256 secret of the one-time UTXO key derived with `ephemeral_key`, for signing its
+    /// spend.
+    pub fn utxo_secret(&self, ephemeral_key: &[u8; 65]) -> Result<[u8; 32], StealthError> {
+        let ephemeral = decode_public_key(ephemeral_key)?;
+        let tweak = shared_tweak(self.view.secret(), &ephemeral, ephemeral_key);
+        let secret = *self.spend.to_nonzero_scalar() + tweak;
+        if bool::from(secret.is_zero()) {
+            return Err(StealthError::DegenerateKey);
+        }
+        Ok(secret.to_bytes().into())
+    }
+}
+
+/// A one-time UTXO key and the ephemeral key to publish with its create.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StealthUtxo {
+    pub utxo: [u8; 65],
+    pub ephemeral_key: [u8; 65],
+}
+
+/// Derives a fresh one-time UTXO key for the owner of `meta_key`.
+pub fn derive_stealth_utxo(
+    rng: &mut impl CryptoRngCore,
+    meta_key: &StealthMetaKey,
+) -> Result<StealthUtxo, StealthError> {
+    derive_with_ephemeral(&SecretKey::random(rng), meta_key)
+}
+
+/// [`derive_stealth_utxo`] with a caller-chosen ephemeral secret, for deterministic wallets and
+/// test vectors. Never reuse an ephemeral secret for two payments.
+pub fn derive_stealth_utxo_with_ephemeral(
+    ephemeral_secret: &[u8; 32],
+    meta_key: &StealthMetaKey,
+) -> Result<StealthUtxo, StealthError> {
+    let ephemeral =
+        SecretKey::from_slice(ephemeral_secret).map_err(|_| StealthError::InvalidSecretKey)?;
+    derive_with_ephemeral(&ephemeral, meta_key)
+}
+
+/// Checks one created UTXO and its ephemeral key, as published in a `transact` event, against the
+/// owner of `viewing_key` and `spend_public_key`. Returns the UTXO key if `utxo_hash` (its
+/// `sha256`) is theirs.
+pub fn scan(
+    viewing_key: &ViewingKey,
+    spend_public_key: &[u8; 65],
+    utxo_hash: &[u8; 32],
+    ephemeral_key: &[u8; 65],
+) -> Result<Option<[u8; 65]>, StealthError> {
+    let spend = decode_public_key(spend_public_key)?;
+    let ephemeral = decode_public_key(ephemeral_key)?;
+    let tweak = shared_tweak(viewing_key.secret(), &ephemeral, ephemeral_key);
+    let utxo = one_time_key(&spend, &tweak)?;
+
+    let hash: [u8; 32] = Sha256::digest(utxo).into();
+    Ok((&hash == utxo_hash).then_some(utxo))
+}
+
+fn derive_with_ephemeral(
+    ephemeral: &SecretKey,
+    meta_key: &StealthMetaKey,
+) -> Result<StealthUtxo, StealthError> {
+    let spend = decode_public_key(&meta_key.spend)?;
+    let view = decode_public_key(&meta_key.view)?;
+    let ephemeral_key = encode_public_key(&ephemeral.public_key());
+    let tweak = shared_tweak(ephemeral, &view, &ephemeral_key);
+
+    Ok(StealthUtxo {
+        utxo: one_time_key(&spend, &tweak)?,
+        ephemeral_key,
+    })
+}
+
+/// `h = SHA-256(DOMAIN || x(ECDH(secret, public)) || ephemeral_key) mod n`.
+fn shared_tweak(secret: &SecretKey, public: &PublicKey, ephemeral_key: &[u8; 65]) -> Scalar {
+    let shared = diffie_hellman(secret.to_nonzero_scalar(), public.as_affine());
+    let digest = Sha256::new()
+        .chain_update(DOMAIN)
+        .chain_update(shared.raw_secret_bytes())
+        .chain_update(ephemeral_key)
+        .finalize();
+    <Scalar as Reduce<U256>>::reduce_bytes(&digest)
+}
+
+/// `P = S + h·G`.
+fn one_time_key(spend: &PublicKey, tweak: &Scalar) -> Result<[u8; 65], StealthError> {
+    let point = spend.to_projective() + ProjectivePoint::GENERATOR * tweak;
+    if bool::from(point.is_identity()) {
+        return Err(StealthError::DegenerateKey);
+    }
+    let public =
+        PublicKey::from_affine(point.to_affine()).map_err(|_| StealthError::DegenerateKey)?;
+    Ok(encode_public_key(&public))
+}
+
+fn decode_public_key(bytes: &[u8; 65]) -> Result<PublicKey, StealthError> {
+    PublicKey::from_sec1_bytes(bytes).map_err(|_| StealthError::InvalidPublicKey)
+}
+
+fn encode_public_key(public: &PublicKey) -> [u8; 65] {
+    public
+        .to_encoded_point(false)
+        .as_bytes()
+        .try_into()
+        .expect("an uncompressed P256 point is 65 bytes")
+}
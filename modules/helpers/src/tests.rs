@@ -56,3 +56,187 @@ fn contract_address_rejected_for_ed25519_extraction() {
 
     address_to_ed25519_pk_bytes(&e, &contract);
 }
+
+mod notes {
+    use rand::rngs::OsRng;
+
+    use crate::notes::{
+        decrypt_note, encrypt_note, NoteError, NotePlaintext, ViewingKey, NOTE_OVERHEAD,
+        NOTE_VERSION,
+    };
+
+    fn plaintext() -> NotePlaintext {
+        NotePlaintext {
+            amount: 1_500_000,
+            blinding: [9; 32],
+            memo: b"invoice 42".to_vec(),
+        }
+    }
+
+    #[test]
+    fn note_round_trips_for_its_recipient() {
+        let viewing_key = ViewingKey::generate(&mut OsRng);
+        let utxo = [4; 65];
+
+        let note =
+            encrypt_note(&mut OsRng, &viewing_key.public_key(), &utxo, &plaintext()).unwrap();
+        assert_eq!(note[0], NOTE_VERSION);
+        assert_eq!(note.len(), NOTE_OVERHEAD + plaintext().to_bytes().len());
+
+        let restored = ViewingKey::from_bytes(&viewing_key.to_bytes()).unwrap();
+        assert_eq!(decrypt_note(&restored, &utxo, &note), Ok(plaintext()));
+    }
+
+    #[test]
+    fn note_is_bound_to_its_key_and_utxo() {
+        let viewing_key = ViewingKey::generate(&mut OsRng);
+        let utxo = [4; 65];
+        let mut note =
+            encrypt_note(&mut OsRng, &viewing_key.public_key(), &utxo, &plaintext()).unwrap();
+
+        let other_key = ViewingKey::generate(&mut OsRng);
+        assert_eq!(
+            decrypt_note(&other_key, &utxo, &note),
+            Err(NoteError::Decryption)
+        );
+        assert_eq!(
+            decrypt_note(&viewing_key, &[5; 65], &note),
+            Err(NoteError::Decryption)
+        );
+
+        let last = note.len() - 1;
+        note[last] ^= 1;
+        assert_eq!(
+            decrypt_note(&viewing_key, &utxo, &note),
+            Err(NoteError::Decryption)
+        );
+        note[0] = 2;
+        assert_eq!(
+            decrypt_note(&viewing_key, &utxo, &note),
+            Err(NoteError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            decrypt_note(&viewing_key, &utxo, &note[..NOTE_OVERHEAD - 1]),
+            Err(NoteError::Malformed)
+        );
+        assert_eq!(
+            encrypt_note(&mut OsRng, &[0; 65], &utxo, &plaintext()),
+            Err(NoteError::InvalidPublicKey)
+        );
+    }
+}
+
+mod stealth {
+    use p256::elliptic_curve::sec1::ToEncodedPoint;
+    use rand::rngs::OsRng;
+    use sha2::{Digest, Sha256};
+
+    use crate::stealth::{
+        derive_stealth_utxo, derive_stealth_utxo_with_ephemeral, scan, StealthError, StealthKeys,
+        StealthMetaKey,
+    };
+
+    fn hash(utxo: &[u8; 65]) -> [u8; 32] {
+        Sha256::digest(utxo).into()
+    }
+
+    fn public_key(secret: &[u8; 32]) -> [u8; 65] {
+        p256::SecretKey::from_slice(secret)
+            .unwrap()
+            .public_key()
+            .to_encoded_point(false)
+            .as_bytes()
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn stealth_vectors() {
+        // spend secret 0x01.., view secret 0x02.., ephemeral secret 0x03..
+        let keys = StealthKeys::from_bytes(&[1; 32], &[2; 32]).unwrap();
+        let meta_key = keys.meta_key();
+        assert_eq!(
+            hex::encode(meta_key.to_bytes()),
+            "046ff03b949241ce1dadd43519e6960e0a85b41a69a05c328103aa2bce1594ca163c4f753a55bf01dc53f6c0b0c7eee78b40c6ff7d25a96e2282b989cef71c144a\
+             04550f471003f3df97c3df506ac797f6721fb1a1fb7b8f6f83d224498a65c88e24136093d7012e509a73715cbd0b00a3cc0ff4b5c01b3ffa196ab1fb327036b8e6"
+        );
+
+        let payment = derive_stealth_utxo_with_ephemeral(&[3; 32], &meta_key).unwrap();
+        assert_eq!(
+            hex::encode(payment.ephemeral_key),
+            "04591ab771ebbcfd6d9cb9094d106528add1a69d44c2c1f627f089ec58b9c61adf9f4e6abf0d045c0c693a3c68ad7c97ca72be64def4a26fecd263dd98a92780f0"
+        );
+        assert_eq!(
+            hex::encode(payment.utxo),
+            "040bba806b5a795b8214aa827595150c8434a327a4b0e51de728e541d5ba7d81cf6cc8800874bbe605947ad47749f9f60c63e6673aed2cc5ffc541906d065aa22d"
+        );
+        assert_eq!(
+            hex::encode(keys.utxo_secret(&payment.ephemeral_key).unwrap()),
+            "fe13c05c9da9b907f20d95d085e93cc24afe579876957b79df3a37acf522aeb0"
+        );
+    }
+
+    #[test]
+    fn recipient_finds_and_spends_stealth_utxo() {
+        let keys = StealthKeys::generate(&mut OsRng);
+        let meta_key = StealthMetaKey::from_bytes(&keys.meta_key().to_bytes()).unwrap();
+
+        let first = derive_stealth_utxo(&mut OsRng, &meta_key).unwrap();
+        let second = derive_stealth_utxo(&mut OsRng, &meta_key).unwrap();
+        assert_ne!(first.utxo, second.utxo);
+        assert_ne!(first.utxo, meta_key.spend);
+
+        for payment in [first, second] {
+            assert_eq!(
+                scan(
+                    keys.viewing_key(),
+                    &meta_key.spend,
+                    &hash(&payment.utxo),
+                    &payment.ephemeral_key
+                ),
+                Ok(Some(payment.utxo))
+            );
+            let secret = keys.utxo_secret(&payment.ephemeral_key).unwrap();
+            assert_eq!(public_key(&secret), payment.utxo);
+        }
+        assert_eq!(
+            scan(
+                keys.viewing_key(),
+                &meta_key.spend,
+                &hash(&first.utxo),
+                &second.ephemeral_key
+            ),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn scan_ignores_other_recipients() {
+        let keys = StealthKeys::generate(&mut OsRng);
+        let other = StealthKeys::generate(&mut OsRng);
+        let payment = derive_stealth_utxo(&mut OsRng, &other.meta_key()).unwrap();
+
+        assert_eq!(
+            scan(
+                keys.viewing_key(),
+                &keys.meta_key().spend,
+                &hash(&payment.utxo),
+                &payment.ephemeral_key
+            ),
+            Ok(None)
+        );
+        assert_eq!(
+            scan(
+                keys.viewing_key(),
+                &keys.meta_key().spend,
+                &hash(&payment.utxo),
+                &[0; 65]
+            ),
+            Err(StealthError::InvalidPublicKey)
+        );
+        assert_eq!(
+            StealthMetaKey::from_bytes(&[4; 130]),
+            Err(StealthError::InvalidPublicKey)
+        );
+    }
+}
@@ -0,0 +1,332 @@
+//! Contract events as read from a log, and their decoded Moonlight form.
+
+use std::io::{BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+use soroban_sdk::xdr::{
+    ContractEvent, ContractEventBody, Limits, ReadXdr, ScAddress, ScVal, WriteXdr,
+};
+
+use crate::IndexerError;
+
+/// `sha256` of a 65-byte UTXO key: its storage key and its identifier in `Transact` events.
+pub type UtxoHash = [u8; 32];
+
+/// A contract event and the ledger it was emitted in.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EventRecord {
+    pub ledger: u32,
+    pub contract: ScAddress,
+    pub topics: Vec<ScVal>,
+    pub value: ScVal,
+}
+
+impl EventRecord {
+    /// Returns `None` for system and diagnostic events, which carry no contract id.
+    pub fn from_xdr(ledger: u32, event: &ContractEvent) -> Option<Self> {
+        let contract = ScAddress::Contract(event.contract_id.clone()?);
+        let ContractEventBody::V0(body) = &event.body;
+
+        Some(Self {
+            ledger,
+            contract,
+            topics: body.topics.to_vec(),
+            value: body.data.clone(),
+        })
+    }
+}
+
+/// One line of an event log, with the field names of Stellar RPC `getEvents`.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EventLine {
+    ledger: u32,
+    contract_id: String,
+    topic: Vec<String>,
+    value: String,
+}
+
+/// Reads an event log: one JSON object per line with `ledger`, `contractId`, and the base64 XDR
+/// `topic` list and `value`. Blank lines are skipped.
+pub fn read_events(reader: impl BufRead) -> Result<Vec<EventRecord>, IndexerError> {
+    let mut records = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(|err| IndexerError::Io(err.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let line: EventLine =
+            serde_json::from_str(&line).map_err(|err| IndexerError::Json(err.to_string()))?;
+        let contract = line
+            .contract_id
+            .parse()
+            .map_err(|_| IndexerError::InvalidAddress(line.contract_id.clone()))?;
+        let topics = line
+            .topic
+            .iter()
+            .map(|topic| sc_val_from_base64(topic))
+            .collect::<Result<_, _>>()?;
+
+        records.push(EventRecord {
+            ledger: line.ledger,
+            contract,
+            topics,
+            value: sc_val_from_base64(&line.value)?,
+        });
+    }
+    Ok(records)
+}
+
+/// Writes `records` in the format read by [`read_events`].
+pub fn write_events<'a>(
+    mut writer: impl Write,
+    records: impl IntoIterator<Item = &'a EventRecord>,
+) -> Result<(), IndexerError> {
+    for record in records {
+        let line = EventLine {
+            ledger: record.ledger,
+            contract_id: record.contract.to_string(),
+            topic: record
+                .topics
+                .iter()
+                .map(sc_val_to_base64)
+                .collect::<Result<_, _>>()?,
+            value: sc_val_to_base64(&record.value)?,
+        };
+        let line =
+            serde_json::to_string(&line).map_err(|err| IndexerError::Json(err.to_string()))?;
+        writeln!(writer, "{line}").map_err(|err| IndexerError::Io(err.to_string()))?;
+    }
+    Ok(())
+}
+
+fn sc_val_from_base64(xdr: &str) -> Result<ScVal, IndexerError> {
+    ScVal::from_xdr_base64(xdr, Limits::none()).map_err(|_| IndexerError::Xdr)
+}
+
+fn sc_val_to_base64(value: &ScVal) -> Result<String, IndexerError> {
+    value
+        .to_xdr_base64(Limits::none())
+        .map_err(|_| IndexerError::Xdr)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UtxoAction {
+    Create,
+    Spend,
+}
+
+/// The events the indexer understands.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ChannelEvent {
+    /// `UtxoEvent` of `moonlight-utxo-core`, published unless `no-utxo-events` is set.
+    Utxo {
+        utxo: [u8; 65],
+        action: UtxoAction,
+        amount: i128,
+    },
+    /// `BundleEvent` of `moonlight-utxo-core`, published unless `no-bundle-events` is set.
+    Bundle {
+        spend: Vec<[u8; 65]>,
+        create: Vec<([u8; 65], i128)>,
+        deposited: i128,
+        withdrawn: i128,
+    },
+    /// The Privacy Channel's `Transact` event.
+    Transact {
+        spent: Vec<UtxoHash>,
+        created: Vec<UtxoHash>,
+        /// Encrypted notes of created UTXOs; empty for channels that predate notes.
+        notes: Vec<(UtxoHash, Vec<u8>)>,
+        /// Stealth ephemeral keys of created UTXOs; empty for channels that predate them.
+        ephemeral_keys: Vec<(UtxoHash, [u8; 65])>,
+        deposits: Vec<(ScAddress, i128)>,
+        withdrawals: Vec<(ScAddress, i128)>,
+        supply: i128,
+        payload: Option<Vec<u8>>,
+    },
+    /// Channel Auth `ProviderAdded`.
+    ProviderAdded(ScAddress),
+    /// Channel Auth `ProviderRemoved`.
+    ProviderRemoved(ScAddress),
+    /// Channel Auth `ChannelStateChanged`.
+    ChannelStateChanged {
+        channel: ScAddress,
+        asset: ScAddress,
+        enabled: bool,
+    },
+}
+
+impl ChannelEvent {
+    /// Decodes `record` by its event name, the first topic.
+    ///
+    /// Returns `Ok(None)` for events with other names, such as token transfers, and
+    /// [`IndexerError::Malformed`] for a known name whose topics or value do not match it.
+    pub fn decode(record: &EventRecord) -> Result<Option<Self>, IndexerError> {
+        let Some(name) = record.topics.first().and_then(symbol) else {
+            return Ok(None);
+        };
+        let topics = &record.topics[1..];
+        let value = &record.value;
+
+        let event = match name {
+            "utxo_event" => decode_utxo(value),
+            "bundle_event" => decode_bundle(value),
+            "transact" => decode_transact(value),
+            "provider_added" => single_address(topics).map(Self::ProviderAdded),
+            "provider_removed" => single_address(topics).map(Self::ProviderRemoved),
+            "channel_state_changed" => decode_channel_state(topics, value),
+            _ => return Ok(None),
+        };
+        event
+            .map(Some)
+            .ok_or_else(|| IndexerError::Malformed(name.to_string()))
+    }
+}
+
+fn decode_utxo(value: &ScVal) -> Option<ChannelEvent> {
+    let [utxo, action, amount] = vec(value)? else {
+        return None;
+    };
+    let action = match symbol(action)? {
+        "create" => UtxoAction::Create,
+        "spend" => UtxoAction::Spend,
+        _ => return None,
+    };
+
+    Some(ChannelEvent::Utxo {
+        utxo: bytes(utxo)?,
+        action,
+        amount: int(amount)?,
+    })
+}
+
+fn decode_bundle(value: &ScVal) -> Option<ChannelEvent> {
+    let [spend, create, deposited, withdrawn] = vec(value)? else {
+        return None;
+    };
+
+    Some(ChannelEvent::Bundle {
+        spend: list(spend, bytes)?,
+        create: list(create, |entry| match vec(entry)? {
+            [utxo, amount] => Some((bytes(utxo)?, int(amount)?)),
+            _ => None,
+        })?,
+        deposited: int(deposited)?,
+        withdrawn: int(withdrawn)?,
+    })
+}
+
+fn decode_transact(value: &ScVal) -> Option<ChannelEvent> {
+    let payload = match field(value, "payload")? {
+        ScVal::Void => None,
+        ScVal::Bytes(payload) => Some(payload.to_vec()),
+        _ => return None,
+    };
+    let notes = match field(value, "notes") {
+        Some(notes) => list(notes, |entry| match vec(entry)? {
+            [utxo, ScVal::Bytes(note)] => Some((bytes(utxo)?, note.to_vec())),
+            _ => None,
+        })?,
+        None => Vec::new(),
+    };
+    let ephemeral_keys = match field(value, "ephemeral_keys") {
+        Some(keys) => list(keys, |entry| match vec(entry)? {
+            [utxo, key] => Some((bytes(utxo)?, bytes(key)?)),
+            _ => None,
+        })?,
+        None => Vec::new(),
+    };
+
+    Some(ChannelEvent::Transact {
+        spent: list(field(value, "spent")?, bytes)?,
+        created: list(field(value, "created")?, bytes)?,
+        notes,
+        ephemeral_keys,
+        deposits: list(field(value, "deposits")?, leg)?,
+        withdrawals: list(field(value, "withdrawals")?, leg)?,
+        supply: int(field(value, "supply")?)?,
+        payload,
+    })
+}
+
+fn decode_channel_state(topics: &[ScVal], value: &ScVal) -> Option<ChannelEvent> {
+    let [channel, asset] = topics else {
+        return None;
+    };
+    let ScVal::Bool(enabled) = value else {
+        return None;
+    };
+
+    Some(ChannelEvent::ChannelStateChanged {
+        channel: address(channel)?,
+        asset: address(asset)?,
+        enabled: *enabled,
+    })
+}
+
+fn single_address(topics: &[ScVal]) -> Option<ScAddress> {
+    match topics {
+        [provider] => address(provider),
+        _ => None,
+    }
+}
+
+/// A deposit or withdrawal `(Address, i128)`.
+fn leg(value: &ScVal) -> Option<(ScAddress, i128)> {
+    match vec(value)? {
+        [account, amount] => Some((address(account)?, int(amount)?)),
+        _ => None,
+    }
+}
+
+fn symbol(value: &ScVal) -> Option<&str> {
+    match value {
+        ScVal::Symbol(symbol) => core::str::from_utf8(symbol.as_slice()).ok(),
+        _ => None,
+    }
+}
+
+fn vec(value: &ScVal) -> Option<&[ScVal]> {
+    match value {
+        ScVal::Vec(Some(items)) => Some(items.as_slice()),
+        _ => None,
+    }
+}
+
+fn list<T>(value: &ScVal, item: impl Fn(&ScVal) -> Option<T>) -> Option<Vec<T>> {
+    vec(value)?.iter().map(item).collect()
+}
+
+/// A field of a map-format event.
+fn field<'a>(value: &'a ScVal, name: &str) -> Option<&'a ScVal> {
+    match value {
+        ScVal::Map(Some(map)) => map
+            .iter()
+            .find(|entry| symbol(&entry.key) == Some(name))
+            .map(|entry| &entry.val),
+        _ => None,
+    }
+}
+
+fn bytes<const N: usize>(value: &ScVal) -> Option<[u8; N]> {
+    match value {
+        ScVal::Bytes(bytes) => bytes.as_slice().try_into().ok(),
+        _ => None,
+    }
+}
+
+fn int(value: &ScVal) -> Option<i128> {
+    match value {
+        ScVal::I128(parts) => Some((i128::from(parts.hi) << 64) | i128::from(parts.lo)),
+        _ => None,
+    }
+}
+
+fn address(value: &ScVal) -> Option<ScAddress> {
+    match value {
+        ScVal::Address(address) => Some(address.clone()),
+        _ => None,
+    }
+}
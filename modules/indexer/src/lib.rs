@@ -0,0 +1,317 @@
+//! Off-chain indexer for Privacy Channel events.
+//!
+//! [`Indexer`] replays the events of one channel and its Channel Auth contract, read from a log
+//! file or any iterator of [`EventRecord`]s, and keeps the UTXO set, the provider set and the
+//! channel's supply history. [`Indexer::check_supply`] cross-checks the result against the
+//! channel's `supply()`.
+
+use core::fmt;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::BufReader,
+    path::Path,
+};
+
+use sha2::{Digest, Sha256};
+use soroban_sdk::xdr::ScAddress;
+
+mod event;
+
+pub use event::{read_events, write_events, ChannelEvent, EventRecord, UtxoAction, UtxoHash};
+
+#[cfg(test)]
+mod test;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IndexerError {
+    /// The event log cannot be read or written.
+    Io(String),
+    /// A line of the event log is not a valid event object.
+    Json(String),
+    /// A topic or value is not valid base64 XDR.
+    Xdr,
+    /// A contract id is not a valid strkey.
+    InvalidAddress(String),
+    /// An event with a known name has unexpected topics or value.
+    Malformed(String),
+    /// The indexed state disagrees with the channel's `supply()`.
+    SupplyMismatch { indexed: i128, on_chain: i128 },
+}
+
+impl fmt::Display for IndexerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "event log I/O failed: {err}"),
+            Self::Json(err) => write!(f, "invalid event log line: {err}"),
+            Self::Xdr => write!(f, "invalid XDR in event log"),
+            Self::InvalidAddress(value) => write!(f, "invalid contract id `{value}`"),
+            Self::Malformed(name) => write!(f, "malformed `{name}` event"),
+            Self::SupplyMismatch { indexed, on_chain } => {
+                write!(
+                    f,
+                    "indexed supply {indexed} does not match on-chain supply {on_chain}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for IndexerError {}
+
+/// What the indexer knows about one UTXO.
+///
+/// `Transact` events identify UTXOs by hash only; `key` and `amount` are filled in when the
+/// channel also publishes `UtxoEvent`s. The ledgers are unknown for UTXOs created before the
+/// first indexed event. `note` and `ephemeral_key` are the encrypted note and stealth ephemeral key
+/// attached when the UTXO was created.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct UtxoRecord {
+    pub key: Option<[u8; 65]>,
+    pub amount: Option<i128>,
+    pub created_ledger: Option<u32>,
+    pub spent_ledger: Option<u32>,
+    pub note: Option<Vec<u8>>,
+    pub ephemeral_key: Option<[u8; 65]>,
+}
+
+impl UtxoRecord {
+    pub fn is_unspent(&self) -> bool {
+        self.spent_ledger.is_none()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SupplyPoint {
+    pub ledger: u32,
+    pub supply: i128,
+}
+
+/// The latest `ChannelStateChanged` for a channel.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChannelState {
+    pub asset: ScAddress,
+    pub enabled: bool,
+}
+
+/// Channel state rebuilt from events.
+///
+/// UTXO and supply events are taken from `channel` only, and provider and channel-state events
+/// from `auth` only; everything else, such as token transfers, is ignored. Replaying the same
+/// UTXO event twice is harmless, so a channel that publishes both `UtxoEvent`s and `Transact`
+/// is indexed correctly.
+#[derive(Clone, Debug)]
+pub struct Indexer {
+    channel: ScAddress,
+    auth: ScAddress,
+    utxos: BTreeMap<UtxoHash, UtxoRecord>,
+    providers: BTreeSet<ScAddress>,
+    channels: BTreeMap<ScAddress, ChannelState>,
+    supply_history: Vec<SupplyPoint>,
+}
+
+impl Indexer {
+    pub fn new(channel: ScAddress, auth: ScAddress) -> Self {
+        Self {
+            channel,
+            auth,
+            utxos: BTreeMap::new(),
+            providers: BTreeSet::new(),
+            channels: BTreeMap::new(),
+            supply_history: Vec::new(),
+        }
+    }
+
+    /// Applies one event. Events must be ingested in the order they were emitted.
+    pub fn ingest(&mut self, record: &EventRecord) -> Result<(), IndexerError> {
+        if record.contract != self.channel && record.contract != self.auth {
+            return Ok(());
+        }
+        let Some(event) = ChannelEvent::decode(record)? else {
+            return Ok(());
+        };
+        let ledger = record.ledger;
+        let from_channel = record.contract == self.channel;
+
+        match event {
+            ChannelEvent::Utxo {
+                utxo,
+                action,
+                amount,
+            } if from_channel => {
+                let hash = hash_utxo_key(&utxo);
+                let entry = match action {
+                    UtxoAction::Create => self.created(hash, ledger),
+                    UtxoAction::Spend => self.spent(hash, ledger),
+                };
+                entry.key = Some(utxo);
+                entry.amount = Some(amount);
+            }
+            ChannelEvent::Bundle {
+                spend,
+                create,
+                deposited,
+                withdrawn,
+            } if from_channel => {
+                for utxo in spend {
+                    self.spent(hash_utxo_key(&utxo), ledger).key = Some(utxo);
+                }
+                for (utxo, amount) in create {
+                    let entry = self.created(hash_utxo_key(&utxo), ledger);
+                    entry.key = Some(utxo);
+                    entry.amount = Some(amount);
+                }
+                let supply = self.supply() + deposited - withdrawn;
+                self.record_supply(ledger, supply);
+            }
+            ChannelEvent::Transact {
+                spent,
+                created,
+                notes,
+                ephemeral_keys,
+                supply,
+                ..
+            } if from_channel => {
+                for hash in spent {
+                    self.spent(hash, ledger);
+                }
+                for hash in created {
+                    self.created(hash, ledger);
+                }
+                for (hash, note) in notes {
+                    self.created(hash, ledger).note = Some(note);
+                }
+                for (hash, key) in ephemeral_keys {
+                    self.created(hash, ledger).ephemeral_key = Some(key);
+                }
+                self.record_supply(ledger, supply);
+            }
+            ChannelEvent::ProviderAdded(provider) if !from_channel => {
+                self.providers.insert(provider);
+            }
+            ChannelEvent::ProviderRemoved(provider) if !from_channel => {
+                self.providers.remove(&provider);
+            }
+            ChannelEvent::ChannelStateChanged {
+                channel,
+                asset,
+                enabled,
+            } if !from_channel => {
+                self.channels
+                    .insert(channel, ChannelState { asset, enabled });
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub fn ingest_all<'a>(
+        &mut self,
+        records: impl IntoIterator<Item = &'a EventRecord>,
+    ) -> Result<(), IndexerError> {
+        records
+            .into_iter()
+            .try_for_each(|record| self.ingest(record))
+    }
+
+    /// Ingests an event log in the format of [`read_events`].
+    pub fn ingest_file(&mut self, path: impl AsRef<Path>) -> Result<(), IndexerError> {
+        let file = File::open(path).map_err(|err| IndexerError::Io(err.to_string()))?;
+        self.ingest_all(&read_events(BufReader::new(file))?)
+    }
+
+    /// Looks a UTXO up by `sha256` of its key, as listed in `Transact` events.
+    pub fn utxo(&self, hash: &UtxoHash) -> Option<&UtxoRecord> {
+        self.utxos.get(hash)
+    }
+
+    /// Looks a UTXO up by its 65-byte key.
+    pub fn utxo_by_key(&self, key: &[u8; 65]) -> Option<&UtxoRecord> {
+        self.utxo(&hash_utxo_key(key))
+    }
+
+    pub fn unspent(&self) -> impl Iterator<Item = (&UtxoHash, &UtxoRecord)> {
+        self.utxos.iter().filter(|(_, utxo)| utxo.is_unspent())
+    }
+
+    /// Unspent UTXOs that carry a note, for recipients to trial-decrypt with their viewing key.
+    pub fn unspent_notes(&self) -> impl Iterator<Item = (&UtxoHash, &[u8])> {
+        self.unspent()
+            .filter_map(|(hash, utxo)| Some((hash, utxo.note.as_deref()?)))
+    }
+
+    /// Unspent UTXOs that carry a stealth ephemeral key, for recipients to scan with
+    /// `moonlight_helpers::stealth::scan`.
+    pub fn unspent_ephemeral_keys(&self) -> impl Iterator<Item = (&UtxoHash, &[u8; 65])> {
+        self.unspent()
+            .filter_map(|(hash, utxo)| Some((hash, utxo.ephemeral_key.as_ref()?)))
+    }
+
+    pub fn providers(&self) -> impl Iterator<Item = &ScAddress> {
+        self.providers.iter()
+    }
+
+    pub fn is_provider(&self, provider: &ScAddress) -> bool {
+        self.providers.contains(provider)
+    }
+
+    pub fn channel_state(&self, channel: &ScAddress) -> Option<&ChannelState> {
+        self.channels.get(channel)
+    }
+
+    /// The supply after the last indexed bundle, `0` before any.
+    pub fn supply(&self) -> i128 {
+        self.supply_at(u32::MAX)
+    }
+
+    /// The supply at the end of `ledger`.
+    pub fn supply_at(&self, ledger: u32) -> i128 {
+        self.supply_history
+            .iter()
+            .rev()
+            .find(|point| point.ledger <= ledger)
+            .map_or(0, |point| point.supply)
+    }
+
+    /// One point per ledger that changed the supply, in ledger order.
+    pub fn supply_history(&self) -> &[SupplyPoint] {
+        &self.supply_history
+    }
+
+    /// Checks the indexed supply, and the sum of unspent UTXO amounts when every amount is known,
+    /// against the channel's `supply()` at the last indexed ledger.
+    pub fn check_supply(&self, on_chain: i128) -> Result<(), IndexerError> {
+        let unspent: Option<i128> = self.unspent().map(|(_, utxo)| utxo.amount).sum();
+
+        for indexed in [Some(self.supply()), unspent].into_iter().flatten() {
+            if indexed != on_chain {
+                return Err(IndexerError::SupplyMismatch { indexed, on_chain });
+            }
+        }
+        Ok(())
+    }
+
+    fn spent(&mut self, hash: UtxoHash, ledger: u32) -> &mut UtxoRecord {
+        let entry = self.utxos.entry(hash).or_default();
+        entry.spent_ledger = Some(ledger);
+        entry
+    }
+
+    fn created(&mut self, hash: UtxoHash, ledger: u32) -> &mut UtxoRecord {
+        let entry = self.utxos.entry(hash).or_default();
+        entry.created_ledger.get_or_insert(ledger);
+        entry
+    }
+
+    fn record_supply(&mut self, ledger: u32, supply: i128) {
+        match self.supply_history.last_mut() {
+            Some(last) if last.ledger == ledger => last.supply = supply,
+            _ => self.supply_history.push(SupplyPoint { ledger, supply }),
+        }
+    }
+}
+
+/// `sha256(utxo_key)`, matching `moonlight_storage::hash_utxo_key`.
+pub fn hash_utxo_key(key: &[u8; 65]) -> UtxoHash {
+    Sha256::digest(key).into()
+}
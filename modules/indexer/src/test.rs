@@ -0,0 +1,326 @@
+use channel_auth_contract::contract::{
+    ChannelAuthContract, ChannelAuthContractArgs, ChannelAuthContractClient,
+};
+use moonlight_helpers::testutils::keys::P256KeyPair;
+use moonlight_primitives::Condition;
+use privacy_channel::{
+    contract::{PrivacyChannelContract, PrivacyChannelContractArgs, PrivacyChannelContractClient},
+    transact::ChannelOperation,
+};
+use soroban_sdk::{
+    contractevent, map,
+    testutils::{Address as _, Events, Ledger},
+    vec,
+    xdr::{ScAddress, ScVal},
+    Address, Bytes, BytesN, Env, Event, FromVal, Map, String, Symbol, Vec,
+};
+use token_contract::{TestToken as Token, TestTokenClient as TokenClient};
+
+use crate::{
+    hash_utxo_key, read_events, write_events, ChannelState, EventRecord, Indexer, IndexerError,
+    SupplyPoint,
+};
+
+// Mirrors of the `moonlight-utxo-core` events, which the workspace builds with
+// `no-utxo-events` and `no-bundle-events`.
+#[contractevent(data_format = "vec")]
+struct UtxoEvent {
+    #[topic]
+    name: Symbol,
+    utxo: BytesN<65>,
+    action: Symbol,
+    amount: i128,
+}
+
+#[contractevent(data_format = "vec")]
+struct BundleEvent {
+    #[topic]
+    name: Symbol,
+    spend: Vec<BytesN<65>>,
+    create: Vec<(BytesN<65>, i128)>,
+    deposited: i128,
+    withdrawn: i128,
+}
+
+fn create_contracts(
+    e: &Env,
+) -> (
+    PrivacyChannelContractClient<'_>,
+    ChannelAuthContractClient<'_>,
+    TokenClient<'_>,
+) {
+    e.mock_all_auths();
+    let admin = Address::generate(e);
+    let auth_id = e.register(
+        ChannelAuthContract,
+        ChannelAuthContractArgs::__constructor(&admin),
+    );
+    let token_id = e.register(
+        Token,
+        (
+            admin.clone(),
+            7_u32,
+            String::from_val(e, &"Moon Token"),
+            String::from_val(e, &"MOON"),
+        ),
+    );
+    let channel_id = e.register(
+        PrivacyChannelContract,
+        PrivacyChannelContractArgs::__constructor(&admin, &auth_id, &token_id),
+    );
+
+    (
+        PrivacyChannelContractClient::new(e, &channel_id),
+        ChannelAuthContractClient::new(e, &auth_id),
+        TokenClient::new(e, &token_id),
+    )
+}
+
+/// Appends the events of the last invocation to `log` and moves to the next ledger.
+fn capture(e: &Env, log: &mut std::vec::Vec<EventRecord>) {
+    let ledger = e.ledger().sequence();
+    log.extend(
+        e.events()
+            .all()
+            .events()
+            .iter()
+            .filter_map(|event| EventRecord::from_xdr(ledger, event)),
+    );
+    e.ledger().with_mut(|info| info.sequence_number += 1);
+}
+
+fn sc_address(address: &Address) -> ScAddress {
+    ScAddress::from(address)
+}
+
+fn indexer(channel: &PrivacyChannelContractClient, auth: &ChannelAuthContractClient) -> Indexer {
+    Indexer::new(sc_address(&channel.address), sc_address(&auth.address))
+}
+
+#[test]
+fn replays_captured_channel_and_auth_events() {
+    let e = Env::default();
+    let (channel, auth, token) = create_contracts(&e);
+    let mut log = std::vec::Vec::new();
+
+    let (provider_a, provider_b) = (Address::generate(&e), Address::generate(&e));
+    let (depositor, recipient) = (Address::generate(&e), Address::generate(&e));
+    let (utxo_a, utxo_b) = (P256KeyPair::generate(&e), P256KeyPair::generate(&e));
+    let note = Bytes::from_array(&e, &[8; 24]);
+    let ephemeral_key = P256KeyPair::generate(&e).public_key;
+    token.mint(&depositor, &500_i128);
+
+    auth.add_provider(&provider_a);
+    capture(&e, &mut log);
+    auth.add_provider(&provider_b);
+    capture(&e, &mut log);
+    auth.remove_provider(&provider_a);
+    capture(&e, &mut log);
+    auth.enable_channel(&channel.address, &token.address);
+    capture(&e, &mut log);
+
+    let deposit_ledger = e.ledger().sequence();
+    channel.transact(&ChannelOperation {
+        spend: vec![&e],
+        create: vec![&e, (utxo_a.public_key.clone(), 500_i128)],
+        deposit: vec![&e, (depositor.clone(), 500_i128, vec![&e])],
+        withdraw: vec![&e],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
+    });
+    capture(&e, &mut log);
+
+    let transfer_ledger = e.ledger().sequence();
+    channel.transact(&ChannelOperation {
+        spend: vec![
+            &e,
+            (
+                utxo_a.public_key.clone(),
+                vec![
+                    &e,
+                    Condition::Create(utxo_b.public_key.clone(), 300_i128),
+                    Condition::ExtWithdraw(recipient.clone(), 200_i128),
+                ],
+            ),
+        ],
+        create: vec![&e, (utxo_b.public_key.clone(), 300_i128)],
+        deposit: vec![&e],
+        withdraw: vec![&e, (recipient, 200_i128, vec![&e])],
+        notes: map![&e, (utxo_b.public_key.clone(), note.clone())],
+        ephemeral_keys: map![&e, (utxo_b.public_key.clone(), ephemeral_key.clone())],
+        allowance_deposits: vec![&e],
+    });
+    capture(&e, &mut log);
+
+    let path = std::env::temp_dir().join(std::format!(
+        "moonlight-indexer-{}.jsonl",
+        std::process::id()
+    ));
+    write_events(std::fs::File::create(&path).unwrap(), &log).unwrap();
+    let mut indexer = indexer(&channel, &auth);
+    indexer.ingest_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let (key_a, key_b) = (utxo_a.public_key.to_array(), utxo_b.public_key.to_array());
+    let spent = indexer.utxo_by_key(&key_a).unwrap();
+    assert_eq!(spent.created_ledger, Some(deposit_ledger));
+    assert_eq!(spent.spent_ledger, Some(transfer_ledger));
+    assert_eq!(spent.amount, None);
+    assert_eq!(
+        indexer
+            .unspent()
+            .map(|(hash, _)| *hash)
+            .collect::<std::vec::Vec<_>>(),
+        std::vec![hash_utxo_key(&key_b)]
+    );
+    assert_eq!(
+        indexer.unspent_notes().collect::<std::vec::Vec<_>>(),
+        std::vec![(&hash_utxo_key(&key_b), note.to_alloc_vec().as_slice())]
+    );
+    assert_eq!(
+        indexer
+            .unspent_ephemeral_keys()
+            .collect::<std::vec::Vec<_>>(),
+        std::vec![(&hash_utxo_key(&key_b), &ephemeral_key.to_array())]
+    );
+
+    assert_eq!(
+        indexer.providers().collect::<std::vec::Vec<_>>(),
+        std::vec![&sc_address(&provider_b)]
+    );
+    assert_eq!(
+        indexer.channel_state(&sc_address(&channel.address)),
+        Some(&ChannelState {
+            asset: sc_address(&token.address),
+            enabled: true,
+        })
+    );
+
+    assert_eq!(
+        indexer.supply_history(),
+        &[
+            SupplyPoint {
+                ledger: deposit_ledger,
+                supply: 500,
+            },
+            SupplyPoint {
+                ledger: transfer_ledger,
+                supply: 300,
+            },
+        ]
+    );
+    assert_eq!(indexer.supply_at(deposit_ledger), 500);
+    assert_eq!(indexer.check_supply(channel.supply()), Ok(()));
+    assert_eq!(
+        indexer.check_supply(301),
+        Err(IndexerError::SupplyMismatch {
+            indexed: 300,
+            on_chain: 301,
+        })
+    );
+}
+
+#[test]
+fn indexes_utxo_core_events_with_amounts() {
+    let e = Env::default();
+    let (channel, auth, _) = create_contracts(&e);
+    let (a, b) = (
+        P256KeyPair::generate(&e).public_key,
+        P256KeyPair::generate(&e).public_key,
+    );
+
+    let events = [
+        (
+            1,
+            BundleEvent {
+                name: Symbol::new(&e, "bundle"),
+                spend: vec![&e],
+                create: vec![&e, (a.clone(), 100_i128)],
+                deposited: 100,
+                withdrawn: 0,
+            }
+            .to_xdr(&e, &channel.address),
+        ),
+        (
+            2,
+            UtxoEvent {
+                name: Symbol::new(&e, "utxo"),
+                utxo: a.clone(),
+                action: Symbol::new(&e, "spend"),
+                amount: 100,
+            }
+            .to_xdr(&e, &channel.address),
+        ),
+        (
+            2,
+            UtxoEvent {
+                name: Symbol::new(&e, "utxo"),
+                utxo: b.clone(),
+                action: Symbol::new(&e, "create"),
+                amount: 60,
+            }
+            .to_xdr(&e, &channel.address),
+        ),
+        (
+            2,
+            BundleEvent {
+                name: Symbol::new(&e, "bundle"),
+                spend: vec![&e, a.clone()],
+                create: vec![&e, (b.clone(), 60_i128)],
+                deposited: 0,
+                withdrawn: 40,
+            }
+            .to_xdr(&e, &channel.address),
+        ),
+    ];
+
+    let mut indexer = indexer(&channel, &auth);
+    for (ledger, event) in &events {
+        indexer
+            .ingest(&EventRecord::from_xdr(*ledger, event).unwrap())
+            .unwrap();
+    }
+
+    let b = indexer.utxo_by_key(&b.to_array()).unwrap();
+    assert_eq!(
+        (b.amount, b.created_ledger, b.spent_ledger),
+        (Some(60), Some(2), None)
+    );
+    assert!(!indexer.utxo_by_key(&a.to_array()).unwrap().is_unspent());
+    assert_eq!(indexer.supply_at(1), 100);
+    assert_eq!(indexer.supply(), 60);
+    assert_eq!(indexer.check_supply(60), Ok(()));
+}
+
+#[test]
+fn ignores_other_contracts_and_rejects_malformed_events() {
+    let e = Env::default();
+    let (channel, auth, token) = create_contracts(&e);
+    let mut indexer = indexer(&channel, &auth);
+
+    let transact = ScVal::Symbol("transact".try_into().unwrap());
+    let record = |contract: &Address| EventRecord {
+        ledger: 1,
+        contract: sc_address(contract),
+        topics: std::vec![transact.clone()],
+        value: ScVal::Void,
+    };
+
+    assert_eq!(indexer.ingest(&record(&token.address)), Ok(()));
+    assert_eq!(
+        indexer.ingest(&record(&channel.address)),
+        Err(IndexerError::Malformed("transact".into()))
+    );
+
+    let mut log = std::vec::Vec::new();
+    write_events(&mut log, &[record(&channel.address)]).unwrap();
+    assert_eq!(
+        read_events(log.as_slice()).unwrap(),
+        std::vec![record(&channel.address)]
+    );
+    assert!(matches!(
+        read_events(&b"{\"ledger\": 1}\n"[..]),
+        Err(IndexerError::Json(_))
+    ));
+}
@@ -0,0 +1,16 @@
+//! The interface a compliance-policy contract implements to screen a Privacy Channel's external
+//! legs.
+
+use soroban_sdk::{contractclient, Address, Env};
+
+/// Called by a Privacy Channel with a compliance policy set, once per deposit and withdrawal leg
+/// and before the asset is moved. Returning `false` rejects the whole bundle; a policy may also
+/// panic with its own error.
+#[contractclient(name = "CompliancePolicyClient")]
+pub trait CompliancePolicy {
+    /// Screens a deposit of `amount` from `from`.
+    fn check_deposit(e: Env, from: Address, amount: i128) -> bool;
+
+    /// Screens a withdrawal of `amount` to `to`.
+    fn check_withdraw(e: Env, to: Address, amount: i128) -> bool;
+}
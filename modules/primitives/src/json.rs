@@ -0,0 +1,590 @@
+//! Canonical JSON schema for channel types, enabled by the `std` feature.
+//!
+//! Keys, hashes and signatures are lowercase hex, addresses are strkeys (`G...` / `C...`) and
+//! `i128` amounts are decimal strings, so values survive JSON parsers without 64-bit integers.
+//! Enums are tagged by their snake_case variant name, e.g.
+//! `{"create": {"utxo": "04...", "amount": "100"}}`.
+//!
+//! Documents exchanged between services are wrapped in a [`Versioned`] envelope carrying
+//! [`JSON_SCHEMA_VERSION`]; see [`to_json_string`] and [`from_json_str`].
+
+use core::{fmt, str::FromStr};
+use std::{string::String, string::ToString, vec::Vec as StdVec};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use soroban_sdk::{
+    xdr::{Limits, ReadXdr, ScAddress, ScVal, WriteXdr},
+    Address, Bytes, BytesN, Env, IntoVal, Map, TryFromVal, Val, Vec,
+};
+
+use crate::{AuthRequirements, ChannelOperation, Condition, Signature, Signatures, SignerKey};
+
+/// The version of the JSON schema produced by this crate.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum JsonError {
+    /// The document is not valid JSON for the expected type.
+    Json(String),
+    /// The document was written for a schema version this crate does not read.
+    UnsupportedVersion(u32),
+    /// A hex field is malformed or has the wrong length.
+    InvalidHex(String),
+    /// An address field is not a valid strkey.
+    InvalidAddress(String),
+    /// The value cannot be converted to or from XDR.
+    Xdr,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "invalid JSON: {err}"),
+            Self::UnsupportedVersion(version) => {
+                write!(
+                    f,
+                    "unsupported schema version {version}, expected {JSON_SCHEMA_VERSION}"
+                )
+            }
+            Self::InvalidHex(value) => write!(f, "invalid hex value `{value}`"),
+            Self::InvalidAddress(value) => write!(f, "invalid address `{value}`"),
+            Self::Xdr => write!(f, "value cannot be converted to XDR"),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+impl From<serde_json::Error> for JsonError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err.to_string())
+    }
+}
+
+/// A contract type with a canonical JSON form.
+pub trait JsonCodec: Sized {
+    type Json: Serialize + DeserializeOwned;
+
+    fn to_json(&self) -> Self::Json;
+
+    fn from_json(e: &Env, json: &Self::Json) -> Result<Self, JsonError>;
+}
+
+/// A JSON document tagged with the schema version it was written with.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Versioned<T> {
+    pub version: u32,
+    pub value: T,
+}
+
+/// Serializes `value` as a versioned JSON document.
+pub fn to_json_string<T: JsonCodec>(value: &T) -> Result<String, JsonError> {
+    Ok(serde_json::to_string_pretty(&Versioned {
+        version: JSON_SCHEMA_VERSION,
+        value: value.to_json(),
+    })?)
+}
+
+/// Parses a versioned JSON document written by [`to_json_string`].
+pub fn from_json_str<T: JsonCodec>(e: &Env, json: &str) -> Result<T, JsonError> {
+    let document: Versioned<T::Json> = serde_json::from_str(json)?;
+    if document.version != JSON_SCHEMA_VERSION {
+        return Err(JsonError::UnsupportedVersion(document.version));
+    }
+    T::from_json(e, &document.value)
+}
+
+/// Converts a contract value to its XDR `ScVal`.
+pub fn to_sc_val<T: IntoVal<Env, Val> + Clone>(e: &Env, value: &T) -> Result<ScVal, JsonError> {
+    let val: Val = value.clone().into_val(e);
+    ScVal::try_from_val(e, &val).map_err(|_| JsonError::Xdr)
+}
+
+/// Converts an XDR `ScVal` back to a contract value.
+pub fn from_sc_val<T: TryFromVal<Env, Val>>(e: &Env, sc_val: &ScVal) -> Result<T, JsonError> {
+    let val = Val::try_from_val(e, sc_val).map_err(|_| JsonError::Xdr)?;
+    T::try_from_val(e, &val).map_err(|_| JsonError::Xdr)
+}
+
+/// Encodes a contract value as base64 `ScVal` XDR.
+pub fn to_xdr_base64<T: IntoVal<Env, Val> + Clone>(
+    e: &Env,
+    value: &T,
+) -> Result<String, JsonError> {
+    to_sc_val(e, value)?
+        .to_xdr_base64(Limits::none())
+        .map_err(|_| JsonError::Xdr)
+}
+
+/// Decodes a contract value from base64 `ScVal` XDR.
+pub fn from_xdr_base64<T: TryFromVal<Env, Val>>(e: &Env, xdr: &str) -> Result<T, JsonError> {
+    let sc_val = ScVal::from_xdr_base64(xdr.trim(), Limits::none()).map_err(|_| JsonError::Xdr)?;
+    from_sc_val(e, &sc_val)
+}
+
+/// Serde adapter for `i128` amounts as decimal strings.
+pub mod decimal {
+    use std::string::{String, ToString};
+
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(amount: &i128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&amount.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i128, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value
+            .parse()
+            .map_err(|_| D::Error::custom("amount must be a decimal i128 string"))
+    }
+}
+
+pub fn hex_string<const N: usize>(bytes: &BytesN<N>) -> String {
+    hex::encode(bytes.to_array())
+}
+
+pub fn hex_bytes<const N: usize>(e: &Env, value: &str) -> Result<BytesN<N>, JsonError> {
+    let mut array = [0u8; N];
+    hex::decode_to_slice(value, &mut array)
+        .map_err(|_| JsonError::InvalidHex(value.to_string()))?;
+    Ok(BytesN::from_array(e, &array))
+}
+
+pub fn address_string(address: &Address) -> String {
+    ScAddress::from(address).to_string()
+}
+
+pub fn parse_address(e: &Env, value: &str) -> Result<Address, JsonError> {
+    let sc_address =
+        ScAddress::from_str(value).map_err(|_| JsonError::InvalidAddress(value.to_string()))?;
+    Address::try_from_val(e, &sc_address).map_err(|_| JsonError::InvalidAddress(value.to_string()))
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum ConditionJson {
+    Create {
+        utxo: String,
+        #[serde(with = "decimal")]
+        amount: i128,
+    },
+    ExtDeposit {
+        address: String,
+        #[serde(with = "decimal")]
+        amount: i128,
+    },
+    ExtWithdraw {
+        address: String,
+        #[serde(with = "decimal")]
+        amount: i128,
+    },
+    ExtIntegration {
+        adapter: String,
+        utxos: StdVec<String>,
+        #[serde(with = "decimal")]
+        amount: i128,
+    },
+    Note {
+        utxo: String,
+        hash: String,
+    },
+    Recovery {
+        utxo: String,
+        guardian: String,
+        delay: u32,
+    },
+    CancelRecovery {
+        utxo: String,
+    },
+    EphemeralKey {
+        utxo: String,
+        key: String,
+    },
+}
+
+impl JsonCodec for Condition {
+    type Json = ConditionJson;
+
+    fn to_json(&self) -> ConditionJson {
+        match self {
+            Condition::Create(utxo, amount) => ConditionJson::Create {
+                utxo: hex_string(utxo),
+                amount: *amount,
+            },
+            Condition::ExtDeposit(address, amount) => ConditionJson::ExtDeposit {
+                address: address_string(address),
+                amount: *amount,
+            },
+            Condition::ExtWithdraw(address, amount) => ConditionJson::ExtWithdraw {
+                address: address_string(address),
+                amount: *amount,
+            },
+            Condition::ExtIntegration(adapter, utxos, amount) => ConditionJson::ExtIntegration {
+                adapter: address_string(adapter),
+                utxos: utxos.iter().map(|utxo| hex_string(&utxo)).collect(),
+                amount: *amount,
+            },
+            Condition::Note(utxo, hash) => ConditionJson::Note {
+                utxo: hex_string(utxo),
+                hash: hex_string(hash),
+            },
+            Condition::Recovery(utxo, guardian, delay) => ConditionJson::Recovery {
+                utxo: hex_string(utxo),
+                guardian: hex_string(guardian),
+                delay: *delay,
+            },
+            Condition::CancelRecovery(utxo) => ConditionJson::CancelRecovery {
+                utxo: hex_string(utxo),
+            },
+            Condition::EphemeralKey(utxo, key) => ConditionJson::EphemeralKey {
+                utxo: hex_string(utxo),
+                key: hex_string(key),
+            },
+        }
+    }
+
+    fn from_json(e: &Env, json: &ConditionJson) -> Result<Self, JsonError> {
+        Ok(match json {
+            ConditionJson::Create { utxo, amount } => {
+                Condition::Create(hex_bytes(e, utxo)?, *amount)
+            }
+            ConditionJson::ExtDeposit { address, amount } => {
+                Condition::ExtDeposit(parse_address(e, address)?, *amount)
+            }
+            ConditionJson::ExtWithdraw { address, amount } => {
+                Condition::ExtWithdraw(parse_address(e, address)?, *amount)
+            }
+            ConditionJson::ExtIntegration {
+                adapter,
+                utxos,
+                amount,
+            } => {
+                let mut keys = Vec::new(e);
+                for utxo in utxos {
+                    keys.push_back(hex_bytes(e, utxo)?);
+                }
+                Condition::ExtIntegration(parse_address(e, adapter)?, keys, *amount)
+            }
+            ConditionJson::Note { utxo, hash } => {
+                Condition::Note(hex_bytes(e, utxo)?, hex_bytes(e, hash)?)
+            }
+            ConditionJson::Recovery {
+                utxo,
+                guardian,
+                delay,
+            } => Condition::Recovery(hex_bytes(e, utxo)?, hex_bytes(e, guardian)?, *delay),
+            ConditionJson::CancelRecovery { utxo } => {
+                Condition::CancelRecovery(hex_bytes(e, utxo)?)
+            }
+            ConditionJson::EphemeralKey { utxo, key } => {
+                Condition::EphemeralKey(hex_bytes(e, utxo)?, hex_bytes(e, key)?)
+            }
+        })
+    }
+}
+
+impl<T: JsonCodec + Clone + IntoVal<Env, Val> + TryFromVal<Env, Val>> JsonCodec for Vec<T> {
+    type Json = StdVec<T::Json>;
+
+    fn to_json(&self) -> StdVec<T::Json> {
+        self.iter().map(|item| item.to_json()).collect()
+    }
+
+    fn from_json(e: &Env, json: &StdVec<T::Json>) -> Result<Self, JsonError> {
+        let mut items = Vec::new(e);
+        for item in json {
+            items.push_back(T::from_json(e, item)?);
+        }
+        Ok(items)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum SignerKeyJson {
+    P256(String),
+    Ed25519(String),
+    Provider(String),
+    ProviderContract(String),
+}
+
+impl JsonCodec for SignerKey {
+    type Json = SignerKeyJson;
+
+    fn to_json(&self) -> SignerKeyJson {
+        match self {
+            SignerKey::P256(key) => SignerKeyJson::P256(hex_string(key)),
+            SignerKey::Ed25519(key) => SignerKeyJson::Ed25519(hex_string(key)),
+            SignerKey::Provider(key) => SignerKeyJson::Provider(hex_string(key)),
+            SignerKey::ProviderContract(address) => {
+                SignerKeyJson::ProviderContract(address_string(address))
+            }
+        }
+    }
+
+    fn from_json(e: &Env, json: &SignerKeyJson) -> Result<Self, JsonError> {
+        Ok(match json {
+            SignerKeyJson::P256(key) => SignerKey::P256(hex_bytes(e, key)?),
+            SignerKeyJson::Ed25519(key) => SignerKey::Ed25519(hex_bytes(e, key)?),
+            SignerKeyJson::Provider(key) => SignerKey::Provider(hex_bytes(e, key)?),
+            SignerKeyJson::ProviderContract(address) => {
+                SignerKey::ProviderContract(parse_address(e, address)?)
+            }
+        })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum SignatureJson {
+    P256(String),
+    Ed25519(String),
+    Secp256k1(String),
+    Bls12_381(String),
+    Delegated,
+}
+
+impl JsonCodec for Signature {
+    type Json = SignatureJson;
+
+    fn to_json(&self) -> SignatureJson {
+        match self {
+            Signature::P256(sig) => SignatureJson::P256(hex_string(sig)),
+            Signature::Ed25519(sig) => SignatureJson::Ed25519(hex_string(sig)),
+            Signature::Secp256k1(sig) => SignatureJson::Secp256k1(hex_string(sig)),
+            Signature::BLS12_381(sig) => SignatureJson::Bls12_381(hex_string(sig)),
+            Signature::Delegated => SignatureJson::Delegated,
+        }
+    }
+
+    fn from_json(e: &Env, json: &SignatureJson) -> Result<Self, JsonError> {
+        Ok(match json {
+            SignatureJson::P256(sig) => Signature::P256(hex_bytes(e, sig)?),
+            SignatureJson::Ed25519(sig) => Signature::Ed25519(hex_bytes(e, sig)?),
+            SignatureJson::Secp256k1(sig) => Signature::Secp256k1(hex_bytes(e, sig)?),
+            SignatureJson::Bls12_381(sig) => Signature::BLS12_381(hex_bytes(e, sig)?),
+            SignatureJson::Delegated => Signature::Delegated,
+        })
+    }
+}
+
+/// One signer's conditions. `AuthRequirements` is a list of these, ordered by signer.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RequirementJson {
+    pub signer: SignerKeyJson,
+    pub conditions: StdVec<ConditionJson>,
+}
+
+impl JsonCodec for AuthRequirements {
+    type Json = StdVec<RequirementJson>;
+
+    fn to_json(&self) -> StdVec<RequirementJson> {
+        self.0
+            .iter()
+            .map(|(signer, conditions)| RequirementJson {
+                signer: signer.to_json(),
+                conditions: conditions.to_json(),
+            })
+            .collect()
+    }
+
+    fn from_json(e: &Env, json: &StdVec<RequirementJson>) -> Result<Self, JsonError> {
+        let mut map = Map::new(e);
+        for requirement in json {
+            map.set(
+                SignerKey::from_json(e, &requirement.signer)?,
+                Vec::<Condition>::from_json(e, &requirement.conditions)?,
+            );
+        }
+        Ok(AuthRequirements(map))
+    }
+}
+
+/// One signer's signature. `Signatures` is a list of these, ordered by signer.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignatureEntryJson {
+    pub signer: SignerKeyJson,
+    pub signature: SignatureJson,
+    pub live_until_ledger: u32,
+}
+
+impl JsonCodec for Signatures {
+    type Json = StdVec<SignatureEntryJson>;
+
+    fn to_json(&self) -> StdVec<SignatureEntryJson> {
+        self.0
+            .iter()
+            .map(
+                |(signer, (signature, live_until_ledger))| SignatureEntryJson {
+                    signer: signer.to_json(),
+                    signature: signature.to_json(),
+                    live_until_ledger,
+                },
+            )
+            .collect()
+    }
+
+    fn from_json(e: &Env, json: &StdVec<SignatureEntryJson>) -> Result<Self, JsonError> {
+        let mut map = Map::new(e);
+        for entry in json {
+            map.set(
+                SignerKey::from_json(e, &entry.signer)?,
+                (
+                    Signature::from_json(e, &entry.signature)?,
+                    entry.live_until_ledger,
+                ),
+            );
+        }
+        Ok(Signatures(map))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpendJson {
+    pub utxo: String,
+    #[serde(default)]
+    pub conditions: StdVec<ConditionJson>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateJson {
+    pub utxo: String,
+    #[serde(with = "decimal")]
+    pub amount: i128,
+    /// The UTXO's encrypted note from `ChannelOperation::notes`, as hex.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// The UTXO's stealth ephemeral key from `ChannelOperation::ephemeral_keys`, as hex.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ephemeral_key: Option<String>,
+}
+
+/// A deposit or withdraw entry.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExternalJson {
+    pub address: String,
+    #[serde(with = "decimal")]
+    pub amount: i128,
+    #[serde(default)]
+    pub conditions: StdVec<ConditionJson>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChannelOperationJson {
+    pub spend: StdVec<SpendJson>,
+    pub create: StdVec<CreateJson>,
+    pub deposit: StdVec<ExternalJson>,
+    pub withdraw: StdVec<ExternalJson>,
+    /// The depositors from `ChannelOperation::allowance_deposits`, as strkeys.
+    #[serde(skip_serializing_if = "StdVec::is_empty")]
+    pub allowance_deposits: StdVec<String>,
+}
+
+impl JsonCodec for ChannelOperation {
+    type Json = ChannelOperationJson;
+
+    fn to_json(&self) -> ChannelOperationJson {
+        ChannelOperationJson {
+            spend: self
+                .spend
+                .iter()
+                .map(|(utxo, conditions)| SpendJson {
+                    utxo: hex_string(&utxo),
+                    conditions: conditions.to_json(),
+                })
+                .collect(),
+            create: self
+                .create
+                .iter()
+                .map(|(utxo, amount)| CreateJson {
+                    note: self
+                        .notes
+                        .get(utxo.clone())
+                        .map(|note| hex::encode(note.to_alloc_vec())),
+                    ephemeral_key: self
+                        .ephemeral_keys
+                        .get(utxo.clone())
+                        .map(|key| hex_string(&key)),
+                    utxo: hex_string(&utxo),
+                    amount,
+                })
+                .collect(),
+            deposit: external_json(&self.deposit),
+            withdraw: external_json(&self.withdraw),
+            allowance_deposits: self
+                .allowance_deposits
+                .iter()
+                .map(|address| address_string(&address))
+                .collect(),
+        }
+    }
+
+    fn from_json(e: &Env, json: &ChannelOperationJson) -> Result<Self, JsonError> {
+        let mut op = ChannelOperation {
+            spend: Vec::new(e),
+            create: Vec::new(e),
+            deposit: Vec::new(e),
+            withdraw: Vec::new(e),
+            notes: Map::new(e),
+            ephemeral_keys: Map::new(e),
+            allowance_deposits: Vec::new(e),
+        };
+        for spend in &json.spend {
+            op.spend.push_back((
+                hex_bytes(e, &spend.utxo)?,
+                Vec::from_json(e, &spend.conditions)?,
+            ));
+        }
+        for create in &json.create {
+            let utxo: BytesN<65> = hex_bytes(e, &create.utxo)?;
+            if let Some(note) = &create.note {
+                let note = hex::decode(note).map_err(|_| JsonError::InvalidHex(note.clone()))?;
+                op.notes.set(utxo.clone(), Bytes::from_slice(e, &note));
+            }
+            if let Some(ephemeral_key) = &create.ephemeral_key {
+                op.ephemeral_keys
+                    .set(utxo.clone(), hex_bytes(e, ephemeral_key)?);
+            }
+            op.create.push_back((utxo, create.amount));
+        }
+        op.deposit = external_from_json(e, &json.deposit)?;
+        op.withdraw = external_from_json(e, &json.withdraw)?;
+        for address in &json.allowance_deposits {
+            op.allowance_deposits.push_back(parse_address(e, address)?);
+        }
+        Ok(op)
+    }
+}
+
+fn external_json(list: &Vec<(Address, i128, Vec<Condition>)>) -> StdVec<ExternalJson> {
+    list.iter()
+        .map(|(address, amount, conditions)| ExternalJson {
+            address: address_string(&address),
+            amount,
+            conditions: conditions.to_json(),
+        })
+        .collect()
+}
+
+fn external_from_json(
+    e: &Env,
+    json: &[ExternalJson],
+) -> Result<Vec<(Address, i128, Vec<Condition>)>, JsonError> {
+    let mut list = Vec::new(e);
+    for external in json {
+        list.push_back((
+            parse_address(e, &external.address)?,
+            external.amount,
+            Vec::from_json(e, &external.conditions)?,
+        ));
+    }
+    Ok(list)
+}
@@ -1,5 +1,15 @@
 #![no_std]
 
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod compliance;
+#[cfg(feature = "std")]
+pub mod json;
+
+#[cfg(all(test, feature = "std"))]
+mod test;
+
 use soroban_sdk::{contracttype, crypto::Hash, xdr::ToXdr, Address, Bytes, BytesN, Env, Map, Vec};
 
 #[derive(Clone)]
@@ -9,6 +19,10 @@ pub enum Condition {
     ExtDeposit(Address, i128),                      // Spend to deposit to an account
     ExtWithdraw(Address, i128),                     // Spend to withdraw to an account
     ExtIntegration(Address, Vec<BytesN<65>>, i128), // contract id of the adapter, the keys to authorize the withdrawal, the amount to deposit
+    Note(BytesN<65>, BytesN<32>), // Spend to attach a note to a created UTXO: the UTXO and sha256 of the note
+    Recovery(BytesN<65>, BytesN<65>, u32), // Spend to give a created UTXO a recovery guardian: the UTXO, the guardian's P256 key and the delay in ledgers
+    CancelRecovery(BytesN<65>), // Signed by a UTXO's own key to cancel its pending guardian recovery
+    EphemeralKey(BytesN<65>, BytesN<65>), // Spend to publish a created UTXO's stealth ephemeral key: the UTXO and the key
 }
 
 impl Condition {
@@ -23,6 +37,16 @@ impl Condition {
             (Condition::ExtWithdraw(addr1, amount1), Condition::ExtWithdraw(addr2, amount2)) => {
                 addr1 == addr2 && amount1 != amount2
             }
+            (Condition::Note(utxo1, hash1), Condition::Note(utxo2, hash2)) => {
+                utxo1 == utxo2 && hash1 != hash2
+            }
+            (Condition::EphemeralKey(utxo1, key1), Condition::EphemeralKey(utxo2, key2)) => {
+                utxo1 == utxo2 && key1 != key2
+            }
+            (
+                Condition::Recovery(utxo1, guardian1, delay1),
+                Condition::Recovery(utxo2, guardian2, delay2),
+            ) => utxo1 == utxo2 && (guardian1 != guardian2 || delay1 != delay2),
             (
                 Condition::ExtIntegration(adapter1, utxos1, amount1),
                 Condition::ExtIntegration(adapter2, utxos2, amount2),
@@ -79,9 +103,10 @@ pub struct Signatures(pub Map<SignerKey, (Signature, u32)>); // Signature with a
 #[contracttype]
 #[derive(Clone, Debug)]
 pub enum SignerKey {
-    P256(BytesN<65>),     // SEC1 uncompressed
-    Ed25519(BytesN<32>),  // Ed25519 public key
-    Provider(BytesN<32>), // Ed25519 public key of the provider account (Only native keys for now)
+    P256(BytesN<65>),          // SEC1 uncompressed
+    Ed25519(BytesN<32>),       // Ed25519 public key
+    Provider(BytesN<32>),      // Ed25519 public key of a native (G-address) provider account
+    ProviderContract(Address), // Contract (C-address) provider, authorized by its own `__check_auth`
 }
 
 #[contracttype]
@@ -91,6 +116,7 @@ pub enum Signature {
     Ed25519(BytesN<64>),
     Secp256k1(BytesN<65>),
     BLS12_381(BytesN<96>),
+    Delegated, // No signature: the signer authorizes the payload through `require_auth_for_args`
 }
 
 #[derive(Clone)]
@@ -100,6 +126,28 @@ pub struct AuthPayload {
     pub live_until_ledger: u32,
 }
 
+/// A privacy channel bundle, as submitted to `transact` and `transact_batch`.
+#[derive(Clone)]
+#[contracttype]
+pub struct ChannelOperation {
+    pub spend: Vec<(BytesN<65>, Vec<Condition>)>,
+    pub create: Vec<(BytesN<65>, i128)>,
+    pub deposit: Vec<(Address, i128, Vec<Condition>)>,
+    pub withdraw: Vec<(Address, i128, Vec<Condition>)>,
+    /// Encrypted notes for created UTXOs, keyed by the UTXO. Published in the `Transact` event;
+    /// a spender binds one with a `Condition::Note` of its `sha256`.
+    pub notes: Map<BytesN<65>, Bytes>,
+    /// Stealth ephemeral keys for created UTXOs, keyed by the UTXO. Published in the `Transact`
+    /// event for recipients to scan with their viewing key; a spender binds one with a
+    /// `Condition::EphemeralKey`.
+    pub ephemeral_keys: Map<BytesN<65>, BytesN<65>>,
+    /// Depositors in `deposit` that pay through an allowance they approved for the channel
+    /// (`transfer_from`) instead of authorizing the transfer. They authorize their conditions
+    /// followed by `true`, and the conditions must include `Condition::ExtDeposit` of the
+    /// depositor and amount, since no signed transfer binds the amount.
+    pub allowance_deposits: Vec<Address>,
+}
+
 /// Constructs the payload for processing a bundle of UTXO operations.
 ///
 /// The payload is built by concatenating in order:
@@ -107,7 +155,16 @@ pub struct AuthPayload {
 ///  - followed by all `create` conditions,
 ///  - followed by all `deposit` conditions,
 ///  - followed by all `withdraw` conditions,
-///  - followed by all `integration` conditions.
+///  - followed by all `integration` conditions,
+///  - followed by all `note` conditions,
+///  - followed by all `recovery` conditions,
+///  - followed by all `cancel recovery` conditions,
+///  - followed by all `ephemeral key` conditions.
+///
+/// The `note`, `recovery`, `cancel recovery` and `ephemeral key` sections are each prefixed with
+/// a domain tag and their condition count (little-endian 4 bytes), so conditions of one kind
+/// cannot be read as another. An empty section appends nothing, keeping the digest of payloads
+/// without these conditions unchanged.
 ///
 /// The resulting byte stream is hashed using SHA-256 to produce a digest that is
 /// used for verifying the signatures of the bundle.
@@ -118,6 +175,15 @@ pub struct AuthPayload {
 /// deterministic payloads.
 ///
 pub fn hash_payload(e: &Env, auth_payload: &AuthPayload, contract: &Bytes) -> Hash<32> {
+    // Appends a section of `count` conditions after its tag and count; nothing if it is empty.
+    let append_section = |b: &mut Bytes, tag: &[u8], count: u32, section: &Bytes| {
+        if count > 0 {
+            b.append(&Bytes::from_slice(e, tag));
+            b.append(&Bytes::from_slice(e, &count.to_le_bytes()));
+            b.append(section);
+        }
+    };
+
     let mut b = Bytes::new(&e);
     b.append(&contract);
 
@@ -125,6 +191,11 @@ pub fn hash_payload(e: &Env, auth_payload: &AuthPayload, contract: &Bytes) -> Ha
     let mut b_deposit = Bytes::new(&e);
     let mut b_withdraw = Bytes::new(&e);
     let mut b_integrate = Bytes::new(&e);
+    let mut b_note = Bytes::new(e);
+    let mut b_recovery = Bytes::new(e);
+    let mut b_cancel_recovery = Bytes::new(e);
+    let mut b_ephemeral_key = Bytes::new(e);
+    let (mut notes, mut recoveries, mut cancel_recoveries, mut ephemeral_keys) = (0, 0, 0, 0);
 
     for cond in auth_payload.conditions.iter() {
         match cond {
@@ -147,12 +218,41 @@ pub fn hash_payload(e: &Env, auth_payload: &AuthPayload, contract: &Bytes) -> Ha
                 }
                 b_integrate.append(&Bytes::from_slice(&e, &amount.to_le_bytes()));
             }
+            Condition::Note(utxo, hash) => {
+                b_note.append(&Bytes::from_slice(e, utxo.to_array().as_ref()));
+                b_note.append(&Bytes::from_slice(e, hash.to_array().as_ref()));
+                notes += 1;
+            }
+            Condition::Recovery(utxo, guardian, delay) => {
+                b_recovery.append(&Bytes::from_slice(e, utxo.to_array().as_ref()));
+                b_recovery.append(&Bytes::from_slice(e, guardian.to_array().as_ref()));
+                b_recovery.append(&Bytes::from_slice(e, &delay.to_le_bytes()));
+                recoveries += 1;
+            }
+            Condition::CancelRecovery(utxo) => {
+                b_cancel_recovery.append(&Bytes::from_slice(e, utxo.to_array().as_ref()));
+                cancel_recoveries += 1;
+            }
+            Condition::EphemeralKey(utxo, key) => {
+                b_ephemeral_key.append(&Bytes::from_slice(e, utxo.to_array().as_ref()));
+                b_ephemeral_key.append(&Bytes::from_slice(e, key.to_array().as_ref()));
+                ephemeral_keys += 1;
+            }
         }
     }
     b.append(&b_create);
     b.append(&b_deposit);
     b.append(&b_withdraw);
     b.append(&b_integrate);
+    append_section(&mut b, b"note", notes, &b_note);
+    append_section(&mut b, b"recovery", recoveries, &b_recovery);
+    append_section(
+        &mut b,
+        b"cancel_recovery",
+        cancel_recoveries,
+        &b_cancel_recovery,
+    );
+    append_section(&mut b, b"ephemeral_key", ephemeral_keys, &b_ephemeral_key);
 
     b.append(&Bytes::from_slice(
         &e,
@@ -0,0 +1,179 @@
+use std::{format, string::ToString};
+
+use soroban_sdk::{testutils::Address as _, vec, Address, Bytes, BytesN, Env, Map, Vec};
+
+use crate::{
+    hash_payload,
+    json::{
+        address_string, from_json_str, from_sc_val, from_xdr_base64, to_json_string, to_sc_val,
+        to_xdr_base64, ConditionJson, JsonCodec, JsonError, JSON_SCHEMA_VERSION,
+    },
+    AuthPayload, AuthRequirements, Condition, Signature, Signatures, SignerKey,
+};
+
+fn utxo(e: &Env, seed: u8) -> BytesN<65> {
+    let mut bytes = [seed; 65];
+    bytes[0] = 4;
+    BytesN::from_array(e, &bytes)
+}
+
+fn conditions(e: &Env) -> Vec<Condition> {
+    let adapter = Address::generate(e);
+    let mut utxos = Vec::new(e);
+    utxos.push_back(utxo(e, 3));
+
+    let mut conditions = Vec::new(e);
+    conditions.push_back(Condition::Create(utxo(e, 1), i128::MAX));
+    conditions.push_back(Condition::ExtDeposit(Address::generate(e), 1));
+    conditions.push_back(Condition::ExtWithdraw(Address::generate(e), i128::MIN));
+    conditions.push_back(Condition::ExtIntegration(
+        adapter,
+        utxos,
+        9_007_199_254_740_993,
+    ));
+    conditions.push_back(Condition::Note(utxo(e, 1), BytesN::from_array(e, &[7; 32])));
+    conditions.push_back(Condition::Recovery(utxo(e, 1), utxo(e, 4), 17_280));
+    conditions.push_back(Condition::CancelRecovery(utxo(e, 3)));
+    conditions.push_back(Condition::EphemeralKey(utxo(e, 1), utxo(e, 5)));
+    conditions
+}
+
+#[test]
+fn test_conditions_round_trip_through_json_and_sc_val() {
+    let e = Env::default();
+    let original = conditions(&e);
+
+    let json = to_json_string(&original).unwrap();
+    let parsed: Vec<Condition> = from_json_str(&e, &json).unwrap();
+    let sc_val = to_sc_val(&e, &parsed).unwrap();
+    let decoded: Vec<Condition> = from_sc_val(&e, &sc_val).unwrap();
+
+    assert_eq!(to_sc_val(&e, &original).unwrap(), sc_val);
+    assert_eq!(to_json_string(&decoded).unwrap(), json);
+}
+
+#[test]
+fn test_amounts_are_decimal_strings() {
+    let e = Env::default();
+    let json = serde_json::to_value(conditions(&e).to_json()).unwrap();
+
+    assert_eq!(
+        json[0]["create"]["amount"],
+        serde_json::json!(i128::MAX.to_string())
+    );
+    assert_eq!(
+        json[2]["ext_withdraw"]["amount"],
+        "-170141183460469231731687303715884105728"
+    );
+    assert_eq!(json[3]["ext_integration"]["amount"], "9007199254740993");
+
+    let number: Result<ConditionJson, _> =
+        serde_json::from_str(r#"{"ext_deposit": {"address": "G", "amount": 5}}"#);
+    assert!(number.is_err());
+}
+
+#[test]
+fn test_auth_requirements_and_signatures_round_trip() {
+    let e = Env::default();
+    let owner = SignerKey::P256(utxo(&e, 1));
+    let depositor = SignerKey::Ed25519(BytesN::from_array(&e, &[2; 32]));
+
+    let mut requirements = Map::new(&e);
+    requirements.set(owner.clone(), conditions(&e));
+    requirements.set(depositor.clone(), Vec::new(&e));
+    let requirements = AuthRequirements(requirements);
+
+    let mut signatures = Map::new(&e);
+    signatures.set(
+        owner,
+        (Signature::P256(BytesN::from_array(&e, &[5; 64])), 100),
+    );
+    signatures.set(
+        depositor,
+        (Signature::Ed25519(BytesN::from_array(&e, &[6; 64])), 200),
+    );
+    let signatures = Signatures(signatures);
+
+    let json = to_json_string(&requirements).unwrap();
+    let xdr = to_xdr_base64(&e, &from_json_str::<AuthRequirements>(&e, &json).unwrap()).unwrap();
+    let decoded: AuthRequirements = from_xdr_base64(&e, &xdr).unwrap();
+    assert_eq!(to_xdr_base64(&e, &requirements).unwrap(), xdr);
+    assert_eq!(to_json_string(&decoded).unwrap(), json);
+
+    let json = to_json_string(&signatures).unwrap();
+    let xdr = to_xdr_base64(&e, &from_json_str::<Signatures>(&e, &json).unwrap()).unwrap();
+    let decoded: Signatures = from_xdr_base64(&e, &xdr).unwrap();
+    assert_eq!(to_xdr_base64(&e, &signatures).unwrap(), xdr);
+    assert_eq!(to_json_string(&decoded).unwrap(), json);
+}
+
+#[test]
+fn test_rejects_unknown_versions_and_malformed_fields() {
+    let e = Env::default();
+    let address = address_string(&Address::generate(&e));
+
+    let future = format!(r#"{{"version": {}, "value": []}}"#, JSON_SCHEMA_VERSION + 1);
+    assert_eq!(
+        from_json_str::<Vec<Condition>>(&e, &future).err(),
+        Some(JsonError::UnsupportedVersion(JSON_SCHEMA_VERSION + 1))
+    );
+
+    let short_utxo = r#"{"version": 1, "value": [{"create": {"utxo": "04ab", "amount": "1"}}]}"#;
+    assert_eq!(
+        from_json_str::<Vec<Condition>>(&e, short_utxo).err(),
+        Some(JsonError::InvalidHex("04ab".to_string()))
+    );
+
+    let bad_address = format!(
+        r#"{{"version": 1, "value": [{{"ext_deposit": {{"address": "{}x", "amount": "1"}}}}]}}"#,
+        address
+    );
+    assert!(matches!(
+        from_json_str::<Vec<Condition>>(&e, &bad_address),
+        Err(JsonError::InvalidAddress(_))
+    ));
+
+    assert!(matches!(
+        from_json_str::<Vec<Condition>>(&e, r#"{"value": []}"#),
+        Err(JsonError::Json(_))
+    ));
+}
+
+fn payload_hash(e: &Env, conditions: Vec<Condition>) -> BytesN<32> {
+    let payload = AuthPayload {
+        conditions,
+        live_until_ledger: 100,
+    };
+    hash_payload(e, &payload, &Bytes::from_array(e, &[9; 32])).to_bytes()
+}
+
+#[test]
+fn test_payload_sections_of_equal_bytes_hash_differently() {
+    let e = Env::default();
+    let (a, b) = (utxo(&e, 1), utxo(&e, 2));
+
+    // 130 condition bytes each: two cancels against one ephemeral key.
+    let cancels = payload_hash(
+        &e,
+        vec![
+            &e,
+            Condition::CancelRecovery(a.clone()),
+            Condition::CancelRecovery(b.clone()),
+        ],
+    );
+    let ephemeral = payload_hash(&e, vec![&e, Condition::EphemeralKey(a.clone(), b.clone())]);
+    assert_ne!(cancels, ephemeral);
+}
+
+#[test]
+fn test_payloads_without_tagged_sections_keep_their_hash() {
+    let e = Env::default();
+    let utxo = utxo(&e, 1);
+    let hash = payload_hash(&e, vec![&e, Condition::Create(utxo.clone(), 5)]);
+
+    let mut preimage = Bytes::from_array(&e, &[9; 32]);
+    preimage.extend_from_array(&utxo.to_array());
+    preimage.extend_from_array(&5_i128.to_le_bytes());
+    preimage.extend_from_array(&100_u32.to_le_bytes());
+    assert_eq!(hash, e.crypto().sha256(&preimage).to_bytes());
+}
@@ -1,55 +1,391 @@
 #![no_std]
 
+use core::cmp::Ordering;
+
 use soroban_sdk::{contracttype, panic_with_error, Bytes, BytesN, Env};
 
 pub use moonlight_errors::Error;
 
+mod memory;
+mod temporary;
 #[cfg(test)]
 mod test;
 
-/// Persistent storage key for a UTXO's spend state.
+pub use memory::MemoryStore;
+pub use temporary::TemporaryStore;
+
+/// Persistent storage keys for UTXO spend state.
 #[derive(Clone)]
 #[contracttype]
 pub enum UTXOCoreDataKey {
-    /// A 32-byte hash of a 65-byte UTXO public key.
+    /// A 32-byte hash of a 65-byte UTXO public key. Holds the amount of an unspent UTXO.
     UTXO(BytesN<32>),
+    /// A spent-set bucket, addressed by the leading [`Store::SPENT_BUCKET_BITS`] bits of a UTXO
+    /// key hash and a generation. Holds the sorted, packed fingerprints of up to
+    /// [`Store::SPENT_BUCKET_CAPACITY`] spent UTXOs; once a generation is full, later spends with
+    /// the same prefix roll over into the next one.
+    SpentBucket(u32, u32),
+}
+
+/// The spend state of a UTXO key.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum UtxoState {
+    /// No record exists for the key; it is free to be created.
+    Missing,
+    /// The UTXO is unspent and holds this amount.
+    Unspent(i128),
+    /// The UTXO has been spent and can never be recreated.
+    Spent,
+    /// An entry backing the key is archived and must be restored before its state can be read.
+    ///
+    /// A contract never observes this state: the host restores or rejects archived entries before
+    /// the contract runs. It is produced off-chain by [`UtxoState::from_ledger`].
+    Archived,
+}
+
+impl UtxoState {
+    /// Classifies a UTXO key from ledger data read off-chain, e.g. through RPC `getLedgerEntries`.
+    ///
+    /// `unspent` is the key's [`UTXOCoreDataKey::UTXO`] entry as `(amount, live_until_ledger)`,
+    /// and `spent_bucket` summarizes its spent-set generations as `(contains_key,
+    /// live_until_ledger)`, taking the earliest `live_until_ledger` among the generations read
+    /// (see [`Store::ledger_keys`], [`Store::spent_bucket_key`] and [`Store::bucket_contains`]).
+    /// An entry whose `live_until_ledger` is behind `current_ledger` is archived. The spent set is
+    /// only consulted when the key has no unspent entry.
+    pub fn from_ledger(
+        current_ledger: u32,
+        unspent: Option<(i128, u32)>,
+        spent_bucket: Option<(bool, u32)>,
+    ) -> Self {
+        match (unspent, spent_bucket) {
+            (Some((_, live_until)), _) | (None, Some((_, live_until)))
+                if live_until < current_ledger =>
+            {
+                UtxoState::Archived
+            }
+            (Some((amount, _)), _) if amount > 0 => UtxoState::Unspent(amount),
+            // A `0` amount is a tombstone written by the previous per-UTXO layout.
+            (Some(_), _) | (None, Some((true, _))) => UtxoState::Spent,
+            (None, _) => UtxoState::Missing,
+        }
+    }
+
+    /// Returns the amount of an unspent UTXO, or the error raised when spending a UTXO in any
+    /// other state.
+    pub fn spendable(&self) -> Result<i128, Error> {
+        match self {
+            UtxoState::Unspent(amount) => Ok(*amount),
+            UtxoState::Spent => Err(Error::UtxoAlreadySpent),
+            UtxoState::Missing => Err(Error::UtxoDoesNotExist),
+            UtxoState::Archived => Err(Error::UtxoArchived),
+        }
+    }
+
+    /// Returns the amount of an unspent UTXO.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the [`UtxoState::spendable`] error for any other state.
+    pub fn unspent_amount(&self, e: &Env) -> i128 {
+        self.spendable()
+            .unwrap_or_else(|err| panic_with_error!(e, err))
+    }
+}
+
+/// Instance storage keys for storage configuration and totals.
+#[derive(Clone)]
+#[contracttype]
+pub enum StorageConfigKey {
+    /// The [`TtlPolicy`] applied to UTXO entries and spent-set buckets.
+    TtlPolicy,
+    /// The total amount held by unspent UTXOs, see [`Store::unspent_value`].
+    UnspentValue,
+}
+
+/// TTL extension policy applied to every persistent entry the store touches.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TtlPolicy {
+    /// An entry is only extended once its remaining TTL drops below this many ledgers.
+    pub threshold: u32,
+    /// The remaining TTL, in ledgers, an extended entry is bumped to.
+    pub extend_to: u32,
+}
+
+/// A backend for UTXO spend state.
+///
+/// [`Store`] is the Soroban persistent-storage backend used by deployed channels.
+/// [`MemoryStore`] keeps state in host memory so bundle accounting can run
+/// without touching the ledger, and [`TemporaryStore`] keeps it in temporary
+/// storage for ephemeral channels.
+pub trait UtxoStore: Sized {
+    /// Runs one logical group of storage operations against a scoped store.
+    fn apply<R>(e: &Env, f: impl FnOnce(&mut Self) -> R) -> R;
+
+    /// Returns `-1` if the key has no record, `0` if it is spent, or the amount
+    /// of an unspent UTXO.
+    fn balance(&mut self, utxo65: &BytesN<65>) -> i128;
+
+    /// Creates a new unspent UTXO.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the amount is not positive or the key already has a record.
+    fn create(&mut self, utxo65: &BytesN<65>, amount: i128);
+
+    /// Spends an unspent UTXO and returns its amount.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the UTXO does not exist or was already spent.
+    fn spend(&mut self, utxo65: &BytesN<65>) -> i128;
+
+    /// Extends the lifetime of an unspent UTXO's entry. Backends without entry
+    /// lifetimes do nothing.
+    fn extend(&mut self, _utxo65: &BytesN<65>) {}
+
+    /// Returns the TTL policy applied to the entries this backend touches.
+    /// Backends without entry lifetimes report [`Store::DEFAULT_TTL_POLICY`].
+    fn ttl_policy(_e: &Env) -> TtlPolicy {
+        Store::DEFAULT_TTL_POLICY
+    }
+
+    /// Stores the TTL policy applied by every subsequent [`UtxoStore::apply`] scope.
+    /// Backends without entry lifetimes do nothing.
+    ///
+    /// Callers are responsible for authorizing the change.
+    fn set_ttl_policy(_e: &Env, _policy: &TtlPolicy) {}
+
+    /// Returns the typed state of a UTXO key.
+    ///
+    /// Equivalent to [`UtxoStore::balance`]. Never returns [`UtxoState::Archived`].
+    fn state(&mut self, utxo65: &BytesN<65>) -> UtxoState {
+        match self.balance(utxo65) {
+            amount if amount > 0 => UtxoState::Unspent(amount),
+            0 => UtxoState::Spent,
+            _ => UtxoState::Missing,
+        }
+    }
 }
 
-/// Per-UTXO spend-state storage.
+/// UTXO spend-state storage in Soroban persistent storage.
 ///
-/// Each UTXO owns a single persistent entry keyed by `hash(utxo65)` whose value
-/// is the UTXO's amount: a positive value means unspent, `0` means spent, and an
-/// absent entry means no record exists. No state is shared between UTXOs, so one
-/// UTXO's liveness never depends on another's.
+/// Each unspent UTXO owns a single persistent entry keyed by `hash(utxo65)`
+/// whose value is the UTXO's amount. Spending removes that entry and records a
+/// fingerprint of the hash in a spent-set bucket shared with other keys of the
+/// same prefix. Buckets hold at most [`Store::SPENT_BUCKET_CAPACITY`]
+/// fingerprints and roll over into a new generation when full, so every bucket
+/// write stays bounded while spent state grows by one entry per
+/// `SPENT_BUCKET_CAPACITY` spends of a prefix instead of one entry per spend. A
+/// key is unspent if its entry exists, spent if its fingerprint is in one of its
+/// prefix's generations, and has no record otherwise.
 ///
-/// Use [`Store::apply`] to run one logical group of storage reads and writes.
+/// Use [`UtxoStore::apply`] to run one logical group of storage reads and writes.
 pub struct Store {
     env: Env,
+    ttl: TtlPolicy,
+    /// Created minus spent amounts in this scope, added to the unspent total when it ends.
+    unspent_delta: i128,
 }
 
 impl Store {
-    // MOON-02: persistent-entry TTL management. A UTXO's spend-state entry backs user funds and
-    // must outlive long idle periods; without an explicit bump it would archive. Because each UTXO
-    // has its own entry, a holder keeps their own UTXO alive independently of everyone else.
+    // MOON-02: persistent-entry TTL management. An unspent UTXO's entry backs user funds and must
+    // outlive long idle periods; without an explicit bump it would archive. Because each unspent
+    // UTXO has its own entry, a holder keeps their own UTXO alive independently of everyone else,
+    // either by touching it or through `extend`. Spent-set buckets are bumped whenever they are
+    // read or written. Archival never deletes a persistent entry, so an archived bucket must be
+    // restored before it can be read and can never be observed as "absent".
+    //
+    // The bump amounts come from the configured `TtlPolicy`; these constants are the default used
+    // until a policy is set.
     const DAY_IN_LEDGERS: u32 = 17_280;
     const PERSISTENT_BUMP_AMOUNT: u32 = 30 * Self::DAY_IN_LEDGERS;
-    const PERSISTENT_LIFETIME_THRESHOLD: u32 =
-        Self::PERSISTENT_BUMP_AMOUNT - Self::DAY_IN_LEDGERS;
+    const PERSISTENT_LIFETIME_THRESHOLD: u32 = Self::PERSISTENT_BUMP_AMOUNT - Self::DAY_IN_LEDGERS;
+
+    /// The TTL policy used when none has been configured: extend to 30 days once an entry has
+    /// less than 29 days left.
+    pub const DEFAULT_TTL_POLICY: TtlPolicy = TtlPolicy {
+        threshold: Self::PERSISTENT_LIFETIME_THRESHOLD,
+        extend_to: Self::PERSISTENT_BUMP_AMOUNT,
+    };
+
+    // Spent-set layout. The top 12 bits of the key hash select one of 4,096 prefixes and hash bytes
+    // 2..10 are stored as the fingerprint. A fingerprint match can only over-report "spent"
+    // (blocking creation of a fresh key, ~2^-76 per spent UTXO); it can never hide a spent key, so
+    // re-spend and re-create protection stays exact.
+    //
+    // Each prefix is a chain of generations. Only the first non-full generation is ever written,
+    // so a full generation is immutable and a spend rewrites at most `SPENT_BUCKET_CAPACITY * 8`
+    // bytes (2 KiB). A non-full generation is always the last one, so lookups stop there. The
+    // chain grows by one entry per 256 spends of a prefix, i.e. roughly once every 1M spends
+    // across the channel.
+    //
+    // The capacity trades the per-spend rewrite against the generations a lookup walks. Each
+    // fingerprint a spend rewrites costs ~28 stroops of write fee (`bench_spent_set_cost_per_spend`):
+    // at 256 the rewrite adds at most ~7k stroops to a ~34k tombstone spend, where 1,024 added up
+    // to ~28k. Generations written with a larger capacity read as full, so lowering it keeps
+    // existing buckets valid.
+    const SPENT_BUCKET_BITS: u32 = 12;
+    const SPENT_FINGERPRINT_BYTES: u32 = 8;
 
+    /// The number of fingerprints a spent-set bucket generation holds before it rolls over.
+    pub const SPENT_BUCKET_CAPACITY: u32 = 256;
+
+    /// Returns the configured TTL policy, or [`Store::DEFAULT_TTL_POLICY`] if none is set.
+    pub fn ttl_policy(e: &Env) -> TtlPolicy {
+        e.storage()
+            .instance()
+            .get(&StorageConfigKey::TtlPolicy)
+            .unwrap_or(Self::DEFAULT_TTL_POLICY)
+    }
+
+    /// Stores the TTL policy applied by every subsequent [`UtxoStore::apply`] scope.
+    ///
+    /// Callers are responsible for authorizing the change.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threshold` is not below `extend_to`, or if `extend_to` exceeds the
+    /// network's maximum entry TTL.
+    pub fn set_ttl_policy(e: &Env, policy: &TtlPolicy) {
+        if policy.threshold >= policy.extend_to || policy.extend_to > e.storage().max_ttl() {
+            panic_with_error!(e, Error::InvalidTtlPolicy);
+        }
+
+        e.storage()
+            .instance()
+            .set(&StorageConfigKey::TtlPolicy, policy);
+    }
+
+    /// Returns the total amount held by unspent UTXOs.
+    ///
+    /// Every [`UtxoStore::apply`] scope adds the amounts it created and subtracts the amounts it
+    /// spent. Storage written before the total was introduced seeds it with
+    /// [`Store::set_unspent_value`].
+    pub fn unspent_value(e: &Env) -> i128 {
+        e.storage()
+            .instance()
+            .get(&StorageConfigKey::UnspentValue)
+            .unwrap_or(0)
+    }
+
+    /// Overwrites the unspent total, for storage migrations that predate it.
+    ///
+    /// Callers are responsible for authorizing the change and for `total` matching the unspent
+    /// UTXOs in storage.
+    pub fn set_unspent_value(e: &Env, total: i128) {
+        e.storage()
+            .instance()
+            .set(&StorageConfigKey::UnspentValue, &total);
+    }
+
+    /// Returns the persistent keys backing a UTXO: its own entry and the first generation of its
+    /// spent-set bucket.
+    ///
+    /// Off-chain readers fetch both to classify the key with [`UtxoState::from_ledger`]. When the
+    /// bucket holds [`Store::SPENT_BUCKET_CAPACITY`] fingerprints without the key, they continue
+    /// with the next generation from [`Store::spent_bucket_key`].
+    pub fn ledger_keys(e: &Env, utxo65: &BytesN<65>) -> (UTXOCoreDataKey, UTXOCoreDataKey) {
+        let h = hash_utxo_key(e, utxo65);
+        let (bucket_key, _) = spent_slot(e, &h, 0);
+        (UTXOCoreDataKey::UTXO(h), bucket_key)
+    }
+
+    /// Returns the key of a UTXO's spent-set bucket in the given generation.
+    pub fn spent_bucket_key(e: &Env, utxo65: &BytesN<65>, generation: u32) -> UTXOCoreDataKey {
+        spent_slot(e, &hash_utxo_key(e, utxo65), generation).0
+    }
+
+    /// Returns `true` if a spent-set bucket value records the UTXO as spent.
+    pub fn bucket_contains(e: &Env, bucket: &Bytes, utxo65: &BytesN<65>) -> bool {
+        let (_, fingerprint) = spent_slot(e, &hash_utxo_key(e, utxo65), 0);
+        search_bucket(bucket, &fingerprint).is_ok()
+    }
+
+    /// Returns `true` if the key hash's fingerprint is recorded in any generation of its
+    /// spent-set bucket.
+    fn is_spent(&self, h: &BytesN<32>) -> bool {
+        let mut generation = 0;
+        loop {
+            let (k, fingerprint) = spent_slot(&self.env, h, generation);
+            let Some(bucket) = self.env.storage().persistent().get::<_, Bytes>(&k) else {
+                return false;
+            };
+            self.bump_ttl(&k);
+
+            if search_bucket(&bucket, &fingerprint).is_ok() {
+                return true;
+            }
+            if !is_full(&bucket) {
+                return false;
+            }
+            generation += 1;
+        }
+    }
+
+    /// Inserts the key hash's fingerprint into the first non-full generation of its spent-set
+    /// bucket, keeping that generation sorted.
+    fn mark_spent(&self, h: &BytesN<32>) {
+        let mut generation = 0;
+        loop {
+            let (k, fingerprint) = spent_slot(&self.env, h, generation);
+            let mut bucket = self
+                .env
+                .storage()
+                .persistent()
+                .get::<_, Bytes>(&k)
+                .unwrap_or_else(|| Bytes::new(&self.env));
+
+            if !is_full(&bucket) {
+                if let Err(idx) = search_bucket(&bucket, &fingerprint) {
+                    bucket.insert_from_bytes(idx * Self::SPENT_FINGERPRINT_BYTES, fingerprint);
+                    self.env.storage().persistent().set(&k, &bucket);
+                }
+                self.bump_ttl(&k);
+                return;
+            }
+            self.bump_ttl(&k);
+            generation += 1;
+        }
+    }
+
+    #[inline(always)]
+    fn bump_ttl(&self, key: &UTXOCoreDataKey) {
+        self.env
+            .storage()
+            .persistent()
+            .extend_ttl(key, self.ttl.threshold, self.ttl.extend_to);
+    }
+}
+
+impl UtxoStore for Store {
     /// Runs UTXO storage operations in a scoped store.
     ///
     /// The closure receives the only mutable handle to storage operations. Each
-    /// `create`/`spend`/`balance` writes its own per-UTXO entry directly, so
-    /// there is no deferred commit step. If the closure panics, the invocation
-    /// aborts before any further writes.
+    /// `create`/`spend`/`balance` writes its own per-UTXO entry directly; only the
+    /// net change to [`Store::unspent_value`] is written once the closure returns.
+    /// If the closure panics, the invocation aborts before any further writes.
     ///
     /// # Panics
     ///
-    /// Panics if the closure panics.
-    pub fn apply<R>(e: &Env, f: impl FnOnce(&mut Store) -> R) -> R {
-        let mut store = Self { env: e.clone() };
+    /// Panics if the closure panics or the unspent total overflows.
+    fn apply<R>(e: &Env, f: impl FnOnce(&mut Self) -> R) -> R {
+        let mut store = Self {
+            env: e.clone(),
+            ttl: Self::ttl_policy(e),
+            unspent_delta: 0,
+        };
+
+        let result = f(&mut store);
 
-        f(&mut store)
+        if store.unspent_delta != 0 {
+            let total = Self::unspent_value(e)
+                .checked_add(store.unspent_delta)
+                .unwrap_or_else(|| panic_with_error!(e, Error::AmountOverflow));
+            Self::set_unspent_value(e, total);
+        }
+        result
     }
 
     /// Returns the balance state for a UTXO key.
@@ -60,13 +396,16 @@ impl Store {
     ///
     /// Reading an existing entry refreshes its TTL (MOON-02), so a holder keeps
     /// their own UTXO alive simply by observing it.
-    pub fn balance(&mut self, utxo65: &BytesN<65>) -> i128 {
-        let k = self.utxo_key(utxo65);
+    fn balance(&mut self, utxo65: &BytesN<65>) -> i128 {
+        let h = hash_utxo_key(&self.env, utxo65);
+        let k = UTXOCoreDataKey::UTXO(h.clone());
         match self.env.storage().persistent().get::<_, i128>(&k) {
+            // A `0` here is a tombstone written by the previous per-UTXO layout.
             Some(amount) => {
                 self.bump_ttl(&k);
                 amount
             }
+            None if self.is_spent(&h) => 0,
             None => -1,
         }
     }
@@ -77,60 +416,119 @@ impl Store {
     ///
     /// Panics if the amount is not positive or if a record already exists for
     /// the UTXO key (including a spent record, which can never be recreated).
-    pub fn create(&mut self, utxo65: &BytesN<65>, amount: i128) {
+    fn create(&mut self, utxo65: &BytesN<65>, amount: i128) {
         if amount <= 0 {
             panic_with_error!(&self.env, Error::InvalidCreateAmount);
         }
 
-        let k = self.utxo_key(utxo65);
+        let h = hash_utxo_key(&self.env, utxo65);
+        let k = UTXOCoreDataKey::UTXO(h.clone());
 
-        if self.env.storage().persistent().get::<_, i128>(&k).is_some() {
+        if self.env.storage().persistent().has(&k) || self.is_spent(&h) {
             panic_with_error!(&self.env, Error::UtxoAlreadyExists);
         }
 
         self.env.storage().persistent().set(&k, &amount);
         self.bump_ttl(&k);
+        self.unspent_delta = self
+            .unspent_delta
+            .checked_add(amount)
+            .unwrap_or_else(|| panic_with_error!(&self.env, Error::AmountOverflow));
     }
 
     /// Spends an existing unspent UTXO and returns its amount.
     ///
-    /// The entry is tombstoned in place (amount set to `0`) rather than removed,
-    /// so the spent record keeps blocking re-spend and re-creation. Its TTL is
-    /// refreshed so the tombstone survives long idle periods (MOON-02).
+    /// The UTXO's own entry is removed and its fingerprint is added to the
+    /// spent-set bucket, which keeps blocking re-spend and re-creation. The
+    /// bucket's TTL is refreshed so it survives long idle periods (MOON-02).
     ///
     /// # Panics
     ///
     /// Panics if the UTXO does not exist or was already spent.
-    pub fn spend(&mut self, utxo65: &BytesN<65>) -> i128 {
-        let k = self.utxo_key(utxo65);
+    fn spend(&mut self, utxo65: &BytesN<65>) -> i128 {
+        let h = hash_utxo_key(&self.env, utxo65);
+        let k = UTXOCoreDataKey::UTXO(h.clone());
         match self.env.storage().persistent().get::<_, i128>(&k) {
             Some(amount) if amount > 0 => {
-                self.env.storage().persistent().set(&k, &0i128);
-                // Keep the spent record alive so the UTXO cannot be recreated after archival.
-                self.bump_ttl(&k);
+                self.env.storage().persistent().remove(&k);
+                self.mark_spent(&h);
+                self.unspent_delta = self
+                    .unspent_delta
+                    .checked_sub(amount)
+                    .unwrap_or_else(|| panic_with_error!(&self.env, Error::AmountOverflow));
                 amount
             }
             Some(_) => panic_with_error!(&self.env, Error::UtxoAlreadySpent),
+            None if self.is_spent(&h) => panic_with_error!(&self.env, Error::UtxoAlreadySpent),
             None => panic_with_error!(&self.env, Error::UtxoDoesNotExist),
         }
     }
 
-    #[inline(always)]
-    fn bump_ttl(&self, key: &UTXOCoreDataKey) {
-        self.env.storage().persistent().extend_ttl(
-            key,
-            Self::PERSISTENT_LIFETIME_THRESHOLD,
-            Self::PERSISTENT_BUMP_AMOUNT,
-        );
+    fn ttl_policy(e: &Env) -> TtlPolicy {
+        Store::ttl_policy(e)
     }
 
-    #[inline(always)]
-    fn utxo_key(&self, utxo65: &BytesN<65>) -> UTXOCoreDataKey {
-        UTXOCoreDataKey::UTXO(hash_utxo_key(&self.env, utxo65))
+    fn set_ttl_policy(e: &Env, policy: &TtlPolicy) {
+        Store::set_ttl_policy(e, policy);
+    }
+
+    /// Extends the TTL of an unspent UTXO's entry without reading its value.
+    ///
+    /// Keys with no unspent entry are skipped, so a batch of keep-alive calls never
+    /// fails because one of its UTXOs was spent in the meantime.
+    fn extend(&mut self, utxo65: &BytesN<65>) {
+        let k = UTXOCoreDataKey::UTXO(hash_utxo_key(&self.env, utxo65));
+        if self.env.storage().persistent().has(&k) {
+            self.bump_ttl(&k);
+        }
+    }
+}
+
+/// Splits a key hash into its spent-set bucket key for `generation` and its fingerprint.
+fn spent_slot(e: &Env, h: &BytesN<32>, generation: u32) -> (UTXOCoreDataKey, Bytes) {
+    let raw = h.to_array();
+    let prefix = (u32::from(raw[0]) << 8) | u32::from(raw[1]);
+    let bucket = prefix >> (16 - Store::SPENT_BUCKET_BITS);
+
+    let start = 2;
+    let end = start + Store::SPENT_FINGERPRINT_BYTES as usize;
+    let fingerprint = Bytes::from_slice(e, &raw[start..end]);
+
+    (
+        UTXOCoreDataKey::SpentBucket(bucket, generation),
+        fingerprint,
+    )
+}
+
+/// Returns `true` once a bucket generation holds [`Store::SPENT_BUCKET_CAPACITY`] fingerprints.
+fn is_full(bucket: &Bytes) -> bool {
+    bucket.len() >= Store::SPENT_BUCKET_CAPACITY * Store::SPENT_FINGERPRINT_BYTES
+}
+
+/// Binary-searches a bucket of packed, sorted fingerprints.
+///
+/// Returns `Ok(index)` of the matching fingerprint, or `Err(index)` of the
+/// position where it would be inserted to keep the bucket sorted.
+fn search_bucket(bucket: &Bytes, fingerprint: &Bytes) -> Result<u32, u32> {
+    let width = Store::SPENT_FINGERPRINT_BYTES;
+    let mut lo = 0;
+    let mut hi = bucket.len() / width;
+
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let probe = bucket.slice(mid * width..(mid + 1) * width);
+        match probe.cmp(fingerprint) {
+            Ordering::Less => lo = mid + 1,
+            Ordering::Greater => hi = mid,
+            Ordering::Equal => return Ok(mid),
+        }
     }
+
+    Err(lo)
 }
 
-fn hash_utxo_key(e: &Env, utxo65: &BytesN<65>) -> BytesN<32> {
+/// `sha256(utxo_id)`, the storage key of a UTXO and its public identifier in events.
+pub fn hash_utxo_key(e: &Env, utxo65: &BytesN<65>) -> BytesN<32> {
     let b = Bytes::from_slice(e, utxo65.to_array().as_ref());
     let h = e.crypto().sha256(&b);
     BytesN::<32>::from_array(e, &h.to_array())
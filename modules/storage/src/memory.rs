@@ -0,0 +1,104 @@
+use soroban_sdk::{contracttype, panic_with_error, BytesN, Env, Map};
+
+use crate::{Error, UtxoStore};
+
+/// Temporary-storage key under which [`UtxoStore::apply`] keeps a [`MemoryStore`] between scopes.
+#[derive(Clone)]
+#[contracttype]
+enum MemoryStoreKey {
+    Entries,
+}
+
+/// UTXO spend state held in host memory.
+///
+/// Nothing is written to persistent storage, so the exact bundle accounting
+/// can run off-chain against a known UTXO set. A spent UTXO keeps a `0` entry,
+/// which blocks re-spend and re-creation for the store's lifetime.
+///
+/// A `MemoryStore` can be operated on directly. [`UtxoStore::apply`] instead
+/// loads the current contract's store with [`MemoryStore::load`] and saves it
+/// back when the scope ends, so state carries across calls of a handler on
+/// the same `Env`. Seed that state with [`MemoryStore::save`].
+#[derive(Clone)]
+pub struct MemoryStore {
+    env: Env,
+    entries: Map<BytesN<65>, i128>,
+}
+
+impl MemoryStore {
+    /// Creates an empty store.
+    pub fn new(e: &Env) -> Self {
+        Self::from_entries(e, Map::new(e))
+    }
+
+    /// Creates a store preloaded with `utxo -> amount` entries, where a `0`
+    /// amount marks a spent UTXO.
+    pub fn from_entries(e: &Env, entries: Map<BytesN<65>, i128>) -> Self {
+        Self {
+            env: e.clone(),
+            entries,
+        }
+    }
+
+    /// Returns every `utxo -> amount` entry, with `0` for spent UTXOs.
+    pub fn entries(&self) -> Map<BytesN<65>, i128> {
+        self.entries.clone()
+    }
+
+    /// Returns the store last saved by the current contract, or an empty one.
+    pub fn load(e: &Env) -> Self {
+        let entries = e
+            .storage()
+            .temporary()
+            .get(&MemoryStoreKey::Entries)
+            .unwrap_or_else(|| Map::new(e));
+        Self::from_entries(e, entries)
+    }
+
+    /// Saves this store as the current contract's state for later [`UtxoStore::apply`] scopes.
+    pub fn save(&self) {
+        let storage = self.env.storage();
+        let max_ttl = storage.max_ttl();
+        storage
+            .temporary()
+            .set(&MemoryStoreKey::Entries, &self.entries);
+        storage
+            .temporary()
+            .extend_ttl(&MemoryStoreKey::Entries, max_ttl, max_ttl);
+    }
+}
+
+impl UtxoStore for MemoryStore {
+    fn apply<R>(e: &Env, f: impl FnOnce(&mut Self) -> R) -> R {
+        let mut store = Self::load(e);
+        let result = f(&mut store);
+        store.save();
+        result
+    }
+
+    fn balance(&mut self, utxo65: &BytesN<65>) -> i128 {
+        self.entries.get(utxo65.clone()).unwrap_or(-1)
+    }
+
+    fn create(&mut self, utxo65: &BytesN<65>, amount: i128) {
+        if amount <= 0 {
+            panic_with_error!(&self.env, Error::InvalidCreateAmount);
+        }
+        if self.entries.contains_key(utxo65.clone()) {
+            panic_with_error!(&self.env, Error::UtxoAlreadyExists);
+        }
+
+        self.entries.set(utxo65.clone(), amount);
+    }
+
+    fn spend(&mut self, utxo65: &BytesN<65>) -> i128 {
+        match self.entries.get(utxo65.clone()) {
+            Some(amount) if amount > 0 => {
+                self.entries.set(utxo65.clone(), 0);
+                amount
+            }
+            Some(_) => panic_with_error!(&self.env, Error::UtxoAlreadySpent),
+            None => panic_with_error!(&self.env, Error::UtxoDoesNotExist),
+        }
+    }
+}
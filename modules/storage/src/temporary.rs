@@ -0,0 +1,92 @@
+use soroban_sdk::{panic_with_error, BytesN, Env};
+
+use crate::{hash_utxo_key, Error, Store, TtlPolicy, UTXOCoreDataKey, UtxoStore};
+
+/// UTXO spend state in Soroban temporary storage, for ephemeral channels.
+///
+/// Each UTXO owns one temporary entry keyed like [`Store`]'s per-UTXO entry;
+/// a spent UTXO keeps a `0` entry. Entries follow the [`TtlPolicy`] configured
+/// for [`Store`] and are deleted once they expire, taking both unspent funds
+/// and spent markers with them, so a spent key can be recreated after expiry.
+/// Never use it for a channel that holds real value.
+pub struct TemporaryStore {
+    env: Env,
+    ttl: TtlPolicy,
+}
+
+impl TemporaryStore {
+    fn bump_ttl(&self, key: &UTXOCoreDataKey) {
+        self.env
+            .storage()
+            .temporary()
+            .extend_ttl(key, self.ttl.threshold, self.ttl.extend_to);
+    }
+
+    fn key(&self, utxo65: &BytesN<65>) -> UTXOCoreDataKey {
+        UTXOCoreDataKey::UTXO(hash_utxo_key(&self.env, utxo65))
+    }
+}
+
+impl UtxoStore for TemporaryStore {
+    fn apply<R>(e: &Env, f: impl FnOnce(&mut Self) -> R) -> R {
+        let mut store = Self {
+            env: e.clone(),
+            ttl: Store::ttl_policy(e),
+        };
+
+        f(&mut store)
+    }
+
+    fn balance(&mut self, utxo65: &BytesN<65>) -> i128 {
+        let k = self.key(utxo65);
+        match self.env.storage().temporary().get::<_, i128>(&k) {
+            Some(amount) => {
+                self.bump_ttl(&k);
+                amount
+            }
+            None => -1,
+        }
+    }
+
+    fn create(&mut self, utxo65: &BytesN<65>, amount: i128) {
+        if amount <= 0 {
+            panic_with_error!(&self.env, Error::InvalidCreateAmount);
+        }
+
+        let k = self.key(utxo65);
+        if self.env.storage().temporary().has(&k) {
+            panic_with_error!(&self.env, Error::UtxoAlreadyExists);
+        }
+
+        self.env.storage().temporary().set(&k, &amount);
+        self.bump_ttl(&k);
+    }
+
+    fn spend(&mut self, utxo65: &BytesN<65>) -> i128 {
+        let k = self.key(utxo65);
+        match self.env.storage().temporary().get::<_, i128>(&k) {
+            Some(amount) if amount > 0 => {
+                self.env.storage().temporary().set(&k, &0_i128);
+                self.bump_ttl(&k);
+                amount
+            }
+            Some(_) => panic_with_error!(&self.env, Error::UtxoAlreadySpent),
+            None => panic_with_error!(&self.env, Error::UtxoDoesNotExist),
+        }
+    }
+
+    fn extend(&mut self, utxo65: &BytesN<65>) {
+        let k = self.key(utxo65);
+        if self.env.storage().temporary().has(&k) {
+            self.bump_ttl(&k);
+        }
+    }
+
+    fn ttl_policy(e: &Env) -> TtlPolicy {
+        Store::ttl_policy(e)
+    }
+
+    fn set_ttl_policy(e: &Env, policy: &TtlPolicy) {
+        Store::set_ttl_policy(e, policy);
+    }
+}
@@ -1,12 +1,37 @@
-use soroban_sdk::testutils::storage::Persistent as _;
+use soroban_sdk::testutils::storage::{Persistent as _, Temporary as _};
 use soroban_sdk::testutils::Ledger as _;
-use soroban_sdk::{contract, Address, BytesN, Env};
+use soroban_sdk::{contract, contractimpl, map, Address, Bytes, BytesN, Env};
 
-use crate::{hash_utxo_key, Store, UTXOCoreDataKey};
+use crate::{
+    hash_utxo_key, MemoryStore, Store, TemporaryStore, TtlPolicy, UTXOCoreDataKey, UtxoState,
+    UtxoStore,
+};
 
 #[contract]
 struct StorageTestContract;
 
+#[contractimpl]
+impl StorageTestContract {
+    /// Spends a UTXO under the spent-set layout.
+    pub fn spend(e: Env, utxo65: BytesN<65>) {
+        Store::apply(&e, |store| {
+            store.spend(&utxo65);
+        });
+    }
+
+    /// Spends a UTXO under the previous layout, which overwrote its entry with a `0` tombstone.
+    pub fn spend_to_tombstone(e: Env, utxo65: BytesN<65>) {
+        let k = utxo_key(&e, &utxo65);
+        let amount: i128 = e.storage().persistent().get(&k).unwrap();
+        e.storage().persistent().set(&k, &0i128);
+        let ttl = Store::ttl_policy(&e);
+        e.storage()
+            .persistent()
+            .extend_ttl(&k, ttl.threshold, ttl.extend_to);
+        Store::set_unspent_value(&e, Store::unspent_value(&e) - amount);
+    }
+}
+
 fn storage_contract(e: &Env) -> Address {
     e.register(StorageTestContract, ())
 }
@@ -26,7 +51,7 @@ fn utxo(e: &Env, seed: u8) -> BytesN<65> {
     BytesN::<65>::from_array(e, &bytes)
 }
 
-/// Reads the raw per-UTXO spend-state entry (the stored amount, or `None` if no record exists).
+/// Reads the raw per-UTXO entry (the stored amount, or `None` if the UTXO has no unspent entry).
 fn spend_state(e: &Env, utxo65: &BytesN<65>) -> Option<i128> {
     e.storage()
         .persistent()
@@ -37,6 +62,17 @@ fn utxo_key(e: &Env, utxo65: &BytesN<65>) -> UTXOCoreDataKey {
     UTXOCoreDataKey::UTXO(hash_utxo_key(e, utxo65))
 }
 
+fn spent_bucket_key(e: &Env, utxo65: &BytesN<65>) -> UTXOCoreDataKey {
+    Store::ledger_keys(e, utxo65).1
+}
+
+fn bucket_id(key: &UTXOCoreDataKey) -> u32 {
+    match key {
+        UTXOCoreDataKey::SpentBucket(id, _) => *id,
+        UTXOCoreDataKey::UTXO(_) => panic!("not a spent-set bucket key"),
+    }
+}
+
 #[test]
 fn missing_utxo_balance_is_negative_one_without_creating_an_entry() {
     let e = Env::default();
@@ -90,7 +126,7 @@ fn each_created_utxo_gets_its_own_independent_entry() {
 }
 
 #[test]
-fn spend_tombstones_only_the_target_and_leaves_other_entries_unspent() {
+fn spend_moves_only_the_target_into_the_spent_set() {
     let e = Env::default();
     let contract_id = storage_contract(&e);
 
@@ -116,10 +152,10 @@ fn spend_tombstones_only_the_target_and_leaves_other_entries_unspent() {
             assert_eq!(store.balance(&third), 0);
         });
 
-        // Spent UTXOs are tombstoned to 0; the untouched one keeps its amount.
-        assert_eq!(spend_state(&e, &first), Some(0));
+        // Spent UTXOs drop their own entry; the untouched one keeps its amount.
+        assert_eq!(spend_state(&e, &first), None);
         assert_eq!(spend_state(&e, &second), Some(20));
-        assert_eq!(spend_state(&e, &third), Some(0));
+        assert_eq!(spend_state(&e, &third), None);
     });
 }
 
@@ -142,6 +178,46 @@ fn created_utxo_can_be_read_and_spent_inside_the_same_scope() {
     });
 }
 
+#[test]
+fn unspent_value_tracks_created_minus_spent_amounts() {
+    let e = Env::default();
+    let contract_id = storage_contract(&e);
+
+    in_contract(&e, &contract_id, || {
+        let (first, second, third) = (utxo(&e, 1), utxo(&e, 2), utxo(&e, 3));
+        assert_eq!(Store::unspent_value(&e), 0);
+
+        Store::apply(&e, |store| {
+            store.create(&first, 100);
+            store.create(&second, 30);
+        });
+        assert_eq!(Store::unspent_value(&e), 130);
+
+        Store::apply(&e, |store| {
+            store.spend(&first);
+            store.create(&third, 60);
+        });
+        assert_eq!(Store::unspent_value(&e), 90);
+
+        Store::apply(&e, |store| store.balance(&second));
+        assert_eq!(Store::unspent_value(&e), 90);
+    });
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3003)")]
+fn unspent_delta_overflow_fails_the_scope() {
+    let e = Env::default();
+    let contract_id = storage_contract(&e);
+
+    in_contract(&e, &contract_id, || {
+        Store::apply(&e, |store| {
+            store.create(&utxo(&e, 1), i128::MAX);
+            store.create(&utxo(&e, 2), 1);
+        });
+    });
+}
+
 #[test]
 fn create_spend_and_balance_bump_persistent_ttl() {
     // MOON-02: the per-UTXO spend-state entry must be pushed to the long (30-day) TTL window so it
@@ -163,12 +239,115 @@ fn create_spend_and_balance_bump_persistent_ttl() {
         Store::apply(&e, |store| store.balance(&key));
         assert!(e.storage().persistent().get_ttl(&uk) >= min_ttl);
 
-        // Spending keeps the now-spent tombstone alive (so it cannot be recreated post-archival).
+        // Spending keeps the spent-set bucket alive (so the key cannot be recreated post-archival).
         Store::apply(&e, |store| {
             store.spend(&key);
         });
-        assert_eq!(spend_state(&e, &key), Some(0));
-        assert!(e.storage().persistent().get_ttl(&uk) >= min_ttl);
+        assert_eq!(spend_state(&e, &key), None);
+        let bucket = spent_bucket_key(&e, &key);
+        assert!(e.storage().persistent().get_ttl(&bucket) >= min_ttl);
+
+        // Observing a spent key refreshes its bucket as well.
+        e.ledger()
+            .with_mut(|l| l.sequence_number += Store::DAY_IN_LEDGERS * 2);
+        assert_eq!(Store::apply(&e, |store| store.balance(&key)), 0);
+        assert!(e.storage().persistent().get_ttl(&bucket) >= min_ttl);
+    });
+}
+
+#[test]
+fn configured_ttl_policy_replaces_the_default_bump() {
+    let e = Env::default();
+    let contract_id = storage_contract(&e);
+
+    in_contract(&e, &contract_id, || {
+        let policy = TtlPolicy {
+            threshold: 90 * Store::DAY_IN_LEDGERS,
+            extend_to: 120 * Store::DAY_IN_LEDGERS,
+        };
+        assert_eq!(Store::ttl_policy(&e), Store::DEFAULT_TTL_POLICY);
+
+        Store::set_ttl_policy(&e, &policy);
+        assert_eq!(Store::ttl_policy(&e), policy);
+
+        let key = utxo(&e, 1);
+        Store::apply(&e, |store| store.create(&key, 100));
+        assert!(e.storage().persistent().get_ttl(&utxo_key(&e, &key)) >= policy.threshold);
+
+        Store::apply(&e, |store| {
+            store.spend(&key);
+        });
+        let bucket = spent_bucket_key(&e, &key);
+        assert!(e.storage().persistent().get_ttl(&bucket) >= policy.threshold);
+    });
+}
+
+#[test]
+#[should_panic]
+fn set_ttl_policy_rejects_threshold_not_below_extension() {
+    let e = Env::default();
+    let contract_id = storage_contract(&e);
+
+    in_contract(&e, &contract_id, || {
+        Store::set_ttl_policy(
+            &e,
+            &TtlPolicy {
+                threshold: Store::DAY_IN_LEDGERS,
+                extend_to: Store::DAY_IN_LEDGERS,
+            },
+        );
+    });
+}
+
+#[test]
+#[should_panic]
+fn set_ttl_policy_rejects_extension_beyond_max_ttl() {
+    let e = Env::default();
+    let contract_id = storage_contract(&e);
+
+    in_contract(&e, &contract_id, || {
+        let max_ttl = e.storage().max_ttl();
+        Store::set_ttl_policy(
+            &e,
+            &TtlPolicy {
+                threshold: Store::DAY_IN_LEDGERS,
+                extend_to: max_ttl + 1,
+            },
+        );
+    });
+}
+
+#[test]
+fn extend_bumps_unspent_entries_and_skips_the_rest() {
+    let e = Env::default();
+    let contract_id = storage_contract(&e);
+
+    in_contract(&e, &contract_id, || {
+        let unspent = utxo(&e, 1);
+        let spent = utxo(&e, 2);
+        let missing = utxo(&e, 3);
+        let min_ttl = Store::PERSISTENT_LIFETIME_THRESHOLD;
+
+        Store::apply(&e, |store| {
+            store.create(&unspent, 100);
+            store.create(&spent, 50);
+            store.spend(&spent);
+        });
+
+        // Let the entry drop below the threshold so the extension is observable.
+        e.ledger()
+            .with_mut(|l| l.sequence_number += Store::DAY_IN_LEDGERS * 2);
+        assert!(e.storage().persistent().get_ttl(&utxo_key(&e, &unspent)) < min_ttl);
+
+        Store::apply(&e, |store| {
+            store.extend(&unspent);
+            store.extend(&spent);
+            store.extend(&missing);
+        });
+
+        assert!(e.storage().persistent().get_ttl(&utxo_key(&e, &unspent)) >= min_ttl);
+        assert_eq!(spend_state(&e, &spent), None);
+        assert_eq!(spend_state(&e, &missing), None);
     });
 }
 
@@ -186,7 +365,7 @@ fn create_rejects_duplicate_utxo_even_after_spend() {
             store.spend(&key);
         });
 
-        // The spent tombstone blocks recreation.
+        // The spent-set fingerprint blocks recreation.
         Store::apply(&e, |store| store.create(&key, 100));
     });
 }
@@ -244,3 +423,577 @@ fn spend_rejects_already_spent_utxo() {
         });
     });
 }
+
+#[test]
+fn legacy_zero_tombstone_still_reads_as_spent() {
+    let e = Env::default();
+    let contract_id = storage_contract(&e);
+
+    in_contract(&e, &contract_id, || {
+        let key = utxo(&e, 1);
+        // Entry left behind by the previous per-UTXO tombstone layout.
+        e.storage().persistent().set(&utxo_key(&e, &key), &0i128);
+
+        assert_eq!(Store::apply(&e, |store| store.balance(&key)), 0);
+    });
+}
+
+#[test]
+#[should_panic]
+fn legacy_zero_tombstone_blocks_recreation() {
+    let e = Env::default();
+    let contract_id = storage_contract(&e);
+
+    in_contract(&e, &contract_id, || {
+        let key = utxo(&e, 1);
+        e.storage().persistent().set(&utxo_key(&e, &key), &0i128);
+
+        Store::apply(&e, |store| store.create(&key, 100));
+    });
+}
+
+#[test]
+fn keys_sharing_a_spent_bucket_keep_distinct_states() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    let contract_id = storage_contract(&e);
+
+    // Find two keys whose hashes land in the same spent-set bucket.
+    let first = numbered_utxo(&e, 0);
+    let bucket = spent_bucket_key(&e, &first);
+    let second = (1..)
+        .map(|i| numbered_utxo(&e, i))
+        .find(|candidate| bucket_id(&spent_bucket_key(&e, candidate)) == bucket_id(&bucket))
+        .unwrap();
+
+    in_contract(&e, &contract_id, || {
+        Store::apply(&e, |store| {
+            store.create(&first, 10);
+            store.create(&second, 20);
+            store.spend(&first);
+        });
+
+        Store::apply(&e, |store| {
+            assert_eq!(store.balance(&first), 0);
+            assert_eq!(store.balance(&second), 20);
+            assert_eq!(store.spend(&second), 20);
+            assert_eq!(store.balance(&second), 0);
+        });
+
+        let packed = e
+            .storage()
+            .persistent()
+            .get::<_, soroban_sdk::Bytes>(&bucket)
+            .unwrap();
+        assert_eq!(packed.len(), 2 * Store::SPENT_FINGERPRINT_BYTES);
+    });
+}
+
+#[test]
+#[should_panic]
+fn spend_rejects_spent_key_recorded_only_in_the_spent_set() {
+    let e = Env::default();
+    let contract_id = storage_contract(&e);
+
+    in_contract(&e, &contract_id, || {
+        let key = utxo(&e, 1);
+        Store::apply(&e, |store| {
+            store.create(&key, 100);
+            store.spend(&key);
+        });
+
+        Store::apply(&e, |store| {
+            store.spend(&key);
+        });
+    });
+}
+
+#[test]
+fn full_spent_bucket_rolls_over_into_a_new_generation() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    let contract_id = storage_contract(&e);
+
+    let key = utxo(&e, 1);
+    let first = Store::spent_bucket_key(&e, &key, 0);
+    let second = Store::spent_bucket_key(&e, &key, 1);
+
+    // Fill the key's first generation to capacity with fingerprints that sort before any real one.
+    let mut full = Bytes::new(&e);
+    for i in 0..u64::from(Store::SPENT_BUCKET_CAPACITY) {
+        full.extend_from_array(&i.to_be_bytes());
+    }
+    let capacity_bytes = Store::SPENT_BUCKET_CAPACITY * Store::SPENT_FINGERPRINT_BYTES;
+    assert_eq!(full.len(), capacity_bytes);
+
+    in_contract(&e, &contract_id, || {
+        e.storage().persistent().set(&first, &full);
+
+        Store::apply(&e, |store| {
+            store.create(&key, 100);
+            store.spend(&key);
+        });
+
+        // The full generation is left untouched; the spend lands in the next one.
+        assert_eq!(e.storage().persistent().get::<_, Bytes>(&first), Some(full));
+        let rolled: Bytes = e.storage().persistent().get(&second).unwrap();
+        assert_eq!(rolled.len(), Store::SPENT_FINGERPRINT_BYTES);
+        assert!(Store::bucket_contains(&e, &rolled, &key));
+
+        assert_eq!(
+            Store::apply(&e, |store| store.state(&key)),
+            UtxoState::Spent
+        );
+    });
+}
+
+#[test]
+fn generation_above_capacity_reads_as_full() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    let contract_id = storage_contract(&e);
+
+    let key = utxo(&e, 1);
+    let first = Store::spent_bucket_key(&e, &key, 0);
+
+    // A generation filled under the previous capacity of 1,024 fingerprints.
+    let mut oversized = Bytes::new(&e);
+    for i in 0..1_024u64 {
+        oversized.extend_from_array(&i.to_be_bytes());
+    }
+
+    in_contract(&e, &contract_id, || {
+        e.storage().persistent().set(&first, &oversized);
+
+        Store::apply(&e, |store| {
+            store.create(&key, 100);
+            store.spend(&key);
+        });
+
+        assert_eq!(
+            e.storage().persistent().get::<_, Bytes>(&first),
+            Some(oversized)
+        );
+        let rolled: Bytes = e
+            .storage()
+            .persistent()
+            .get(&Store::spent_bucket_key(&e, &key, 1))
+            .unwrap();
+        assert!(Store::bucket_contains(&e, &rolled, &key));
+    });
+}
+
+#[test]
+#[should_panic]
+fn key_spent_in_a_rolled_over_generation_cannot_be_recreated() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    let contract_id = storage_contract(&e);
+
+    let key = utxo(&e, 1);
+    let mut full = Bytes::new(&e);
+    for i in 0..u64::from(Store::SPENT_BUCKET_CAPACITY) {
+        full.extend_from_array(&i.to_be_bytes());
+    }
+
+    in_contract(&e, &contract_id, || {
+        e.storage()
+            .persistent()
+            .set(&Store::spent_bucket_key(&e, &key, 0), &full);
+        Store::apply(&e, |store| {
+            store.create(&key, 100);
+            store.spend(&key);
+        });
+
+        Store::apply(&e, |store| store.create(&key, 100));
+    });
+}
+
+/// Persistent-entry count and 30-day rent held for 10k spent UTXOs, comparing the
+/// previous per-UTXO `0` tombstone layout with the spent-set buckets.
+///
+/// Run with `cargo test -p moonlight-storage --release -- --ignored --nocapture`.
+#[test]
+#[ignore]
+fn bench_spent_set_footprint_for_10k_spends() {
+    extern crate std;
+
+    const SPENDS: u32 = 10_000;
+
+    let tombstones = spent_state_footprint(SPENDS, |e, key| {
+        e.storage().persistent().set(&utxo_key(e, key), &0i128);
+    });
+    let buckets = spent_state_footprint(SPENDS, |e, key| {
+        Store::apply(e, |store| {
+            store.create(key, 1);
+            store.spend(key);
+        });
+    });
+
+    std::println!("spends: {SPENDS}");
+    std::println!(
+        "tombstones: {} entries, {} bytes, {} stroops rent / 30 days",
+        tombstones.0,
+        tombstones.1,
+        rent_for_30_days(tombstones.1)
+    );
+    std::println!(
+        "spent set:  {} entries, {} bytes, {} stroops rent / 30 days",
+        buckets.0,
+        buckets.1,
+        rent_for_30_days(buckets.1)
+    );
+
+    assert!(buckets.0 < tombstones.0);
+    assert!(buckets.1 < tombstones.1);
+}
+
+/// Read/write bytes and fee of a single spend, comparing the previous per-UTXO `0` tombstone
+/// layout with a spend into spent-set bucket generations of increasing fill.
+///
+/// Run with `cargo test -p moonlight-storage --release -- --ignored --nocapture`.
+#[test]
+#[ignore]
+fn bench_spent_set_cost_per_spend() {
+    extern crate std;
+
+    let row = |layout: &str, cost: SpendCost| {
+        std::println!(
+            "{layout:<22} {:>6} {:>6} {:>8} {:>8} {:>8}",
+            cost.read_bytes,
+            cost.write_bytes,
+            cost.write_fee,
+            cost.rent_fee,
+            cost.fee
+        );
+    };
+    std::println!(
+        "{:<22} {:>6} {:>6} {:>8} {:>8} {:>8}",
+        "layout",
+        "read",
+        "write",
+        "write fee",
+        "rent fee",
+        "fee"
+    );
+    row("tombstone", spend_cost(None));
+    let capacity = Store::SPENT_BUCKET_CAPACITY;
+    for fill in [0, capacity / 4, capacity / 2, capacity - 1] {
+        row(
+            &std::format!("bucket, {fill} spent"),
+            spend_cost(Some(fill)),
+        );
+    }
+}
+
+/// Resources of one spend: the bytes of the entries it reads and writes, and its fee in stroops.
+struct SpendCost {
+    read_bytes: u64,
+    write_bytes: u32,
+    write_fee: i64,
+    rent_fee: i64,
+    fee: i64,
+}
+
+/// Creates a UTXO and measures spending it, into a tombstone for `None` or into a bucket
+/// generation already holding `bucket_fill` fingerprints.
+fn spend_cost(bucket_fill: Option<u32>) -> SpendCost {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    let contract_id = storage_contract(&e);
+    let client = StorageTestContractClient::new(&e, &contract_id);
+    let key = utxo(&e, 1);
+
+    in_contract(&e, &contract_id, || {
+        if let Some(fill) = bucket_fill {
+            // Fingerprints that sort before any real one, so the key's lands at the end.
+            let mut bucket = Bytes::new(&e);
+            for i in 0..u64::from(fill) {
+                bucket.extend_from_array(&i.to_be_bytes());
+            }
+            // A live bucket was last bumped by an earlier spend, like the UTXO entry by its create.
+            let k = Store::spent_bucket_key(&e, &key, 0);
+            let ttl = Store::DEFAULT_TTL_POLICY;
+            e.storage().persistent().set(&k, &bucket);
+            e.storage()
+                .persistent()
+                .extend_ttl(&k, ttl.threshold, ttl.extend_to);
+        }
+        Store::apply(&e, |store| store.create(&key, 100));
+    });
+
+    // The spend reads every entry of the contract: its instance, the UTXO and its bucket.
+    let read_bytes = contract_data_footprint(&e).1;
+    match bucket_fill {
+        Some(_) => client.spend(&key),
+        None => client.spend_to_tombstone(&key),
+    }
+
+    let resources = e.cost_estimate().resources();
+    let fee = e.cost_estimate().fee();
+    SpendCost {
+        read_bytes,
+        write_bytes: resources.write_bytes,
+        write_fee: fee.write_entries + fee.write_bytes,
+        rent_fee: fee.persistent_entry_rent,
+        fee: fee.total,
+    }
+}
+
+/// Spends `count` distinct UTXOs with `spend` and returns the number and total
+/// XDR size of the persistent contract-data entries left behind.
+fn spent_state_footprint(count: u32, spend: impl Fn(&Env, &BytesN<65>)) -> (u32, u64) {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.cost_estimate().disable_resource_limits();
+    let contract_id = storage_contract(&e);
+
+    in_contract(&e, &contract_id, || {
+        for i in 0..count {
+            spend(&e, &numbered_utxo(&e, i));
+        }
+    });
+
+    contract_data_footprint(&e)
+}
+
+/// Returns the number and total XDR size of the persistent contract-data entries in the ledger.
+fn contract_data_footprint(e: &Env) -> (u32, u64) {
+    use soroban_sdk::xdr::{ContractDataDurability, LedgerEntryData, LedgerKey, Limits, WriteXdr};
+
+    let mut entries = 0;
+    let mut bytes = 0;
+    for (key, (entry, _ttl)) in e.to_ledger_snapshot().ledger_entries.iter() {
+        let is_utxo_state = matches!(
+            (key.as_ref(), &entry.data),
+            (LedgerKey::ContractData(_), LedgerEntryData::ContractData(d))
+                if d.durability == ContractDataDurability::Persistent
+        );
+        if is_utxo_state {
+            entries += 1;
+            bytes += entry.to_xdr(Limits::none()).unwrap().len() as u64;
+        }
+    }
+
+    (entries, bytes)
+}
+
+fn numbered_utxo(e: &Env, i: u32) -> BytesN<65> {
+    let mut bytes = [0u8; 65];
+    bytes[0] = 4;
+    bytes[1..5].copy_from_slice(&i.to_be_bytes());
+    BytesN::<65>::from_array(e, &bytes)
+}
+
+/// Persistent rent for holding `bytes` for 30 days at the SDK's pubnet fee snapshot
+/// (12,000 stroops per KiB, persistent rent-rate denominator 2,103).
+fn rent_for_30_days(bytes: u64) -> u64 {
+    let ledgers = u64::from(Store::PERSISTENT_BUMP_AMOUNT);
+    bytes * ledgers * 12_000 / (1024 * 2_103)
+}
+
+#[test]
+fn state_reports_missing_unspent_and_spent() {
+    let e = Env::default();
+    let contract_id = storage_contract(&e);
+
+    in_contract(&e, &contract_id, || {
+        let key = utxo(&e, 1);
+
+        assert_eq!(
+            Store::apply(&e, |store| store.state(&key)),
+            UtxoState::Missing
+        );
+
+        Store::apply(&e, |store| store.create(&key, 100));
+        assert_eq!(
+            Store::apply(&e, |store| store.state(&key)),
+            UtxoState::Unspent(100)
+        );
+
+        Store::apply(&e, |store| {
+            store.spend(&key);
+        });
+        assert_eq!(
+            Store::apply(&e, |store| store.state(&key)),
+            UtxoState::Spent
+        );
+    });
+}
+
+#[test]
+fn ledger_keys_and_bucket_contains_classify_spent_keys_off_chain() {
+    let e = Env::default();
+    let contract_id = storage_contract(&e);
+
+    in_contract(&e, &contract_id, || {
+        let spent = utxo(&e, 1);
+        let fresh = utxo(&e, 2);
+
+        Store::apply(&e, |store| {
+            store.create(&spent, 100);
+            store.spend(&spent);
+        });
+
+        let (unspent_key, bucket_key) = Store::ledger_keys(&e, &spent);
+        assert_eq!(spend_state(&e, &spent), None);
+        assert!(!e.storage().persistent().has(&unspent_key));
+
+        let bucket: Bytes = e.storage().persistent().get(&bucket_key).unwrap();
+        assert!(Store::bucket_contains(&e, &bucket, &spent));
+        assert!(!Store::bucket_contains(&e, &bucket, &fresh));
+    });
+}
+
+#[test]
+fn from_ledger_distinguishes_archived_from_missing() {
+    let now = 1_000;
+    let live = now + 10;
+    let expired = now - 1;
+
+    assert_eq!(UtxoState::from_ledger(now, None, None), UtxoState::Missing);
+    assert_eq!(
+        UtxoState::from_ledger(now, None, Some((false, live))),
+        UtxoState::Missing
+    );
+    assert_eq!(
+        UtxoState::from_ledger(now, Some((100, live)), None),
+        UtxoState::Unspent(100)
+    );
+    assert_eq!(
+        UtxoState::from_ledger(now, None, Some((true, live))),
+        UtxoState::Spent
+    );
+    assert_eq!(
+        UtxoState::from_ledger(now, Some((0, live)), None),
+        UtxoState::Spent
+    );
+
+    assert_eq!(
+        UtxoState::from_ledger(now, Some((100, expired)), None),
+        UtxoState::Archived
+    );
+    // Without a live bucket, a key cannot be told apart from one that was never created.
+    assert_eq!(
+        UtxoState::from_ledger(now, None, Some((false, expired))),
+        UtxoState::Archived
+    );
+    // The bucket is irrelevant once the key's own entry is live.
+    assert_eq!(
+        UtxoState::from_ledger(now, Some((100, live)), Some((true, expired))),
+        UtxoState::Unspent(100)
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #2010)")]
+fn archived_state_has_no_unspent_amount() {
+    let e = Env::default();
+    UtxoState::Archived.unspent_amount(&e);
+}
+
+#[test]
+fn memory_store_tracks_state_without_touching_the_ledger() {
+    let e = Env::default();
+    let contract_id = storage_contract(&e);
+
+    in_contract(&e, &contract_id, || {
+        let spent = utxo(&e, 1);
+        let unspent = utxo(&e, 2);
+        let fresh = utxo(&e, 3);
+        let mut store = MemoryStore::from_entries(&e, map![&e, (spent.clone(), 100_i128)]);
+
+        assert_eq!(store.spend(&spent), 100);
+        store.create(&unspent, 40);
+
+        assert_eq!(store.state(&spent), UtxoState::Spent);
+        assert_eq!(store.state(&unspent), UtxoState::Unspent(40));
+        assert_eq!(store.state(&fresh), UtxoState::Missing);
+        assert_eq!(
+            store.entries(),
+            map![&e, (spent.clone(), 0_i128), (unspent.clone(), 40_i128)]
+        );
+
+        assert!(!e.storage().persistent().has(&utxo_key(&e, &unspent)));
+        assert!(!e.storage().temporary().has(&utxo_key(&e, &unspent)));
+    });
+}
+
+#[test]
+fn memory_store_apply_keeps_state_between_scopes() {
+    let e = Env::default();
+    let contract_id = storage_contract(&e);
+
+    in_contract(&e, &contract_id, || {
+        let key = utxo(&e, 1);
+
+        MemoryStore::apply(&e, |store| store.create(&key, 100));
+        assert_eq!(MemoryStore::apply(&e, |store| store.spend(&key)), 100);
+        assert_eq!(
+            MemoryStore::apply(&e, |store| store.state(&key)),
+            UtxoState::Spent
+        );
+        assert_eq!(
+            MemoryStore::load(&e).entries(),
+            map![&e, (key.clone(), 0_i128)]
+        );
+
+        assert!(!e.storage().persistent().has(&utxo_key(&e, &key)));
+        assert_eq!(MemoryStore::ttl_policy(&e), Store::DEFAULT_TTL_POLICY);
+    });
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #2000)")]
+fn memory_store_rejects_recreating_a_spent_utxo() {
+    let e = Env::default();
+    let key = utxo(&e, 1);
+    let mut store = MemoryStore::new(&e);
+
+    store.create(&key, 100);
+    store.spend(&key);
+    store.create(&key, 100);
+}
+
+#[test]
+fn temporary_store_keeps_state_in_temporary_storage() {
+    let e = Env::default();
+    let contract_id = storage_contract(&e);
+
+    in_contract(&e, &contract_id, || {
+        let key = utxo(&e, 1);
+        let uk = utxo_key(&e, &key);
+
+        TemporaryStore::apply(&e, |store| store.create(&key, 100));
+        assert_eq!(e.storage().temporary().get::<_, i128>(&uk), Some(100));
+        assert!(e.storage().temporary().get_ttl(&uk) >= Store::PERSISTENT_LIFETIME_THRESHOLD);
+        assert!(!e.storage().persistent().has(&uk));
+
+        assert_eq!(TemporaryStore::apply(&e, |store| store.spend(&key)), 100);
+        assert_eq!(
+            TemporaryStore::apply(&e, |store| store.state(&key)),
+            UtxoState::Spent
+        );
+        assert_eq!(
+            Store::apply(&e, |store| store.state(&key)),
+            UtxoState::Missing
+        );
+    });
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #2002)")]
+fn temporary_store_rejects_double_spend() {
+    let e = Env::default();
+    let contract_id = storage_contract(&e);
+
+    in_contract(&e, &contract_id, || {
+        let key = utxo(&e, 1);
+
+        TemporaryStore::apply(&e, |store| {
+            store.create(&key, 100);
+            store.spend(&key);
+            store.spend(&key);
+        });
+    });
+}
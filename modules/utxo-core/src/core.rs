@@ -5,6 +5,9 @@ use soroban_sdk::{
 };
 
 use moonlight_storage::Store;
+pub use moonlight_storage::{
+    hash_utxo_key, MemoryStore, TemporaryStore, TtlPolicy, UtxoState, UtxoStore,
+};
 
 use soroban_sdk::symbol_short;
 
@@ -28,9 +31,43 @@ pub struct UTXOOperation {
     pub create: Vec<(BytesN<65>, i128)>,
 }
 
+/// The part of an operation a [`Violation`] refers to. Indices are positions in the
+/// corresponding operation list.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BundleItem {
+    /// The operation as a whole.
+    Operation,
+    Spend(u32),
+    Create(u32),
+    Deposit(u32),
+    Withdraw(u32),
+}
+
+/// A rule broken by an operation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Violation {
+    pub error: MoonlightError,
+    pub item: BundleItem,
+}
+
+impl Violation {
+    pub fn new(error: MoonlightError, item: BundleItem) -> Self {
+        Self { error, item }
+    }
+}
+
 pub const STORAGE_KEY_UTXO_AUTH: &Symbol = &symbol_short!("UTXO_AUTH");
 
-pub trait UtxoHandlerTrait {
+/// UTXO accounting over a [`UtxoStore`] backend, defaulting to the persistent [`Store`].
+pub trait UtxoHandlerTrait<S: UtxoStore = Store> {
+    /// Runs `f` against the store backing this handler.
+    ///
+    /// Defaults to a fresh [`UtxoStore::apply`] scope. Handlers that keep their
+    /// own store elsewhere override this to hand it out.
+    fn with_store<R>(e: &Env, f: impl FnOnce(&mut S) -> R) -> R {
+        S::apply(e, f)
+    }
+
     fn auth(env: &Env) -> soroban_sdk::Address {
         env.storage()
             .instance()
@@ -50,7 +87,7 @@ pub trait UtxoHandlerTrait {
     /// If the UTXO is spent, 0 is returned.
     /// If no record exists for the UTXO (represented by –1), it is considered free to be created.
     fn utxo_balance(e: &Env, utxo: BytesN<65>) -> i128 {
-        Store::apply(e, |store| store.balance(&utxo))
+        Self::with_store(e, |store| store.balance(&utxo))
     }
     fn utxo_balances(e: &Env, utxos: Vec<BytesN<65>>) -> Vec<i128> {
         let mut balances: Vec<i128> = vec![&e];
@@ -62,27 +99,68 @@ pub trait UtxoHandlerTrait {
         balances
     }
 
+    /// Returns the typed state of a given UTXO.
+    ///
+    /// Archived entries are restored or rejected by the host before the contract runs, so this
+    /// never returns [`UtxoState::Archived`].
+    fn utxo_state(e: &Env, utxo: BytesN<65>) -> UtxoState {
+        Self::with_store(e, |store| store.state(&utxo))
+    }
+
+    fn utxo_states(e: &Env, utxos: Vec<BytesN<65>>) -> Vec<UtxoState> {
+        let mut states: Vec<UtxoState> = vec![e];
+
+        for u in utxos {
+            states.push_back(Self::utxo_state(e, u));
+        }
+
+        states
+    }
+
+    /// Returns the TTL policy applied to UTXO storage entries.
+    fn ttl_policy(e: &Env) -> TtlPolicy {
+        S::ttl_policy(e)
+    }
+
+    /// Returns the total amount held by unspent UTXOs in the persistent [`Store`].
+    fn unspent_value(e: &Env) -> i128 {
+        Store::unspent_value(e)
+    }
+
+    /// Seeds the unspent total of the persistent [`Store`], for storage written before it existed.
+    ///
+    /// This does not check authorization; the implementing contract must gate it.
+    fn set_unspent_value(e: &Env, total: i128) {
+        Store::set_unspent_value(e, total);
+    }
+
+    /// Sets the TTL policy applied to UTXO storage entries.
+    ///
+    /// This does not check authorization; the implementing contract must gate it.
+    fn set_ttl_policy(e: &Env, policy: &TtlPolicy) {
+        S::set_ttl_policy(e, policy);
+    }
+
+    /// Extends the TTL of every unspent UTXO in `utxos` without returning their values.
+    ///
+    /// UTXOs that are spent or have no record are skipped.
+    fn extend_utxos(e: &Env, utxos: Vec<BytesN<65>>) {
+        Self::with_store(e, |store| {
+            for u in utxos.iter() {
+                store.extend(&u);
+            }
+        });
+    }
+
     fn process_bundle(
         e: &Env,
         bundle: InternalBundle,
         incoming_amount: i128,
         expected_outgoing: i128,
     ) -> i128 {
-        let mut total_available_balance = incoming_amount;
-
-        assert_with_error!(
-            &e,
-            no_duplicate_keys(&e, bundle.spend.iter(), |spend_utxo| spend_utxo.clone()),
-            MoonlightError::RepeatedSpendUtxo
-        );
-
-        assert_with_error!(
-            &e,
-            no_duplicate_keys(&e, bundle.create.iter(), |(create_utxo, _amt)| {
-                create_utxo.clone()
-            }),
-            MoonlightError::RepeatedCreateUtxo
-        );
+        let fail_fast = &mut |v: Violation| panic_with_error!(e, v.error);
+
+        check_bundle_keys(e, &bundle, fail_fast);
 
         let auth_args = if bundle.req.0.is_empty() {
             vec![&e]
@@ -92,54 +170,17 @@ pub trait UtxoHandlerTrait {
 
         Self::auth(&e).require_auth_for_args(auth_args);
 
-        Store::apply(e, |store| {
-            for spend_utxo in bundle.spend.iter() {
-                let amount = match store.balance(&spend_utxo) {
-                    a if a > 0 => a,
-                    0 => panic_with_error!(e, MoonlightError::UtxoAlreadySpent),
-                    _ => panic_with_error!(e, MoonlightError::UtxoDoesNotExist),
-                };
-
-                store.spend(&spend_utxo);
-                total_available_balance += amount;
-
-                #[cfg(not(feature = "no-utxo-events"))]
-                UtxoEvent {
-                    name: symbol_short!("utxo"),
-                    utxo: spend_utxo.clone(),
-                    action: symbol_short!("spend"),
-                    amount,
-                }
-                .publish(&e);
-            }
-
-            for (create_utxo, amount) in bundle.create.iter() {
-                if store.balance(&create_utxo) != -1 {
-                    panic_with_error!(e, MoonlightError::UtxoAlreadyExists);
-                }
-
-                assert_with_error!(&e, amount > 0, MoonlightError::InvalidCreateAmount);
-
-                store.create(&create_utxo, amount);
-                total_available_balance -= amount;
-
-                #[cfg(not(feature = "no-utxo-events"))]
-                UtxoEvent {
-                    name: symbol_short!("utxo"),
-                    utxo: create_utxo.clone(),
-                    action: symbol_short!("create"),
-                    amount,
-                }
-                .publish(&e);
-            }
+        let total_available_balance = Self::with_store(e, |store| {
+            apply_bundle(
+                e,
+                store,
+                &bundle,
+                incoming_amount,
+                expected_outgoing,
+                fail_fast,
+            )
         });
 
-        assert_with_error!(
-            &e,
-            total_available_balance == expected_outgoing,
-            MoonlightError::UnbalancedBundle
-        );
-
         #[cfg(not(feature = "no-bundle-events"))]
         BundleEvent {
             name: soroban_sdk::symbol_short!("bundle"),
@@ -151,8 +192,76 @@ pub trait UtxoHandlerTrait {
         .publish(&e);
 
         total_available_balance
+    }
+
+    /// Processes several independent bundles under a single auth contract authorization.
+    ///
+    /// Each entry is `(bundle, incoming_amount, expected_outgoing)` and must balance on its own.
+    /// No UTXO may be spent or created twice across the bundles, and a UTXO created by one bundle
+    /// cannot be spent by another: it does not exist until the whole batch has run. The auth contract is called once with the union of the
+    /// bundles' requirements.
+    fn process_bundles(e: &Env, bundles: &Vec<(InternalBundle, i128, i128)>) {
+        let fail_fast = &mut |v: Violation| panic_with_error!(e, v.error);
+
+        let mut merged = InternalBundle {
+            spend: Vec::new(e),
+            create: Vec::new(e),
+            req: AuthRequirements(Map::new(e)),
+        };
+        for (bundle, _, _) in bundles.iter() {
+            merged.spend.append(&bundle.spend);
+            merged.create.append(&bundle.create);
+            for (signer, conditions) in bundle.req.0.iter() {
+                merged.req.0.set(signer, conditions);
+            }
+        }
+        check_bundle_keys(e, &merged, fail_fast);
+
+        let mut created: Map<BytesN<65>, ()> = Map::new(e);
+        for (utxo, _) in merged.create.iter() {
+            created.set(utxo, ());
+        }
+        for (i, utxo) in merged.spend.iter().enumerate() {
+            if created.contains_key(utxo) {
+                fail_fast(Violation::new(
+                    MoonlightError::UtxoDoesNotExist,
+                    BundleItem::Spend(i as u32),
+                ));
+            }
+        }
+
+        let auth_args = if merged.req.0.is_empty() {
+            vec![e]
+        } else {
+            vec![e, merged.req.into_val(e)]
+        };
+
+        Self::auth(e).require_auth_for_args(auth_args);
+
+        Self::with_store(e, |store| {
+            for (bundle, incoming_amount, expected_outgoing) in bundles.iter() {
+                apply_bundle(
+                    e,
+                    store,
+                    &bundle,
+                    incoming_amount,
+                    expected_outgoing,
+                    fail_fast,
+                );
+            }
+        });
 
-        // bundle_funds
+        #[cfg(not(feature = "no-bundle-events"))]
+        for (bundle, incoming_amount, expected_outgoing) in bundles.iter() {
+            BundleEvent {
+                name: soroban_sdk::symbol_short!("bundle"),
+                spend: bundle.spend,
+                create: bundle.create,
+                deposited: incoming_amount,
+                withdrawn: expected_outgoing,
+            }
+            .publish(&e);
+        }
     }
 
     /// Creates a new UTXO with the specified balance after verifying it does not already exist.
@@ -183,7 +292,7 @@ pub trait UtxoHandlerTrait {
     }
 
     fn unchecked_create(e: &Env, amount: i128, utxo: &BytesN<65>) {
-        Store::apply(e, |store| store.create(utxo, amount));
+        Self::with_store(e, |store| store.create(utxo, amount));
 
         #[cfg(not(feature = "no-utxo-events"))]
         UtxoEvent {
@@ -196,7 +305,7 @@ pub trait UtxoHandlerTrait {
     }
 
     fn unchecked_spend(e: &Env, utxo: BytesN<65>, _amount: i128) {
-        Store::apply(e, |store| {
+        Self::with_store(e, |store| {
             store.spend(&utxo);
         });
 
@@ -211,18 +320,108 @@ pub trait UtxoHandlerTrait {
     }
 
     fn verify_utxo_not_exists(e: &Env, utxo: BytesN<65>) {
-        if Store::apply(e, |store| store.balance(&utxo)) != -1 {
+        if Self::utxo_state(e, utxo) != UtxoState::Missing {
             panic_with_error!(e, MoonlightError::UtxoAlreadyExists);
         }
     }
 
     fn verify_utxo_unspent(e: &Env, utxo: BytesN<65>) -> i128 {
-        match Store::apply(e, |store| store.balance(&utxo)) {
-            a if a > 0 => a,
-            0 => panic_with_error!(e, MoonlightError::UtxoAlreadySpent),
-            _ => panic_with_error!(e, MoonlightError::UtxoDoesNotExist),
+        Self::utxo_state(e, utxo).unspent_amount(e)
+    }
+}
+
+/// Checks that no UTXO is spent or created twice in the bundle.
+///
+/// Every violation is passed to `report`; [`UtxoHandlerTrait::process_bundle`] panics on the first.
+pub fn check_bundle_keys(e: &Env, bundle: &InternalBundle, report: &mut impl FnMut(Violation)) {
+    if !no_duplicate_keys(e, bundle.spend.iter(), |spend_utxo| spend_utxo.clone()) {
+        report(Violation::new(
+            MoonlightError::RepeatedSpendUtxo,
+            BundleItem::Operation,
+        ));
+    }
+
+    if !no_duplicate_keys(e, bundle.create.iter(), |(create_utxo, _amt)| {
+        create_utxo.clone()
+    }) {
+        report(Violation::new(
+            MoonlightError::RepeatedCreateUtxo,
+            BundleItem::Operation,
+        ));
+    }
+}
+
+/// Applies the bundle's spends and creates to `store` and checks that the bundle balances.
+///
+/// Spends and creates that break a rule are reported and skipped, so every violation is passed to
+/// `report`; [`UtxoHandlerTrait::process_bundle`] panics on the first. Returns the balance left
+/// after the spends, creates and `incoming_amount`.
+#[cfg_attr(feature = "no-utxo-events", allow(unused_variables))]
+pub fn apply_bundle<S: UtxoStore>(
+    e: &Env,
+    store: &mut S,
+    bundle: &InternalBundle,
+    incoming_amount: i128,
+    expected_outgoing: i128,
+    report: &mut impl FnMut(Violation),
+) -> i128 {
+    let mut total_available_balance = incoming_amount;
+
+    for (i, spend_utxo) in bundle.spend.iter().enumerate() {
+        let amount = match store.state(&spend_utxo).spendable() {
+            Ok(amount) => amount,
+            Err(error) => {
+                report(Violation::new(error, BundleItem::Spend(i as u32)));
+                continue;
+            }
+        };
+
+        store.spend(&spend_utxo);
+        total_available_balance += amount;
+
+        #[cfg(not(feature = "no-utxo-events"))]
+        UtxoEvent {
+            name: symbol_short!("utxo"),
+            utxo: spend_utxo.clone(),
+            action: symbol_short!("spend"),
+            amount,
+        }
+        .publish(e);
+    }
+
+    for (i, (create_utxo, amount)) in bundle.create.iter().enumerate() {
+        let item = BundleItem::Create(i as u32);
+
+        if store.state(&create_utxo) != UtxoState::Missing {
+            report(Violation::new(MoonlightError::UtxoAlreadyExists, item));
+            continue;
         }
+        if amount <= 0 {
+            report(Violation::new(MoonlightError::InvalidCreateAmount, item));
+            continue;
+        }
+
+        store.create(&create_utxo, amount);
+        total_available_balance -= amount;
+
+        #[cfg(not(feature = "no-utxo-events"))]
+        UtxoEvent {
+            name: symbol_short!("utxo"),
+            utxo: create_utxo.clone(),
+            action: symbol_short!("create"),
+            amount,
+        }
+        .publish(e);
     }
+
+    if total_available_balance != expected_outgoing {
+        report(Violation::new(
+            MoonlightError::UnbalancedBundle,
+            BundleItem::Operation,
+        ));
+    }
+
+    total_available_balance
 }
 
 // This should be different depending on the contract impl
@@ -0,0 +1,99 @@
+use moonlight_errors::Error as ContractError;
+use moonlight_helpers::testutils::keys::P256KeyPair;
+use moonlight_primitives::AuthRequirements;
+use soroban_sdk::{
+    contract, map,
+    testutils::{storage::Persistent as _, Address as _},
+    vec, Address, BytesN, Env, Error, Map,
+};
+
+use crate::core::{InternalBundle, MemoryStore, UtxoHandlerTrait, UtxoState};
+
+/// Runs the exact `process_bundle` accounting against an in-memory UTXO set.
+struct MemoryHandler;
+
+impl UtxoHandlerTrait<MemoryStore> for MemoryHandler {}
+
+#[contract]
+struct HostContract;
+
+fn setup(e: &Env, entries: Map<BytesN<65>, i128>) -> Address {
+    e.mock_all_auths();
+    let contract_id = e.register(HostContract, ());
+    e.as_contract(&contract_id, || {
+        MemoryHandler::set_auth(e, &Address::generate(e));
+        MemoryStore::from_entries(e, entries).save();
+    });
+    contract_id
+}
+
+#[test]
+fn test_process_bundle_runs_against_memory_store() {
+    let e = Env::default();
+    let utxo_a = P256KeyPair::generate(&e);
+    let utxo_b = P256KeyPair::generate(&e);
+    let utxo_c = P256KeyPair::generate(&e);
+    let contract_id = setup(&e, map![&e, (utxo_a.public_key.clone(), 250_i128)]);
+
+    let bundle = InternalBundle {
+        spend: vec![&e, utxo_a.public_key.clone()],
+        create: vec![
+            &e,
+            (utxo_b.public_key.clone(), 200_i128),
+            (utxo_c.public_key.clone(), 100_i128),
+        ],
+        req: AuthRequirements(Map::new(&e)),
+    };
+
+    let leftover = e.as_contract(&contract_id, || {
+        MemoryHandler::process_bundle(&e, bundle, 50, 0)
+    });
+    assert_eq!(leftover, 0);
+
+    e.as_contract(&contract_id, || {
+        assert_eq!(
+            MemoryHandler::utxo_states(
+                &e,
+                vec![
+                    &e,
+                    utxo_a.public_key.clone(),
+                    utxo_b.public_key.clone(),
+                    utxo_c.public_key.clone(),
+                ]
+            ),
+            vec![
+                &e,
+                UtxoState::Spent,
+                UtxoState::Unspent(200),
+                UtxoState::Unspent(100)
+            ]
+        );
+        // Nothing reached the ledger.
+        assert_eq!(e.storage().persistent().all().len(), 0);
+    });
+}
+
+#[test]
+fn test_memory_store_rejects_unbalanced_bundle() {
+    let e = Env::default();
+    let utxo_a = P256KeyPair::generate(&e);
+    let utxo_b = P256KeyPair::generate(&e);
+    let contract_id = setup(&e, map![&e, (utxo_a.public_key.clone(), 250_i128)]);
+
+    let bundle = InternalBundle {
+        spend: vec![&e, utxo_a.public_key.clone()],
+        create: vec![&e, (utxo_b.public_key.clone(), 300_i128)],
+        req: AuthRequirements(Map::new(&e)),
+    };
+
+    let res = e.try_as_contract::<_, Error>(&contract_id, || {
+        MemoryHandler::process_bundle(&e, bundle, 0, 0);
+    });
+
+    assert_eq!(
+        res.err(),
+        Some(Ok(Error::from_contract_error(
+            ContractError::UnbalancedBundle as u32
+        )))
+    );
+}
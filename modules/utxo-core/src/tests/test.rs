@@ -9,7 +9,7 @@ use soroban_sdk::{
 };
 
 use crate::{
-    core::{calculate_auth_requirements, UTXOOperation},
+    core::{calculate_auth_requirements, UTXOOperation, UtxoState},
     testutils::contract::create_contract_with_mocked_auth,
 };
 
@@ -81,6 +81,42 @@ fn test_mint_and_burn() {
     );
 }
 
+#[test]
+fn test_utxo_states() {
+    let e = Env::default();
+    let (client, _) = create_contract_with_mocked_auth(&e);
+
+    let unspent = P256KeyPair::generate(&e);
+    let spent = P256KeyPair::generate(&e);
+    let missing = P256KeyPair::generate(&e);
+
+    client.mint(&vec![
+        &e,
+        (unspent.public_key.clone(), 250_i128),
+        (spent.public_key.clone(), 100_i128),
+    ]);
+    client.burn(&vec![&e, spent.public_key.clone()]);
+
+    assert_eq!(
+        client.utxo_state(&unspent.public_key),
+        UtxoState::Unspent(250)
+    );
+    assert_eq!(
+        client.utxo_states(&vec![
+            &e,
+            unspent.public_key.clone(),
+            spent.public_key.clone(),
+            missing.public_key.clone(),
+        ]),
+        vec![
+            &e,
+            UtxoState::Unspent(250),
+            UtxoState::Spent,
+            UtxoState::Missing
+        ]
+    );
+}
+
 #[test]
 fn test_transfer() {
     let e = Env::default();
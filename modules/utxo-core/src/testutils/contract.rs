@@ -1,4 +1,6 @@
-use crate::core::{calculate_auth_requirements, InternalBundle, UTXOOperation, UtxoHandlerTrait};
+use crate::core::{
+    calculate_auth_requirements, InternalBundle, UTXOOperation, UtxoHandlerTrait, UtxoState,
+};
 
 use soroban_sdk::{
     auth::{Context, CustomAccountInterface},
@@ -38,6 +40,14 @@ impl UTXOModuleTestContract {
         <Self as UtxoHandlerTrait>::utxo_balances(e, utxos)
     }
 
+    pub fn utxo_state(e: &Env, utxo: BytesN<65>) -> UtxoState {
+        <Self as UtxoHandlerTrait>::utxo_state(e, utxo)
+    }
+
+    pub fn utxo_states(e: &Env, utxos: Vec<BytesN<65>>) -> Vec<UtxoState> {
+        <Self as UtxoHandlerTrait>::utxo_states(e, utxos)
+    }
+
     pub fn transact_with_external(
         e: Env,
         op: UTXOOperation,
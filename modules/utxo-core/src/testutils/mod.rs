@@ -1,2 +1 @@
 pub mod contract;
-pub mod operation_bundle;
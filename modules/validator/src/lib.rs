@@ -0,0 +1,157 @@
+//! Off-chain validation of privacy-channel operations.
+//!
+//! [`validate`] runs the privacy channel's own rule functions against a
+//! snapshot of UTXO states, without a network or a deployed contract, and
+//! returns every violation instead of stopping at the first one. Signatures
+//! and provider authorization are not checked, and the channel state it
+//! checks against is the [`ChannelState`] the caller passes.
+
+use moonlight_utxo_core::core::{apply_bundle, check_bundle_keys, UtxoStore};
+use privacy_channel::{
+    limits::check_channel_limits,
+    transact::{check_channel_operation, to_internal_bundle},
+};
+use soroban_sdk::{Address, BytesN, Env, Map};
+
+pub use moonlight_errors::Error;
+pub use moonlight_utxo_core::core::{BundleItem, UtxoState, Violation};
+pub use privacy_channel::{limits::ChannelLimits, transact::ChannelOperation};
+
+#[cfg(test)]
+mod test;
+
+/// The channel state `transact` checks besides UTXOs, as read from the channel's getters.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ChannelState {
+    /// `limits()`.
+    pub limits: ChannelLimits,
+    /// `supply()`.
+    pub supply: i128,
+    /// `is_paused()`, set by a tripped circuit breaker until it is reset.
+    pub paused: bool,
+}
+
+/// Checks `op` against the channel and UTXO rules enforced by `transact`, for an unpaused channel
+/// without limits or a compliance policy.
+///
+/// `utxos` holds the known state of every UTXO the operation touches; keys
+/// that are absent are treated as [`UtxoState::Missing`]. Violations are
+/// returned in the order the contract enforces them, so the first one is the
+/// error `transact` would fail with. See [`validate_with_state`] for what an
+/// empty result does not cover.
+pub fn validate(
+    e: &Env,
+    op: &ChannelOperation,
+    utxos: &Map<BytesN<65>, UtxoState>,
+) -> Vec<Violation> {
+    validate_with_state(e, op, utxos, &ChannelState::default(), |_, _, _| true)
+}
+
+/// [`validate`] against the channel's `state` and compliance policy.
+///
+/// `compliance` stands in for the channel's `compliance_policy()`: it is asked about every
+/// deposit (`BundleItem::Deposit`) and withdrawal (`BundleItem::Withdraw`) leg with its address
+/// and amount, and returning `false` reports `ComplianceCheckFailed`. Pass `|_, _, _| true` for a
+/// channel without a policy.
+///
+/// An empty result means `transact` passes every rule except these, which are not checked:
+/// - P256 spend signatures, depositor signatures and provider authorization;
+/// - depositor token balances and allowances;
+/// - the circuit breaker, which fails no bundle: the one that trips it completes and pauses the
+///   channel for the next.
+pub fn validate_with_state(
+    e: &Env,
+    op: &ChannelOperation,
+    utxos: &Map<BytesN<65>, UtxoState>,
+    state: &ChannelState,
+    mut compliance: impl FnMut(BundleItem, &Address, i128) -> bool,
+) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let report = &mut |v: Violation| violations.push(v);
+
+    if state.paused {
+        report(Violation::new(Error::ChannelPaused, BundleItem::Operation));
+    }
+
+    let (total_deposit, total_withdraw) = check_channel_operation(e, op, report);
+    check_channel_limits(
+        op,
+        &state.limits,
+        state.supply,
+        total_deposit,
+        total_withdraw,
+        report,
+    );
+
+    let bundle = to_internal_bundle(e, op.clone());
+    check_bundle_keys(e, &bundle, report);
+
+    let mut store = SnapshotStore {
+        states: utxos.clone(),
+    };
+    apply_bundle(
+        e,
+        &mut store,
+        &bundle,
+        total_deposit,
+        total_withdraw,
+        report,
+    );
+
+    // Screened as the legs execute, after the bundle is applied.
+    let deposits = op
+        .deposit
+        .iter()
+        .enumerate()
+        .map(|(i, (from, amount, _))| (BundleItem::Deposit(i as u32), from, amount));
+    let withdrawals = op
+        .withdraw
+        .iter()
+        .enumerate()
+        .map(|(i, (to, amount, _))| (BundleItem::Withdraw(i as u32), to, amount));
+    for (item, address, amount) in deposits.chain(withdrawals) {
+        if !compliance(item, &address, amount) {
+            report(Violation::new(Error::ComplianceCheckFailed, item));
+        }
+    }
+
+    violations
+}
+
+/// A [`UtxoStore`] over a snapshot of UTXO states. It only records state
+/// changes; rule checks happen in `apply_bundle` before any write.
+struct SnapshotStore {
+    states: Map<BytesN<65>, UtxoState>,
+}
+
+impl UtxoStore for SnapshotStore {
+    fn apply<R>(e: &Env, f: impl FnOnce(&mut Self) -> R) -> R {
+        f(&mut Self {
+            states: Map::new(e),
+        })
+    }
+
+    fn balance(&mut self, utxo65: &BytesN<65>) -> i128 {
+        match self.state(utxo65) {
+            UtxoState::Unspent(amount) => amount,
+            UtxoState::Spent => 0,
+            UtxoState::Missing | UtxoState::Archived => -1,
+        }
+    }
+
+    fn state(&mut self, utxo65: &BytesN<65>) -> UtxoState {
+        self.states
+            .get(utxo65.clone())
+            .unwrap_or(UtxoState::Missing)
+    }
+
+    fn create(&mut self, utxo65: &BytesN<65>, amount: i128) {
+        self.states.set(utxo65.clone(), UtxoState::Unspent(amount));
+    }
+
+    fn spend(&mut self, utxo65: &BytesN<65>) -> i128 {
+        let amount = self.balance(utxo65);
+        self.states.set(utxo65.clone(), UtxoState::Spent);
+        amount
+    }
+}
@@ -0,0 +1,246 @@
+use channel_auth_contract::contract::{ChannelAuthContract, ChannelAuthContractArgs};
+use moonlight_helpers::testutils::keys::P256KeyPair;
+use moonlight_primitives::Condition;
+use privacy_channel::contract::{
+    PrivacyChannelContract, PrivacyChannelContractArgs, PrivacyChannelContractClient,
+};
+use soroban_sdk::{map, testutils::Address as _, vec, Address, BytesN, Env, Map};
+
+use crate::{
+    validate, validate_with_state, BundleItem, ChannelLimits, ChannelOperation, ChannelState,
+    Error, UtxoState, Violation,
+};
+
+fn key(e: &Env) -> BytesN<65> {
+    P256KeyPair::generate(e).public_key
+}
+
+fn empty_op(e: &Env) -> ChannelOperation {
+    ChannelOperation {
+        spend: vec![e],
+        create: vec![e],
+        deposit: vec![e],
+        withdraw: vec![e],
+        notes: Map::new(e),
+        ephemeral_keys: Map::new(e),
+        allowance_deposits: vec![e],
+    }
+}
+
+/// Registers a channel whose asset is never reached, for ops that fail before any transfer.
+fn channel(e: &Env) -> PrivacyChannelContractClient<'_> {
+    e.mock_all_auths();
+    let admin = Address::generate(e);
+    let auth_id = e.register(
+        ChannelAuthContract,
+        ChannelAuthContractArgs::__constructor(&admin),
+    );
+    let channel_id = e.register(
+        PrivacyChannelContract,
+        PrivacyChannelContractArgs::__constructor(&admin, &auth_id, &Address::generate(e)),
+    );
+    PrivacyChannelContractClient::new(e, &channel_id)
+}
+
+#[test]
+fn valid_internal_transfer_has_no_violations() {
+    let e = Env::default();
+    let (a, b, c) = (key(&e), key(&e), key(&e));
+
+    let mut op = empty_op(&e);
+    op.spend = vec![&e, (a.clone(), vec![&e, Condition::Create(b.clone(), 70)])];
+    op.create = vec![&e, (b.clone(), 70), (c.clone(), 30)];
+
+    let utxos = map![&e, (a, UtxoState::Unspent(100))];
+
+    assert_eq!(validate(&e, &op, &utxos), std::vec![]);
+}
+
+#[test]
+fn reports_every_violation_in_enforcement_order() {
+    let e = Env::default();
+    let (spent, archived, missing, existing, fresh) = (key(&e), key(&e), key(&e), key(&e), key(&e));
+    let depositor = Address::generate(&e);
+
+    let op = ChannelOperation {
+        spend: vec![
+            &e,
+            (spent.clone(), vec![&e]),
+            (archived.clone(), vec![&e]),
+            (missing.clone(), vec![&e]),
+        ],
+        create: vec![&e, (existing.clone(), 10), (fresh.clone(), 0)],
+        deposit: vec![
+            &e,
+            (
+                depositor.clone(),
+                0,
+                vec![&e, Condition::Create(fresh.clone(), 5)],
+            ),
+        ],
+        withdraw: vec![&e],
+        notes: Map::new(&e),
+        ephemeral_keys: Map::new(&e),
+        allowance_deposits: vec![&e],
+    };
+    let utxos = map![
+        &e,
+        (spent, UtxoState::Spent),
+        (archived, UtxoState::Archived),
+        (existing, UtxoState::Unspent(10))
+    ];
+
+    assert_eq!(
+        validate(&e, &op, &utxos),
+        std::vec![
+            Violation::new(Error::InvalidExternalAmount, BundleItem::Deposit(0)),
+            Violation::new(Error::UnauthorizedOperation, BundleItem::Operation),
+            Violation::new(Error::UtxoAlreadySpent, BundleItem::Spend(0)),
+            Violation::new(Error::UtxoArchived, BundleItem::Spend(1)),
+            Violation::new(Error::UtxoDoesNotExist, BundleItem::Spend(2)),
+            Violation::new(Error::UtxoAlreadyExists, BundleItem::Create(0)),
+            Violation::new(Error::InvalidCreateAmount, BundleItem::Create(1)),
+        ]
+    );
+}
+
+#[test]
+fn reports_repeated_keys_and_unbalanced_bundles() {
+    let e = Env::default();
+    let (a, b) = (key(&e), key(&e));
+
+    let mut op = empty_op(&e);
+    op.spend = vec![&e, (a.clone(), vec![&e]), (a.clone(), vec![&e])];
+    op.create = vec![&e, (b.clone(), 150)];
+
+    let utxos = map![&e, (a, UtxoState::Unspent(100))];
+
+    assert_eq!(
+        validate(&e, &op, &utxos),
+        std::vec![
+            Violation::new(Error::RepeatedSpendUtxo, BundleItem::Operation),
+            Violation::new(Error::UtxoAlreadySpent, BundleItem::Spend(1)),
+            Violation::new(Error::UnbalancedBundle, BundleItem::Operation),
+        ]
+    );
+}
+
+#[test]
+fn first_violation_matches_the_contract_error() {
+    let e = Env::default();
+    let channel = channel(&e);
+    let (a, b) = (key(&e), key(&e));
+    let depositor = Address::generate(&e);
+    let no_utxos: Map<BytesN<65>, UtxoState> = Map::new(&e);
+
+    let mut conflicting = empty_op(&e);
+    conflicting.spend = vec![
+        &e,
+        (a.clone(), vec![&e, Condition::Create(b.clone(), 1)]),
+        (b.clone(), vec![&e, Condition::Create(b.clone(), 2)]),
+    ];
+
+    let mut repeated_deposit = empty_op(&e);
+    repeated_deposit.deposit = vec![
+        &e,
+        (depositor.clone(), 5, vec![&e]),
+        (depositor.clone(), 5, vec![&e]),
+    ];
+
+    let mut missing_spend = empty_op(&e);
+    missing_spend.spend = vec![&e, (a.clone(), vec![&e])];
+
+    let mut unbalanced = empty_op(&e);
+    unbalanced.create = vec![&e, (b.clone(), 10)];
+
+    for op in [conflicting, repeated_deposit, missing_spend, unbalanced] {
+        let expected = validate(&e, &op, &no_utxos)[0].error;
+        let res = channel.try_transact(&op);
+
+        assert_eq!(
+            res.err(),
+            Some(Ok(soroban_sdk::Error::from_contract_error(expected as u32)))
+        );
+    }
+}
+
+#[test]
+fn reports_limit_violations_with_validate_with_state() {
+    let e = Env::default();
+    let (a, b) = (key(&e), key(&e));
+    let depositor = Address::generate(&e);
+
+    let mut op = empty_op(&e);
+    op.create = vec![&e, (a, 95), (b, 5)];
+    op.deposit = vec![&e, (depositor, 100, vec![&e])];
+
+    let state = ChannelState {
+        limits: ChannelLimits {
+            max_supply: Some(1_000),
+            max_deposit: Some(50),
+            min_create: Some(10),
+            ..ChannelLimits::default()
+        },
+        supply: 950,
+        paused: false,
+    };
+    let no_utxos: Map<BytesN<65>, UtxoState> = Map::new(&e);
+
+    assert_eq!(validate(&e, &op, &no_utxos), std::vec![]);
+    assert_eq!(
+        validate_with_state(&e, &op, &no_utxos, &state, |_, _, _| true),
+        std::vec![
+            Violation::new(Error::CreateBelowMinimum, BundleItem::Create(1)),
+            Violation::new(Error::DepositAboveLimit, BundleItem::Deposit(0)),
+            Violation::new(Error::SupplyCeilingExceeded, BundleItem::Operation),
+        ]
+    );
+}
+
+#[test]
+fn reports_pause_first_and_compliance_after_the_bundle() {
+    let e = Env::default();
+    let (a, b) = (key(&e), key(&e));
+    let (depositor, recipient) = (Address::generate(&e), Address::generate(&e));
+
+    let mut op = empty_op(&e);
+    op.create = vec![&e, (a.clone(), 100)];
+    op.deposit = vec![&e, (depositor.clone(), 100, vec![&e])];
+    let no_utxos: Map<BytesN<65>, UtxoState> = Map::new(&e);
+
+    // The policy only blocks `recipient`, so the withdrawal leg fails after the unbalanced create.
+    let mut screened = std::vec::Vec::new();
+    let policy = |item: BundleItem, address: &Address, amount: i128| {
+        screened.push((item, address.clone(), amount));
+        *address != recipient
+    };
+    let mut draining = op.clone();
+    draining.create = vec![&e, (b.clone(), 100)];
+    draining.withdraw = vec![&e, (recipient.clone(), 50, vec![&e])];
+    assert_eq!(
+        validate_with_state(&e, &draining, &no_utxos, &ChannelState::default(), policy),
+        std::vec![
+            Violation::new(Error::UnbalancedBundle, BundleItem::Operation),
+            Violation::new(Error::ComplianceCheckFailed, BundleItem::Withdraw(0)),
+        ]
+    );
+    assert_eq!(
+        screened,
+        std::vec![
+            (BundleItem::Deposit(0), depositor.clone(), 100),
+            (BundleItem::Withdraw(0), recipient.clone(), 50),
+        ]
+    );
+
+    let paused = ChannelState {
+        paused: true,
+        ..ChannelState::default()
+    };
+    assert_eq!(
+        validate_with_state(&e, &draining, &no_utxos, &paused, |_, _, _| true),
+        std::vec![
+            Violation::new(Error::ChannelPaused, BundleItem::Operation),
+            Violation::new(Error::UnbalancedBundle, BundleItem::Operation),
+        ]
+    );
+}